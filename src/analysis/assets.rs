@@ -0,0 +1,146 @@
+//! Referenced asset metadata (`--asset-metadata`).
+//!
+//! Binary files never make it into the pack, but code often names them (`"img/logo.png"`,
+//! `'models/weights.onnx'`). For each string literal in the included chunks that resolves
+//! to a binary file inside the repository, the export lists its path, size and SHA-256.
+
+use crate::domain::{Chunk, ReferencedAsset};
+use crate::utils::{is_binary_file, normalize_path, sha256_file_hex};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Bytes sampled for binary detection, as in the scanner.
+const BINARY_SAMPLE_SIZE: usize = 8192;
+
+/// Quoted literals short enough to be a path, on a single line.
+fn string_literal_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#""([^"\\\n]{3,256})"|'([^'\\\n]{3,256})'|`([^`\\\n]{3,256})`"#)
+            .expect("string literal regex")
+    })
+}
+
+/// Binary files named by string literals in `chunks`, sorted by path.
+///
+/// A literal is tried relative to the referencing file's directory, then to the
+/// repository root. Only `candidates`, the scanned files the export skipped, are hashed, so
+/// literals naming gitignored, excluded or out-of-root files are ignored.
+pub fn referenced_assets(
+    root: &Path,
+    chunks: &[Chunk],
+    candidates: &BTreeSet<String>,
+) -> Vec<ReferencedAsset> {
+    let Ok(root) = root.canonicalize() else {
+        return Vec::new();
+    };
+    let mut referrers: BTreeMap<String, (PathBuf, BTreeSet<String>)> = BTreeMap::new();
+    for chunk in chunks {
+        let dir = Path::new(&chunk.path).parent().unwrap_or(Path::new(""));
+        for caps in string_literal_re().captures_iter(&chunk.content) {
+            let Some(literal) = caps.iter().skip(1).flatten().next().map(|m| m.as_str()) else {
+                continue;
+            };
+            if !looks_like_file_path(literal) {
+                continue;
+            }
+            let Some((relative, absolute)) = resolve(&root, dir, literal) else {
+                continue;
+            };
+            if !candidates.contains(&relative) {
+                continue;
+            }
+            referrers
+                .entry(relative)
+                .or_insert_with(|| (absolute, BTreeSet::new()))
+                .1
+                .insert(chunk.path.clone());
+        }
+    }
+
+    referrers
+        .into_iter()
+        .filter(|(_, (absolute, _))| is_binary_file(absolute, BINARY_SAMPLE_SIZE))
+        .filter_map(|(path, (absolute, referenced_by))| {
+            Some(ReferencedAsset {
+                size_bytes: absolute.metadata().ok()?.len(),
+                sha256: sha256_file_hex(&absolute).ok()?,
+                path,
+                referenced_by: referenced_by.into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+/// No whitespace or URL scheme, and a final segment with a short extension.
+fn looks_like_file_path(literal: &str) -> bool {
+    if literal.contains(char::is_whitespace) || literal.contains("://") {
+        return false;
+    }
+    let name = literal.rsplit('/').next().unwrap_or(literal);
+    matches!(
+        name.rsplit_once('.'),
+        Some((stem, ext)) if !stem.is_empty()
+            && (1..=8).contains(&ext.len())
+            && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    )
+}
+
+/// Repository-relative path and canonical location of the file `literal` names, if any.
+fn resolve(root: &Path, dir: &Path, literal: &str) -> Option<(String, PathBuf)> {
+    let literal = literal.trim_start_matches("./");
+    let candidates = if let Some(absolute) = literal.strip_prefix('/') {
+        vec![root.join(absolute)]
+    } else {
+        vec![root.join(dir).join(literal), root.join(literal)]
+    };
+    candidates.into_iter().find_map(|candidate| {
+        let canonical = candidate.canonicalize().ok().filter(|p| p.is_file())?;
+        let relative = canonical.strip_prefix(root).ok()?.to_str()?;
+        Some((normalize_path(relative), canonical))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::referenced_assets;
+    use crate::domain::test_support::chunk;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn lists_binary_files_named_by_string_literals() {
+        let repo = TempDir::new().expect("temp repo");
+        fs::create_dir_all(repo.path().join("app/assets")).expect("mkdir assets");
+        fs::write(repo.path().join("app/assets/logo.png"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR")
+            .expect("write png");
+        fs::write(repo.path().join("model.bin"), [0u8, 1, 2, 3]).expect("write model");
+        fs::write(repo.path().join("notes.txt"), "plain text\n").expect("write notes");
+        fs::write(repo.path().join("secret.bin"), [0u8, 9]).expect("write secret");
+
+        let chunks = vec![
+            chunk(
+                "app/ui.py",
+                "LOGO = 'assets/logo.png'\nNOTES = \"notes.txt\"\nURL = \"https://x.io/a.png\"\n",
+            ),
+            chunk("app/load.py", "weights = open(\"./model.bin\", \"rb\")\nmissing = 'gone.png'\n"),
+            chunk("app/escape.py", "secret = '../../etc/passwd.bin'\nkey = 'secret.bin'\n"),
+        ];
+        // `secret.bin` is gitignored or excluded, so the scan never saw it.
+        let candidates =
+            BTreeSet::from(["app/assets/logo.png", "model.bin", "notes.txt"].map(str::to_string));
+        let assets = referenced_assets(repo.path(), &chunks, &candidates);
+
+        let paths: Vec<&str> = assets.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, vec!["app/assets/logo.png", "model.bin"]);
+        assert_eq!(assets[0].size_bytes, 16);
+        assert_eq!(assets[0].referenced_by, vec!["app/ui.py"]);
+        assert_eq!(
+            assets[1].sha256,
+            "054edec1d0211f624fed0cbca9d4f9400b0e491c43742af2c5b0abebf0c990d8"
+        );
+    }
+}
@@ -104,6 +104,8 @@ mod tests {
             priority: 0.5,
             tags: BTreeSet::new(),
             token_estimate: 10,
+            doc: None,
+            byte_range: None,
         }];
 
         let found = detect_async_boundaries(&chunks);
@@ -129,6 +131,8 @@ mod tests {
             priority: 0.5,
             tags: BTreeSet::new(),
             token_estimate: 10,
+            doc: None,
+            byte_range: None,
         }];
 
         let found = detect_async_boundaries(&chunks);
@@ -0,0 +1,129 @@
+//! Ownership tags from CODEOWNERS (`--annotate-ownership`)
+//!
+//! Reads a GitHub/GitLab `CODEOWNERS` file and tags each included file with its owners
+//! (`owner:@team`), so review packs show who to ask about a change.
+
+use crate::domain::FileInfo;
+use crate::utils::read_file_safe;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+pub const OWNER_TAG_PREFIX: &str = "owner:";
+
+/// Where GitHub and GitLab look for the file, in their lookup order.
+const CODEOWNERS_LOCATIONS: &[&str] =
+    &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+/// Parsed CODEOWNERS rules, in file order.
+pub struct CodeOwners {
+    /// Repository-relative location of the file the rules came from
+    pub source: String,
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Load the first CODEOWNERS file found under `root_path`.
+    pub fn load(root_path: &Path) -> Option<Self> {
+        CODEOWNERS_LOCATIONS.iter().find_map(|location| {
+            let (content, _) = read_file_safe(&root_path.join(location), None, None).ok()?;
+            Some(Self::parse(root_path, location, &content))
+        })
+    }
+
+    fn parse(root_path: &Path, source: &str, content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            // GitLab section headers (`[Docs]`, `^[Optional]`) only group rules.
+            if line.is_empty() || line.starts_with('#') || line.starts_with(['[', '^']) {
+                continue;
+            }
+            let line = line.split(" #").next().unwrap_or(line);
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners: Vec<String> = parts.map(str::to_string).collect();
+            let mut builder = GitignoreBuilder::new(root_path);
+            if builder.add_line(None, pattern).is_err() {
+                continue;
+            }
+            if let Ok(matcher) = builder.build() {
+                rules.push((matcher, owners));
+            }
+        }
+        Self { source: source.to_string(), rules }
+    }
+
+    /// Owners of `relative_path`; the last matching rule wins, and a matching rule with no
+    /// owners leaves the file unowned.
+    pub fn owners_for(&self, relative_path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| {
+                matcher.matched_path_or_any_parents(relative_path, false).is_ignore()
+            })
+            .map(|(_, owners)| owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Tag `files` with `owner:<owner>` for each of their owners; returns how many files got
+/// at least one owner.
+pub fn apply_ownership_tags(codeowners: &CodeOwners, files: &mut [FileInfo]) -> usize {
+    let mut owned = 0;
+    for file in files.iter_mut() {
+        let owners = codeowners.owners_for(&file.relative_path);
+        if !owners.is_empty() {
+            owned += 1;
+        }
+        for owner in owners {
+            file.tags.insert(format!("{OWNER_TAG_PREFIX}{owner}"));
+        }
+    }
+    owned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_ownership_tags, CodeOwners};
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use std::fs;
+    use std::path::Path;
+
+    fn file(root: &Path, path: &str) -> FileInfo {
+        FileInfo { path: root.join(path), size_bytes: 10, ..file_info(path) }
+    }
+
+    #[test]
+    fn last_matching_rule_sets_owner_tags() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let root = tmp.path();
+        fs::create_dir_all(root.join(".github")).expect("create .github");
+        fs::write(
+            root.join(".github/CODEOWNERS"),
+            "# Default owners\n*  @org/core\n\n[Backend]\n/api/ @org/backend @alice\n\
+             api/generated/\n*.md @org/docs # docs team\n",
+        )
+        .expect("write codeowners");
+
+        let codeowners = CodeOwners::load(root).expect("codeowners");
+        assert_eq!(codeowners.source, ".github/CODEOWNERS");
+
+        let mut files = vec![
+            file(root, "api/handlers.py"),
+            file(root, "api/generated/client.py"),
+            file(root, "lib/util.py"),
+            file(root, "api/README.md"),
+        ];
+        assert_eq!(apply_ownership_tags(&codeowners, &mut files), 3);
+
+        let tags = |idx: usize| files[idx].tags.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(tags(0), vec!["owner:@alice", "owner:@org/backend"]);
+        assert!(tags(1).is_empty());
+        assert_eq!(tags(2), vec!["owner:@org/core"]);
+        assert_eq!(tags(3), vec!["owner:@org/docs"]);
+    }
+}
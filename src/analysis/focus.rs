@@ -0,0 +1,103 @@
+//! File-rooted selection (`--focus-file`).
+//!
+//! Keeps one file plus every file within a number of import hops of it, in either
+//! direction (files it imports and files importing it).
+
+use crate::domain::{Chunk, FileInfo};
+use crate::rank::dependency_graph;
+use crate::utils::{estimate_tokens, read_file_safe, EncodingOverrides};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Files reachable from `focus` within `depth` import hops, mapped to their distance
+/// (`focus` itself is 0). Empty when `focus` is not among `files`.
+pub fn focus_files_by_dependency(
+    files: &[FileInfo],
+    focus: &str,
+    depth: usize,
+    encodings: &EncodingOverrides,
+) -> BTreeMap<String, usize> {
+    let mut reached = BTreeMap::new();
+    if !files.iter().any(|f| f.relative_path == focus) {
+        return reached;
+    }
+
+    // One whole-file chunk per file: only import edges are wanted, and those need no
+    // chunking or symbol tags.
+    let chunks: Vec<Chunk> = files
+        .iter()
+        .filter_map(|f| {
+            let encoding = encodings.encoding_for(&f.relative_path);
+            let (content, _) = read_file_safe(&f.path, None, encoding).ok()?;
+            Some(Chunk {
+                id: f.id.clone(),
+                path: f.relative_path.clone(),
+                language: f.language.clone(),
+                start_line: 1,
+                end_line: content.lines().count().max(1),
+                token_estimate: estimate_tokens(&content),
+                content,
+                priority: f.priority,
+                tags: BTreeSet::new(),
+                doc: None,
+                byte_range: None,
+            })
+        })
+        .collect();
+    let known_files: HashSet<String> = files.iter().map(|f| f.relative_path.clone()).collect();
+    let graph = dependency_graph(&chunks, &known_files, &HashMap::new());
+
+    reached.insert(focus.to_string(), 0);
+    let mut queue = VecDeque::from([(focus.to_string(), 0usize)]);
+    while let Some((path, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for neighbor in graph.get(&path).into_iter().flatten() {
+            if !reached.contains_key(neighbor) {
+                reached.insert(neighbor.clone(), distance + 1);
+                queue.push_back((neighbor.clone(), distance + 1));
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::focus_files_by_dependency;
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use crate::utils::EncodingOverrides;
+    use std::fs;
+
+    #[test]
+    fn depth_limits_how_far_imports_are_followed() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let sources = [
+            ("app/db.py", "def connect():\n    pass\n"),
+            ("app/store.py", "from app.db import connect\n"),
+            ("app/auth.py", "from app.store import save\n"),
+            ("app/views.py", "from app.auth import login\n"),
+            ("app/billing.py", "def charge():\n    pass\n"),
+        ];
+        fs::create_dir_all(dir.path().join("app")).expect("create app dir");
+        let files: Vec<FileInfo> = sources
+            .iter()
+            .map(|(path, content)| {
+                let abs = dir.path().join(path);
+                fs::write(&abs, content).expect("write source");
+                FileInfo { path: abs, size_bytes: content.len() as u64, ..file_info(path) }
+            })
+            .collect();
+
+        let encodings = EncodingOverrides::default();
+        let near = focus_files_by_dependency(&files, "app/auth.py", 1, &encodings);
+        let paths: Vec<&str> = near.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["app/auth.py", "app/store.py", "app/views.py"]);
+
+        let far = focus_files_by_dependency(&files, "app/auth.py", 2, &encodings);
+        assert_eq!(far.get("app/db.py"), Some(&2));
+        assert!(!far.contains_key("app/billing.py"));
+        assert!(focus_files_by_dependency(&files, "app/missing.py", 1, &encodings).is_empty());
+    }
+}
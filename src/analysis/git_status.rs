@@ -0,0 +1,117 @@
+//! Working-tree status tags (`--include-git-status`)
+//!
+//! Marks each included file as `git:modified`, `git:untracked` or `git:clean`, so a pack
+//! exported from a dirty checkout shows which files carry uncommitted changes.
+
+use crate::domain::FileInfo;
+use git2::{Repository, Status, StatusOptions};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const GIT_MODIFIED_TAG: &str = "git:modified";
+pub const GIT_UNTRACKED_TAG: &str = "git:untracked";
+pub const GIT_CLEAN_TAG: &str = "git:clean";
+
+/// How many files got each status tag.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GitStatusSummary {
+    pub modified: usize,
+    pub untracked: usize,
+    pub clean: usize,
+}
+
+/// Tag `files` with their working-tree status.
+///
+/// Returns `None` (and tags nothing) when `root_path` is not inside a git work tree.
+/// Staged changes count as modified, including newly added files.
+pub fn apply_git_status_tags(root_path: &Path, files: &mut [FileInfo]) -> Option<GitStatusSummary> {
+    let repo = Repository::discover(root_path).ok()?;
+    let workdir = repo.workdir()?;
+    let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+    let root = root_path.canonicalize().unwrap_or_else(|_| root_path.to_path_buf());
+    let prefix = root.strip_prefix(&workdir).unwrap_or(Path::new("")).to_string_lossy();
+    let prefix = prefix.replace('\\', "/");
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options)).ok()?;
+    let by_path: HashMap<String, Status> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path()?;
+            let relative = if prefix.is_empty() {
+                path
+            } else {
+                path.strip_prefix(prefix.as_str())?.strip_prefix('/')?
+            };
+            Some((relative.to_string(), entry.status()))
+        })
+        .collect();
+
+    let mut summary = GitStatusSummary::default();
+    for file in files.iter_mut() {
+        let tag = match by_path.get(&file.relative_path) {
+            Some(status) if status.contains(Status::WT_NEW) => {
+                summary.untracked += 1;
+                GIT_UNTRACKED_TAG
+            }
+            Some(status) if !status.is_empty() && !status.contains(Status::IGNORED) => {
+                summary.modified += 1;
+                GIT_MODIFIED_TAG
+            }
+            _ => {
+                summary.clean += 1;
+                GIT_CLEAN_TAG
+            }
+        };
+        file.tags.insert(tag.to_string());
+    }
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_git_status_tags, GIT_CLEAN_TAG, GIT_MODIFIED_TAG, GIT_UNTRACKED_TAG};
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use std::fs;
+    use std::path::Path;
+
+    fn file(root: &Path, path: &str) -> FileInfo {
+        FileInfo { path: root.join(path), size_bytes: 10, ..file_info(path) }
+    }
+
+    #[test]
+    fn modified_and_untracked_files_are_tagged() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let root = tmp.path();
+        let repo = git2::Repository::init(root).expect("init");
+        fs::write(root.join("app.py"), "x = 1\n").expect("write app");
+        fs::write(root.join("util.py"), "y = 1\n").expect("write util");
+        let mut index = repo.index().expect("index");
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).expect("add");
+        index.write().expect("write index");
+        let tree = repo.find_tree(index.write_tree().expect("tree")).expect("find tree");
+        let sig = git2::Signature::now("test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "base", &tree, &[]).expect("commit");
+
+        fs::write(root.join("app.py"), "x = 2\n").expect("modify app");
+        fs::write(root.join("new.py"), "z = 1\n").expect("write new");
+
+        let mut files = vec![file(root, "app.py"), file(root, "util.py"), file(root, "new.py")];
+        let summary = apply_git_status_tags(root, &mut files).expect("git repo");
+
+        assert!(files[0].tags.contains(GIT_MODIFIED_TAG));
+        assert!(files[1].tags.contains(GIT_CLEAN_TAG));
+        assert!(files[2].tags.contains(GIT_UNTRACKED_TAG));
+        assert_eq!((summary.modified, summary.untracked, summary.clean), (1, 1, 1));
+    }
+
+    #[test]
+    fn non_git_directories_are_skipped() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let mut files = vec![file(tmp.path(), "app.py")];
+        assert!(apply_git_status_tags(tmp.path(), &mut files).is_none());
+        assert!(files[0].tags.is_empty());
+    }
+}
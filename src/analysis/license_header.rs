@@ -0,0 +1,169 @@
+//! Repeated license header elision (`--dedup-license-headers`).
+//!
+//! Runs per file as the pack is budgeted: the first kept file with a leading comment
+//! block keeps it, and later files sharing that block get a one-line pointer instead, so
+//! the saved tokens count toward the budget.
+
+use crate::domain::{Chunk, ScanStats};
+use crate::utils::{estimate_tokens, line_comment_prefix, stable_hash};
+use std::collections::HashMap;
+
+/// Minimum number of lines for a leading comment block to count as a header.
+const MIN_HEADER_LINES: usize = 2;
+
+/// What [`LicenseHeaderDedup::elide`] did to one file's chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderElision {
+    /// No header to elide.
+    Unchanged,
+    /// The file keeps this header and owns it once kept.
+    Owns(String),
+    /// The header was replaced with a pointer to its owner.
+    Elided { bytes_saved: usize, tokens_saved: usize },
+}
+
+/// Headers seen in kept files, keyed by header text, with the file that shows them.
+#[derive(Debug, Default)]
+pub struct LicenseHeaderDedup {
+    owners: HashMap<String, String>,
+}
+
+impl LicenseHeaderDedup {
+    /// Replace the leading header of a file's first chunk when a kept file already shows
+    /// it. When code follows the header in that chunk, `start_line` moves past the header;
+    /// a chunk that is all header becomes the pointer and keeps its range. Either way the
+    /// id and token estimate are recomputed.
+    pub fn elide(&self, file_chunks: &mut [Chunk]) -> HeaderElision {
+        let Some(chunk) = file_chunks.iter_mut().find(|chunk| chunk.start_line == 1) else {
+            return HeaderElision::Unchanged;
+        };
+        let Some(header) = leading_comment_block(&chunk.content, &chunk.language) else {
+            return HeaderElision::Unchanged;
+        };
+        let rest = &chunk.content[header.len()..];
+        let Some(owner) = self.owners.get(&header) else {
+            return HeaderElision::Owns(header);
+        };
+
+        let marker =
+            format!("{} license header (see {})\n", line_comment_prefix(&chunk.language), owner);
+        let content = format!("{marker}{rest}");
+        let tokens = estimate_tokens(&content);
+        let elision = HeaderElision::Elided {
+            bytes_saved: header.len().saturating_sub(marker.len()),
+            tokens_saved: chunk.token_estimate.saturating_sub(tokens),
+        };
+        if !rest.trim().is_empty() {
+            chunk.start_line = header.lines().count() + 1;
+        }
+        chunk.id = stable_hash(&content, &chunk.path, chunk.start_line, chunk.end_line);
+        chunk.token_estimate = tokens;
+        chunk.content = content;
+        chunk.tags.insert("license-header-elided".to_string());
+        elision
+    }
+
+    /// Record the outcome of [`elide`](Self::elide) once `path` is kept in the pack.
+    pub fn keep(&mut self, elision: HeaderElision, path: &str, stats: &mut ScanStats) {
+        match elision {
+            HeaderElision::Unchanged => {}
+            HeaderElision::Owns(header) => {
+                self.owners.entry(header).or_insert_with(|| path.to_string());
+            }
+            HeaderElision::Elided { bytes_saved, tokens_saved } => {
+                stats.license_header_files_elided += 1;
+                stats.license_header_bytes_saved += bytes_saved;
+                stats.license_header_tokens_saved += tokens_saved;
+            }
+        }
+    }
+}
+
+/// The comment lines at the top of `content`, including their line endings, when there
+/// are at least [`MIN_HEADER_LINES`] of them. Files starting with a shebang are skipped.
+fn leading_comment_block(content: &str, language: &str) -> Option<String> {
+    let mut end = 0usize;
+    let mut lines = 0usize;
+    let mut in_block = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if lines == 0 && trimmed.starts_with("#!") {
+            return None;
+        }
+        let is_comment = if in_block {
+            if trimmed.contains("*/") {
+                in_block = false;
+            }
+            true
+        } else if trimmed.starts_with("/*") {
+            in_block = !trimmed.contains("*/");
+            true
+        } else {
+            is_line_comment(trimmed, language)
+        };
+        if !is_comment {
+            break;
+        }
+        end += line.len();
+        lines += 1;
+    }
+    if in_block || lines < MIN_HEADER_LINES {
+        return None;
+    }
+    Some(content[..end].to_string())
+}
+
+fn is_line_comment(trimmed: &str, language: &str) -> bool {
+    match line_comment_prefix(language) {
+        "#" => trimmed.starts_with('#'),
+        "--" => trimmed.starts_with("--"),
+        _ => trimmed.starts_with("//"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeaderElision, LicenseHeaderDedup};
+    use crate::domain::test_support::chunk;
+    use crate::domain::ScanStats;
+    use crate::utils::{estimate_tokens, stable_hash};
+
+    #[test]
+    fn shared_header_is_kept_only_in_the_first_kept_file() {
+        let header = "// Copyright 2024 Example Corp.\n// SPDX-License-Identifier: Apache-2.0\n";
+        let mut dedup = LicenseHeaderDedup::default();
+        let mut stats = ScanStats::default();
+        let mut files = [
+            vec![chunk("src/a.rs", &format!("{header}\npub fn a() {{}}\n"))],
+            vec![chunk("src/b.rs", &format!("{header}\npub fn b() {{}}\n"))],
+            vec![chunk("src/c.rs", &format!("{header}\npub fn c() {{}}\n"))],
+            vec![chunk("src/d.rs", "// Only one comment line\npub fn d() {}\n")],
+            vec![chunk("src/e.rs", header), chunk("src/e.rs", "pub fn e() {}\n")],
+        ];
+        files[4][1].start_line = 3;
+
+        // a.rs is dropped by the budget, so b.rs becomes the header's owner.
+        let dropped = dedup.elide(&mut files[0]);
+        assert_eq!(dropped, HeaderElision::Owns(header.to_string()));
+        for file in &mut files[1..] {
+            let elision = dedup.elide(file);
+            let path = file[0].path.clone();
+            dedup.keep(elision, &path, &mut stats);
+        }
+
+        assert!(files[1][0].content.starts_with(header));
+        let c = &files[2][0];
+        assert_eq!(c.content, "// license header (see src/b.rs)\n\npub fn c() {}\n");
+        assert_eq!((c.start_line, c.end_line), (3, 4));
+        assert_eq!(c.id, stable_hash(&c.content, "src/c.rs", 3, 4));
+        assert_eq!(c.token_estimate, estimate_tokens(&c.content));
+        assert!(files[3][0].content.starts_with("// Only one comment line"));
+        let e = &files[4][0];
+        assert_eq!(e.content, "// license header (see src/b.rs)\n");
+        assert_eq!((e.start_line, e.end_line), (1, 2));
+        assert_eq!(files[4][1].content, "pub fn e() {}\n");
+        assert_eq!(stats.license_header_files_elided, 2);
+        assert!(stats.license_header_bytes_saved > 0);
+        assert!(stats.license_header_tokens_saved > 0);
+    }
+}
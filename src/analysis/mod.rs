@@ -1,4 +1,10 @@
 //! Higher-level analyses for contribution workflows.
 
+pub mod assets;
 pub mod async_boundary;
+pub mod codeowners;
+pub mod focus;
+pub mod git_status;
+pub mod license_header;
 pub mod pr;
+pub mod symbol_scope;
@@ -1,8 +1,13 @@
 //! PR-oriented context synthesis.
 
-use crate::domain::{Chunk, FileInfo};
+use crate::chunk::chunk_content;
+use crate::domain::{get_language, Chunk, FileInfo};
 use crate::rank::{dependency_graph, symbol_definitions};
-use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::utils::EncodingOverrides;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct PrContextReport {
@@ -12,6 +17,7 @@ pub struct PrContextReport {
     pub feature_flags: Vec<FeatureFlagBoundary>,
     pub trait_impls: Vec<TraitImplEdge>,
     pub error_flows: Vec<ErrorFlowSignal>,
+    pub symbol_diff: Option<SymbolDiff>,
     pub graph_available: bool,
 }
 
@@ -60,10 +66,25 @@ pub struct ErrorFlowSignal {
     pub chunk_id: String,
 }
 
+/// Definitions added, removed, or modified relative to a base git ref.
+#[derive(Debug, Clone)]
+pub struct SymbolDiff {
+    pub base_ref: String,
+    pub changes: Vec<SymbolChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolChange {
+    pub kind: &'static str,
+    pub path: String,
+    pub symbol: String,
+}
+
 pub fn build_pr_context(
     files: &[FileInfo],
     chunks: &[Chunk],
     task_query: Option<&str>,
+    symbol_diff: Option<SymbolDiff>,
     graph_available: bool,
 ) -> PrContextReport {
     let mut touch_points = Vec::new();
@@ -260,10 +281,139 @@ pub fn build_pr_context(
         feature_flags,
         trait_impls,
         error_flows,
+        symbol_diff,
         graph_available,
     }
 }
 
+/// Compare definition chunks of files changed since `merge_base` (resolved from `base_ref`
+/// by [`crate::fetch::changed::since_merge_base`]) against the working tree. Only files in
+/// `pack_paths` are diffed, so excluded or dropped files never show up in the report.
+///
+/// Each `def:`/`type:` symbol is hashed over the content of the chunks that define it, so a
+/// symbol is `modified` when its definition text changed and `added`/`removed` when it only
+/// exists on one side.
+pub fn diff_symbols_since(
+    root: &Path,
+    base_ref: &str,
+    merge_base: git2::Oid,
+    chunk_tokens: usize,
+    pack_paths: &HashSet<&str>,
+    encodings: &EncodingOverrides,
+) -> Result<SymbolDiff> {
+    let repo = git2::Repository::discover(root)
+        .with_context(|| format!("--since requires a git repository at {}", root.display()))?;
+    let workdir = repo.workdir().context("--since is not supported for bare repositories")?;
+    let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let prefix = root.strip_prefix(&workdir).unwrap_or(Path::new("")).to_path_buf();
+
+    let base_tree = repo
+        .find_commit(merge_base)
+        .and_then(|commit| commit.tree())
+        .with_context(|| format!("failed to read the merge base of --since ref '{base_ref}'"))?;
+    let mut diff_options = git2::DiffOptions::new();
+    diff_options.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut diff_options))?;
+
+    let mut changes = Vec::new();
+    for delta in diff.deltas() {
+        let Some(repo_path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        let Ok(relative) = repo_path.strip_prefix(&prefix) else {
+            continue;
+        };
+        let relative_path = relative.to_string_lossy().replace('\\', "/");
+        if !pack_paths.contains(relative_path.as_str()) {
+            continue;
+        }
+
+        let old_content = if delta.old_file().id().is_zero() {
+            String::new()
+        } else {
+            repo.find_blob(delta.old_file().id())
+                .map(|blob| encodings.decode(&relative_path, blob.content()))
+                .unwrap_or_default()
+        };
+        let new_content = std::fs::read(workdir.join(repo_path))
+            .map(|bytes| encodings.decode(&relative_path, &bytes))
+            .unwrap_or_default();
+
+        let before = symbol_hashes(&relative_path, &old_content, chunk_tokens);
+        let after = symbol_hashes(&relative_path, &new_content, chunk_tokens);
+        for (symbol, hash) in &after {
+            let kind = match before.get(symbol) {
+                None => "added",
+                Some(old_hash) if old_hash != hash => "modified",
+                Some(_) => continue,
+            };
+            changes.push(SymbolChange {
+                kind,
+                path: relative_path.clone(),
+                symbol: symbol.clone(),
+            });
+        }
+        for symbol in before.keys().filter(|symbol| !after.contains_key(*symbol)) {
+            changes.push(SymbolChange {
+                kind: "removed",
+                path: relative_path.clone(),
+                symbol: symbol.clone(),
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.symbol.cmp(&b.symbol)));
+    Ok(SymbolDiff { base_ref: base_ref.to_string(), changes })
+}
+
+fn symbol_hashes(
+    relative_path: &str,
+    content: &str,
+    chunk_tokens: usize,
+) -> BTreeMap<String, String> {
+    if content.is_empty() {
+        return BTreeMap::new();
+    }
+    let path = PathBuf::from(relative_path);
+    let extension =
+        path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let filename =
+        path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let info = FileInfo {
+        path: path.clone(),
+        relative_path: relative_path.to_string(),
+        size_bytes: content.len() as u64,
+        language: get_language(&extension, &filename),
+        extension,
+        id: relative_path.to_string(),
+        priority: 0.0,
+        token_estimate: 0,
+        tags: BTreeSet::new(),
+        is_readme: false,
+        is_config: false,
+        is_doc: false,
+        modified_at: None,
+        priority_reason: None,
+    };
+    let Ok(chunks) = chunk_content(&info, content, chunk_tokens, 0) else {
+        return BTreeMap::new();
+    };
+
+    let mut hashers: BTreeMap<String, Sha256> = BTreeMap::new();
+    for chunk in &chunks {
+        for symbol in
+            chunk.tags.iter().filter(|tag| tag.starts_with("def:") || tag.starts_with("type:"))
+        {
+            hashers.entry(symbol.clone()).or_default().update(chunk.content.trim_end().as_bytes());
+        }
+    }
+    hashers
+        .into_iter()
+        .map(|(symbol, hasher)| (symbol, format!("{:x}", hasher.finalize())))
+        .collect()
+}
+
 fn extract_feature_names(content: &str) -> Vec<String> {
     let mut out = BTreeSet::new();
     for line in content.lines() {
@@ -325,7 +475,13 @@ fn extract_error_flow_signals(content: &str) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_error_flow_signals, extract_feature_names, extract_trait_impls};
+    use super::{
+        diff_symbols_since, extract_error_flow_signals, extract_feature_names, extract_trait_impls,
+        SymbolChange,
+    };
+    use crate::utils::EncodingOverrides;
+    use std::collections::HashSet;
+    use std::path::Path;
 
     #[test]
     fn extracts_feature_flags_from_cfg_lines() {
@@ -350,4 +506,65 @@ mod tests {
         assert!(signals.iter().any(|s| s == "result return"));
         assert!(signals.iter().any(|s| s == "anyhow context"));
     }
+
+    fn commit_all(repo: &git2::Repository, message: &str) {
+        let mut index = repo.index().expect("index");
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).expect("add");
+        index.write().expect("write index");
+        let tree = repo.find_tree(index.write_tree().expect("tree")).expect("find tree");
+        let sig = git2::Signature::now("test", "test@example.com").expect("signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).expect("commit");
+    }
+
+    #[test]
+    fn symbol_diff_reports_modified_function_body() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let root = tmp.path();
+        let repo = git2::Repository::init(root).expect("init");
+        let write = |path: &str, content: &str| {
+            std::fs::write(root.join(path), content).expect("write");
+        };
+        write(
+            "calc.py",
+            "def add(a, b):\n    return a + b\n\n\ndef sub(a, b):\n    return a - b\n",
+        );
+        write("old.py", "def legacy():\n    pass\n");
+        commit_all(&repo, "base");
+
+        write(
+            "calc.py",
+            "def add(a, b):\n    return b + a\n\n\ndef mul(a, b):\n    return a * b\n",
+        );
+        std::fs::remove_file(root.join("old.py")).expect("remove");
+        write("ignored.py", "def skipped():\n    pass\n");
+
+        let base = repo.head().and_then(|head| head.peel_to_commit()).expect("base").id();
+        // Deleted and unselected files are not in the pack.
+        let pack_paths = HashSet::from(["calc.py"]);
+        let diff = diff_symbols_since(
+            Path::new(root),
+            "HEAD",
+            base,
+            800,
+            &pack_paths,
+            &EncodingOverrides::default(),
+        )
+        .expect("diff");
+        assert_eq!(diff.base_ref, "HEAD");
+        let change = |kind, path: &str, symbol: &str| SymbolChange {
+            kind,
+            path: path.to_string(),
+            symbol: symbol.to_string(),
+        };
+        assert_eq!(
+            diff.changes,
+            vec![
+                change("modified", "calc.py", "def:add"),
+                change("added", "calc.py", "def:mul"),
+                change("removed", "calc.py", "def:sub"),
+            ]
+        );
+    }
 }
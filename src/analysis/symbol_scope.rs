@@ -0,0 +1,129 @@
+//! Symbol-scoped file selection (`--symbol`).
+//!
+//! Keeps the files that define or mention a symbol, plus their direct import neighbors.
+
+use crate::domain::FileInfo;
+use crate::rank::{extract_import_references, resolve_reference};
+use crate::utils::{read_file_safe, EncodingOverrides};
+use regex::Regex;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SymbolScope {
+    /// Files defining or mentioning the symbol
+    pub matched: BTreeSet<String>,
+    /// Files importing, or imported by, a matched file
+    pub neighbors: BTreeSet<String>,
+}
+
+impl SymbolScope {
+    pub fn contains(&self, path: &str) -> bool {
+        self.matched.contains(path) || self.neighbors.contains(path)
+    }
+}
+
+/// Find the files among `files` that touch `symbol`.
+///
+/// A file matches when it mentions the symbol as a whole word, or when the index at
+/// `index_db` records a definition of it there (scoped methods such as `Type.symbol`
+/// included). Neighbors are one import hop away in either direction.
+pub fn scope_files_by_symbol(
+    files: &[FileInfo],
+    symbol: &str,
+    index_db: Option<&Path>,
+    encodings: &EncodingOverrides,
+) -> SymbolScope {
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(symbol))).expect("escaped symbol");
+    let contents: HashMap<&str, String> = files
+        .iter()
+        .filter_map(|f| {
+            let encoding = encodings.encoding_for(&f.relative_path);
+            let (content, _) = read_file_safe(&f.path, None, encoding).ok()?;
+            Some((f.relative_path.as_str(), content))
+        })
+        .collect();
+
+    let mut scope = SymbolScope::default();
+    for (path, content) in &contents {
+        if word.is_match(content) {
+            scope.matched.insert(path.to_string());
+        }
+    }
+    let known_files: HashSet<String> = files.iter().map(|f| f.relative_path.clone()).collect();
+    if let Some(db) = index_db {
+        scope.matched.extend(
+            indexed_definition_files(db, symbol).into_iter().filter(|p| known_files.contains(p)),
+        );
+    }
+
+    for (path, content) in &contents {
+        let is_match = scope.matched.contains(*path);
+        for reference in extract_import_references(content) {
+            for target in resolve_reference(&reference, path, &known_files) {
+                if target == *path {
+                    continue;
+                }
+                if is_match && !scope.matched.contains(&target) {
+                    scope.neighbors.insert(target);
+                } else if !is_match && scope.matched.contains(&target) {
+                    scope.neighbors.insert(path.to_string());
+                }
+            }
+        }
+    }
+    scope
+}
+
+/// Files the index records as defining `symbol`; empty when there is no usable index.
+fn indexed_definition_files(db: &Path, symbol: &str) -> Vec<String> {
+    let lookup = || -> rusqlite::Result<Vec<String>> {
+        let conn = Connection::open(db)?;
+        let symbol = symbol.to_ascii_lowercase();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT file_path FROM symbols WHERE symbol = ?1 OR symbol GLOB ?2",
+        )?;
+        let rows = stmt.query_map(params![symbol, format!("*.{symbol}")], |row| row.get(0))?;
+        rows.collect()
+    };
+    lookup().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scope_files_by_symbol;
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use crate::utils::EncodingOverrides;
+    use std::fs;
+
+    #[test]
+    fn import_neighbors_of_matching_files_are_kept() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let sources = [
+            ("app/tokens.py", "from app.store import save\n\ndef refresh_token():\n    save()\n"),
+            ("app/store.py", "def save():\n    pass\n"),
+            ("app/views.py", "from app.tokens import refresh_token\n"),
+            ("app/billing.py", "def charge():\n    pass\n"),
+        ];
+        fs::create_dir_all(dir.path().join("app")).expect("create app dir");
+        let files: Vec<FileInfo> = sources
+            .iter()
+            .map(|(path, content)| {
+                let abs = dir.path().join(path);
+                fs::write(&abs, content).expect("write source");
+                FileInfo { path: abs, size_bytes: content.len() as u64, ..file_info(path) }
+            })
+            .collect();
+
+        let scope =
+            scope_files_by_symbol(&files, "refresh_token", None, &EncodingOverrides::default());
+
+        let matched: Vec<&str> = scope.matched.iter().map(String::as_str).collect();
+        assert_eq!(matched, vec!["app/tokens.py", "app/views.py"]);
+        let neighbors: Vec<&str> = scope.neighbors.iter().map(String::as_str).collect();
+        assert_eq!(neighbors, vec!["app/store.py"]);
+        assert!(!scope.contains("app/billing.py"));
+    }
+}
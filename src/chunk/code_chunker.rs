@@ -1,15 +1,149 @@
 //! Code-aware chunking.
 
 use crate::chunk::line_chunker::LineChunker;
-use crate::domain::{Chunk, FileInfo};
+use crate::domain::{
+    default_max_chunk_lines, default_tree_sitter_max_bytes, Chunk, Config, FileInfo, TagQuery,
+    DEFINITION_KINDS,
+};
 use crate::utils::{estimate_tokens, stable_hash};
 use std::collections::{BTreeSet, HashMap};
-use tree_sitter::{Language, Parser};
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Language, Parser, Query, QueryCursor};
 
-pub struct CodeChunker;
+pub struct CodeChunker {
+    limits: ParseLimits,
+    kinds: DefinitionKinds,
+    max_lines: usize,
+}
 
 type SymbolTagsByBoundary = HashMap<usize, BTreeSet<String>>;
 
+/// Rows inside a definition where it may be split when it exceeds the chunk size.
+type NestedSplitsByBoundary = HashMap<usize, Vec<usize>>;
+
+/// Tag on chunks of files whose tree-sitter parse contained errors.
+pub const PARSE_FALLBACK_TAG: &str = "parse-fallback";
+
+/// Tag on chunks of files too large or too slow to parse, which were line-chunked.
+pub const PARSE_SKIPPED_TAG: &str = "parse-skipped";
+
+/// Size and time guards on tree-sitter parsing, so pathological inputs cannot dominate
+/// runtime.
+///
+/// The timeout is off by default: a file that times out depends on machine load, so only
+/// the size guard keeps the same config chunking a file the same way between runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Files with more bytes than this are not parsed
+    pub max_bytes: usize,
+    /// Parse timeout in microseconds (0 = no limit)
+    pub timeout_micros: u64,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self { max_bytes: default_tree_sitter_max_bytes(), timeout_micros: 0 }
+    }
+}
+
+impl ParseLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_bytes: config.tree_sitter_max_bytes,
+            timeout_micros: config.tree_sitter_timeout_ms.saturating_mul(1000),
+        }
+    }
+
+    /// A parser for `language` with the timeout applied, or `None` when `content` is over
+    /// the size limit or the grammar fails to load.
+    fn parser(&self, language: &Language, content: &str) -> Option<Parser> {
+        if content.len() > self.max_bytes {
+            return None;
+        }
+        let mut parser = Parser::new();
+        parser.set_language(language).ok()?;
+        parser.set_timeout_micros(self.timeout_micros);
+        Some(parser)
+    }
+}
+
+/// Which definition kinds (see [`DEFINITION_KINDS`]) become tree-sitter chunk boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefinitionKinds {
+    /// Bit `i` set when `DEFINITION_KINDS[i]` is enabled
+    enabled: u8,
+}
+
+impl Default for DefinitionKinds {
+    fn default() -> Self {
+        Self { enabled: (1 << DEFINITION_KINDS.len()) - 1 }
+    }
+}
+
+impl DefinitionKinds {
+    /// Kinds named in `definition_kinds`; unknown names are ignored (validation reports them).
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_names(&config.definition_kinds)
+    }
+
+    pub fn from_names(names: &[String]) -> Self {
+        let enabled = names
+            .iter()
+            .filter_map(|name| DEFINITION_KINDS.iter().position(|kind| kind == name))
+            .fold(0, |bits, idx| bits | (1 << idx));
+        Self { enabled }
+    }
+
+    fn allows(self, kind: &str) -> bool {
+        DEFINITION_KINDS
+            .iter()
+            .position(|k| *k == kind)
+            .is_some_and(|idx| self.enabled & (1 << idx) != 0)
+    }
+}
+
+/// Definition kind of a top-level tree-sitter node, across grammars.
+fn definition_kind(content: &str, node: tree_sitter::Node<'_>) -> Option<&'static str> {
+    Some(match node.kind() {
+        "function_definition" | "function_item" | "function_declaration" => "function",
+        "method_definition" | "method_declaration" => "method",
+        "class_definition"
+        | "class_declaration"
+        | "struct_item"
+        | "enum_item"
+        | "trait_item"
+        | "interface_declaration"
+        | "type_alias_declaration"
+        | "type_declaration" => "type",
+        "impl_item" => "impl",
+        "mod_item" => "module",
+        "const_declaration" => "const",
+        "var_declaration" => "var",
+        "lexical_declaration" => {
+            let keyword = node.child(0)?.utf8_text(content.as_bytes()).ok()?;
+            if keyword == "const" {
+                "const"
+            } else {
+                "var"
+            }
+        }
+        "decorated_definition" => {
+            return definition_kind(content, node.child_by_field_name("definition")?)
+        }
+        _ => return None,
+    })
+}
+
+enum TreeSitterChunks {
+    /// No grammar for the language.
+    Unsupported,
+    /// The parse tree contained errors; chunks use the boundaries tree-sitter recovered.
+    ParseErrors(Vec<Chunk>),
+    /// Over the size limit, or the parse timed out.
+    Skipped,
+    Chunks(Vec<Chunk>),
+}
+
 pub fn supported_tree_sitter_languages() -> &'static [&'static str] {
     &["python", "rust", "javascript", "typescript", "go"]
 }
@@ -22,7 +156,44 @@ impl Default for CodeChunker {
 
 impl CodeChunker {
     pub fn new() -> Self {
-        Self
+        Self {
+            limits: ParseLimits::default(),
+            kinds: DefinitionKinds::default(),
+            max_lines: default_max_chunk_lines(),
+        }
+    }
+
+    /// Cap the lines per chunk; definitions spanning more are split even when their
+    /// tokens fit.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// Replace the tree-sitter size and time guards.
+    pub fn with_parse_limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Restrict which definition kinds start a chunk.
+    pub fn with_definition_kinds(mut self, kinds: DefinitionKinds) -> Self {
+        self.kinds = kinds;
+        self
+    }
+
+    /// Like [`CodeChunker::chunk`], additionally applying user-defined tag queries.
+    pub fn chunk_with_tag_queries(
+        &self,
+        file_info: &FileInfo,
+        content: &str,
+        max_tokens: usize,
+        overlap_tokens: usize,
+        tag_queries: &[TagQuery],
+    ) -> Vec<Chunk> {
+        let mut chunks = self.chunk(file_info, content, max_tokens, overlap_tokens);
+        apply_tag_queries(file_info, content, &mut chunks, tag_queries, &self.limits);
+        chunks
     }
 
     pub fn chunk(
@@ -32,146 +203,293 @@ impl CodeChunker {
         max_tokens: usize,
         overlap_tokens: usize,
     ) -> Vec<Chunk> {
-        if let Some(chunks) = chunk_with_tree_sitter(file_info, content, max_tokens, overlap_tokens)
-        {
-            if !chunks.is_empty() {
+        let size = ChunkSize { max_tokens, max_lines: self.max_lines };
+        let parse_errors = match chunk_with_tree_sitter(
+            file_info,
+            content,
+            size,
+            overlap_tokens,
+            &self.limits,
+            self.kinds,
+        ) {
+            TreeSitterChunks::Chunks(chunks) if !chunks.is_empty() => return chunks,
+            TreeSitterChunks::ParseErrors(mut chunks) if !chunks.is_empty() => {
+                for chunk in &mut chunks {
+                    chunk.tags.insert(PARSE_FALLBACK_TAG.to_string());
+                }
+                return chunks;
+            }
+            TreeSitterChunks::ParseErrors(_) => true,
+            TreeSitterChunks::Skipped => {
+                let mut chunks =
+                    size.line_chunker().chunk(file_info, content, max_tokens, overlap_tokens);
+                for chunk in &mut chunks {
+                    chunk.tags.insert(PARSE_SKIPPED_TAG.to_string());
+                }
                 return chunks;
             }
+            TreeSitterChunks::Chunks(_) | TreeSitterChunks::Unsupported => false,
+        };
+
+        let mut chunks = chunk_by_patterns(file_info, content, size, overlap_tokens);
+        if parse_errors {
+            for chunk in &mut chunks {
+                chunk.tags.insert(PARSE_FALLBACK_TAG.to_string());
+            }
         }
+        chunks
+    }
+}
+
+/// Token and line caps on one chunk.
+#[derive(Debug, Clone, Copy)]
+struct ChunkSize {
+    max_tokens: usize,
+    max_lines: usize,
+}
 
-        let lines: Vec<&str> = content.split_inclusive('\n').collect();
-        if lines.is_empty() {
-            return Vec::new();
+impl ChunkSize {
+    fn fits(self, text: &str, lines: usize) -> bool {
+        lines <= self.max_lines && estimate_tokens(text) <= self.max_tokens
+    }
+
+    fn line_chunker(self) -> LineChunker {
+        LineChunker::new().with_max_lines(self.max_lines)
+    }
+}
+
+/// Heuristic chunking at regex-detected definition boundaries.
+fn chunk_by_patterns(
+    file_info: &FileInfo,
+    content: &str,
+    size: ChunkSize,
+    overlap_tokens: usize,
+) -> Vec<Chunk> {
+    let max_tokens = size.max_tokens;
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let boundaries = find_definition_boundaries(&lines, &file_info.language);
+    let line_chunker = size.line_chunker();
+    if boundaries.len() <= 1 {
+        return line_chunker.chunk(file_info, content, max_tokens, overlap_tokens);
+    }
+
+    let symbol_tags = find_boundary_symbol_tags(&lines, &file_info.language, &boundaries);
+    let mut chunks = Vec::new();
+
+    for window in boundaries.windows(2) {
+        let start = window[0];
+        let end = window[1];
+        if end <= start || start >= lines.len() {
+            continue;
         }
 
-        let boundaries = find_definition_boundaries(&lines, &file_info.language);
-        if boundaries.len() <= 1 {
-            return LineChunker::new().chunk(file_info, content, max_tokens, overlap_tokens);
+        let section_content = lines[start..end.min(lines.len())].join("");
+        if section_content.trim().is_empty() {
+            continue;
         }
 
-        let symbol_tags = find_boundary_symbol_tags(&lines, &file_info.language, &boundaries);
-        let mut chunks = Vec::new();
-        let line_chunker = LineChunker::new();
+        let mut section_tags = file_info.tags.clone();
+        section_tags
+            .extend(extract_symbol_tags_from_section(&file_info.language, &section_content));
+        if let Some(boundary_tags) = symbol_tags.get(&start) {
+            section_tags.extend(boundary_tags.iter().cloned());
+        }
 
-        for window in boundaries.windows(2) {
-            let start = window[0];
-            let end = window[1];
-            if end <= start || start >= lines.len() {
-                continue;
+        if size.fits(&section_content, end.min(lines.len()) - start) {
+            chunks.push(Chunk {
+                id: stable_hash(&section_content, &file_info.relative_path, start + 1, end),
+                path: file_info.relative_path.clone(),
+                language: file_info.language.clone(),
+                start_line: start + 1,
+                end_line: end,
+                token_estimate: estimate_tokens(&section_content),
+                content: section_content,
+                priority: file_info.priority,
+                tags: section_tags,
+                doc: None,
+                byte_range: None,
+            });
+        } else {
+            let nested =
+                line_chunker.chunk(file_info, &section_content, max_tokens, overlap_tokens);
+            for mut chunk in nested {
+                chunk.start_line += start;
+                chunk.end_line += start;
+                chunk.id =
+                    stable_hash(&chunk.content, &chunk.path, chunk.start_line, chunk.end_line);
+                chunk.tags.extend(section_tags.iter().cloned());
+                chunks.push(chunk);
             }
+        }
+    }
 
-            let section_content = lines[start..end.min(lines.len())].join("");
-            if section_content.trim().is_empty() {
-                continue;
-            }
+    if chunks.is_empty() {
+        return line_chunker.chunk(file_info, content, max_tokens, overlap_tokens);
+    }
 
-            let mut section_tags = file_info.tags.clone();
-            section_tags
-                .extend(extract_symbol_tags_from_section(&file_info.language, &section_content));
-            if let Some(boundary_tags) = symbol_tags.get(&start) {
-                section_tags.extend(boundary_tags.iter().cloned());
-            }
+    chunks.sort_by_key(|a| a.start_line);
+    chunks
+}
 
-            if estimate_tokens(&section_content) <= max_tokens {
-                chunks.push(Chunk {
-                    id: stable_hash(&section_content, &file_info.relative_path, start + 1, end),
-                    path: file_info.relative_path.clone(),
-                    language: file_info.language.clone(),
-                    start_line: start + 1,
-                    end_line: end,
-                    token_estimate: estimate_tokens(&section_content),
-                    content: section_content,
-                    priority: file_info.priority,
-                    tags: section_tags,
-                });
-            } else {
-                let nested =
-                    line_chunker.chunk(file_info, &section_content, max_tokens, overlap_tokens);
-                for mut chunk in nested {
-                    chunk.start_line += start;
-                    chunk.end_line += start;
-                    chunk.id =
-                        stable_hash(&chunk.content, &chunk.path, chunk.start_line, chunk.end_line);
-                    chunk.tags.extend(section_tags.iter().cloned());
-                    chunks.push(chunk);
+/// Grammar for a file: TSX files need the TSX variant of the TypeScript grammar.
+fn tree_sitter_language_for_file(file_info: &FileInfo) -> Option<Language> {
+    if file_info.language == "typescript" && file_info.extension.eq_ignore_ascii_case(".tsx") {
+        return Some(tree_sitter_typescript::LANGUAGE_TSX.into());
+    }
+    tree_sitter_language(&file_info.language)
+}
+
+fn tree_sitter_language(name: &str) -> Option<Language> {
+    Some(match name {
+        "python" => tree_sitter_python::LANGUAGE.into(),
+        "rust" => tree_sitter_rust::LANGUAGE.into(),
+        "javascript" => tree_sitter_javascript::LANGUAGE.into(),
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        "go" => tree_sitter_go::LANGUAGE.into(),
+        _ => return None,
+    })
+}
+
+/// Check that a tag query compiles against its language's grammar.
+pub fn validate_tag_query(language: &str, query: &str) -> Result<(), String> {
+    let grammar = tree_sitter_language(language)
+        .ok_or_else(|| format!("no tree-sitter grammar for language '{language}'"))?;
+    Query::new(&grammar, query).map(|_| ()).map_err(|err| err.to_string())
+}
+
+/// Run user tag queries for the file's language and tag each chunk containing a capture
+/// with `<tag_prefix>:<captured text>`. Invalid queries are skipped with a warning.
+fn apply_tag_queries(
+    file_info: &FileInfo,
+    content: &str,
+    chunks: &mut [Chunk],
+    tag_queries: &[TagQuery],
+    limits: &ParseLimits,
+) {
+    let queries: Vec<&TagQuery> =
+        tag_queries.iter().filter(|q| q.language == file_info.language).collect();
+    if queries.is_empty() || chunks.is_empty() {
+        return;
+    }
+    let Some(language) = tree_sitter_language_for_file(file_info) else {
+        return;
+    };
+    let Some(mut parser) = limits.parser(&language, content) else {
+        return;
+    };
+    let Some(tree) = parser.parse(content, None) else {
+        return;
+    };
+
+    for tag_query in queries {
+        let query = match Query::new(&language, &tag_query.query) {
+            Ok(query) => query,
+            Err(err) => {
+                tracing::warn!(
+                    "Skipping invalid tag query for {} ({}): {}",
+                    tag_query.language,
+                    tag_query.tag_prefix,
+                    err
+                );
+                continue;
+            }
+        };
+        let mut cursor = QueryCursor::new();
+        let mut captures = cursor.captures(&query, tree.root_node(), content.as_bytes());
+        while let Some((found, capture_idx)) = captures.next() {
+            let node = found.captures[*capture_idx].node;
+            let Some(text) = node
+                .utf8_text(content.as_bytes())
+                .ok()
+                .and_then(|t| t.lines().next())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+            else {
+                continue;
+            };
+            let line = node.start_position().row + 1;
+            let tag = format!("{}:{}", tag_query.tag_prefix, text);
+            for chunk in chunks.iter_mut() {
+                if chunk.start_line <= line && line <= chunk.end_line {
+                    chunk.tags.insert(tag.clone());
                 }
             }
         }
-
-        if chunks.is_empty() {
-            return LineChunker::new().chunk(file_info, content, max_tokens, overlap_tokens);
-        }
-
-        chunks.sort_by(|a, b| a.start_line.cmp(&b.start_line));
-        chunks
     }
 }
 
 fn chunk_with_tree_sitter(
     file_info: &FileInfo,
     content: &str,
-    max_tokens: usize,
+    size: ChunkSize,
     overlap_tokens: usize,
-) -> Option<Vec<Chunk>> {
-    let (language, definition_kinds): (Language, &[&str]) = match file_info.language.as_str() {
-        "python" => (
-            tree_sitter_python::LANGUAGE.into(),
-            &["function_definition", "class_definition", "decorated_definition"],
-        ),
-        "rust" => (
-            tree_sitter_rust::LANGUAGE.into(),
-            &["function_item", "impl_item", "struct_item", "enum_item", "trait_item", "mod_item"],
-        ),
-        "javascript" => (
-            tree_sitter_javascript::LANGUAGE.into(),
-            &[
-                "function_declaration",
-                "class_declaration",
-                "method_definition",
-                "lexical_declaration",
-            ],
-        ),
-        "typescript" => (
-            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-            &[
-                "function_declaration",
-                "class_declaration",
-                "method_definition",
-                "interface_declaration",
-                "type_alias_declaration",
-                "lexical_declaration",
-            ],
-        ),
-        "go" => (
-            tree_sitter_go::LANGUAGE.into(),
-            &[
-                "function_declaration",
-                "method_declaration",
-                "type_declaration",
-                "const_declaration",
-                "var_declaration",
-            ],
-        ),
-        _ => return None,
+    limits: &ParseLimits,
+    kinds: DefinitionKinds,
+) -> TreeSitterChunks {
+    let definition_kinds: &[&str] = match file_info.language.as_str() {
+        "python" => &["function_definition", "class_definition", "decorated_definition"],
+        "rust" => {
+            &["function_item", "impl_item", "struct_item", "enum_item", "trait_item", "mod_item"]
+        }
+        "javascript" => &[
+            "function_declaration",
+            "class_declaration",
+            "method_definition",
+            "lexical_declaration",
+        ],
+        "typescript" => &[
+            "function_declaration",
+            "class_declaration",
+            "method_definition",
+            "interface_declaration",
+            "type_alias_declaration",
+            "lexical_declaration",
+        ],
+        "go" => &[
+            "function_declaration",
+            "method_declaration",
+            "type_declaration",
+            "const_declaration",
+            "var_declaration",
+        ],
+        _ => return TreeSitterChunks::Unsupported,
+    };
+    let Some(language) = tree_sitter_language_for_file(file_info) else {
+        return TreeSitterChunks::Unsupported;
     };
 
-    let mut parser = Parser::new();
-    parser.set_language(&language).ok()?;
-
-    let tree = parser.parse(content, None)?;
+    let Some(mut parser) = limits.parser(&language, content) else {
+        return TreeSitterChunks::Skipped;
+    };
+    // With a grammar loaded, `parse` only returns `None` when the timeout is hit.
+    let Some(tree) = parser.parse(content, None) else {
+        return TreeSitterChunks::Skipped;
+    };
     let root = tree.root_node();
+    // Error recovery keeps the well-formed definitions around a syntax error as top-level
+    // nodes, so their boundaries are still used; the chunks are only tagged.
+    let wrap =
+        if root.has_error() { TreeSitterChunks::ParseErrors } else { TreeSitterChunks::Chunks };
 
     let lines: Vec<&str> = content.split_inclusive('\n').collect();
     if lines.is_empty() {
-        return Some(Vec::new());
+        return wrap(Vec::new());
     }
 
     let mut boundaries = vec![0usize];
     let mut symbol_tags: SymbolTagsByBoundary = HashMap::new();
+    let mut docs: HashMap<usize, String> = HashMap::new();
+    let mut nested_splits: NestedSplitsByBoundary = HashMap::new();
     for i in 0..root.named_child_count() {
         if let Some(child) = root.named_child(i) {
             let kind = child.kind();
-            if definition_kinds.contains(&kind) {
+            if definition_kinds.contains(&kind)
+                && definition_kind(content, child).is_some_and(|k| kinds.allows(k))
+            {
                 let row = child.start_position().row;
                 if row > 0 {
                     boundaries.push(row);
@@ -181,6 +499,14 @@ fn chunk_with_tree_sitter(
                 if !tags.is_empty() {
                     symbol_tags.entry(row).or_default().extend(tags);
                 }
+                if let Some(doc) = extract_doc_comment(content, file_info.language.as_str(), child)
+                {
+                    docs.insert(row, doc);
+                }
+                let splits = nested_split_rows(content, child, size);
+                if !splits.is_empty() {
+                    nested_splits.insert(row, splits);
+                }
             }
         }
     }
@@ -189,17 +515,132 @@ fn chunk_with_tree_sitter(
     boundaries.dedup();
 
     if boundaries.len() <= 2 {
-        return Some(Vec::new());
+        return wrap(Vec::new());
     }
 
-    Some(chunk_by_boundaries(
+    let mut chunks = chunk_by_boundaries(
         file_info,
         &lines,
         &boundaries,
         &symbol_tags,
-        max_tokens,
+        &nested_splits,
+        size,
         overlap_tokens,
-    ))
+    );
+    for chunk in &mut chunks {
+        if let Some(doc) = docs.get(&(chunk.start_line - 1)) {
+            chunk.doc = Some(doc.clone());
+        }
+    }
+    wrap(chunks)
+}
+
+/// Leading documentation for a top-level definition: a Python docstring, Rust `///` lines,
+/// or a JS/TS `/** */` block directly above the node.
+fn extract_doc_comment(
+    content: &str,
+    language: &str,
+    node: tree_sitter::Node<'_>,
+) -> Option<String> {
+    let text_of = |n: tree_sitter::Node<'_>| n.utf8_text(content.as_bytes()).ok();
+    let doc = match language {
+        "python" => {
+            let definition = if node.kind() == "decorated_definition" {
+                node.child_by_field_name("definition")?
+            } else {
+                node
+            };
+            let first = definition.child_by_field_name("body")?.named_child(0)?;
+            let string = first.named_child(0).filter(|_| first.kind() == "expression_statement")?;
+            if string.kind() != "string" {
+                return None;
+            }
+            let raw = text_of(string)?.trim_start_matches(['r', 'u', 'b', 'R', 'U', 'B']);
+            let quote = ["\"\"\"", "'''", "\"", "'"].into_iter().find(|q| raw.starts_with(q))?;
+            let inner = raw.strip_prefix(quote)?.strip_suffix(quote)?;
+            inner.lines().map(str::trim).collect::<Vec<_>>().join("\n")
+        }
+        "rust" => {
+            let mut lines = Vec::new();
+            let mut next_row = node.start_position().row;
+            let mut sibling = node.prev_sibling();
+            while let Some(prev) = sibling {
+                if prev.end_position().row + 1 < next_row {
+                    break;
+                }
+                match prev.kind() {
+                    "attribute_item" => {}
+                    "line_comment" => {
+                        let line = text_of(prev)?.trim_end();
+                        let Some(doc_line) = line.strip_prefix("///") else {
+                            break;
+                        };
+                        lines.push(doc_line.strip_prefix(' ').unwrap_or(doc_line).to_string());
+                    }
+                    _ => break,
+                }
+                next_row = prev.start_position().row;
+                sibling = prev.prev_sibling();
+            }
+            lines.reverse();
+            lines.join("\n")
+        }
+        "javascript" | "typescript" => {
+            let prev = node.prev_sibling().filter(|prev| prev.kind() == "comment")?;
+            if prev.end_position().row + 1 < node.start_position().row {
+                return None;
+            }
+            let inner = text_of(prev)?.strip_prefix("/**")?.strip_suffix("*/")?;
+            inner
+                .lines()
+                .map(|line| line.trim().trim_start_matches('*').trim())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        _ => return None,
+    };
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// Start rows of the members or statements directly inside an oversized definition's
+/// body, descending into members that are themselves oversized.
+///
+/// A member preceded by an adjacent comment is split at the comment instead, so doc
+/// comments stay with what they document. Empty when the definition fits in `size`.
+fn nested_split_rows(content: &str, node: tree_sitter::Node<'_>, size: ChunkSize) -> Vec<usize> {
+    let too_big = |n: tree_sitter::Node<'_>| {
+        let lines = n.end_position().row - n.start_position().row + 1;
+        n.utf8_text(content.as_bytes()).is_ok_and(|text| !size.fits(text, lines))
+    };
+    if !too_big(node) {
+        return Vec::new();
+    }
+    let definition = match node.kind() {
+        "decorated_definition" => node.child_by_field_name("definition").unwrap_or(node),
+        _ => node,
+    };
+    let Some(body) = definition.child_by_field_name("body") else {
+        return Vec::new();
+    };
+
+    let mut rows = Vec::new();
+    let mut previous: Option<tree_sitter::Node<'_>> = None;
+    for i in 0..body.named_child_count() {
+        let Some(member) = body.named_child(i) else {
+            continue;
+        };
+        let row = member.start_position().row;
+        let after_comment = previous.is_some_and(|prev| {
+            prev.kind().contains("comment") && prev.end_position().row + 1 >= row
+        });
+        if !after_comment {
+            rows.push(row);
+        }
+        rows.extend(nested_split_rows(content, member, size));
+        previous = Some(member);
+    }
+    rows
 }
 
 fn chunk_by_boundaries(
@@ -207,10 +648,12 @@ fn chunk_by_boundaries(
     lines: &[&str],
     boundaries: &[usize],
     symbol_tags: &SymbolTagsByBoundary,
-    max_tokens: usize,
+    nested_splits: &NestedSplitsByBoundary,
+    size: ChunkSize,
     overlap_tokens: usize,
 ) -> Vec<Chunk> {
-    let line_chunker = LineChunker::new();
+    let max_tokens = size.max_tokens;
+    let line_chunker = size.line_chunker();
     let mut chunks = Vec::new();
 
     for window in boundaries.windows(2) {
@@ -232,36 +675,86 @@ fn chunk_by_boundaries(
             section_tags.extend(boundary_tags.iter().cloned());
         }
 
-        if estimate_tokens(&section_content) <= max_tokens {
-            chunks.push(Chunk {
-                id: stable_hash(&section_content, &file_info.relative_path, start + 1, end),
-                path: file_info.relative_path.clone(),
-                language: file_info.language.clone(),
-                start_line: start + 1,
-                end_line: end,
-                token_estimate: estimate_tokens(&section_content),
-                content: section_content,
-                priority: file_info.priority,
-                tags: section_tags,
+        let end = end.min(lines.len());
+        if size.fits(&section_content, end - start) {
+            chunks.push(span_chunk(file_info, section_content, start, end, section_tags));
+            continue;
+        }
+
+        // Oversized: pack runs of nested members up to the limit, line-chunking only the
+        // members that are too large on their own.
+        let mut cuts = vec![start];
+        cuts.extend(
+            nested_splits.get(&start).into_iter().flatten().filter(|&&r| r > start && r < end),
+        );
+        cuts.push(end);
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let mut run_start = start;
+        for (idx, window) in cuts.windows(2).enumerate() {
+            let (piece_start, piece_end) = (window[0], window[1]);
+            let next_end = cuts.get(idx + 2).copied();
+            let piece = lines[piece_start..piece_end].join("");
+            // The run before this piece was closed when it could not take the piece.
+            if !size.fits(&piece, piece_end - piece_start) {
+                for mut chunk in line_chunker.chunk(file_info, &piece, max_tokens, overlap_tokens) {
+                    chunk.start_line += piece_start;
+                    chunk.end_line += piece_start;
+                    chunk.id =
+                        stable_hash(&chunk.content, &chunk.path, chunk.start_line, chunk.end_line);
+                    chunk.tags.extend(section_tags.iter().cloned());
+                    chunks.push(chunk);
+                }
+                run_start = piece_end;
+                continue;
+            }
+            let extended_fits = next_end.is_some_and(|next_end| {
+                size.fits(&lines[run_start..next_end].join(""), next_end - run_start)
             });
-        } else {
-            let nested =
-                line_chunker.chunk(file_info, &section_content, max_tokens, overlap_tokens);
-            for mut chunk in nested {
-                chunk.start_line += start;
-                chunk.end_line += start;
-                chunk.id =
-                    stable_hash(&chunk.content, &chunk.path, chunk.start_line, chunk.end_line);
-                chunk.tags.extend(section_tags.iter().cloned());
-                chunks.push(chunk);
+            if !extended_fits {
+                let run = lines[run_start..piece_end].join("");
+                if !run.trim().is_empty() {
+                    chunks.push(span_chunk(
+                        file_info,
+                        run,
+                        run_start,
+                        piece_end,
+                        section_tags.clone(),
+                    ));
+                }
+                run_start = piece_end;
             }
         }
     }
 
-    chunks.sort_by(|a, b| a.start_line.cmp(&b.start_line));
+    chunks.sort_by_key(|a| a.start_line);
     chunks
 }
 
+/// A chunk covering 0-indexed lines `start..end` of the file.
+fn span_chunk(
+    file_info: &FileInfo,
+    content: String,
+    start: usize,
+    end: usize,
+    tags: BTreeSet<String>,
+) -> Chunk {
+    Chunk {
+        id: stable_hash(&content, &file_info.relative_path, start + 1, end),
+        path: file_info.relative_path.clone(),
+        language: file_info.language.clone(),
+        start_line: start + 1,
+        end_line: end,
+        token_estimate: estimate_tokens(&content),
+        content,
+        priority: file_info.priority,
+        tags,
+        doc: None,
+        byte_range: None,
+    }
+}
+
 fn find_definition_boundaries(lines: &[&str], language: &str) -> Vec<usize> {
     let mut boundaries = vec![0usize];
 
@@ -345,6 +838,12 @@ fn extract_symbol_tags_from_tree_node(
     let mut tags = BTreeSet::new();
     let kind = node.kind();
 
+    if kind == "decorated_definition" {
+        if let Some(definition) = node.child_by_field_name("definition") {
+            return extract_symbol_tags_from_tree_node(content, language, definition);
+        }
+    }
+
     let prefix = match (language, kind) {
         (
             _,
@@ -371,6 +870,15 @@ fn extract_symbol_tags_from_tree_node(
 
     if let Some(prefix) = prefix {
         if let Some(name) = extract_node_name(content, node) {
+            let scope =
+                if kind == "impl_item" { extract_impl_type_name(content, node) } else { None };
+            if matches!(prefix, "type" | "impl") {
+                tags.extend(extract_scoped_method_tags(
+                    content,
+                    node,
+                    scope.as_deref().unwrap_or(&name),
+                ));
+            }
             tags.insert(format!("{prefix}:{name}"));
             return tags;
         }
@@ -407,6 +915,42 @@ fn extract_node_name(content: &str, node: tree_sitter::Node<'_>) -> Option<Strin
     None
 }
 
+/// `impl Trait for Type` names the trait first; methods belong to the implementing type.
+fn extract_impl_type_name(content: &str, node: tree_sitter::Node<'_>) -> Option<String> {
+    let type_node = node.child_by_field_name("type")?;
+    clean_symbol_name(type_node.utf8_text(content.as_bytes()).ok()?)
+}
+
+/// Tag methods declared directly in a class/impl body as `def:Scope.method`, so same-named
+/// methods on different types stay distinct symbols.
+fn extract_scoped_method_tags(
+    content: &str,
+    node: tree_sitter::Node<'_>,
+    scope: &str,
+) -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+    let Some(body) = node.child_by_field_name("body") else {
+        return tags;
+    };
+    for i in 0..body.named_child_count() {
+        let Some(mut member) = body.named_child(i) else {
+            continue;
+        };
+        if member.kind() == "decorated_definition" {
+            match member.child_by_field_name("definition") {
+                Some(definition) => member = definition,
+                None => continue,
+            }
+        }
+        if matches!(member.kind(), "function_definition" | "function_item" | "method_definition") {
+            if let Some(name) = extract_node_name(content, member) {
+                tags.insert(format!("def:{scope}.{name}"));
+            }
+        }
+    }
+    tags
+}
+
 fn extract_symbol_tags_from_section(language: &str, section: &str) -> BTreeSet<String> {
     let mut tags = BTreeSet::new();
     let Some(first_code_line) = section
@@ -474,8 +1018,10 @@ fn clean_symbol_name(raw: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::CodeChunker;
-    use crate::domain::FileInfo;
+    use super::{CodeChunker, DefinitionKinds, ParseLimits, PARSE_FALLBACK_TAG, PARSE_SKIPPED_TAG};
+    use crate::chunk::line_chunker::LineChunker;
+    use crate::domain::test_support::file_info;
+    use crate::domain::{FileInfo, TagQuery};
     use std::collections::BTreeSet;
     use std::path::PathBuf;
 
@@ -494,6 +1040,8 @@ mod tests {
             is_readme: false,
             is_config: false,
             is_doc: false,
+            modified_at: None,
+            priority_reason: None,
         };
 
         let content = "def a():\n    pass\n\ndef b():\n    pass\n\ndef c():\n    pass\n";
@@ -519,6 +1067,8 @@ mod tests {
             is_readme: false,
             is_config: false,
             is_doc: false,
+            modified_at: None,
+            priority_reason: None,
         };
 
         let content = "struct S;\nfn a() {}\nimpl S { fn b(&self) {} }\nfn c() {}\n";
@@ -544,6 +1094,8 @@ mod tests {
             is_readme: false,
             is_config: false,
             is_doc: false,
+            modified_at: None,
+            priority_reason: None,
         };
 
         let content = "package main\n\nfunc a() {}\n\nfunc b() {}\n\nfunc main() {}\n";
@@ -552,4 +1104,188 @@ mod tests {
         assert!(chunks.len() >= 2);
         assert!(chunks.iter().any(|c| c.tags.contains("def:a")));
     }
+
+    #[test]
+    fn excluded_definition_kinds_are_not_boundaries() {
+        let info = file_info("limits.go");
+
+        let content = "package limits\n\nfunc Open() {}\n\nconst MaxConns = 10\n\nvar pool = 0\n\ntype Pool struct{}\n";
+        let starts = |chunker: CodeChunker| {
+            chunker.chunk(&info, content, 200, 0).iter().map(|c| c.start_line).collect::<Vec<_>>()
+        };
+        assert_eq!(starts(CodeChunker::new()), vec![1, 3, 5, 7, 9]);
+
+        let kinds = DefinitionKinds::from_names(&["function".to_string(), "type".to_string()]);
+        let chunker = CodeChunker::new().with_definition_kinds(kinds);
+        let chunks = chunker.chunk(&info, content, 200, 0);
+        assert_eq!(chunks.iter().map(|c| c.start_line).collect::<Vec<_>>(), vec![1, 3, 9]);
+        assert!(!chunks.iter().any(|c| c.tags.contains("def:MaxConns")));
+        assert!(chunks.iter().any(|c| c.tags.contains("type:Pool")));
+    }
+
+    #[test]
+    fn oversized_class_splits_at_method_boundaries() {
+        let info = file_info("worker.js");
+
+        // Methods of uneven length with no blank lines between them, so fixed line
+        // windows would cut through method bodies.
+        let mut content = String::from("class Worker {\n");
+        let mut method_lines = Vec::new();
+        for i in 0..6 {
+            method_lines.push(content.lines().count() + 1);
+            content.push_str(&format!("  step{i}(items) {{\n    let total = 0;\n"));
+            for j in 0..(2 + i % 3) {
+                content.push_str(&format!("    total += items[{j}] * {i};\n"));
+            }
+            content.push_str("    return total;\n  }\n");
+        }
+        content.push_str("}\n");
+        let class_end = content.lines().count();
+        content.push_str("function main() {\n  return new Worker();\n}\n");
+
+        let chunks = CodeChunker::new().chunk(&info, &content, 60, 0);
+        let class_chunks: Vec<_> = chunks.iter().filter(|c| c.start_line <= class_end).collect();
+        assert!(class_chunks.len() > 1, "{chunks:?}");
+        for chunk in &class_chunks {
+            assert!(
+                chunk.start_line == 1 || method_lines.contains(&chunk.start_line),
+                "chunk starts mid-method at line {}",
+                chunk.start_line
+            );
+            let next_line = chunk.end_line + 1;
+            assert!(
+                method_lines.contains(&next_line) || chunk.end_line == class_end,
+                "chunk ends mid-method at line {}",
+                chunk.end_line
+            );
+        }
+    }
+
+    #[test]
+    fn code_chunker_scopes_methods_by_enclosing_class() {
+        let info = file_info("jobs.py");
+
+        let content = "class Helper:\n    def run(self):\n        pass\n\nclass Worker:\n    @staticmethod\n    def run():\n        pass\n";
+        let chunks = CodeChunker::new().chunk(&info, content, 200, 0);
+        assert!(chunks.iter().any(|c| c.tags.contains("def:Helper.run")));
+        assert!(chunks.iter().any(|c| c.tags.contains("def:Worker.run")));
+        assert!(chunks.iter().any(|c| c.tags.contains("type:Worker")));
+    }
+
+    #[test]
+    fn code_chunker_extracts_leading_docstrings() {
+        let info = file_info("greet.py");
+
+        let content = "import os\n\ndef greet():\n    \"\"\"Say hi.\n\n    Prints a greeting.\n    \"\"\"\n    print('hi')\n\ndef bare():\n    pass\n";
+        let chunks = CodeChunker::new().chunk(&info, content, 20, 0);
+        let greet = chunks.iter().find(|c| c.tags.contains("def:greet")).expect("greet chunk");
+        assert_eq!(greet.doc.as_deref(), Some("Say hi.\n\nPrints a greeting."));
+        let bare = chunks.iter().find(|c| c.tags.contains("def:bare")).expect("bare chunk");
+        assert_eq!(bare.doc, None);
+
+        let rust_info = file_info("lib.rs");
+        let content = "use std::fmt;\n\n/// Adds numbers.\n#[inline]\nfn add() {}\n";
+        let chunks = CodeChunker::new().chunk(&rust_info, content, 20, 0);
+        let add = chunks.iter().find(|c| c.tags.contains("def:add")).expect("add chunk");
+        assert_eq!(add.doc.as_deref(), Some("Adds numbers."));
+    }
+
+    #[test]
+    fn custom_tag_queries_tag_captured_function_names() {
+        let info = file_info("resolvers.py");
+        let queries = vec![
+            TagQuery {
+                language: "python".to_string(),
+                query: "(function_definition name: (identifier) @name)".to_string(),
+                tag_prefix: "resolver".to_string(),
+            },
+            TagQuery {
+                language: "python".to_string(),
+                query: "(not_a_node) @x".to_string(),
+                tag_prefix: "broken".to_string(),
+            },
+            TagQuery {
+                language: "rust".to_string(),
+                query: "(function_item name: (identifier) @name)".to_string(),
+                tag_prefix: "rs".to_string(),
+            },
+        ];
+
+        let content = "def resolve_user():\n    pass\n\ndef resolve_order():\n    pass\n\ndef c():\n    pass\n";
+        let chunks = CodeChunker::new().chunk_with_tag_queries(&info, content, 20, 0, &queries);
+        let user_chunk = chunks
+            .iter()
+            .find(|c| c.tags.contains("def:resolve_user"))
+            .expect("resolve_user chunk");
+        assert!(user_chunk.tags.contains("resolver:resolve_user"));
+        assert!(!user_chunk.tags.contains("resolver:resolve_order"));
+        assert!(chunks.iter().any(|c| c.tags.contains("resolver:resolve_order")));
+        assert!(chunks
+            .iter()
+            .all(|c| !c.tags.iter().any(|t| t.starts_with("broken:") || t.starts_with("rs:"))));
+    }
+
+    #[test]
+    fn broken_rust_falls_back_with_parse_fallback_tag() {
+        let info = file_info("broken.rs");
+
+        let broken = "fn a() {\n    let x = ;\n}\n\nfn b( {\n    1\n}\n";
+        let chunks = CodeChunker::new().chunk(&info, broken, 200, 0);
+        assert!(!chunks.is_empty());
+        assert!(chunks.iter().all(|c| c.tags.contains(PARSE_FALLBACK_TAG)));
+
+        // A local error keeps the definitions tree-sitter recovered around it.
+        let local_error = "fn a() {\n    let x = ;\n}\n\nfn b() {\n    1\n}\n";
+        let chunks = CodeChunker::new().chunk(&info, local_error, 200, 0);
+        assert!(chunks.iter().all(|c| c.tags.contains(PARSE_FALLBACK_TAG)));
+        let starts: Vec<usize> = chunks.iter().map(|c| c.start_line).collect();
+        assert_eq!(starts, vec![1, 5]);
+        assert!(chunks[1].tags.contains("def:b"));
+
+        let valid = "fn a() {\n    let x = 1;\n}\n\nfn b() {\n    1\n}\n";
+        let chunks = CodeChunker::new().chunk(&info, valid, 200, 0);
+        assert!(chunks.iter().all(|c| !c.tags.contains(PARSE_FALLBACK_TAG)));
+    }
+
+    #[test]
+    fn tsx_files_parse_with_the_tsx_grammar() {
+        let info = file_info("App.tsx");
+        let source = "function Header() {\n  return <h1 className=\"title\">Hi</h1>;\n}\n\n\
+                      function App() {\n  return <div><Header /></div>;\n}\n";
+        let chunks = CodeChunker::new().chunk(&info, source, 200, 0);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| !c.tags.contains(PARSE_FALLBACK_TAG)));
+    }
+
+    #[test]
+    fn file_over_parse_limit_is_line_chunked() {
+        let info = file_info("big.rs");
+        let content = "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n\nfn c() {\n    3\n}\n";
+
+        let parsed = CodeChunker::new().chunk(&info, content, 200, 0);
+        assert!(parsed.iter().any(|c| c.tags.contains("def:a")));
+        assert!(parsed.iter().all(|c| !c.tags.contains(PARSE_SKIPPED_TAG)));
+
+        let limits = ParseLimits { max_bytes: content.len() - 1, ..ParseLimits::default() };
+        let chunks = CodeChunker::new().with_parse_limits(limits).chunk(&info, content, 200, 0);
+        let expected = LineChunker::new().chunk(&info, content, 200, 0);
+        assert_eq!(
+            chunks.iter().map(|c| (c.start_line, c.end_line)).collect::<Vec<_>>(),
+            expected.iter().map(|c| (c.start_line, c.end_line)).collect::<Vec<_>>()
+        );
+        assert!(chunks.iter().all(|c| c.tags.contains(PARSE_SKIPPED_TAG)));
+        assert!(chunks.iter().all(|c| !c.tags.contains("def:a")));
+    }
+
+    #[test]
+    fn parse_past_timeout_is_line_chunked() {
+        let info = file_info("slow.rs");
+        let content =
+            (0..20_000).map(|i| format!("fn f{i}() {{\n    {i}\n}}\n\n")).collect::<String>();
+
+        let limits = ParseLimits { timeout_micros: 1, ..ParseLimits::default() };
+        let chunks = CodeChunker::new().with_parse_limits(limits).chunk(&info, &content, 200, 0);
+        assert!(!chunks.is_empty());
+        assert!(chunks.iter().all(|c| c.tags.contains(PARSE_SKIPPED_TAG)));
+    }
 }
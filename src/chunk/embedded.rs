@@ -0,0 +1,155 @@
+//! Embedded-language detection for mixed files (Vue, Svelte, HTML).
+//!
+//! A `.vue` file is chunked as one language, but its `<script>` and `<style>` blocks hold
+//! JavaScript/TypeScript and CSS. Chunks overlapping such a block are tagged
+//! `embedded:<language>`, much like tree-sitter injections mark nested languages.
+
+use super::line_start_offsets;
+use crate::domain::Chunk;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static BLOCK_OPEN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<(script|style)\b([^>]*)>").expect("valid block regex"));
+static LANG_ATTR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(lang|type)\s*=\s*["']?([\w/+.-]+)"#).expect("valid attribute regex")
+});
+
+/// Languages whose files can embed other languages.
+fn hosts_embedded_languages(language: &str) -> bool {
+    matches!(language, "vue" | "svelte" | "html")
+}
+
+/// A `<script>`/`<style>` block's language and the 1-indexed lines of its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedRegion {
+    pub language: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Non-empty `<script>` and `<style>` blocks in `content`; empty for other languages.
+pub fn embedded_regions(language: &str, content: &str) -> Vec<EmbeddedRegion> {
+    if !hosts_embedded_languages(language) {
+        return Vec::new();
+    }
+    let line_starts = line_start_offsets(content.as_bytes());
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset);
+    let lowered = content.to_ascii_lowercase();
+
+    let mut regions = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = BLOCK_OPEN.captures_at(content, pos) {
+        let tag = open[1].to_ascii_lowercase();
+        let body_start = open.get(0).map_or(pos, |m| m.end());
+        let close = format!("</{tag}");
+        let body_end =
+            lowered[body_start..].find(&close).map_or(content.len(), |idx| body_start + idx);
+        pos = body_end;
+
+        let body = &content[body_start..body_end];
+        let Some(first) = body.find(|c: char| !c.is_whitespace()) else {
+            continue;
+        };
+        let last = body.trim_end().len() - 1;
+        let Some(language) = block_language(&tag, &open[2]) else {
+            continue;
+        };
+        regions.push(EmbeddedRegion {
+            language: language.to_string(),
+            start_line: line_of(body_start + first),
+            end_line: line_of(body_start + last),
+        });
+    }
+    regions
+}
+
+/// Language of a block from its `lang`/`type` attribute; `None` for script types that
+/// are not code (`text/x-template` and the like).
+fn block_language(tag: &str, attrs: &str) -> Option<&'static str> {
+    let declared = LANG_ATTR
+        .captures_iter(attrs)
+        .find(|c| c[1].eq_ignore_ascii_case("lang"))
+        .or_else(|| LANG_ATTR.captures_iter(attrs).next())
+        .map(|c| c[2].to_ascii_lowercase());
+    let declared = declared.as_deref().unwrap_or("");
+    if tag == "style" {
+        return Some(match declared {
+            "scss" => "scss",
+            "sass" => "sass",
+            "less" => "less",
+            "stylus" | "styl" => "stylus",
+            _ => "css",
+        });
+    }
+    match declared {
+        ""
+        | "js"
+        | "jsx"
+        | "javascript"
+        | "module"
+        | "text/javascript"
+        | "application/javascript" => Some("javascript"),
+        "ts" | "tsx" | "typescript" | "text/typescript" => Some("typescript"),
+        d if d.contains("json") => Some("json"),
+        _ => None,
+    }
+}
+
+/// Tag each chunk overlapping an embedded block with `embedded:<language>`.
+pub fn tag_embedded_languages(language: &str, content: &str, chunks: &mut [Chunk]) {
+    let regions = embedded_regions(language, content);
+    for chunk in chunks.iter_mut() {
+        for region in &regions {
+            if chunk.start_line <= region.end_line && chunk.end_line >= region.start_line {
+                chunk.tags.insert(format!("embedded:{}", region.language));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{embedded_regions, EmbeddedRegion};
+    use crate::chunk::chunk_content;
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use std::path::PathBuf;
+
+    #[test]
+    fn vue_blocks_map_to_their_languages() {
+        let content = "<template>\n  <div>{{ msg }}</div>\n</template>\n\n<script lang=\"ts\">\nexport default { data: () => ({ msg: 'hi' }) }\n</script>\n\n<style scoped>\ndiv { color: red; }\n</style>\n<script type=\"text/x-template\">\n<p>ignored</p>\n</script>\n";
+
+        assert_eq!(
+            embedded_regions("vue", content),
+            vec![
+                EmbeddedRegion { language: "typescript".to_string(), start_line: 6, end_line: 6 },
+                EmbeddedRegion { language: "css".to_string(), start_line: 10, end_line: 10 },
+            ]
+        );
+        assert!(embedded_regions("python", content).is_empty());
+    }
+
+    #[test]
+    fn vue_script_chunk_is_tagged_embedded_javascript() {
+        let info =
+            FileInfo { path: PathBuf::from("/tmp/Counter.vue"), ..file_info("src/Counter.vue") };
+        let template: String =
+            (0..40).map(|i| format!("    <li class=\"row\">Row number {i}</li>\n")).collect();
+        let script: String = (0..40)
+            .map(|i| format!("    step{i}() {{ this.count += {i}; return this.count; }},\n"))
+            .collect();
+        let content = format!(
+            "<template>\n  <ul>\n{template}  </ul>\n</template>\n\n<script>\nexport default {{\n  methods: {{\n{script}  }},\n}};\n</script>\n"
+        );
+
+        let chunks = chunk_content(&info, &content, 200, 0).expect("chunk vue file");
+        assert!(chunks.len() > 1);
+        let script_chunk =
+            chunks.iter().find(|c| c.content.contains("step20()")).expect("script chunk");
+        assert!(script_chunk.tags.contains("embedded:javascript"));
+        let template_chunk = &chunks[0];
+        assert!(template_chunk.content.contains("Row number 0"));
+        assert!(!template_chunk.tags.contains("embedded:javascript"));
+    }
+}
@@ -1,9 +1,11 @@
 //! Line-based chunking.
 
-use crate::domain::{Chunk, FileInfo};
+use crate::domain::{default_max_chunk_lines, Chunk, FileInfo};
 use crate::utils::{estimate_tokens, stable_hash};
 
-pub struct LineChunker;
+pub struct LineChunker {
+    max_lines: usize,
+}
 
 impl Default for LineChunker {
     fn default() -> Self {
@@ -13,7 +15,13 @@ impl Default for LineChunker {
 
 impl LineChunker {
     pub fn new() -> Self {
-        Self
+        Self { max_lines: default_max_chunk_lines() }
+    }
+
+    /// Cap the lines per chunk, independently of the token limit.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
     }
 
     pub fn chunk(
@@ -30,7 +38,7 @@ impl LineChunker {
 
         let total_tokens = estimate_tokens(content).max(1);
         let avg_tokens_per_line = (total_tokens / lines.len()).max(1);
-        let target_lines = (max_tokens / avg_tokens_per_line).max(1);
+        let target_lines = (max_tokens / avg_tokens_per_line).clamp(1, self.max_lines);
         let overlap_lines = overlap_tokens / avg_tokens_per_line;
 
         let mut chunks = Vec::new();
@@ -42,7 +50,7 @@ impl LineChunker {
             if end < lines.len() {
                 let window_start = start + ((target_lines as f64 * 0.8) as usize);
                 let search_start = window_start.min(end);
-                let search_end = (end + 10).min(lines.len());
+                let search_end = (end + 10).min(lines.len()).min(start + self.max_lines + 1);
                 if let Some(boundary) = find_boundary(&lines, search_start, search_end) {
                     end = boundary;
                 }
@@ -68,6 +76,8 @@ impl LineChunker {
                 content: chunk_content,
                 priority: file_info.priority,
                 tags: file_info.tags.clone(),
+                doc: None,
+                byte_range: None,
             };
             chunks.push(chunk);
 
@@ -1,10 +1,12 @@
 //! Markdown-aware chunking.
 
 use crate::chunk::line_chunker::LineChunker;
-use crate::domain::{Chunk, FileInfo};
+use crate::domain::{default_max_chunk_lines, Chunk, FileInfo};
 use crate::utils::{estimate_tokens, stable_hash};
 
-pub struct MarkdownChunker;
+pub struct MarkdownChunker {
+    max_lines: usize,
+}
 
 impl Default for MarkdownChunker {
     fn default() -> Self {
@@ -14,7 +16,13 @@ impl Default for MarkdownChunker {
 
 impl MarkdownChunker {
     pub fn new() -> Self {
-        Self
+        Self { max_lines: default_max_chunk_lines() }
+    }
+
+    /// Cap the lines per chunk; longer sections are line-chunked.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
     }
 
     pub fn chunk(
@@ -55,12 +63,12 @@ impl MarkdownChunker {
         }
         sections.push((section_start, lines.len(), current_heading.take()));
 
-        let line_chunker = LineChunker::new();
+        let line_chunker = LineChunker::new().with_max_lines(self.max_lines);
         let mut result = Vec::new();
 
         for (start, end, heading) in sections {
             let section_content = lines[start..end].join("");
-            if estimate_tokens(&section_content) <= max_tokens {
+            if estimate_tokens(&section_content) <= max_tokens && end - start <= self.max_lines {
                 let mut tags = file_info.tags.clone();
                 if let Some(ref h) = heading {
                     if !h.is_empty() {
@@ -77,6 +85,8 @@ impl MarkdownChunker {
                     content: section_content,
                     priority: file_info.priority,
                     tags,
+                    doc: None,
+                    byte_range: None,
                 });
             } else {
                 let nested =
@@ -91,7 +101,7 @@ impl MarkdownChunker {
             }
         }
 
-        result.sort_by(|a, b| a.start_line.cmp(&b.start_line));
+        result.sort_by_key(|a| a.start_line);
         result
     }
 }
@@ -118,6 +128,8 @@ mod tests {
             is_readme: true,
             is_config: false,
             is_doc: true,
+            modified_at: None,
+            priority_reason: None,
         };
         let content = "# A\n\nIntro\n\n# B\n".to_string() + &"line\n".repeat(200);
         let chunks = MarkdownChunker::new().chunk(&info, &content, 80, 10);
@@ -1,16 +1,20 @@
 //! Content chunking strategies
 
-use crate::domain::{Chunk, FileInfo};
+use crate::domain::{default_max_chunk_lines, Chunk, Config, FileInfo, TagQuery};
 use crate::utils::{estimate_tokens, read_file_safe, stable_hash};
 use anyhow::Result;
+use std::collections::BTreeSet;
 
-use code_chunker::CodeChunker;
+use code_chunker::{CodeChunker, DefinitionKinds, ParseLimits};
+use embedded::tag_embedded_languages;
 use line_chunker::LineChunker;
 use markdown_chunker::MarkdownChunker;
 
 pub mod code_chunker;
+pub mod embedded;
 pub mod line_chunker;
 pub mod markdown_chunker;
+pub mod schema;
 
 #[allow(dead_code)]
 pub fn chunk_file(file_info: &FileInfo) -> Result<Vec<Chunk>> {
@@ -34,50 +38,129 @@ pub fn chunk_content(
     content: &str,
     max_tokens: usize,
     overlap_tokens: usize,
+) -> Result<Vec<Chunk>> {
+    chunk_content_with_tag_queries(
+        file_info,
+        content,
+        max_tokens,
+        overlap_tokens,
+        &[],
+        ParseLimits::default(),
+        DefinitionKinds::default(),
+        default_max_chunk_lines(),
+    )
+}
+
+/// [`chunk_content`] with user-defined tree-sitter tag queries applied to code chunks,
+/// explicit tree-sitter parse limits, a filter on definition kinds, and a cap on the
+/// lines per chunk.
+#[allow(clippy::too_many_arguments)]
+pub fn chunk_content_with_tag_queries(
+    file_info: &FileInfo,
+    content: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    tag_queries: &[TagQuery],
+    parse_limits: ParseLimits,
+    definition_kinds: DefinitionKinds,
+    max_lines: usize,
 ) -> Result<Vec<Chunk>> {
     let chunker_kind = chunker_for_language(&file_info.language);
-    let chunks = match chunker_kind {
-        ChunkerKind::Markdown => {
-            MarkdownChunker::new().chunk(file_info, content, max_tokens, overlap_tokens)
-        }
-        ChunkerKind::Code => {
-            CodeChunker::new().chunk(file_info, content, max_tokens, overlap_tokens)
-        }
-        ChunkerKind::Line => {
-            LineChunker::new().chunk(file_info, content, max_tokens, overlap_tokens)
-        }
+    let mut chunks = match chunker_kind {
+        ChunkerKind::Markdown => MarkdownChunker::new().with_max_lines(max_lines).chunk(
+            file_info,
+            content,
+            max_tokens,
+            overlap_tokens,
+        ),
+        ChunkerKind::Code => CodeChunker::new()
+            .with_parse_limits(parse_limits)
+            .with_definition_kinds(definition_kinds)
+            .with_max_lines(max_lines)
+            .chunk_with_tag_queries(file_info, content, max_tokens, overlap_tokens, tag_queries),
+        ChunkerKind::Line => LineChunker::new().with_max_lines(max_lines).chunk(
+            file_info,
+            content,
+            max_tokens,
+            overlap_tokens,
+        ),
     };
 
-    if !chunks.is_empty() {
-        return Ok(chunks);
+    if chunks.is_empty() {
+        let line_count = content.lines().count().max(1);
+        let token_estimate = estimate_tokens(content);
+        let id = stable_hash(content, &file_info.relative_path, 1, line_count);
+
+        chunks.push(Chunk {
+            id,
+            path: file_info.relative_path.clone(),
+            language: file_info.language.clone(),
+            start_line: 1,
+            end_line: line_count,
+            content: content.to_string(),
+            priority: file_info.priority,
+            tags: file_info.tags.clone(),
+            token_estimate,
+            doc: None,
+            byte_range: None,
+        });
+    }
+
+    tag_embedded_languages(&file_info.language, content, &mut chunks);
+    Ok(chunks)
+}
+
+/// Coalescing minimums per content class; unset classes use `default`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinChunkTokens {
+    pub default: usize,
+    pub code: Option<usize>,
+    pub doc: Option<usize>,
+    pub config: Option<usize>,
+}
+
+impl MinChunkTokens {
+    pub fn uniform(min_tokens: usize) -> Self {
+        Self { default: min_tokens, code: None, doc: None, config: None }
     }
 
-    let line_count = content.lines().count().max(1);
-    let token_estimate = estimate_tokens(content);
-    let id = stable_hash(content, &file_info.relative_path, 1, line_count);
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            default: config.min_chunk_tokens,
+            code: config.min_chunk_tokens_code,
+            doc: config.min_chunk_tokens_doc,
+            config: config.min_chunk_tokens_config,
+        }
+    }
 
-    Ok(vec![Chunk {
-        id,
-        path: file_info.relative_path.clone(),
-        language: file_info.language.clone(),
-        start_line: 1,
-        end_line: line_count,
-        content: content.to_string(),
-        priority: file_info.priority,
-        tags: file_info.tags.clone(),
-        token_estimate,
-    }])
+    /// Minimum for a chunk of the given language.
+    pub fn for_language(&self, language: &str) -> usize {
+        let class_min = match language {
+            "markdown" | "restructuredtext" | "asciidoc" | "text" => self.doc,
+            "yaml" | "toml" | "json" | "ini" => self.config,
+            _ => self.code,
+        };
+        class_min.unwrap_or(self.default)
+    }
 }
 
 #[allow(dead_code)]
 pub fn coalesce_small_chunks(chunks: Vec<Chunk>, _min_tokens: usize) -> Vec<Chunk> {
-    coalesce_small_chunks_with_max(chunks, 200, 800)
+    coalesce_small_chunks_with_max(
+        chunks,
+        MinChunkTokens::uniform(200),
+        800,
+        default_max_chunk_lines(),
+    )
 }
 
+/// Merge chunks below their class minimum into a same-file neighbour, as long as the
+/// result stays within `max_tokens` and `max_lines`.
 pub fn coalesce_small_chunks_with_max(
     chunks: Vec<Chunk>,
-    min_tokens: usize,
+    min_tokens: MinChunkTokens,
     max_tokens: usize,
+    max_lines: usize,
 ) -> Vec<Chunk> {
     if chunks.is_empty() {
         return Vec::new();
@@ -86,37 +169,128 @@ pub fn coalesce_small_chunks_with_max(
     let mut sorted = chunks;
     sorted.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.start_line.cmp(&b.start_line)));
 
+    // Pieces of a definition that was split for size share its `def:` tags with a
+    // neighbour; other definitions are never folded into such a piece.
+    let is_piece: Vec<bool> = (0..sorted.len())
+        .map(|i| {
+            let tags = definition_tags(&sorted[i]);
+            let shares =
+                |j: usize| sorted[j].path == sorted[i].path && definition_tags(&sorted[j]) == tags;
+            !tags.is_empty()
+                && ((i > 0 && shares(i - 1)) || (i + 1 < sorted.len() && shares(i + 1)))
+        })
+        .collect();
+
     let mut result: Vec<Chunk> = Vec::new();
+    let mut last_is_piece = false;
 
-    for chunk in sorted {
+    for (chunk, piece) in sorted.into_iter().zip(is_piece) {
         if let Some(last) = result.last_mut() {
             if last.path == chunk.path && chunk.start_line <= last.end_line + 1 {
                 let combined_tokens = last.token_estimate + chunk.token_estimate;
+                let min_tokens = min_tokens.for_language(&chunk.language);
+                let splits_definition =
+                    (piece || last_is_piece) && definition_tags(last) != definition_tags(&chunk);
                 let can_merge = (last.token_estimate < min_tokens
                     || chunk.token_estimate < min_tokens)
-                    && combined_tokens <= max_tokens;
+                    && combined_tokens <= max_tokens
+                    && merged_lines(last, &chunk) <= max_lines
+                    && !splits_definition;
 
                 if can_merge {
-                    let merged_content = merge_chunk_content(last, &chunk);
-                    let merged_tags = last.tags.union(&chunk.tags).cloned().collect();
-                    last.end_line = chunk.end_line;
-                    last.content = merged_content.clone();
-                    last.priority = last.priority.max(chunk.priority);
-                    last.tags = merged_tags;
-                    last.token_estimate = estimate_tokens(&merged_content);
-                    last.id =
-                        stable_hash(&merged_content, &last.path, last.start_line, last.end_line);
+                    absorb_chunk(last, &chunk);
+                    last_is_piece |= piece;
                     continue;
                 }
             }
         }
 
         result.push(chunk);
+        last_is_piece = piece;
+    }
+
+    result
+}
+
+/// Byte offset at which each line of `content` starts.
+pub fn line_start_offsets(content: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(
+        content
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == b'\n')
+            .map(|(idx, _)| idx + 1)
+            .filter(|start| *start < content.len()),
+    );
+    starts
+}
+
+/// `[start, end)` bytes of the 1-indexed, inclusive line range, its final newline included.
+pub fn line_range_bytes(
+    line_starts: &[usize],
+    content_len: usize,
+    start_line: usize,
+    end_line: usize,
+) -> (usize, usize) {
+    let offset = |line: usize| line_starts.get(line).copied().unwrap_or(content_len);
+    let start = offset(start_line.saturating_sub(1));
+    (start, offset(end_line).max(start))
+}
+
+/// Greedily merge consecutive chunks of the same file while the combined size stays within
+/// `max_tokens` and `max_lines`. Unlike [`coalesce_small_chunks_with_max`], chunks of any
+/// size are merged.
+pub fn merge_adjacent_chunks(
+    chunks: Vec<Chunk>,
+    max_tokens: usize,
+    max_lines: usize,
+) -> Vec<Chunk> {
+    let mut sorted = chunks;
+    sorted.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.start_line.cmp(&b.start_line)));
+
+    let mut result: Vec<Chunk> = Vec::new();
+    for chunk in sorted {
+        if let Some(last) = result.last_mut() {
+            if last.path == chunk.path
+                && chunk.start_line <= last.end_line + 1
+                && last.token_estimate + chunk.token_estimate <= max_tokens
+                && merged_lines(last, &chunk) <= max_lines
+            {
+                absorb_chunk(last, &chunk);
+                continue;
+            }
+        }
+        result.push(chunk);
     }
 
     result
 }
 
+/// Lines spanned by `last` once `next` is absorbed into it.
+fn merged_lines(last: &Chunk, next: &Chunk) -> usize {
+    last.end_line.max(next.end_line) + 1 - last.start_line
+}
+
+/// The `def:` tags of a chunk, naming the definitions it covers.
+fn definition_tags(chunk: &Chunk) -> BTreeSet<&str> {
+    chunk.tags.iter().map(String::as_str).filter(|t| t.starts_with("def:")).collect()
+}
+
+/// Extend `last` with `next`, keeping the tag union and the higher priority.
+fn absorb_chunk(last: &mut Chunk, next: &Chunk) {
+    let merged_content = merge_chunk_content(last, next);
+    last.end_line = next.end_line;
+    last.priority = last.priority.max(next.priority);
+    last.tags.extend(next.tags.iter().cloned());
+    if last.doc.is_none() {
+        last.doc = next.doc.clone();
+    }
+    last.token_estimate = estimate_tokens(&merged_content);
+    last.id = stable_hash(&merged_content, &last.path, last.start_line, last.end_line);
+    last.content = merged_content;
+}
+
 fn merge_chunk_content(current: &Chunk, next: &Chunk) -> String {
     if next.start_line > current.end_line {
         // No overlap: simple concatenation
@@ -135,6 +309,21 @@ fn merge_chunk_content(current: &Chunk, next: &Chunk) -> String {
     }
 }
 
+/// How files of `language` are chunked: `tree-sitter` (AST definitions), `heuristic`
+/// (regex definition boundaries), `markdown` (headings) or `lines` (fixed windows).
+pub fn chunking_strategy(language: &str) -> &'static str {
+    match chunker_for_language(language) {
+        ChunkerKind::Code
+            if code_chunker::supported_tree_sitter_languages().contains(&language) =>
+        {
+            "tree-sitter"
+        }
+        ChunkerKind::Code => "heuristic",
+        ChunkerKind::Markdown => "markdown",
+        ChunkerKind::Line => "lines",
+    }
+}
+
 enum ChunkerKind {
     Code,
     Markdown,
@@ -149,3 +338,141 @@ fn chunker_for_language(language: &str) -> ChunkerKind {
         _ => ChunkerKind::Line,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chunk_content_with_tag_queries, coalesce_small_chunks_with_max, line_range_bytes,
+        line_start_offsets, merge_adjacent_chunks, DefinitionKinds, MinChunkTokens, ParseLimits,
+    };
+    use crate::domain::test_support::{self, file_info};
+    use crate::domain::{Chunk, FileInfo};
+    use crate::utils::estimate_tokens;
+    use std::collections::BTreeSet;
+    use std::path::PathBuf;
+
+    fn mk_chunk(path: &str, start_line: usize, end_line: usize, tag: &str) -> Chunk {
+        let content: String =
+            (start_line..=end_line).map(|n| format!("let value_{n} = compute({n});\n")).collect();
+        Chunk {
+            id: format!("{path}:{start_line}"),
+            start_line,
+            end_line,
+            tags: BTreeSet::from([tag.to_string()]),
+            ..test_support::chunk(path, &content)
+        }
+    }
+
+    #[test]
+    fn merge_adjacent_joins_medium_chunks_under_cap() {
+        let first = mk_chunk("src/lib.rs", 1, 10, "def:a");
+        let second = mk_chunk("src/lib.rs", 11, 20, "def:b");
+        let other = mk_chunk("src/main.rs", 1, 10, "def:main");
+        let cap = first.token_estimate + second.token_estimate;
+
+        let merged = merge_adjacent_chunks(vec![second, other.clone(), first], cap, 400);
+        assert_eq!(merged.len(), 2);
+        let lib = &merged[0];
+        assert_eq!((lib.start_line, lib.end_line), (1, 20));
+        assert!(lib.tags.contains("def:a") && lib.tags.contains("def:b"));
+        assert_ne!(lib.id, "src/lib.rs:1");
+        assert_eq!(merged[1].id, other.id);
+
+        let parts = vec![mk_chunk("src/lib.rs", 1, 10, "a"), mk_chunk("src/lib.rs", 11, 20, "b")];
+        assert_eq!(merge_adjacent_chunks(parts.clone(), cap - 1, 400).len(), 2);
+        assert_eq!(merge_adjacent_chunks(parts, cap, 19).len(), 2);
+    }
+
+    #[test]
+    fn class_minimums_coalesce_docs_but_keep_code_separate() {
+        let mut doc_parts =
+            vec![mk_chunk("README.md", 1, 5, "a"), mk_chunk("README.md", 6, 10, "b")];
+        for chunk in &mut doc_parts {
+            chunk.language = "markdown".to_string();
+        }
+        let code_parts =
+            vec![mk_chunk("src/lib.rs", 1, 5, "a"), mk_chunk("src/lib.rs", 6, 10, "b")];
+        let small = code_parts[0].token_estimate;
+        let minimums =
+            MinChunkTokens { default: small * 4, code: Some(small / 2), doc: None, config: None };
+
+        let chunks = [doc_parts.clone(), code_parts.clone()].concat();
+        let coalesced = coalesce_small_chunks_with_max(chunks, minimums, 800, 400);
+        let docs: Vec<_> = coalesced.iter().filter(|c| c.path == "README.md").collect();
+        let code: Vec<_> = coalesced.iter().filter(|c| c.path == "src/lib.rs").collect();
+        assert_eq!(docs.len(), 1);
+        assert_eq!((docs[0].start_line, docs[0].end_line), (1, 10));
+        assert_eq!(code.len(), 2);
+
+        let uniform = [doc_parts, code_parts].concat();
+        let coalesced =
+            coalesce_small_chunks_with_max(uniform, MinChunkTokens::uniform(small * 4), 800, 400);
+        assert_eq!(coalesced.len(), 2);
+    }
+
+    #[test]
+    fn small_definition_is_not_folded_into_split_definition() {
+        let chunks = vec![
+            mk_chunk("src/lib.rs", 1, 10, "def:rebuild"),
+            mk_chunk("src/lib.rs", 11, 14, "def:rebuild"),
+            mk_chunk("src/lib.rs", 15, 16, "def:unrelated"),
+        ];
+        let minimums = MinChunkTokens::uniform(chunks[0].token_estimate);
+
+        let coalesced = coalesce_small_chunks_with_max(chunks, minimums, 800, 400);
+        let spans: Vec<_> = coalesced.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(spans, vec![(1, 14), (15, 16)]);
+    }
+
+    #[test]
+    fn line_cap_splits_token_small_files_and_bounds_coalescing() {
+        let content: String = (1..=50).map(|n| format!("INSERT INTO t VALUES ({n});\n")).collect();
+        let info = FileInfo {
+            path: PathBuf::from("/tmp/seed.sql"),
+            size_bytes: content.len() as u64,
+            ..file_info("seed.sql")
+        };
+        assert!(estimate_tokens(&content) < 800);
+        let chunk = |max_lines| {
+            chunk_content_with_tag_queries(
+                &info,
+                &content,
+                800,
+                0,
+                &[],
+                ParseLimits::default(),
+                DefinitionKinds::default(),
+                max_lines,
+            )
+            .expect("chunk")
+        };
+        let spans = |chunks: &[Chunk]| -> Vec<(usize, usize)> {
+            chunks.iter().map(|c| (c.start_line, c.end_line)).collect()
+        };
+
+        assert_eq!(spans(&chunk(400)), vec![(1, 50)]);
+        let capped = chunk(20);
+        assert_eq!(spans(&capped), vec![(1, 20), (21, 40), (41, 50)]);
+
+        // Every piece is under min_chunk_tokens, but merges stop at the line cap.
+        let minimums = MinChunkTokens::uniform(10_000);
+        let coalesced = coalesce_small_chunks_with_max(capped.clone(), minimums, 800, 20);
+        assert_eq!(spans(&coalesced), spans(&capped));
+        let coalesced = coalesce_small_chunks_with_max(capped, minimums, 800, 30);
+        assert_eq!(spans(&coalesced), vec![(1, 20), (21, 50)]);
+    }
+
+    #[test]
+    fn line_range_bytes_cover_whole_lines() {
+        let content = "first\nsécond\nthird";
+        let starts = line_start_offsets(content.as_bytes());
+        assert_eq!(starts, vec![0, 6, 14]);
+        let slice = |start, end| {
+            let (a, b) = line_range_bytes(&starts, content.len(), start, end);
+            &content[a..b]
+        };
+        assert_eq!(slice(1, 1), "first\n");
+        assert_eq!(slice(2, 3), "sécond\nthird");
+        assert_eq!(slice(3, 9), "third");
+    }
+}
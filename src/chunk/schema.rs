@@ -0,0 +1,204 @@
+//! Compact summaries of GraphQL and OpenAPI schema files.
+//!
+//! With `--summarize-schemas`, a recognised schema file is exported as one chunk listing
+//! its types and operations (GraphQL) or paths and schemas (OpenAPI) instead of verbatim.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static GRAPHQL_TYPE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(?:extend\s+)?(type|interface|enum|input|union|scalar)\s+([_A-Za-z][_0-9A-Za-z]*)",
+    )
+    .expect("valid graphql type regex")
+});
+static GRAPHQL_OPERATION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(query|mutation|subscription)\s+([_A-Za-z][_0-9A-Za-z]*)")
+        .expect("valid graphql operation regex")
+});
+static GRAPHQL_FIELD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*([_A-Za-z][_0-9A-Za-z]*)\s*[(:]").expect("valid field regex"));
+
+const HTTP_METHODS: [&str; 8] =
+    ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Summarize `content` when it is a GraphQL schema or an OpenAPI/Swagger document.
+pub fn summarize_schema(language: &str, content: &str) -> Option<String> {
+    match language {
+        "graphql" => summarize_graphql(content),
+        "yaml" => serde_yaml::from_str::<serde_json::Value>(content)
+            .ok()
+            .and_then(|doc| summarize_openapi(&doc)),
+        "json" => serde_json::from_str(content).ok().and_then(|doc| summarize_openapi(&doc)),
+        _ => None,
+    }
+}
+
+fn summarize_graphql(content: &str) -> Option<String> {
+    let mut types: Vec<String> = Vec::new();
+    let mut operations: Vec<(&'static str, String)> = Vec::new();
+    // Root type whose fields are operations, while inside its `{ ... }` block.
+    let mut root: Option<&'static str> = None;
+    let mut depth = 0usize;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        if depth == 0 {
+            if let Some(caps) = GRAPHQL_TYPE_RE.captures(line) {
+                let name = caps[2].to_string();
+                root = match (&caps[1], name.as_str()) {
+                    ("type", "Query") => Some("query"),
+                    ("type", "Mutation") => Some("mutation"),
+                    ("type", "Subscription") => Some("subscription"),
+                    _ => None,
+                };
+                if root.is_none() {
+                    types.push(format!("{} {}", &caps[1], name));
+                }
+            } else if let Some(caps) = GRAPHQL_OPERATION_RE.captures(line) {
+                let kind = match &caps[1] {
+                    "query" => "query",
+                    "mutation" => "mutation",
+                    _ => "subscription",
+                };
+                operations.push((kind, caps[2].to_string()));
+            }
+        } else if depth == 1 {
+            if let (Some(kind), Some(caps)) = (root, GRAPHQL_FIELD_RE.captures(line)) {
+                operations.push((kind, caps[1].to_string()));
+            }
+        }
+        for ch in line.chars() {
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        root = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if types.is_empty() && operations.is_empty() {
+        return None;
+    }
+    let mut out = String::from("GraphQL schema summary\n");
+    if !types.is_empty() {
+        out.push_str("\nTypes:\n");
+        for ty in &types {
+            out.push_str(&format!("- {ty}\n"));
+        }
+    }
+    if !operations.is_empty() {
+        out.push_str("\nOperations:\n");
+        for (kind, name) in &operations {
+            out.push_str(&format!("- {kind} {name}\n"));
+        }
+    }
+    Some(out)
+}
+
+fn summarize_openapi(doc: &serde_json::Value) -> Option<String> {
+    let version = doc
+        .get("openapi")
+        .map(|v| format!("OpenAPI {}", scalar(v)))
+        .or_else(|| doc.get("swagger").map(|v| format!("Swagger {}", scalar(v))))?;
+
+    let mut out = format!("{version} summary\n");
+    if let Some(title) = doc.pointer("/info/title").and_then(|v| v.as_str()) {
+        out.push_str(&format!("Title: {title}\n"));
+    }
+
+    if let Some(paths) = doc.get("paths").and_then(|v| v.as_object()) {
+        out.push_str("\nPaths:\n");
+        for (path, item) in paths {
+            let methods: Vec<String> = HTTP_METHODS
+                .iter()
+                .filter(|method| item.get(**method).is_some())
+                .map(|method| method.to_ascii_uppercase())
+                .collect();
+            if methods.is_empty() {
+                out.push_str(&format!("- {path}\n"));
+            } else {
+                out.push_str(&format!("- {} {path}\n", methods.join(",")));
+            }
+        }
+    }
+
+    let schemas = doc
+        .pointer("/components/schemas")
+        .or_else(|| doc.get("definitions"))
+        .and_then(|v| v.as_object());
+    if let Some(schemas) = schemas.filter(|s| !s.is_empty()) {
+        out.push_str("\nSchemas:\n");
+        for name in schemas.keys() {
+            out.push_str(&format!("- {name}\n"));
+        }
+    }
+    Some(out)
+}
+
+fn scalar(value: &serde_json::Value) -> String {
+    value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::summarize_schema;
+
+    #[test]
+    fn graphql_summary_lists_types_and_operations() {
+        let schema = "\
+type User {
+  id: ID!
+  posts: [Post!]!
+}
+
+# A post written by a user
+type Post {
+  id: ID!
+  author: User!
+}
+
+enum Role { ADMIN USER }
+
+type Query {
+  user(id: ID!): User
+  posts: [Post!]!
+}
+";
+        let summary = summarize_schema("graphql", schema).expect("summary");
+        assert!(summary.contains("- type User\n"));
+        assert!(summary.contains("- type Post\n"));
+        assert!(summary.contains("- enum Role\n"));
+        assert!(summary.contains("- query user\n"));
+        assert!(summary.contains("- query posts\n"));
+        assert!(!summary.contains("type Query"));
+        assert!(!summary.contains("author"));
+    }
+
+    #[test]
+    fn openapi_summary_lists_paths_and_schemas() {
+        let spec = "\
+openapi: 3.0.0
+info:
+  title: Pets
+paths:
+  /pets:
+    get: {}
+    post: {}
+components:
+  schemas:
+    Pet:
+      type: object
+";
+        let summary = summarize_schema("yaml", spec).expect("summary");
+        assert!(summary.starts_with("OpenAPI 3.0.0 summary\n"));
+        assert!(summary.contains("- GET,POST /pets\n"));
+        assert!(summary.contains("- Pet\n"));
+        assert!(summarize_schema("yaml", "name: not-a-spec\n").is_none());
+    }
+}
@@ -3,12 +3,14 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use rusqlite::{params, Connection, OptionalExtension};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::utils::output::println;
+
 #[derive(Args)]
 pub struct CodeIntelArgs {
     /// SQLite index database path
@@ -18,6 +20,10 @@ pub struct CodeIntelArgs {
     /// Output path for portable code-intel JSON
     #[arg(long, value_name = "FILE", default_value = ".repo-context/codeintel.json")]
     pub out: PathBuf,
+
+    /// Write only what changed since this earlier full export (added/changed/removed)
+    #[arg(long, value_name = "FILE")]
+    pub since: Option<PathBuf>,
 }
 
 pub fn run(args: CodeIntelArgs) -> Result<()> {
@@ -38,7 +44,10 @@ pub fn run(args: CodeIntelArgs) -> Result<()> {
 
     let project_root = metadata_value(&conn, "repo_root")?.unwrap_or_default();
     let files = load_files(&conn)?;
-    let symbol_export = load_symbols(&conn)?;
+    // Honor the index-time cap so references match what the index stored.
+    let max_references = metadata_value(&conn, "max_occurrences_per_symbol")?
+        .and_then(|value| value.parse::<usize>().ok());
+    let symbol_export = load_symbols(&conn, max_references)?;
 
     let payload = CodeIntelDocument {
         schema_version: "0.4.0".to_string(),
@@ -56,6 +65,20 @@ pub fn run(args: CodeIntelArgs) -> Result<()> {
     if let Some(parent) = args.out.parent() {
         fs::create_dir_all(parent)?;
     }
+    if let Some(since) = args.since.as_deref() {
+        let previous = load_previous_export(since)?;
+        let delta = diff_exports(&previous, &payload, since);
+        fs::write(&args.out, serde_json::to_string_pretty(&delta)?)?;
+
+        let summary = &delta.summary;
+        println!("Code-intel delta written to {}", args.out.display());
+        println!(
+            "  symbols: +{} ~{} -{}",
+            summary.symbols_added, summary.symbols_changed, summary.symbols_removed
+        );
+        println!("  occurrences: +{} -{}", summary.occurrences_added, summary.occurrences_removed);
+        return Ok(());
+    }
     let json = serde_json::to_string_pretty(&payload)?;
     fs::write(&args.out, json)?;
 
@@ -111,14 +134,17 @@ struct PortableFile {
     file_hash: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 struct PortableSymbol {
     id: String,
     symbol: String,
+    /// Enclosing class/impl for methods (`helper` for `helper.run`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
     kinds: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd)]
 struct PortableOccurrence {
     id: String,
     symbol_id: String,
@@ -143,6 +169,141 @@ struct PortableSymbolLink {
     to_symbol_id: String,
 }
 
+/// The parts of an earlier full export that `--since` compares against.
+#[derive(Debug, Deserialize)]
+struct PreviousExport {
+    format: String,
+    #[serde(default)]
+    symbols: Vec<PortableSymbol>,
+    #[serde(default)]
+    occurrences: Vec<PortableOccurrence>,
+}
+
+#[derive(Debug, Serialize)]
+struct CodeIntelDelta {
+    schema_version: String,
+    format: String,
+    project_root: String,
+    since: String,
+    symbols: SymbolDelta,
+    occurrences: OccurrenceDelta,
+    summary: DeltaSummary,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct SymbolDelta {
+    added: Vec<PortableSymbol>,
+    /// Present in both exports with different kinds or occurrences
+    changed: Vec<PortableSymbol>,
+    removed: Vec<PortableSymbol>,
+}
+
+/// Occurrence ids hash their chunk and lines, so a moved or edited occurrence shows up
+/// as removed plus added.
+#[derive(Debug, Serialize, Default)]
+struct OccurrenceDelta {
+    added: Vec<PortableOccurrence>,
+    removed: Vec<PortableOccurrence>,
+}
+
+#[derive(Debug, Serialize, Default, PartialEq, Eq)]
+struct DeltaSummary {
+    symbols_added: usize,
+    symbols_changed: usize,
+    symbols_removed: usize,
+    occurrences_added: usize,
+    occurrences_removed: usize,
+}
+
+fn load_previous_export(path: &Path) -> Result<PreviousExport> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read previous export: {}", path.display()))?;
+    let previous: PreviousExport = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid code-intel export: {}", path.display()))?;
+    if previous.format != "scip-lite" {
+        anyhow::bail!(
+            "--since needs a full code-intel export, got format '{}' in {}",
+            previous.format,
+            path.display()
+        );
+    }
+    Ok(previous)
+}
+
+/// Compare two exports by their stable ids.
+fn diff_exports(
+    previous: &PreviousExport,
+    current: &CodeIntelDocument,
+    since: &Path,
+) -> CodeIntelDelta {
+    let occurrence_ids = |occurrences: &[PortableOccurrence]| {
+        let mut by_symbol: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for occ in occurrences {
+            by_symbol.entry(occ.symbol_id.clone()).or_default().insert(occ.id.clone());
+        }
+        by_symbol
+    };
+    let previous_occurrences = occurrence_ids(&previous.occurrences);
+    let current_occurrences = occurrence_ids(&current.occurrences);
+    let previous_symbols: HashMap<&str, &PortableSymbol> =
+        previous.symbols.iter().map(|s| (s.id.as_str(), s)).collect();
+    let current_symbol_ids: HashSet<&str> = current.symbols.iter().map(|s| s.id.as_str()).collect();
+
+    let mut symbols = SymbolDelta::default();
+    for symbol in &current.symbols {
+        match previous_symbols.get(symbol.id.as_str()) {
+            None => symbols.added.push(symbol.clone()),
+            Some(before) => {
+                if *before != symbol
+                    || previous_occurrences.get(&symbol.id) != current_occurrences.get(&symbol.id)
+                {
+                    symbols.changed.push(symbol.clone());
+                }
+            }
+        }
+    }
+    symbols.removed = previous
+        .symbols
+        .iter()
+        .filter(|s| !current_symbol_ids.contains(s.id.as_str()))
+        .cloned()
+        .collect();
+
+    let previous_ids: HashSet<&str> = previous.occurrences.iter().map(|o| o.id.as_str()).collect();
+    let current_ids: HashSet<&str> = current.occurrences.iter().map(|o| o.id.as_str()).collect();
+    let occurrences = OccurrenceDelta {
+        added: current
+            .occurrences
+            .iter()
+            .filter(|o| !previous_ids.contains(o.id.as_str()))
+            .cloned()
+            .collect(),
+        removed: previous
+            .occurrences
+            .iter()
+            .filter(|o| !current_ids.contains(o.id.as_str()))
+            .cloned()
+            .collect(),
+    };
+
+    let summary = DeltaSummary {
+        symbols_added: symbols.added.len(),
+        symbols_changed: symbols.changed.len(),
+        symbols_removed: symbols.removed.len(),
+        occurrences_added: occurrences.added.len(),
+        occurrences_removed: occurrences.removed.len(),
+    };
+    CodeIntelDelta {
+        schema_version: current.schema_version.clone(),
+        format: "scip-lite-delta".to_string(),
+        project_root: current.project_root.clone(),
+        since: since.display().to_string(),
+        symbols,
+        occurrences,
+        summary,
+    }
+}
+
 fn compute_stats(
     files: &[PortableFile],
     symbols: &[PortableSymbol],
@@ -195,7 +356,7 @@ fn load_files(conn: &Connection) -> Result<Vec<PortableFile>> {
     Ok(out)
 }
 
-fn load_symbols(conn: &Connection) -> Result<SymbolExport> {
+fn load_symbols(conn: &Connection, max_references: Option<usize>) -> Result<SymbolExport> {
     let mut by_symbol: BTreeMap<String, SymbolAccumulator> = BTreeMap::new();
 
     let mut defs_stmt = conn.prepare(
@@ -230,6 +391,17 @@ fn load_symbols(conn: &Connection) -> Result<SymbolExport> {
     }
 
     let symbol_set: HashSet<String> = by_symbol.keys().cloned().collect();
+    // Scoped symbols (`helper.run`) are referenced by their member name, but only from
+    // chunks that also mention the scope; that keeps same-named methods apart.
+    let mut scoped_by_member: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for symbol in &symbol_set {
+        if let Some((scope, member)) = split_scoped_symbol(symbol) {
+            scoped_by_member
+                .entry(member.to_string())
+                .or_default()
+                .push((scope.to_string(), symbol.clone()));
+        }
+    }
 
     let mut chunks_stmt =
         conn.prepare("SELECT file_path, id, start_line, end_line, content FROM chunks")?;
@@ -247,17 +419,37 @@ fn load_symbols(conn: &Connection) -> Result<SymbolExport> {
     let chunks: Vec<ChunkRecord> = chunk_rows.collect::<rusqlite::Result<Vec<_>>>()?;
 
     for chunk in &chunks {
+        let occurrence = RawOccurrence {
+            path: chunk.path.clone(),
+            chunk_id: chunk.chunk_id.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+        };
         for token in &chunk.tokens {
-            if !symbol_set.contains(token) {
-                continue;
+            if symbol_set.contains(token) {
+                if let Some(acc) = by_symbol.get_mut(token) {
+                    acc.references.insert(occurrence.clone());
+                }
             }
-            if let Some(acc) = by_symbol.get_mut(token) {
-                acc.references.insert(RawOccurrence {
-                    path: chunk.path.clone(),
-                    chunk_id: chunk.chunk_id.clone(),
-                    start_line: chunk.start_line,
-                    end_line: chunk.end_line,
-                });
+            for (scope, symbol) in scoped_by_member.get(token).into_iter().flatten() {
+                if !chunk.tokens.contains(scope) {
+                    continue;
+                }
+                if let Some(acc) = by_symbol.get_mut(symbol) {
+                    acc.references.insert(occurrence.clone());
+                }
+            }
+        }
+    }
+
+    if let Some(cap) = max_references {
+        for acc in by_symbol.values_mut() {
+            if acc.references.len() > cap {
+                let mut refs: Vec<RawOccurrence> =
+                    std::mem::take(&mut acc.references).into_iter().collect();
+                refs.sort_by(|a, b| (&a.path, a.start_line).cmp(&(&b.path, b.start_line)));
+                refs.truncate(cap);
+                acc.references = refs.into_iter().collect();
             }
         }
     }
@@ -273,6 +465,7 @@ fn load_symbols(conn: &Connection) -> Result<SymbolExport> {
         let symbol_id = stable_id(&format!("symbol:{symbol}"));
         symbols.push(PortableSymbol {
             id: symbol_id.clone(),
+            scope: split_scoped_symbol(&symbol).map(|(scope, _)| scope.to_string()),
             symbol,
             kinds: acc.kinds.into_iter().collect(),
         });
@@ -385,6 +578,17 @@ struct ChunkRecord {
     import_refs: Vec<String>,
 }
 
+/// Split `scope.member` symbols written by the chunker for methods; the scope is the
+/// last dotted segment before the member so tokens can be matched against it.
+fn split_scoped_symbol(symbol: &str) -> Option<(&str, &str)> {
+    let (qualifier, member) = symbol.rsplit_once('.')?;
+    let scope = qualifier.rsplit(['.', ':']).next().unwrap_or(qualifier);
+    if scope.is_empty() || member.is_empty() {
+        return None;
+    }
+    Some((scope, member))
+}
+
 fn tokenize(text: &str) -> Vec<String> {
     text.split(|c: char| !c.is_alphanumeric() && c != '_')
         .filter_map(|token| {
@@ -608,8 +812,15 @@ fn is_test_like_file(path: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{infer_symbol_links, stable_id, tokenize, ChunkRecord, ReferenceOccurrence};
+    use super::{
+        infer_symbol_links, load_symbols, stable_id, tokenize, ChunkRecord, ReferenceOccurrence,
+    };
+    use crate::chunk::code_chunker::CodeChunker;
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use rusqlite::{params, Connection};
     use std::collections::{BTreeMap, BTreeSet};
+    use std::path::PathBuf;
 
     #[test]
     fn stable_id_is_deterministic() {
@@ -673,4 +884,60 @@ mod tests {
         assert!(kinds.contains("tests"));
         assert!(kinds.contains("imports"));
     }
+
+    #[test]
+    fn same_named_methods_in_different_classes_are_distinct_symbols() {
+        let info = FileInfo { path: PathBuf::from("/tmp/jobs.py"), ..file_info("jobs.py") };
+        let content = "class Helper:\n    def run(self):\n        return 1\n\n\
+                       class Worker:\n    def run(self):\n        return 2\n\n\
+                       def main():\n    return Helper().run()\n";
+        let chunks = CodeChunker::new().chunk(&info, content, 200, 0);
+
+        let conn = Connection::open_in_memory().expect("open db");
+        conn.execute_batch(
+            "CREATE TABLE chunks (id TEXT, file_path TEXT, start_line INTEGER, end_line INTEGER, content TEXT);
+             CREATE TABLE symbols (symbol TEXT, kind TEXT, file_path TEXT, chunk_id TEXT);",
+        )
+        .expect("schema");
+        for chunk in &chunks {
+            conn.execute(
+                "INSERT INTO chunks VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    chunk.id,
+                    chunk.path,
+                    chunk.start_line as i64,
+                    chunk.end_line as i64,
+                    chunk.content
+                ],
+            )
+            .expect("insert chunk");
+            for (kind, symbol) in chunk.tags.iter().filter_map(|t| t.split_once(':')) {
+                conn.execute(
+                    "INSERT INTO symbols VALUES (?1, ?2, ?3, ?4)",
+                    params![symbol.to_ascii_lowercase(), kind, chunk.path, chunk.id],
+                )
+                .expect("insert symbol");
+            }
+        }
+
+        let export = load_symbols(&conn, None).expect("load symbols");
+        let names: BTreeSet<&str> = export.symbols.iter().map(|s| s.symbol.as_str()).collect();
+        assert!(names.contains("helper.run"));
+        assert!(names.contains("worker.run"));
+
+        let main_chunk = chunks
+            .iter()
+            .find(|c| c.tags.contains("def:main"))
+            .map(|c| c.id.as_str())
+            .expect("main chunk");
+        let referenced_from_main: BTreeSet<&str> = export
+            .occurrences
+            .iter()
+            .filter(|occ| occ.role == "reference" && occ.chunk_id == main_chunk)
+            .filter_map(|occ| export.symbols.iter().find(|s| s.id == occ.symbol_id))
+            .map(|s| s.symbol.as_str())
+            .collect();
+        assert!(referenced_from_main.contains("helper.run"));
+        assert!(!referenced_from_main.contains("worker.run"));
+    }
 }
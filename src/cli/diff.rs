@@ -1,6 +1,6 @@
 //! Diff command for comparing two export outputs.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{Args, ValueEnum};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
@@ -8,6 +8,8 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::render::JSONL_SCHEMA_VERSION;
+
 #[derive(Args)]
 pub struct DiffArgs {
     /// Path to older export output directory
@@ -21,6 +23,11 @@ pub struct DiffArgs {
     /// Output format: text, markdown, or json
     #[arg(long, value_enum, default_value = "text")]
     pub format: DiffFormat,
+
+    /// Compare chunk content with whitespace runs collapsed to one space (indentation kept
+    /// for Python), so reformatted chunks and files are not reported as changed
+    #[arg(long)]
+    pub ignore_whitespace: bool,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
@@ -49,6 +56,12 @@ struct ChunkRow {
     id: String,
     path: String,
     #[serde(default)]
+    start_line: usize,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    lang: String,
+    #[serde(default)]
     tags: Vec<String>,
 }
 
@@ -82,6 +95,7 @@ struct DiffSummary {
     chunks_before: usize,
     chunks_after: usize,
     chunks_delta: isize,
+    changed_chunks: usize,
     changed_chunk_tags: usize,
     moved_chunks: usize,
     added_files: Vec<ReportFile>,
@@ -96,6 +110,11 @@ pub fn run(args: DiffArgs) -> Result<()> {
 
     let before_chunks = read_chunks(&args.before)?;
     let after_chunks = read_chunks(&args.after)?;
+    let (before_text, after_text) = if args.ignore_whitespace {
+        (normalized_file_text(&before_chunks), normalized_file_text(&after_chunks))
+    } else {
+        Default::default()
+    };
 
     let before_by_path: HashMap<String, ReportFile> =
         before_report.files.into_iter().map(|f| (f.path.clone(), f)).collect();
@@ -110,9 +129,13 @@ pub fn run(args: DiffArgs) -> Result<()> {
     let mut modified_files = Vec::new();
     for (path, before) in &before_by_path {
         if let Some(after) = after_by_path.get(path) {
-            if before.id != after.id
-                || (before.priority - after.priority).abs() >= 0.001
-                || before.tokens != after.tokens
+            let priority_changed = (before.priority - after.priority).abs() >= 0.001;
+            let whitespace_only = !priority_changed
+                && before_text
+                    .get(path.as_str())
+                    .is_some_and(|text| after_text.get(path.as_str()) == Some(text));
+            if !whitespace_only
+                && (before.id != after.id || priority_changed || before.tokens != after.tokens)
             {
                 modified_files.push(ModifiedFile {
                     path: before.path.clone(),
@@ -140,6 +163,24 @@ pub fn run(args: DiffArgs) -> Result<()> {
     let after_chunks_by_id: HashMap<&str, &ChunkRow> =
         after_chunks.iter().map(|row| (row.id.as_str(), row)).collect();
 
+    // Chunks in files present in both exports that have no counterpart in the older one:
+    // matched by id, or by whitespace-normalized content under --ignore-whitespace.
+    let chunk_key = |row: &ChunkRow| -> String {
+        if args.ignore_whitespace {
+            normalize_whitespace(&row.content, &row.lang)
+        } else {
+            row.id.clone()
+        }
+    };
+    let before_keys: HashSet<(&str, String)> =
+        before_chunks.iter().map(|row| (row.path.as_str(), chunk_key(row))).collect();
+    let before_paths: HashSet<&str> = before_chunks.iter().map(|row| row.path.as_str()).collect();
+    let changed_chunks = after_chunks
+        .iter()
+        .filter(|row| before_paths.contains(row.path.as_str()))
+        .filter(|row| !before_keys.contains(&(row.path.as_str(), chunk_key(row))))
+        .count();
+
     let mut tag_changes = 0usize;
     let mut moved_chunks = 0usize;
     for (id, before) in &before_chunks_by_id {
@@ -167,6 +208,7 @@ pub fn run(args: DiffArgs) -> Result<()> {
         chunks_before: before_chunk_count,
         chunks_after: after_chunk_count,
         chunks_delta: after_chunk_count as isize - before_chunk_count as isize,
+        changed_chunks,
         changed_chunk_tags: tag_changes,
         moved_chunks,
         added_files,
@@ -201,6 +243,7 @@ fn render_text(summary: &DiffSummary) {
         "Chunks: {} -> {} ({:+})",
         summary.chunks_before, summary.chunks_after, summary.chunks_delta
     );
+    println!("Changed chunks: {}", summary.changed_chunks);
     println!("Changed chunk tags: {}", summary.changed_chunk_tags);
     println!("Moved chunks: {}", summary.moved_chunks);
 
@@ -259,6 +302,7 @@ fn render_markdown(summary: &DiffSummary) {
         "- Chunks: {} -> {} ({:+})",
         summary.chunks_before, summary.chunks_after, summary.chunks_delta
     );
+    println!("- Changed chunks: {}", summary.changed_chunks);
     println!("- Changed chunk tags: {}", summary.changed_chunk_tags);
     println!("- Moved chunks: {}", summary.moved_chunks);
 
@@ -307,14 +351,72 @@ fn read_chunks(dir: &Path) -> Result<Vec<ChunkRow>> {
     let content = fs::read_to_string(&path)
         .with_context(|| format!("Failed to read chunks.jsonl at {}", path.display()))?;
     let mut rows = Vec::new();
-    for line in content.lines().filter(|line| !line.trim().is_empty()) {
-        if let Ok(row) = serde_json::from_str::<ChunkRow>(line) {
+    for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        // Checked before the row is mapped: a newer record need not fit `ChunkRow`.
+        let schema_version = value
+            .get("schema_version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or_else(legacy_schema_version);
+        if schema_version > JSONL_SCHEMA_VERSION {
+            bail!(
+                "Unsupported chunks.jsonl schema version {} at {}:{}; this build reads versions up to {}",
+                schema_version,
+                path.display(),
+                index + 1,
+                JSONL_SCHEMA_VERSION
+            );
+        }
+        if let Ok(row) = serde_json::from_value::<ChunkRow>(value) {
             rows.push(row);
         }
     }
     Ok(rows)
 }
 
+/// `schema_version` of records written before they were versioned.
+fn legacy_schema_version() -> u64 {
+    1
+}
+
+/// `text` with every whitespace run, line breaks included, collapsed to one space. Python
+/// keeps its line structure and leading indentation, which are syntax there; only runs
+/// after the indentation are collapsed and blank lines dropped.
+fn normalize_whitespace(text: &str, lang: &str) -> String {
+    if lang != "python" {
+        return collapse_whitespace(text);
+    }
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let body = line.trim_start();
+            format!("{}{}", &line[..line.len() - body.len()], collapse_whitespace(body))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whitespace-normalized text of each file, from its chunks in line order.
+fn normalized_file_text(chunks: &[ChunkRow]) -> HashMap<&str, String> {
+    let mut ordered: Vec<&ChunkRow> = chunks.iter().collect();
+    ordered.sort_by(|a, b| a.path.cmp(&b.path).then(a.start_line.cmp(&b.start_line)));
+    let mut text: HashMap<&str, String> = HashMap::new();
+    for row in ordered {
+        let file = text.entry(row.path.as_str()).or_default();
+        if !file.is_empty() {
+            file.push(if row.lang == "python" { '\n' } else { ' ' });
+        }
+        file.push_str(&normalize_whitespace(&row.content, &row.lang));
+    }
+    text
+}
+
 fn compare_graphs(before_dir: &Path, after_dir: &Path) -> Option<GraphDelta> {
     let before_db = resolve_graph_db(before_dir)?;
     let after_db = resolve_graph_db(after_dir)?;
@@ -1,39 +1,72 @@
 //! Export command implementation
 
+use anstream::eprintln;
 use anyhow::{Context, Result};
-use clap::Args;
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use clap::{Args, ValueEnum};
+use dialoguer::{theme::ColorfulTheme, Confirm};
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::IsTerminal;
 use std::path::Path;
 use std::path::PathBuf;
-use std::time::Instant;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
 use super::cache::remote_index_cache_db_path;
 use super::guided::{choose_guided_plan, GuidedPlan};
-use super::utils::{parse_csv, parse_csv_multi};
-use crate::analysis::async_boundary::detect_async_boundaries;
-use crate::analysis::pr::build_pr_context;
-use crate::chunk::{chunk_content, coalesce_small_chunks_with_max};
-use crate::config::{load_config, merge_cli_with_config, CliOverrides};
-use crate::domain::{Chunk, OutputMode, RedactionMode};
+use super::selection::read_selection_file;
+use super::style::{HEADING, PATH, SUCCESS, WARNING};
+use super::utils::{parse_csv, parse_csv_multi, warn_dominant_directory};
+use crate::analysis::assets::referenced_assets;
+use crate::analysis::codeowners::{apply_ownership_tags, CodeOwners};
+use crate::analysis::focus::focus_files_by_dependency;
+use crate::analysis::git_status::apply_git_status_tags;
+use crate::analysis::pr::{build_pr_context, diff_symbols_since};
+use crate::analysis::symbol_scope::scope_files_by_symbol;
+use crate::chunk::{line_range_bytes, line_start_offsets};
+use crate::config::profile::Profile;
+use crate::config::{config_hash, load_config_files, merge_cli_with_config, CliOverrides};
+use crate::domain::{Chunk, ChunkOverlap, OutputMode, RedactionMode};
+use crate::export::coverage::build_coverage_report;
+use crate::export::index_dataset::{
+    evaluate_index_state, load_files_and_stats_from_index, query_graph_stats, IndexFreshness,
+    IndexState,
+};
+use crate::export::pins::{
+    build_pin_plan, default_contribution_globs, default_contribution_paths, PinPlan,
+};
+use crate::export::pipeline::{
+    apply_byte_budget, build_globset, build_pack, build_redactor, report_config, Pack, PackSources,
+};
+use crate::export::{ExportOptions, DEFAULT_MAX_DROPPED_RECORDS};
+use crate::fetch::archive::extract_archive;
+use crate::fetch::changed::{changed_files_since, since_merge_base};
 use crate::fetch::fetch_repository;
-use crate::graph::{lazy_loader::LazyChunkLoader, persist::persist_graph, schema::open_or_create};
-use crate::rank::{
-    dependency_graph, rank_files_with_manifest, rerank_chunks_by_task, stitch_thread_bundles,
-    symbol_definitions, StitchTier,
+use crate::graph::{
+    file_deps::{file_dependencies, unresolved_imports},
+    lazy_loader::LazyChunkLoader,
+    persist::persist_graph,
+    schema::open_or_create,
 };
+use crate::lsp::rust_analyzer;
+use crate::rank::coverage::{apply_coverage_boost, load_coverage};
+use crate::rank::{apply_recency_weight, rank_files_with_manifest, topological_file_order};
+use crate::redact::gitleaks::import_gitleaks_rules;
 use crate::redact::Redactor;
-use crate::render::{render_context_pack, render_jsonl, write_report, ReportOptions};
-use crate::rerank::{build_reranker, normalize_scores};
+use crate::render::{
+    render_context_pack, render_files_json, render_jsonl, render_repo_map, resolve_section_order,
+    write_report, PackRenderOptions, ReportOptions,
+};
 use crate::scan::scanner::FileScanner;
-use crate::scan::tree::generate_tree;
-use crate::utils::read_file_safe;
+use crate::scan::tree::generate_tree_with_redactor;
+use crate::utils::output::println;
+use crate::utils::{
+    format_with_commas, read_file_safe, stable_json_hash, EncodingOverrides, WorkingTree,
+};
 
-#[derive(Args)]
+#[derive(Args, Default)]
 pub struct ExportArgs {
     /// Local directory path to export
     #[arg(short, long, value_name = "PATH")]
@@ -47,9 +80,17 @@ pub struct ExportArgs {
     #[arg(long, value_name = "REF")]
     pub ref_: Option<String>,
 
-    /// Path to config file (repo-context.toml or .r2p.yml)
+    /// Extract a .tar, .tar.gz/.tgz or .zip archive and export its contents
+    #[arg(long, value_name = "FILE")]
+    pub archive: Option<PathBuf>,
+
+    /// Path to config file (repo-context.toml or .r2p.yml); repeat to merge several in order
     #[arg(short = 'c', long, value_name = "FILE")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<PathBuf>,
+
+    /// Built-in preset for a stack's extensions, excludes and ranking weights
+    #[arg(long, value_enum, value_name = "PROFILE", alias = "lang-profile")]
+    pub profile: Option<Profile>,
 
     /// Include only these extensions (comma-separated, e.g., '.py,.ts')
     #[arg(short = 'i', long, value_name = "EXTS")]
@@ -59,6 +100,14 @@ pub struct ExportArgs {
     #[arg(short = 'e', long, value_name = "GLOBS")]
     pub exclude_glob: Option<String>,
 
+    /// Exclude exact relative paths listed in a file, one per line (e.g. from `git diff --name-only`)
+    #[arg(long, value_name = "FILE")]
+    pub exclude_paths_from: Option<PathBuf>,
+
+    /// Boost and tag (`covered`) files with high line coverage in an lcov.info or coverage.xml report
+    #[arg(long, value_name = "FILE")]
+    pub coverage_file: Option<PathBuf>,
+
     /// Skip files larger than this (bytes)
     #[arg(long, value_name = "BYTES")]
     pub max_file_bytes: Option<u64>,
@@ -67,6 +116,10 @@ pub struct ExportArgs {
     #[arg(long, value_name = "BYTES")]
     pub max_total_bytes: Option<u64>,
 
+    /// Abort when the scan sees more than this many files
+    #[arg(long, value_name = "N")]
+    pub max_scan_files: Option<usize>,
+
     /// Ignore .gitignore rules
     #[arg(long)]
     pub no_gitignore: bool,
@@ -79,10 +132,26 @@ pub struct ExportArgs {
     #[arg(long)]
     pub include_minified: bool,
 
+    /// Read Git LFS pointer files from .git/lfs/objects when the object is present
+    #[arg(long)]
+    pub lfs_resolve: bool,
+
     /// Maximum tokens in output
     #[arg(short = 't', long, value_name = "TOKENS")]
     pub max_tokens: Option<usize>,
 
+    /// Cap tokens admitted from any one file (absolute, or a fraction of --max-tokens when <= 1.0)
+    #[arg(long, value_name = "TOKENS|FRACTION")]
+    pub max_tokens_per_file: Option<f64>,
+
+    /// Hold back N tokens of --max-tokens for text sent after the pack (e.g. your prompt)
+    #[arg(long, value_name = "N")]
+    pub reserve_tokens: Option<usize>,
+
+    /// Keep only the first N lines of each file, truncated before chunking
+    #[arg(long, value_name = "N")]
+    pub head_lines: Option<usize>,
+
     /// Allow always-include files to exceed max token budget
     #[arg(long)]
     pub allow_over_budget: bool,
@@ -91,6 +160,10 @@ pub struct ExportArgs {
     #[arg(long)]
     pub strict_budget: bool,
 
+    /// Keep the first chunk of each budget-dropped file as a low-priority `stub` chunk
+    #[arg(long)]
+    pub stub_dropped: bool,
+
     /// Always-include repository-relative paths (repeatable or comma-separated)
     #[arg(long, value_name = "PATHS", value_delimiter = ',', num_args = 1..)]
     pub always_include_path: Vec<String>,
@@ -107,10 +180,46 @@ pub struct ExportArgs {
     #[arg(long, value_name = "WORDS", value_delimiter = ',', num_args = 1..)]
     pub invariant_keywords_add: Vec<String>,
 
+    /// Blend file recency into priority (0.0-1.0)
+    #[arg(long, value_name = "FLOAT")]
+    pub recency_weight: Option<f64>,
+
     /// Task description for retrieval-driven reranking
     #[arg(long, value_name = "TEXT")]
     pub task: Option<String>,
 
+    /// Only export files defining or referencing this symbol, plus their import neighbors
+    #[arg(long, value_name = "NAME")]
+    pub symbol: Option<String>,
+
+    /// Only export this file plus the files within --focus-depth import hops of it
+    #[arg(long, value_name = "PATH")]
+    pub focus_file: Option<PathBuf>,
+
+    /// Import hops followed from --focus-file, in either direction
+    #[arg(long, value_name = "N", default_value_t = 2, requires = "focus_file")]
+    pub focus_depth: usize,
+
+    /// Tag files as git:modified/git:untracked/git:clean and note it in the pack (git repos only)
+    #[arg(long)]
+    pub include_git_status: bool,
+
+    /// Tag files with their CODEOWNERS owners (owner:@team) and show owners in the pack
+    #[arg(long)]
+    pub annotate_ownership: bool,
+
+    /// Tag chunks with rust-analyzer diagnostics (diagnostic:<severity>) and list them in the pack
+    #[arg(long)]
+    pub include_diagnostics: bool,
+
+    /// List binary files named by string literals in included code, with size and SHA-256
+    #[arg(long)]
+    pub asset_metadata: bool,
+
+    /// Bias ranking and dependency expansion toward this file (repeatable)
+    #[arg(long = "seed-file", value_name = "PATH")]
+    pub seed_file: Vec<PathBuf>,
+
     /// Disable second-stage semantic reranking
     #[arg(long)]
     pub no_semantic_rerank: bool,
@@ -123,6 +232,18 @@ pub struct ExportArgs {
     #[arg(long, value_name = "N")]
     pub rerank_top_k: Option<usize>,
 
+    /// Number of top lexical files used as dependency-expansion seeds (default 5)
+    #[arg(long, value_name = "N")]
+    pub expand_seeds: Option<usize>,
+
+    /// Dependency-graph hops followed from each expansion seed (default 2)
+    #[arg(long, value_name = "D")]
+    pub expand_depth: Option<usize>,
+
+    /// Per-level expansion decay factors, comma-separated (default 0.6,0.3)
+    #[arg(long, value_name = "FLOATS", value_delimiter = ',', num_args = 1..)]
+    pub expand_decay: Vec<f64>,
+
     /// Fraction of max tokens reserved for stitched context
     #[arg(long, value_name = "FLOAT")]
     pub stitch_budget_fraction: Option<f64>,
@@ -135,15 +256,28 @@ pub struct ExportArgs {
     #[arg(long, value_name = "TOKENS")]
     pub chunk_tokens: Option<usize>,
 
-    /// Overlap tokens between adjacent chunks
-    #[arg(long, value_name = "TOKENS")]
-    pub chunk_overlap: Option<usize>,
+    /// Overlap between adjacent chunks, in tokens or as a percentage of the chunk size (e.g. 15%)
+    #[arg(long, value_name = "TOKENS|PCT%")]
+    pub chunk_overlap: Option<ChunkOverlap>,
 
     /// Coalesce chunks smaller than this
     #[arg(long, value_name = "TOKENS")]
     pub min_chunk_tokens: Option<usize>,
 
-    /// Output format: 'prompt' (Markdown), 'rag' (JSONL), 'contribution', 'pr-context', or 'both'
+    /// Split chunks spanning more lines than this, even when under the token size
+    #[arg(long, value_name = "LINES")]
+    pub max_chunk_lines: Option<usize>,
+
+    /// After coalescing, merge consecutive same-file chunks up to chunk_tokens * merge factor
+    #[arg(long)]
+    pub merge_adjacent: bool,
+
+    /// Size multiplier for --merge-adjacent (default 2.0)
+    #[arg(long, value_name = "FLOAT", requires = "merge_adjacent")]
+    pub merge_factor: Option<f64>,
+
+    /// Output format: 'prompt' (Markdown), 'rag' (JSONL), 'contribution', 'pr-context',
+    /// 'files-json' (whole-file JSON array), 'map' (one summary line per file), or 'both'
     #[arg(short = 'm', long, value_name = "MODE")]
     pub mode: Option<String>,
 
@@ -151,14 +285,100 @@ pub struct ExportArgs {
     #[arg(short = 'o', long, value_name = "DIR")]
     pub output_dir: Option<PathBuf>,
 
+    /// Keep at most this many dropped-file records for the summary and context pack
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DROPPED_RECORDS)]
+    pub max_dropped_records: usize,
+
+    /// Delete this repository's output directory before writing, dropping stale files
+    #[arg(long, conflicts_with = "no_overwrite")]
+    pub clean: bool,
+
+    /// Fail instead of overwriting outputs left by an earlier export
+    #[arg(long)]
+    pub no_overwrite: bool,
+
+    /// Skip the export when the selected files, config and flags match the last run
+    #[arg(long, conflicts_with = "clean")]
+    pub skip_if_unchanged: bool,
+
     /// Omit timestamps for reproducible diffs
     #[arg(long)]
     pub no_timestamp: bool,
 
+    /// Pretty-print report.json (the default)
+    #[arg(long, conflicts_with = "report_compact")]
+    pub report_pretty: bool,
+
+    /// Write report.json as single-line JSON
+    #[arg(long)]
+    pub report_compact: bool,
+
+    /// Add a table of contents linking to each included file's section
+    #[arg(long)]
+    pub toc: bool,
+
+    /// Group file contents under directory headings (priority order within each directory)
+    #[arg(long)]
+    pub group_by_directory: bool,
+
+    /// Mark where a file continues in a new chunk with a comment in the Markdown pack
+    #[arg(long)]
+    pub annotate_splits: bool,
+
+    /// Order of files in the Markdown pack and chunks.jsonl: by rank, or imported files
+    /// before their importers (import cycles open at the smallest path)
+    #[arg(long, value_enum, default_value = "ranked")]
+    pub order: PackOrder,
+
+    /// Add start_byte/end_byte (offsets into the original, pre-redaction file) to JSONL chunks
+    #[arg(long)]
+    pub emit_byte_offsets: bool,
+
+    /// Write <repo>_file_deps.json mapping each exported file to the files it imports
+    #[arg(long)]
+    pub emit_file_deps: bool,
+
+    /// Record imports in exported chunks that match no scanned file in report.json
+    #[arg(long)]
+    pub report_unresolved_imports: bool,
+
+    /// Export GraphQL and OpenAPI schema files as compact type/operation summaries
+    #[arg(long)]
+    pub summarize_schemas: bool,
+
+    /// Keep a license header shared by several files only in the first of them
+    #[arg(long)]
+    pub dedup_license_headers: bool,
+
     /// Max depth for directory tree in output
     #[arg(long, value_name = "DEPTH")]
     pub tree_depth: Option<usize>,
 
+    /// Collapse chains of single-child directories into one tree line (`a/b/c/`)
+    #[arg(long)]
+    pub tree_collapse_single: bool,
+
+    /// Annotate each file in the tree with its number of symbol definitions
+    #[arg(long)]
+    pub tree_symbol_counts: bool,
+
+    /// Break the estimated token total down by chunk tag (printed and in report.json)
+    #[arg(long)]
+    pub token_report: bool,
+
+    /// List every dropped file with its reason and how full the budget was when it was dropped
+    #[arg(long)]
+    pub explain_budget: bool,
+
+    /// Shell command to run after a successful export (sees R2P_OUTPUT_DIR,
+    /// R2P_PACK_PATH and R2P_TOKEN_COUNT); its failing exit code is relayed
+    #[arg(long, value_name = "CMD")]
+    pub post_export_cmd: Option<String>,
+
+    /// Load extra redaction rules and allowlists from a gitleaks-style TOML file
+    #[arg(long, value_name = "FILE")]
+    pub redaction_rules: Option<PathBuf>,
+
     /// Disable automatic secret/credential redaction
     #[arg(long)]
     pub no_redact: bool,
@@ -167,6 +387,22 @@ pub struct ExportArgs {
     #[arg(long, value_name = "MODE")]
     pub redaction_mode: Option<String>,
 
+    /// Mask secret-looking segments of file paths in the tree, headings, and JSONL
+    #[arg(long)]
+    pub redact_paths: bool,
+
+    /// Write each redaction made in the export as a JSONL row {path, rule, line} to FILE
+    #[arg(long, value_name = "FILE")]
+    pub redaction_audit: Option<PathBuf>,
+
+    /// List where redaction would fire (file:line, rule, masked value) and write nothing
+    #[arg(long, conflicts_with = "no_redact")]
+    pub preview_redaction: bool,
+
+    /// NFC-normalize chunk text and strip invisible zero-width/bidi control characters
+    #[arg(long)]
+    pub normalize_unicode: bool,
+
     /// Skip writing persisted graph database
     #[arg(long)]
     pub no_graph: bool,
@@ -175,6 +411,10 @@ pub struct ExportArgs {
     #[arg(long)]
     pub quick: bool,
 
+    /// Proceed without confirmation when the repository looks unusually large
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
     /// Prefer loading files/chunks from local index when it is fresh
     #[arg(long)]
     pub from_index: bool,
@@ -182,9 +422,38 @@ pub struct ExportArgs {
     /// Require a fresh local index when using --from-index
     #[arg(long)]
     pub require_fresh_index: bool,
+
+    /// Fail instead of building a pack-only symbol graph when no usable index.sqlite graph exists
+    #[arg(long, alias = "no-graph-fallback", conflicts_with = "no_graph")]
+    pub require_index: bool,
+
+    /// Restrict export to paths listed in a selection file (see `select`)
+    #[arg(long, value_name = "FILE")]
+    pub selection: Option<PathBuf>,
+
+    /// Export only files changed since this git ref (`git diff REF...HEAD` plus uncommitted
+    /// changes) and always-include matches; in pr-context mode, also list changed definitions
+    #[arg(long, value_name = "REF")]
+    pub since: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PackOrder {
+    #[default]
+    Ranked,
+    Topological,
+}
+
+impl PackOrder {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ranked => "ranked",
+            Self::Topological => "topological",
+        }
+    }
 }
 
-pub fn run(args: ExportArgs) -> Result<()> {
+pub fn run(args: ExportArgs) -> Result<ExitCode> {
     let start_time = Instant::now();
 
     let interactive_terminal = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
@@ -198,6 +467,17 @@ pub fn run(args: ExportArgs) -> Result<()> {
     if args.path.is_some() && args.repo.is_some() {
         anyhow::bail!("Cannot specify both --path and --repo");
     }
+    if args.archive.is_some() && (args.path.is_some() || args.repo.is_some()) {
+        anyhow::bail!("Cannot combine --archive with --path or --repo");
+    }
+    if let Some(factor) = args.merge_factor {
+        if !factor.is_finite() || factor <= 0.0 {
+            anyhow::bail!("--merge-factor must be positive, got {factor}");
+        }
+    }
+    if args.head_lines == Some(0) {
+        anyhow::bail!("--head-lines must be positive, got 0");
+    }
 
     let cwd = std::env::current_dir()?;
     let config_anchor = match args.path.as_ref() {
@@ -211,7 +491,7 @@ pub fn run(args: ExportArgs) -> Result<()> {
         None => cwd.clone(),
     };
 
-    let file_config = load_config(&config_anchor, args.config.as_deref())?;
+    let file_config = load_config_files(&config_anchor, &args.config, args.profile)?;
     let include_ext = parse_csv(&args.include_ext).map(|v| v.into_iter().collect());
     let exclude_glob = parse_csv(&args.exclude_glob).map(|v| v.into_iter().collect());
     let mode = if args.mode.is_some() { Some(parse_mode(args.mode.as_deref())?) } else { None };
@@ -232,16 +512,27 @@ pub fn run(args: ExportArgs) -> Result<()> {
         respect_gitignore: if args.no_gitignore { Some(false) } else { None },
         follow_symlinks: if args.follow_symlinks { Some(true) } else { None },
         skip_minified: if args.include_minified { Some(false) } else { None },
+        lfs_resolve: if args.lfs_resolve { Some(true) } else { None },
         max_tokens: args.max_tokens,
+        max_tokens_per_file: args.max_tokens_per_file,
+        reserve_tokens: args.reserve_tokens,
         task_query: args.task.clone(),
         semantic_rerank: if args.no_semantic_rerank { Some(false) } else { None },
         rerank_top_k: args.rerank_top_k,
         semantic_model: args.semantic_model.clone(),
+        expand_seeds: args.expand_seeds,
+        expand_depth: args.expand_depth,
+        expand_decay: if args.expand_decay.is_empty() {
+            None
+        } else {
+            Some(args.expand_decay.clone())
+        },
         stitch_budget_fraction: args.stitch_budget_fraction,
         stitch_top_n: args.stitch_top_n,
         chunk_tokens: args.chunk_tokens,
         chunk_overlap: args.chunk_overlap,
         min_chunk_tokens: args.min_chunk_tokens,
+        max_chunk_lines: args.max_chunk_lines,
         mode,
         output_dir: args.output_dir.clone(),
         tree_depth: args.tree_depth,
@@ -250,9 +541,29 @@ pub fn run(args: ExportArgs) -> Result<()> {
         always_include_patterns: None,
         always_include_paths: None,
         invariant_keywords: None,
+        recency_weight: args.recency_weight,
     };
 
     let mut merged = merge_cli_with_config(file_config, cli_overrides);
+    if let Some(factor) = merged.expand_decay.iter().find(|f| !(0.0..=1.0).contains(*f)) {
+        anyhow::bail!("expansion decay factors must be within 0.0-1.0, got {factor}");
+    }
+
+    if let Some(rules_path) = args.redaction_rules.as_deref() {
+        let import = import_gitleaks_rules(rules_path, &mut merged.redaction)?;
+        eprintln!(
+            "info: loaded {} redaction rule(s) from {}",
+            import.rules_loaded,
+            rules_path.display()
+        );
+        if !import.rules_skipped.is_empty() {
+            eprintln!(
+                "warning: skipped {} gitleaks rule(s) without a usable regex: {}",
+                import.rules_skipped.len(),
+                import.rules_skipped.join(", ")
+            );
+        }
+    }
 
     let cli_pin_paths = parse_csv_multi(&args.always_include_path);
     for path in cli_pin_paths {
@@ -293,21 +604,43 @@ pub fn run(args: ExportArgs) -> Result<()> {
         }
     }
 
-    if merged.path.is_none() && merged.repo_url.is_none() {
-        anyhow::bail!("Either --path or --repo must be specified");
-    }
-
-    let repo_ctx = fetch_repository(
-        merged.path.as_deref(),
-        merged.repo_url.as_deref(),
-        merged.ref_.as_deref(),
-    )?;
+    let repo_ctx = if let Some(archive) = args.archive.as_deref() {
+        extract_archive(archive)?
+    } else {
+        if merged.path.is_none() && merged.repo_url.is_none() {
+            anyhow::bail!("Either --path or --repo must be specified");
+        }
+        fetch_repository(
+            merged.path.as_deref(),
+            merged.repo_url.as_deref(),
+            merged.ref_.as_deref(),
+        )?
+    };
     let root_path = repo_ctx.root_path.clone();
     let index_db_path = resolve_index_db_path(&root_path, &merged);
     let lazy_loader = index_db_path.as_deref().map(LazyChunkLoader::new);
+    if args.require_index {
+        match index_db_path.as_deref() {
+            None => anyhow::bail!(
+                "--require-index: no index.sqlite found; run 'repo-context index' first"
+            ),
+            Some(db) if query_graph_stats(db).is_none() => anyhow::bail!(
+                "--require-index: {} has no symbol graph tables; re-run 'repo-context index'",
+                db.display()
+            ),
+            Some(_) => {}
+        }
+    }
 
     let index_state = evaluate_index_state(index_db_path.as_deref(), &root_path, &merged);
     let mut used_index_dataset = false;
+    let preflight = ScanPreflight {
+        max_files: args.max_scan_files,
+        warn_files: merged.scan_warn_files,
+        warn_bytes: merged.scan_warn_bytes,
+        confirm: interactive_terminal && !args.yes,
+    };
+    let encodings = EncodingOverrides::from_config(&merged)?;
     let (mut stats, ranked_files, manifest_info) = if args.from_index {
         match index_state.kind {
             IndexFreshness::Fresh | IndexFreshness::Stale => {
@@ -329,8 +662,15 @@ pub fn run(args: ExportArgs) -> Result<()> {
                 println!("info: using index dataset from {}", db_path.display());
                 let (stats, files) = load_files_and_stats_from_index(db_path, &root_path)?;
                 used_index_dataset = true;
-                let (ranked_files, manifest_info) =
-                    rank_files_with_manifest(&root_path, files, merged.ranking_weights.clone())?;
+                let (mut ranked_files, manifest_info) = rank_files_with_manifest(
+                    &root_path,
+                    files,
+                    merged.ranking_weights.clone(),
+                    &merged.fixture_dirs,
+                    &merged.language_weight_multipliers,
+                    &encodings,
+                )?;
+                apply_recency_weight(&mut ranked_files, merged.recency_weight);
                 (stats, ranked_files, manifest_info)
             }
             _ => {
@@ -343,20 +683,173 @@ pub fn run(args: ExportArgs) -> Result<()> {
                 if let Some(reason) = index_state.reason.as_deref() {
                     eprintln!("info: index not fresh ({reason}); falling back to scan export");
                 }
-                collect_scan_inputs(&root_path, &merged)?
+                collect_scan_inputs(&root_path, &merged, &preflight, &encodings)?
             }
         }
     } else {
-        collect_scan_inputs(&root_path, &merged)?
+        collect_scan_inputs(&root_path, &merged, &preflight, &encodings)?
+    };
+    // The preview covers every scanned file and returns before anything in the output
+    // directory is touched.
+    if args.preview_redaction {
+        if !merged.redact_secrets {
+            anyhow::bail!("--preview-redaction needs redaction enabled (redact_secrets = true)");
+        }
+        let redactor = build_redactor(merged.redaction_mode, &merged.redaction);
+        preview_redaction(&redactor, &ranked_files, &encodings)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    // Unresolved imports are judged against every scanned file, not only the exported ones.
+    let scanned_paths: HashSet<String> = if args.report_unresolved_imports {
+        ranked_files.iter().map(|f| f.relative_path.clone()).collect()
+    } else {
+        HashSet::new()
+    };
+    // Selected files keep the selection file's order, in the file list and in the pack.
+    let mut selection_order: Option<Vec<String>> = None;
+    let ranked_files = match args.selection.as_deref() {
+        Some(selection_path) => {
+            let selected = read_selection_file(selection_path, &root_path)?;
+            let position: HashMap<&str, usize> =
+                selected.iter().enumerate().map(|(i, path)| (path.as_str(), i)).collect();
+            let mut filtered: Vec<_> = ranked_files
+                .into_iter()
+                .filter(|f| position.contains_key(f.relative_path.as_str()))
+                .collect();
+            filtered.sort_by_key(|f| position[f.relative_path.as_str()]);
+            println!(
+                "info: selection file {} keeps {} of {} listed path(s)",
+                selection_path.display(),
+                filtered.len(),
+                selected.len()
+            );
+            selection_order = Some(selected);
+            filtered
+        }
+        None => ranked_files,
+    };
+    let ranked_files = match args.exclude_paths_from.as_deref() {
+        Some(exclude_path) => {
+            let excluded: HashSet<String> =
+                read_selection_file(exclude_path, &root_path)?.into_iter().collect();
+            let before = ranked_files.len();
+            let filtered: Vec<_> =
+                ranked_files.into_iter().filter(|f| !excluded.contains(&f.relative_path)).collect();
+            println!(
+                "info: {} excluded {} file(s)",
+                exclude_path.display(),
+                before - filtered.len()
+            );
+            filtered
+        }
+        None => ranked_files,
+    };
+    let mut changed_since: Option<usize> = None;
+    let since_base = args.since.as_deref().and_then(|base_ref| {
+        since_merge_base(&root_path, base_ref)
+            .map_err(|err| {
+                eprintln!("warning: --since {base_ref} ignored ({err:#}); exporting all files");
+            })
+            .ok()
+            .map(|merge_base| (base_ref, merge_base))
+    });
+    let ranked_files = match since_base {
+        Some((base_ref, merge_base)) => match changed_files_since(&root_path, merge_base) {
+            Ok(changed) => {
+                let always = build_globset(&merged.always_include_patterns)?;
+                let before = ranked_files.len();
+                let filtered: Vec<_> = ranked_files
+                    .into_iter()
+                    .filter(|f| {
+                        changed.contains(&f.relative_path)
+                            || merged.always_include_paths.contains(&f.relative_path)
+                            || always.as_ref().is_some_and(|g| g.is_match(&f.relative_path))
+                    })
+                    .collect();
+                println!(
+                    "info: --since {base_ref}: {} changed path(s); keeping {} of {} file(s)",
+                    changed.len(),
+                    filtered.len(),
+                    before
+                );
+                changed_since = Some(changed.len());
+                filtered
+            }
+            Err(err) => {
+                eprintln!("warning: --since {base_ref} ignored ({err:#}); exporting all files");
+                ranked_files
+            }
+        },
+        None => ranked_files,
     };
+    let mut ranked_files = ranked_files;
+    if let Some(coverage_path) = args.coverage_file.as_deref() {
+        let coverage = load_coverage(coverage_path, &root_path)?;
+        let boosted = apply_coverage_boost(&mut ranked_files, &coverage);
+        eprintln!("info: {} boosted {} well-covered file(s)", coverage_path.display(), boosted);
+    }
+    if let Some(symbol) = args.symbol.as_deref() {
+        let scope =
+            scope_files_by_symbol(&ranked_files, symbol, index_db_path.as_deref(), &encodings);
+        if scope.matched.is_empty() {
+            anyhow::bail!("No files define or reference symbol '{symbol}'");
+        }
+        ranked_files.retain(|f| scope.contains(&f.relative_path));
+        println!(
+            "info: --symbol {symbol} keeps {} file(s) ({} touching it, {} import neighbors)",
+            ranked_files.len(),
+            scope.matched.len(),
+            scope.neighbors.len()
+        );
+    }
+    let focus_file = args.focus_file.as_deref().map(|path| repo_relative_path(path, &root_path));
+    if let Some(focus) = focus_file.as_deref() {
+        let reached = focus_files_by_dependency(&ranked_files, focus, args.focus_depth, &encodings);
+        if reached.is_empty() {
+            anyhow::bail!("--focus-file {focus} is not among the scanned files");
+        }
+        ranked_files.retain(|f| reached.contains_key(&f.relative_path));
+        println!(
+            "info: --focus-file {focus} keeps {} file(s) within {} import hop(s)",
+            ranked_files.len(),
+            args.focus_depth
+        );
+    }
+    if args.include_git_status {
+        match apply_git_status_tags(&root_path, &mut ranked_files) {
+            Some(summary) => println!(
+                "info: git status: {} modified, {} untracked, {} clean",
+                summary.modified, summary.untracked, summary.clean
+            ),
+            None => println!("info: --include-git-status skipped (not a git repository)"),
+        }
+    }
+    if args.annotate_ownership {
+        match CodeOwners::load(&root_path) {
+            Some(codeowners) => {
+                let owned = apply_ownership_tags(&codeowners, &mut ranked_files);
+                println!(
+                    "info: {} of {} file(s) have owners in {}",
+                    owned,
+                    ranked_files.len(),
+                    codeowners.source
+                );
+            }
+            None => println!("info: --annotate-ownership skipped (no CODEOWNERS file)"),
+        }
+    }
     stats.top_ranked_files = ranked_files
         .iter()
         .take(20)
         .map(|f| {
-            std::collections::HashMap::from([
+            let mut record = std::collections::HashMap::from([
                 ("path".to_string(), json!(f.relative_path)),
                 ("priority".to_string(), json!(f.priority)),
-            ])
+            ]);
+            if let Some(boost) = f.priority_reason.as_ref().and_then(|r| r.coverage_boost) {
+                record.insert("coverage_boost".to_string(), json!(boost));
+            }
+            record
         })
         .collect();
 
@@ -369,6 +862,7 @@ pub fn run(args: ExportArgs) -> Result<()> {
         Some(build_pin_plan(
             &root_path,
             &ranked_files,
+            &encodings,
             &merged.always_include_paths,
             &merged.always_include_patterns,
             &merged.invariant_keywords,
@@ -379,7 +873,8 @@ pub fn run(args: ExportArgs) -> Result<()> {
 
     let protected_paths = pin_plan.as_ref().map(|plan| plan.protected_paths()).unwrap_or_default();
 
-    let mut selected_files =
+    stats.max_dropped_records = Some(args.max_dropped_records);
+    let selected_files =
         apply_byte_budget(ranked_files, Some(merged.max_total_bytes), &mut stats, &protected_paths);
 
     if let Some(plan) = pin_plan.as_ref() {
@@ -399,263 +894,128 @@ pub fn run(args: ExportArgs) -> Result<()> {
             .collect();
     }
 
-    let chunk_tokens = merged.chunk_tokens;
-    let chunk_overlap = merged.chunk_overlap;
-    let redactor = if merged.redact_secrets {
-        Some(build_redactor(merged.redaction_mode, &merged.redaction))
+    let output_dir = resolve_output_dir(&merged.output_dir, &root_path, merged.repo_url.as_deref());
+    let repo_name = repo_name_for_output(&root_path, merged.repo_url.as_deref());
+    let inputs_marker = output_dir.join(prefixed_output_file_name(&repo_name, "inputs.sha256"));
+    let inputs_hash = if args.skip_if_unchanged {
+        let hash = export_inputs_hash(&merged, &selected_files)?;
+        if previous_export_matches(&inputs_marker, &hash) {
+            println!("info: no changes since the last export in {}; skipped", output_dir.display());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(hash)
     } else {
+        // This export may rewrite the outputs the marker vouches for.
+        if inputs_marker.exists() {
+            fs::remove_file(&inputs_marker)
+                .with_context(|| format!("Failed to remove {}", inputs_marker.display()))?;
+        }
         None
     };
-    let always_include =
-        if contribution_mode { None } else { build_globset(&merged.always_include_patterns)? };
-    let mut chunks: Vec<Chunk> = Vec::new();
-    let mut always_indices = Vec::new();
-    let mut advisory_indices = Vec::new();
-    let mut normal_indices = Vec::new();
-    for (idx, file) in selected_files.iter().enumerate() {
-        let pin_tier = pin_plan.as_ref().and_then(|plan| plan.tier_for(&file.relative_path));
-        if matches!(pin_tier, Some(PinTier::Tier0 | PinTier::Tier1)) {
-            always_indices.push(idx);
-        } else if matches!(pin_tier, Some(PinTier::Tier2)) {
-            advisory_indices.push(idx);
-        } else if always_include.as_ref().map(|g| g.is_match(&file.relative_path)).unwrap_or(false)
-        {
-            always_indices.push(idx);
-        } else {
-            normal_indices.push(idx);
-        }
-    }
-
-    let mut always_tokens = 0usize;
-    for idx in always_indices {
-        if let Some(file_chunks) = process_file_for_export(
-            &mut selected_files[idx],
-            used_index_dataset,
-            lazy_loader.as_ref(),
-            redactor.as_ref(),
-            chunk_tokens,
-            chunk_overlap,
-            &mut stats,
-        )? {
-            let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
-            always_tokens += file_tokens;
-            chunks.extend(file_chunks);
-        }
-    }
-
-    let mut pinned_only_mode = false;
-    if let Some(max_tokens) = merged.max_tokens {
-        if always_tokens > max_tokens {
-            let overflow = always_tokens.saturating_sub(max_tokens);
-            stats.pinned_overflow_tokens = overflow;
-            if contribution_mode {
-                if args.strict_budget {
-                    anyhow::bail!(
-                        "protected pin files require {always_tokens} tokens but max_tokens={max_tokens}; increase budget or remove --strict-budget"
-                    );
-                }
-                pinned_only_mode = true;
-                stats.pinned_only_mode = true;
-                eprintln!(
-                    "info: protected pins exceed max_tokens by {overflow}; writing pinned-only contribution pack"
-                );
-            } else if !args.allow_over_budget {
-                anyhow::bail!(
-                    "always-include files require {always_tokens} tokens but max_tokens={max_tokens}; use --allow-over-budget to proceed"
-                );
-            }
-        }
-    }
 
-    let mut normal_tokens = 0usize;
-    let mut remaining_budget = merged.max_tokens.map(|max| max.saturating_sub(always_tokens));
-    if let (Some(max_tokens), Some(rest)) = (merged.max_tokens, remaining_budget) {
-        if always_tokens > max_tokens {
-            eprintln!(
-                "Warning: always-include files use {} tokens above max_tokens={} (remaining budget: {})",
-                always_tokens.saturating_sub(max_tokens),
-                max_tokens,
-                rest
-            );
-            remaining_budget = Some(0);
-        }
-    }
-
-    let mut budgeted_indices = Vec::new();
-    if !pinned_only_mode {
-        budgeted_indices.extend(advisory_indices);
-        budgeted_indices.extend(normal_indices);
+    let section_order = resolve_section_order(&merged.section_order)
+        .map_err(|err| anyhow::anyhow!("Invalid section_order: {err}"))?;
+
+    let options = ExportOptions {
+        repo_name: repo_name.clone(),
+        head_lines: args.head_lines,
+        normalize_unicode: args.normalize_unicode,
+        summarize_schemas: args.summarize_schemas,
+        stub_dropped: args.stub_dropped,
+        merge_adjacent: args.merge_adjacent.then(|| args.merge_factor.unwrap_or(2.0)),
+        allow_over_budget: args.allow_over_budget,
+        strict_budget: args.strict_budget,
+        dedup_license_headers: args.dedup_license_headers,
+        token_report: args.token_report,
+        max_dropped_records: args.max_dropped_records,
+        seed_files: args
+            .seed_file
+            .iter()
+            .map(|seed| repo_relative_path(seed, &root_path))
+            .collect(),
+        include_timestamp: !args.no_timestamp,
+        toc: args.toc,
+        group_by_directory: args.group_by_directory,
+        annotate_splits: args.annotate_splits,
+    };
+    let sources = PackSources {
+        lazy_loader: lazy_loader.as_ref(),
+        chunks_from_index: used_index_dataset,
+        pins: pin_plan.as_ref().map(PinPlan::placements).unwrap_or_default(),
+        workspace_members: extract_workspace_members(&manifest_info),
+        redaction_audit: args.redaction_audit.is_some(),
+    };
+    // The map lists every ranked file; `max_tokens` caps the map instead of file contents.
+    let map_config;
+    let pack_config = if merged.mode == OutputMode::Map {
+        map_config =
+            crate::domain::Config { max_tokens: None, max_tokens_per_file: None, ..merged.clone() };
+        &map_config
+    } else {
+        &merged
+    };
+    let pack = build_pack(
+        pack_config,
+        selected_files,
+        stats,
+        &options,
+        &WorkingTree { encodings: &encodings },
+        &sources,
+    )?;
+    let pack_report_config = report_config(&merged, &options, &section_order, &pack);
+    let Pack {
+        files: selected_files,
+        mut chunks,
+        mut stats,
+        reranking_mode,
+        seed_files,
+        per_file_token_cap: _,
+        stitch_tokens_used,
+        stitched_unavailable_chunks,
+        dropped_total,
+        mut redaction_audit,
+    } = pack;
+    if stats.stitched_chunks > 0 {
+        println!(
+            "  Thread stitching: {} chunks (~{} tokens reserved)",
+            stats.stitched_chunks, stitch_tokens_used
+        );
     }
-
-    for idx in budgeted_indices {
-        let Some(file_chunks) = process_file_for_export(
-            &mut selected_files[idx],
-            used_index_dataset,
-            lazy_loader.as_ref(),
-            redactor.as_ref(),
-            chunk_tokens,
-            chunk_overlap,
-            &mut stats,
-        )?
-        else {
-            continue;
-        };
-
-        let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
-        if let Some(budget) = remaining_budget {
-            if normal_tokens + file_tokens > budget {
-                stats.files_dropped_budget += 1;
-                stats.dropped_files.push(std::collections::HashMap::from([
-                    ("path".to_string(), json!(selected_files[idx].relative_path)),
-                    (
-                        "reason".to_string(),
-                        json!(if pin_plan
-                            .as_ref()
-                            .and_then(|plan| plan.tier_for(&selected_files[idx].relative_path))
-                            == Some(PinTier::Tier2)
-                        {
-                            "token_budget_tier2"
-                        } else {
-                            "token_budget"
-                        }),
-                    ),
-                    (
-                        "priority".to_string(),
-                        json!((selected_files[idx].priority * 1000.0).round() / 1000.0),
-                    ),
-                    ("tokens".to_string(), json!(file_tokens)),
-                    ("chunks".to_string(), json!(file_chunks.len())),
-                ]));
-                continue;
-            }
-        }
-        normal_tokens += file_tokens;
-        chunks.extend(file_chunks);
+    if args.include_diagnostics {
+        apply_diagnostics(&root_path, &mut chunks, &mut stats);
     }
-
-    let min_chunk_tokens = merged.min_chunk_tokens;
-    chunks = coalesce_small_chunks_with_max(chunks, min_chunk_tokens, chunk_tokens);
-    let workspace_members = extract_workspace_members(&manifest_info);
-
-    let mut reranking_mode: Option<String> = None;
-    let mut stitched_unavailable_chunks: usize = 0;
-    if let Some(task_query) = merged.task_query.as_deref() {
-        let file_scores = rerank_chunks_by_task(&mut chunks, task_query, 0.4);
-        reranking_mode = Some("bm25+deps".to_string());
-        chunks.sort_by(|a, b| {
-            b.priority
-                .partial_cmp(&a.priority)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.path.cmp(&b.path))
-                .then_with(|| a.start_line.cmp(&b.start_line))
-                .then_with(|| a.id.cmp(&b.id))
-        });
-
-        for (idx, chunk) in chunks.iter_mut().enumerate() {
-            chunk.tags.insert(format!("reason:bm25(rank={})", idx + 1));
-        }
-
-        if merged.semantic_rerank {
-            let reranker = build_reranker(merged.semantic_model.as_deref());
-            let top_k = merged.rerank_top_k.min(chunks.len());
-            let semantic_scores = reranker.rerank(task_query, &chunks[..top_k])?;
-            let normalized = normalize_scores(&semantic_scores);
-            for (chunk, score) in chunks[..top_k].iter_mut().zip(normalized.into_iter()) {
-                chunk.priority =
-                    (((chunk.priority * 0.6) + (score * 0.4)) * 1000.0).round() / 1000.0;
-                chunk.tags.insert(format!("reason:semantic(score={:.3})", score));
-            }
-            chunks.sort_by(|a, b| {
-                b.priority
-                    .partial_cmp(&a.priority)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-                    .then_with(|| a.path.cmp(&b.path))
-                    .then_with(|| a.start_line.cmp(&b.start_line))
-                    .then_with(|| a.id.cmp(&b.id))
-            });
-            reranking_mode = Some(format!("bm25+{}", reranker.name()));
-        }
-
-        if let Some(max_tokens) = merged.max_tokens {
-            let effective_tokens = max_tokens.saturating_sub(always_tokens);
-            let budget =
-                ((effective_tokens as f64) * merged.stitch_budget_fraction).round() as usize;
-            let stitch = stitch_thread_bundles(
-                &chunks,
-                merged.stitch_top_n,
-                budget,
-                lazy_loader.as_ref(),
-                &workspace_members,
+    if args.asset_metadata {
+        if used_index_dataset {
+            eprintln!(
+                "warning: --asset-metadata needs a scan; the index does not record skipped files"
             );
-            if !stitch.lazy_chunks.is_empty() {
-                chunks.extend(stitch.lazy_chunks.iter().cloned());
-            }
-            for chunk in &mut chunks {
-                if let Some(tier) = stitch.stitched.get(&chunk.id) {
-                    chunk.tags.insert(format!("stitch:{}", tier.as_str()));
-                    chunk.tags.insert(format!("reason:stitched({})", tier.as_str()));
-                }
-            }
-            stats.stitched_chunks = stitch.stitched.len();
-            let dropped_chunks: usize = stats
-                .dropped_files
-                .iter()
-                .filter(|dropped| {
-                    dropped.get("reason").and_then(|v| v.as_str()) == Some("token_budget")
-                })
-                .map(|dropped| dropped.get("chunks").and_then(|v| v.as_u64()).unwrap_or(0) as usize)
-                .sum();
-            stitched_unavailable_chunks = dropped_chunks.saturating_sub(stitch.lazy_chunks.len());
-
-            sort_chunks_for_stitch_story(&mut chunks, &stitch.seed_ids, &stitch.stitched);
-
-            if stats.stitched_chunks > 0 {
-                println!(
-                    "  Thread stitching: {} chunks (~{} tokens reserved)",
-                    stats.stitched_chunks, stitch.tokens_used
-                );
-            }
         }
-
-        for file in &mut selected_files {
-            if let Some(task_score) = file_scores.get(&file.relative_path) {
-                file.priority =
-                    (((file.priority * 0.6) + (task_score * 0.4)) * 1000.0).round() / 1000.0;
-            }
-        }
-        selected_files.sort_by(|a, b| {
-            b.priority
-                .partial_cmp(&a.priority)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.relative_path.cmp(&b.relative_path))
-        });
-
-        stats.top_ranked_files = selected_files
+        stats.referenced_assets = referenced_assets(&root_path, &chunks, &stats.skipped_file_paths);
+        println!("  Referenced assets: {}", stats.referenced_assets.len());
+    }
+    if let Some(audit_path) = args.redaction_audit.as_deref() {
+        redaction_audit.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        let rows: String = redaction_audit
             .iter()
-            .take(20)
-            .map(|f| {
-                std::collections::HashMap::from([
-                    ("path".to_string(), json!(f.relative_path)),
-                    ("priority".to_string(), json!(f.priority)),
-                ])
+            .map(|entry| {
+                let row = json!({ "path": entry.path, "rule": entry.rule, "line": entry.line });
+                format!("{row}\n")
             })
             .collect();
+        fs::write(audit_path, rows)
+            .with_context(|| format!("Failed to write redaction audit {}", audit_path.display()))?;
+        println!(
+            "  Redaction audit: {} redaction(s) written to {}",
+            redaction_audit.len(),
+            audit_path.display()
+        );
     }
-
-    for boundary in detect_async_boundaries(&chunks) {
-        if let Some(chunk) = chunks.iter_mut().find(|c| c.id == boundary.chunk_id) {
-            for pattern in boundary.patterns {
-                chunk.tags.insert(pattern.tag().to_string());
-            }
-        }
+    if args.report_unresolved_imports {
+        stats.unresolved_imports = unresolved_imports(&chunks, &scanned_paths);
+        println!("  Unresolved imports: {}", stats.unresolved_imports.len());
     }
 
-    stats.chunks_created = chunks.len();
-    stats.total_tokens_estimated = chunks.iter().map(|c| c.token_estimate).sum();
-
-    let output_dir = resolve_output_dir(&merged.output_dir, &root_path, merged.repo_url.as_deref());
-    let repo_name = repo_name_for_output(&root_path, merged.repo_url.as_deref());
-    fs::create_dir_all(&output_dir)?;
+    prepare_output_dir(&output_dir, &root_path, &repo_name, args.clean, args.no_overwrite)?;
     let mut graph_written: Option<(PathBuf, usize, usize)> = None;
     if !args.no_graph {
         if let Some(index_db) = index_db_path.as_ref() {
@@ -672,7 +1032,7 @@ pub fn run(args: ExportArgs) -> Result<()> {
                 let graph_path =
                     output_dir.join(prefixed_output_file_name(&repo_name, "symbol_graph.db"));
                 match open_or_create(&graph_path) {
-                    Ok(mut conn) => match persist_graph(&mut conn, &chunks) {
+                    Ok(mut conn) => match persist_graph(&mut conn, &chunks, None) {
                         Ok((symbols, edges)) => {
                             graph_written = Some((graph_path, symbols, edges));
                         }
@@ -692,7 +1052,7 @@ pub fn run(args: ExportArgs) -> Result<()> {
             let graph_path =
                 output_dir.join(prefixed_output_file_name(&repo_name, "symbol_graph.db"));
             match open_or_create(&graph_path) {
-                Ok(mut conn) => match persist_graph(&mut conn, &chunks) {
+                Ok(mut conn) => match persist_graph(&mut conn, &chunks, None) {
                     Ok((symbols, edges)) => {
                         graph_written = Some((graph_path, symbols, edges));
                     }
@@ -712,13 +1072,82 @@ pub fn run(args: ExportArgs) -> Result<()> {
         .filter(|f| f.priority >= 0.8)
         .map(|f| f.relative_path.clone())
         .collect();
-    let tree = generate_tree(&root_path, merged.tree_depth, true, &highlight)?;
+    let path_redactor = if args.redact_paths {
+        Some(build_redactor(merged.redaction_mode, &merged.redaction))
+    } else {
+        None
+    };
+    let tree = generate_tree_with_redactor(
+        &root_path,
+        merged.tree_depth,
+        true,
+        &highlight,
+        path_redactor.as_ref(),
+        args.tree_collapse_single,
+        args.tree_symbol_counts.then(|| symbol_counts_by_file(&chunks)).as_ref(),
+    )?;
+
+    if args.emit_byte_offsets {
+        attach_byte_offsets(&mut chunks, &selected_files, &encodings);
+    }
+
+    // Resolved on the real paths; the renderer gets the display (possibly redacted) ones.
+    let file_order: Option<Vec<String>> = match args.order {
+        PackOrder::Ranked => selection_order,
+        PackOrder::Topological => {
+            let order = topological_file_order(&chunks);
+            let position: HashMap<&str, usize> =
+                order.iter().enumerate().map(|(i, path)| (path.as_str(), i)).collect();
+            chunks.sort_by_key(|chunk| position.get(chunk.path.as_str()).copied());
+            Some(order)
+        }
+    };
+    let file_order = match (file_order, path_redactor.as_ref()) {
+        (Some(order), Some(redactor)) => {
+            Some(order.iter().map(|path| redactor.redact_path(path)).collect())
+        }
+        (order, _) => order,
+    };
+
+    // Path redaction only touches rendered copies so file and chunk ids stay stable.
+    let (display_files, display_chunks) = match path_redactor.as_ref() {
+        Some(redactor) => redact_display_paths(redactor, &selected_files, &chunks),
+        None => (selected_files.clone(), chunks.clone()),
+    };
+    let display_stats = path_redactor.as_ref().map(|redactor| {
+        let mut display = stats.clone();
+        redact_record_paths(redactor, &mut display);
+        display
+    });
 
     let pr_report = if matches!(merged.mode, OutputMode::PrContext) {
+        let symbol_diff = since_base.and_then(|(base_ref, merge_base)| {
+            let mut diff = match diff_symbols_since(
+                &root_path,
+                base_ref,
+                merge_base,
+                merged.chunk_tokens,
+                &selected_files.iter().map(|f| f.relative_path.as_str()).collect(),
+                &encodings,
+            ) {
+                Ok(diff) => diff,
+                Err(err) => {
+                    eprintln!("warning: symbol diff since {base_ref} skipped ({err:#})");
+                    return None;
+                }
+            };
+            if let Some(redactor) = path_redactor.as_ref() {
+                for change in &mut diff.changes {
+                    change.path = redactor.redact_path(&change.path);
+                }
+            }
+            Some(diff)
+        });
         Some(build_pr_context(
-            &selected_files,
-            &chunks,
+            &display_files,
+            &display_chunks,
             merged.task_query.as_deref(),
+            symbol_diff,
             graph_written.is_some(),
         ))
     } else {
@@ -727,18 +1156,27 @@ pub fn run(args: ExportArgs) -> Result<()> {
 
     let context_pack = render_context_pack(
         &root_path,
-        &selected_files,
-        &chunks,
-        &stats,
+        &display_files,
+        &display_chunks,
+        &WorkingTree { encodings: &encodings },
+        display_stats.as_ref().unwrap_or(&stats),
         &tree,
-        &manifest_info,
-        merged.task_query.as_deref(),
-        pr_report.as_ref(),
-        !args.no_timestamp,
+        PackRenderOptions {
+            include_timestamp: !args.no_timestamp,
+            task_query: merged.task_query.as_deref(),
+            pr_context: pr_report.as_ref(),
+            manifest_info: Some(&manifest_info),
+            include_toc: args.toc,
+            group_by_directory: args.group_by_directory,
+            annotate_splits: args.annotate_splits,
+            file_order: file_order.as_deref(),
+            section_order: &section_order,
+        },
     );
-    let jsonl = render_jsonl(&chunks);
+    let jsonl = render_jsonl(&display_chunks);
 
     let mut output_files = Vec::new();
+    let mut pack_path: Option<PathBuf> = None;
     if matches!(
         merged.mode,
         OutputMode::Prompt | OutputMode::Both | OutputMode::Contribution | OutputMode::PrContext
@@ -746,6 +1184,7 @@ pub fn run(args: ExportArgs) -> Result<()> {
         let p = output_dir.join(prefixed_output_file_name(&repo_name, "context_pack.md"));
         fs::write(&p, context_pack)?;
         output_files.push(p.display().to_string());
+        pack_path = Some(p);
     }
     if matches!(
         merged.mode,
@@ -754,10 +1193,38 @@ pub fn run(args: ExportArgs) -> Result<()> {
         let p = output_dir.join(prefixed_output_file_name(&repo_name, "chunks.jsonl"));
         fs::write(&p, jsonl)?;
         output_files.push(p.display().to_string());
+        pack_path.get_or_insert(p);
     }
-    if let Some((graph_path, symbols, edges)) = &graph_written {
-        println!("[graph] {}: {symbols} symbols, {edges} import edges", graph_path.display());
-        output_files.push(graph_path.display().to_string());
+    if merged.mode == OutputMode::FilesJson {
+        let p = output_dir.join(prefixed_output_file_name(&repo_name, "files.json"));
+        fs::write(&p, render_files_json(&display_chunks))?;
+        output_files.push(p.display().to_string());
+        pack_path.get_or_insert(p);
+    }
+    if merged.mode == OutputMode::Map {
+        let p = output_dir.join(prefixed_output_file_name(&repo_name, "map.md"));
+        fs::write(&p, render_repo_map(&display_files, &display_chunks, merged.max_tokens))?;
+        output_files.push(p.display().to_string());
+        pack_path.get_or_insert(p);
+    }
+    if args.emit_file_deps {
+        let mut deps = file_dependencies(&selected_files, &chunks);
+        if let Some(redactor) = path_redactor.as_ref() {
+            deps = deps
+                .into_iter()
+                .map(|(path, targets)| {
+                    let targets = targets.iter().map(|t| redactor.redact_path(t)).collect();
+                    (redactor.redact_path(&path), targets)
+                })
+                .collect();
+        }
+        let p = output_dir.join(prefixed_output_file_name(&repo_name, "file_deps.json"));
+        fs::write(&p, format!("{}\n", serde_json::to_string_pretty(&deps)?))?;
+        output_files.push(p.display().to_string());
+    }
+    if let Some((graph_path, symbols, edges)) = &graph_written {
+        println!("[graph] {}: {symbols} symbols, {edges} import edges", graph_path.display());
+        output_files.push(graph_path.display().to_string());
     }
 
     let report_path = output_dir.join(prefixed_output_file_name(&repo_name, "report.json"));
@@ -785,46 +1252,67 @@ pub fn run(args: ExportArgs) -> Result<()> {
             .as_ref()
             .map(|p| serde_json::Value::String(p.to_string_lossy().to_string()))
             .unwrap_or(serde_json::Value::Null);
-        let mode_val = serde_json::to_value(merged.mode)?;
-        let task_val = merged.task_query.clone();
         let mut always_include_patterns = merged.always_include_patterns.clone();
         always_include_patterns.sort();
         let mut always_include_paths = merged.always_include_paths.clone();
         always_include_paths.sort();
         let mut invariant_keywords = merged.invariant_keywords.clone();
         invariant_keywords.sort();
-        json!({
-            "chunk_overlap":        merged.chunk_overlap,
-            "chunk_tokens":         merged.chunk_tokens,
-            "stitch_budget_fraction": merged.stitch_budget_fraction,
-            "stitch_top_n":         merged.stitch_top_n,
-            "exclude_globs":        exclude_globs_val,
-            "follow_symlinks":      merged.follow_symlinks,
-            "include_extensions":   include_extensions_val,
-            "max_file_bytes":       merged.max_file_bytes,
-            "max_tokens":           merged.max_tokens,
-            "allow_over_budget":    args.allow_over_budget,
-            "strict_budget":        args.strict_budget,
-            "max_total_bytes":      merged.max_total_bytes,
-            "semantic_rerank":      merged.semantic_rerank,
-            "semantic_model":       merged.semantic_model,
-            "rerank_top_k":         merged.rerank_top_k,
-            "mode":                 mode_val,
-            "path":                 path_val,
-            "task_query":           task_val,
-            "reranking":            reranking_mode,
-            "redact_secrets":       merged.redact_secrets,
-            "ref":                  merged.ref_.clone(),
-            "repo":                 merged.repo_url.clone(),
-            "skip_minified":        merged.skip_minified,
-            "tree_depth":           merged.tree_depth,
-            "always_include_patterns": always_include_patterns,
-            "always_include_paths": always_include_paths,
-            "invariant_keywords":   invariant_keywords,
-            "pinned_only_mode":     stats.pinned_only_mode,
-            "from_index":           args.from_index,
-            "require_fresh_index":  args.require_fresh_index,
-        })
+        // The pipeline's settings are shared with the library export; the rest are the
+        // command's own scan and output flags.
+        let mut config = pack_report_config;
+        config["exclude_globs"] = exclude_globs_val;
+        config["follow_symlinks"] = json!(merged.follow_symlinks);
+        config["include_extensions"] = include_extensions_val;
+        config["max_file_bytes"] = json!(merged.max_file_bytes);
+        config["path"] = path_val;
+        config["redact_paths"] = json!(args.redact_paths);
+        config["ref"] = json!(merged.ref_);
+        config["repo"] = json!(merged.repo_url);
+        config["skip_minified"] = json!(merged.skip_minified);
+        config["always_include_patterns"] = json!(always_include_patterns);
+        config["always_include_paths"] = json!(always_include_paths);
+        config["invariant_keywords"] = json!(invariant_keywords);
+        config["recency_weight"] = json!(merged.recency_weight);
+        config["pinned_only_mode"] = json!(stats.pinned_only_mode);
+        config["from_index"] = json!(args.from_index);
+        config["require_fresh_index"] = json!(args.require_fresh_index);
+        config["selection"] = json!(args.selection.as_ref().map(|p| p.display().to_string()));
+        config["exclude_paths_from"] =
+            json!(args.exclude_paths_from.as_ref().map(|p| p.display().to_string()));
+        config["redaction_rules"] =
+            json!(args.redaction_rules.as_ref().map(|p| p.display().to_string()));
+        config["coverage_file"] =
+            json!(args.coverage_file.as_ref().map(|p| p.display().to_string()));
+        config["since"] = json!(args.since);
+        config["order"] = json!(args.order.as_str());
+        config["emit_byte_offsets"] = json!(args.emit_byte_offsets);
+        config["emit_file_deps"] = json!(args.emit_file_deps);
+        config["report_unresolved_imports"] = json!(args.report_unresolved_imports);
+        config["require_index"] = json!(args.require_index);
+        config["explain_budget"] = json!(args.explain_budget);
+        config["redaction_audit"] =
+            json!(args.redaction_audit.as_ref().map(|p| p.display().to_string()));
+        config["profile"] = json!(args.profile.map(Profile::name));
+        config["tree_collapse_single"] = json!(args.tree_collapse_single);
+        config["tree_symbol_counts"] = json!(args.tree_symbol_counts);
+        config["report_compact"] = json!(args.report_compact);
+        // Only whether a hook ran; the command line may carry credentials.
+        config["post_export_cmd"] = json!(args.post_export_cmd.is_some());
+        config["symbol"] = json!(args.symbol);
+        config["focus_file"] = json!(focus_file);
+        config["focus_depth"] = json!(args.focus_file.as_ref().map(|_| args.focus_depth));
+        config["include_git_status"] = json!(args.include_git_status);
+        config["annotate_ownership"] = json!(args.annotate_ownership);
+        config["clean"] = json!(args.clean);
+        config["no_overwrite"] = json!(args.no_overwrite);
+        config["skip_if_unchanged"] = json!(args.skip_if_unchanged);
+        config["include_diagnostics"] = json!(args.include_diagnostics);
+        config["asset_metadata"] = json!(args.asset_metadata);
+        config["lfs_resolve"] = json!(merged.lfs_resolve);
+        config["dominant_dir_fraction"] = json!(merged.dominant_dir_fraction);
+        config["archive"] = json!(args.archive.as_ref().map(|p| p.display().to_string()));
+        config
     };
 
     let provenance =
@@ -836,28 +1324,44 @@ pub fn run(args: ExportArgs) -> Result<()> {
         &stats,
         &provenance,
         index_db_path.as_deref(),
+        path_redactor.as_ref(),
+        &encodings,
     );
+    // Coverage needed the real paths to read dropped files; everything after shows them.
+    if let Some(redactor) = path_redactor.as_ref() {
+        redact_record_paths(redactor, &mut stats);
+    }
 
     write_report(
         &report_path,
         &stats,
-        &selected_files,
+        &display_files,
         &output_files,
         &config_dict,
         ReportOptions {
             include_timestamp: !args.no_timestamp,
             provenance: Some(&provenance),
             coverage: Some(&coverage),
+            compact: args.report_compact,
         },
     )?;
     output_files.push(report_path.display().to_string());
+    if let Some(hash) = inputs_hash {
+        let mut marker = format!("{hash}\n");
+        for path in &output_files {
+            marker.push_str(path);
+            marker.push('\n');
+        }
+        fs::write(&inputs_marker, marker)
+            .with_context(|| format!("Failed to write {}", inputs_marker.display()))?;
+    }
 
     // --- Print export summary ---
     println!();
-    println!("Export complete!");
+    println!("{SUCCESS}Export complete!{SUCCESS:#}");
     println!();
-    println!("Statistics:");
-    println!("  Repository:      {}", root_path.display());
+    println!("{HEADING}Statistics:{HEADING:#}");
+    println!("  Repository:      {PATH}{}{PATH:#}", root_path.display());
     println!(
         "  Index status:    {}{}",
         index_state.kind.as_str(),
@@ -865,13 +1369,17 @@ pub fn run(args: ExportArgs) -> Result<()> {
     );
     println!("  Files scanned:   {}", stats.files_scanned);
     println!("  Files included:  {}", stats.files_included);
+    if let (Some(changed), Some(base_ref)) = (changed_since, args.since.as_deref()) {
+        println!("  Changed since {base_ref}: {changed} path(s)");
+    }
 
     // Per-category skip breakdown
     let any_skipped = stats.files_skipped_size > 0
         || stats.files_skipped_binary > 0
         || stats.files_skipped_extension > 0
         || stats.files_skipped_gitignore > 0
-        || stats.files_skipped_glob > 0;
+        || stats.files_skipped_glob > 0
+        || stats.files_skipped_lfs > 0;
     if any_skipped {
         println!("  Files skipped:");
         if stats.files_skipped_size > 0 {
@@ -889,10 +1397,16 @@ pub fn run(args: ExportArgs) -> Result<()> {
         if stats.files_skipped_glob > 0 {
             println!("    glob/minify: {}", stats.files_skipped_glob);
         }
+        if stats.files_skipped_lfs > 0 {
+            println!("    lfs pointer: {}", stats.files_skipped_lfs);
+        }
     }
 
+    if stats.files_stubbed > 0 {
+        println!("  Files kept as first-chunk stubs (budget): {}", stats.files_stubbed);
+    }
     if stats.files_dropped_budget > 0 {
-        println!("  Files dropped (budget): {}", stats.files_dropped_budget);
+        println!("  {WARNING}Files dropped (budget): {}{WARNING:#}", stats.files_dropped_budget);
         if stitched_unavailable_chunks > 0 {
             println!(
                 "  {} stitched chunks unavailable (file dropped pre-budget)",
@@ -903,45 +1417,131 @@ pub fn run(args: ExportArgs) -> Result<()> {
     println!("  Chunks created:  {}", stats.chunks_created);
     println!("  Total bytes:     {}", stats.total_bytes_included);
     println!("  Total tokens:    ~{}", stats.total_tokens_estimated);
+    if stats.license_header_files_elided > 0 {
+        println!(
+            "  License headers: elided in {} file(s) (~{} tokens saved)",
+            stats.license_header_files_elided, stats.license_header_tokens_saved
+        );
+    }
+    if let (Some(max_tokens), reserved @ 1..) = (stats.max_tokens, stats.reserved_tokens) {
+        println!(
+            "  Token budget:    {} of {} ({} reserved)",
+            max_tokens - reserved,
+            max_tokens,
+            reserved
+        );
+    }
     if let Some(task_query) = merged.task_query.as_deref() {
         if let Some(mode) = reranking_mode.as_deref() {
             println!("  Task reranking:  {mode} ({task_query})");
         } else {
             println!("  Task reranking:  bm25+deps ({task_query})");
         }
+    } else if !seed_files.is_empty() {
+        println!("  Seed reranking:  seeds+deps ({})", seed_files.join(", "));
     }
     println!("  Processing time: {:.2}s", stats.processing_time_seconds);
 
+    if !stats.tokens_by_tag.is_empty() {
+        println!();
+        println!("{HEADING}Tokens by tag:{HEADING:#}");
+        let mut by_tokens: Vec<(&String, &usize)> = stats.tokens_by_tag.iter().collect();
+        by_tokens.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let total = stats.total_tokens_estimated.max(1) as f64;
+        for (tag, tokens) in by_tokens {
+            println!("  {tag}: ~{tokens} ({:.1}%)", *tokens as f64 * 100.0 / total);
+        }
+    }
+
     println!();
-    println!("Output files:");
+    println!("{HEADING}Output files:{HEADING:#}");
     for out in &output_files {
-        println!("  {out}");
+        println!("  {PATH}{out}{PATH:#}");
     }
 
     // Redaction counts (top 5)
     if !stats.redaction_counts.is_empty() {
         println!();
-        println!("Redactions applied:");
+        println!("{HEADING}Redactions applied:{HEADING:#}");
         for (name, count) in stats.redaction_counts.iter().take(5) {
             println!("  {name}: {count}");
         }
     }
+    if stats.redactions_suppressed_by_allowlist > 0 {
+        println!(
+            "Redactions suppressed by allowlist: {}",
+            stats.redactions_suppressed_by_allowlist
+        );
+    }
 
-    // Dropped files list (up to 5)
-    if !stats.dropped_files.is_empty() {
+    // Dropped files list (up to 5). Records also cover files the per-file cap only
+    // truncated, so the heading does not claim they were all dropped.
+    if dropped_total > 0 {
         println!();
-        println!("Dropped {} file(s) due to budget constraints:", stats.dropped_files.len());
-        for df in stats.dropped_files.iter().take(5) {
+        println!(
+            "{WARNING}Dropped or truncated {} file(s) due to budget constraints:{WARNING:#}",
+            dropped_total
+        );
+        let shown = if args.explain_budget { stats.dropped_files.len() } else { 5 };
+        for df in stats.dropped_files.iter().take(shown) {
+            if args.explain_budget {
+                println!("  {}", explain_dropped_file(df));
+                continue;
+            }
             let path = df.get("path").and_then(|v| v.as_str()).unwrap_or("?");
             let reason = df.get("reason").and_then(|v| v.as_str()).unwrap_or("?");
-            println!("  {path} ({reason})");
+            let stubbed = if df.contains_key("stubbed") { ", kept as stub" } else { "" };
+            println!("  {path} ({reason}{stubbed})");
         }
-        if stats.dropped_files.len() > 5 {
-            println!("  ... and {} more (see report.json)", stats.dropped_files.len() - 5);
+        let listed = stats.dropped_files.len().min(shown);
+        if dropped_total > listed {
+            println!("  ... and {} more (see report.json)", dropped_total - listed);
         }
     }
 
-    Ok(())
+    if let Some(command) = args.post_export_cmd.as_deref() {
+        let status = run_post_export_cmd(
+            command,
+            &output_dir,
+            pack_path.as_deref(),
+            stats.total_tokens_estimated,
+        )?;
+        if !status.success() {
+            eprintln!("post-export command failed ({status})");
+            // Returned rather than exited on so a fetched or extracted checkout is still
+            // cleaned up on the way out.
+            let code = status.code().and_then(|code| u8::try_from(code).ok()).unwrap_or(1);
+            return Ok(ExitCode::from(code));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Run `--post-export-cmd` through the platform shell with the export's locations in
+/// `R2P_OUTPUT_DIR`, `R2P_PACK_PATH` and `R2P_TOKEN_COUNT`.
+fn run_post_export_cmd(
+    command: &str,
+    output_dir: &Path,
+    pack_path: Option<&Path>,
+    token_count: usize,
+) -> Result<std::process::ExitStatus> {
+    let mut shell = if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    shell
+        .arg(command)
+        .env("R2P_OUTPUT_DIR", output_dir)
+        .env("R2P_PACK_PATH", pack_path.unwrap_or_else(|| Path::new("")))
+        .env("R2P_TOKEN_COUNT", token_count.to_string())
+        .status()
+        .with_context(|| format!("Failed to run post-export command: {command}"))
 }
 
 fn resolve_output_dir(config_output: &Path, root_path: &Path, repo_url: Option<&str>) -> PathBuf {
@@ -963,6 +1563,75 @@ fn resolve_output_dir(config_output: &Path, root_path: &Path, repo_url: Option<&
     }
 }
 
+/// Create the output directory, first deleting this repository's previous outputs for
+/// `--clean` or refusing to reuse it for `--no-overwrite` when it already holds them.
+///
+/// Only `<repo>_*` entries count as outputs, so `--clean` never touches other files that
+/// happen to live in the directory.
+fn prepare_output_dir(
+    output_dir: &Path,
+    root_path: &Path,
+    repo_name: &str,
+    clean: bool,
+    no_overwrite: bool,
+) -> Result<()> {
+    let prefix = prefixed_output_file_name(repo_name, "");
+    let previous_outputs = || -> Result<Vec<PathBuf>> {
+        if !output_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut outputs: Vec<PathBuf> = fs::read_dir(output_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.file_name().to_str().is_some_and(|name| name.starts_with(&prefix))
+            })
+            .map(|entry| entry.path())
+            .collect();
+        outputs.sort();
+        Ok(outputs)
+    };
+
+    if clean && output_dir.exists() {
+        let dir = output_dir.canonicalize()?;
+        let root = root_path.canonicalize().unwrap_or_else(|_| root_path.to_path_buf());
+        if root.starts_with(&dir) {
+            anyhow::bail!(
+                "Refusing to --clean {}: it contains the repository being exported",
+                output_dir.display()
+            );
+        }
+        let outputs = previous_outputs()?;
+        for path in &outputs {
+            let removed =
+                if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+            removed.with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        if !outputs.is_empty() {
+            println!(
+                "info: removed {} previous output(s) from {}",
+                outputs.len(),
+                output_dir.display()
+            );
+        }
+    }
+    if no_overwrite {
+        let existing = previous_outputs()?;
+        if !existing.is_empty() {
+            let names: Vec<String> = existing
+                .iter()
+                .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+                .collect();
+            anyhow::bail!(
+                "Outputs already exist in {} ({}); use --clean to replace them",
+                output_dir.display(),
+                names.join(", ")
+            );
+        }
+    }
+    fs::create_dir_all(output_dir)?;
+    Ok(())
+}
+
 fn repo_name_for_output(root_path: &Path, repo_url: Option<&str>) -> String {
     if let Some(url) = repo_url {
         if let Some(repo_name) = repo_name_from_remote_url(url) {
@@ -1036,6 +1705,51 @@ fn apply_guided_plan(merged: &mut crate::domain::Config, args: &ExportArgs, plan
     }
 }
 
+/// `--skip-if-unchanged`: hash of the tool version, command line, merged config, and the
+/// path and content of every selected file.
+fn export_inputs_hash(
+    merged: &crate::domain::Config,
+    selected_files: &[crate::domain::FileInfo],
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    for arg in std::env::args_os().skip(1) {
+        hasher.update(arg.as_encoded_bytes());
+        hasher.update([0]);
+    }
+    let mut config = serde_json::to_value(merged)?;
+    // Set-valued fields serialize in hash order; sort them so equal configs hash equally.
+    for key in ["include_extensions", "exclude_globs"] {
+        if let Some(values) = config.get_mut(key).and_then(|v| v.as_array_mut()) {
+            values.sort_by_key(|v| v.to_string());
+        }
+    }
+    hasher.update(serde_json::to_vec(&config)?);
+
+    let mut files: Vec<_> = selected_files.iter().collect();
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    for file in files {
+        hasher.update(&file.relative_path);
+        hasher.update([0]);
+        match fs::read(&file.path) {
+            Ok(bytes) => hasher.update(Sha256::digest(&bytes)),
+            Err(_) => hasher.update(b"unreadable"),
+        }
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// `--skip-if-unchanged`: true if `marker` holds `hash` on its first line and every output
+/// file listed after it still exists.
+fn previous_export_matches(marker: &Path, hash: &str) -> bool {
+    let Ok(content) = fs::read_to_string(marker) else {
+        return false;
+    };
+    let mut lines = content.lines();
+    lines.next().is_some_and(|previous| previous.trim() == hash)
+        && lines.all(|output| Path::new(output).exists())
+}
+
 fn prefixed_output_file_name(repo_name: &str, base_name: &str) -> String {
     format!("{repo_name}_{base_name}")
 }
@@ -1104,273 +1818,56 @@ fn build_provenance(
     })
 }
 
-fn stable_json_hash(value: &serde_json::Value) -> String {
-    let canonical = serde_json::to_vec(value).unwrap_or_default();
-    let mut hasher = Sha256::new();
-    hasher.update(&canonical);
-    format!("{:x}", hasher.finalize())
+/// Size guard run between scanning and ranking, so pointing export at something like
+/// `$HOME` is caught before the expensive stages.
+struct ScanPreflight {
+    max_files: Option<usize>,
+    warn_files: usize,
+    warn_bytes: u64,
+    confirm: bool,
 }
 
-fn build_coverage_report(
-    root_path: &Path,
-    selected_files: &[crate::domain::FileInfo],
-    chunks: &[Chunk],
-    stats: &crate::domain::ScanStats,
-    provenance: &serde_json::Value,
-    index_db_path: Option<&Path>,
-) -> serde_json::Value {
-    let dropped_paths: Vec<String> = stats
-        .dropped_files
-        .iter()
-        .filter_map(|entry| entry.get("path").and_then(|v| v.as_str()).map(|p| p.to_string()))
-        .collect();
-    let included_paths: HashSet<String> =
-        selected_files.iter().map(|f| f.relative_path.clone()).collect();
-    let most_imported_not_included = most_imported_not_included(
-        index_db_path,
-        &dropped_paths,
-        &included_paths,
-        &stats.dropped_files,
-    );
-
-    let public_api = public_api_coverage(root_path, selected_files, stats);
-    let hot_paths = hot_paths_from_tests_examples(chunks);
-    let mut missing_context_todos = Vec::new();
-    for entry in stats.dropped_files.iter().take(15) {
-        if let Some(path) = entry.get("path").and_then(|v| v.as_str()) {
-            let reason = entry.get("reason").and_then(|v| v.as_str()).unwrap_or("unknown");
-            missing_context_todos.push(json!({"path": path, "reason": reason}));
-        }
-    }
-
-    if stats.pinned_only_mode {
-        missing_context_todos.push(json!({
-            "path": "<export>",
-            "reason": "pinned-only fallback activated; increase --max-tokens for broader coverage"
-        }));
-    }
-
-    json!({
-        "most_imported_not_included": most_imported_not_included,
-        "public_api_surface_coverage": public_api,
-        "hot_paths_from_tests_examples": hot_paths,
-        "missing_context_todos": missing_context_todos,
-        "fingerprint": provenance.get("fingerprint").cloned().unwrap_or(json!(null)),
-    })
-}
-
-fn most_imported_not_included(
-    index_db_path: Option<&Path>,
-    dropped_paths: &[String],
-    included_paths: &HashSet<String>,
-    dropped_entries: &[HashMap<String, serde_json::Value>],
-) -> Vec<serde_json::Value> {
-    if dropped_paths.is_empty() {
-        return Vec::new();
-    }
-
-    let dropped_set: HashSet<String> = dropped_paths.iter().cloned().collect();
-    let mut dropped_reason: HashMap<String, String> = HashMap::new();
-    let mut dropped_priority: HashMap<String, serde_json::Value> = HashMap::new();
-    for entry in dropped_entries {
-        let Some(path) = entry.get("path").and_then(|v| v.as_str()) else {
-            continue;
-        };
-        if let Some(reason) = entry.get("reason").and_then(|v| v.as_str()) {
-            dropped_reason.insert(path.to_string(), reason.to_string());
-        }
-        if let Some(priority) = entry.get("priority") {
-            dropped_priority.insert(path.to_string(), priority.clone());
-        }
-    }
-
-    let mut inbound_all: HashMap<String, usize> = HashMap::new();
-    let mut inbound_from_included: HashMap<String, usize> = HashMap::new();
-
-    if let Some(db_path) = index_db_path {
-        if let Ok(conn) = rusqlite::Connection::open(db_path) {
-            if let Ok(mut stmt) = conn.prepare("SELECT source_path, target_path FROM file_imports")
-            {
-                if let Ok(rows) = stmt.query_map([], |row| {
-                    let source: String = row.get(0)?;
-                    let target: String = row.get(1)?;
-                    Ok((source, target))
-                }) {
-                    for (source, target) in rows.flatten() {
-                        if !dropped_set.contains(&target) {
-                            continue;
-                        }
-                        *inbound_all.entry(target.clone()).or_insert(0) += 1;
-                        if included_paths.contains(&source) {
-                            *inbound_from_included.entry(target).or_insert(0) += 1;
-                        }
-                    }
-                }
+impl ScanPreflight {
+    fn check(&self, stats: &crate::domain::ScanStats) -> Result<()> {
+        if let Some(max_files) = self.max_files {
+            if stats.files_scanned > max_files {
+                anyhow::bail!(
+                    "scan found {} files, above --max-scan-files {max_files}; narrow --path or add --exclude-glob",
+                    stats.files_scanned
+                );
             }
         }
-    }
-
-    let mut ranked: Vec<(String, usize, usize)> = dropped_set
-        .iter()
-        .map(|path| {
-            (
-                path.clone(),
-                *inbound_all.get(path).unwrap_or(&0),
-                *inbound_from_included.get(path).unwrap_or(&0),
-            )
-        })
-        .collect();
-    ranked.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| b.1.cmp(&a.1)).then_with(|| a.0.cmp(&b.0)));
-
-    ranked
-        .into_iter()
-        .take(10)
-        .map(|(path, inbound, inbound_included)| {
-            let reason =
-                dropped_reason.get(&path).cloned().unwrap_or_else(|| "unknown".to_string());
-            let priority = dropped_priority.get(&path).cloned().unwrap_or(json!(null));
-            json!({
-                "path": path,
-                "reason": reason,
-                "priority": priority,
-                "incoming_edges": inbound,
-                "incoming_edges_from_included": inbound_included,
-            })
-        })
-        .collect()
-}
-
-fn public_api_coverage(
-    root_path: &Path,
-    files: &[crate::domain::FileInfo],
-    stats: &crate::domain::ScanStats,
-) -> serde_json::Value {
-    let included_pub_items = count_pub_items_in_files(files);
-
-    let mut estimated_total_pub_items = included_pub_items;
-    let mut dropped_rs_files = 0usize;
-    for dropped in &stats.dropped_files {
-        let Some(path) = dropped.get("path").and_then(|v| v.as_str()) else {
-            continue;
-        };
-        if !path.ends_with(".rs") {
-            continue;
-        }
-        dropped_rs_files += 1;
-        if let Ok((content, _)) = read_file_safe(&root_path.join(path), None, None) {
-            estimated_total_pub_items += count_pub_items_in_content(&content);
+        if stats.files_scanned <= self.warn_files && stats.total_bytes_scanned <= self.warn_bytes {
+            return Ok(());
         }
-    }
 
-    let coverage_ratio = if estimated_total_pub_items == 0 {
-        1.0
-    } else {
-        (included_pub_items as f64) / (estimated_total_pub_items as f64)
-    };
-
-    json!({
-        "language": "rust",
-        "included_pub_items": included_pub_items,
-        "estimated_total_pub_items": estimated_total_pub_items,
-        "coverage_ratio": ((coverage_ratio * 1000.0).round() / 1000.0),
-        "dropped_rust_files_considered": dropped_rs_files,
-        "notes": "Coverage counts Rust public items in included files and estimates dropped Rust files from disk when readable.",
-    })
-}
-
-fn count_pub_items_in_files(files: &[crate::domain::FileInfo]) -> usize {
-    let mut included_pub_items = 0usize;
-    for file in files {
-        if file.extension != ".rs" {
-            continue;
-        }
-        if let Ok((content, _)) = read_file_safe(&file.path, None, None) {
-            included_pub_items += count_pub_items_in_content(&content);
-        }
-    }
-    included_pub_items
-}
-
-fn count_pub_items_in_content(content: &str) -> usize {
-    content
-        .lines()
-        .filter(|line| {
-            let trimmed = line.trim_start();
-            trimmed.starts_with("pub ")
-                || trimmed.starts_with("pub(")
-                || trimmed.starts_with("pub(crate)")
-        })
-        .count()
-}
-
-fn hot_paths_from_tests_examples(chunks: &[Chunk]) -> Vec<serde_json::Value> {
-    let known_files: HashSet<String> = chunks.iter().map(|c| c.path.clone()).collect();
-    if known_files.is_empty() {
-        return Vec::new();
-    }
-    let defs = symbol_definitions(chunks);
-    let graph = dependency_graph(chunks, &known_files, &defs);
-
-    let mut counts: HashMap<String, usize> = HashMap::new();
-    for (source, targets) in &graph {
-        if !is_test_or_example_path(source) {
-            continue;
-        }
-        for target in targets {
-            if is_test_or_example_path(target) {
-                continue;
+        eprintln!(
+            "warning: large repository: {} files / {} bytes scanned (thresholds: {} files / {} bytes)",
+            format_with_commas(stats.files_scanned as u64),
+            format_with_commas(stats.total_bytes_scanned),
+            format_with_commas(self.warn_files as u64),
+            format_with_commas(self.warn_bytes)
+        );
+        if self.confirm {
+            let proceed = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Continue with export?")
+                .default(false)
+                .interact()?;
+            if !proceed {
+                anyhow::bail!(
+                    "export cancelled at size pre-flight (pass --yes to skip this prompt)"
+                );
             }
-            *counts.entry(target.clone()).or_insert(0) += 1;
-        }
-    }
-
-    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
-    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
-    ranked
-        .into_iter()
-        .take(10)
-        .map(|(path, refs)| json!({"path": path, "incoming_from_tests_examples": refs}))
-        .collect()
-}
-
-fn is_test_or_example_path(path: &str) -> bool {
-    let lower = path.to_ascii_lowercase();
-    lower.starts_with("tests/")
-        || lower.contains("/tests/")
-        || lower.starts_with("examples/")
-        || lower.contains("/examples/")
-        || lower.contains("_test")
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum IndexFreshness {
-    Fresh,
-    Stale,
-    Missing,
-    Error,
-}
-
-impl IndexFreshness {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Fresh => "fresh",
-            Self::Stale => "stale",
-            Self::Missing => "missing",
-            Self::Error => "error",
         }
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-struct IndexState {
-    kind: IndexFreshness,
-    db_path: Option<PathBuf>,
-    reason: Option<String>,
-}
-
 fn collect_scan_inputs(
     root_path: &Path,
     merged: &crate::domain::Config,
+    preflight: &ScanPreflight,
+    encodings: &EncodingOverrides,
 ) -> Result<(
     crate::domain::ScanStats,
     Vec<crate::domain::FileInfo>,
@@ -1381,161 +1878,42 @@ fn collect_scan_inputs(
         .respect_gitignore(merged.respect_gitignore)
         .follow_symlinks(merged.follow_symlinks)
         .skip_minified(merged.skip_minified)
+        .lfs_resolve(merged.lfs_resolve)
+        .editorconfig_hints(merged.editorconfig_hints)
         .include_extensions(merged.include_extensions.iter().cloned().collect())
-        .exclude_globs(merged.exclude_globs.iter().cloned().collect());
+        .exclude_globs(merged.exclude_globs.iter().cloned().collect())
+        .force_text_globs(merged.force_text_globs.clone());
 
     let scanned_files = scanner.scan()?;
     let stats = scanner.stats().clone();
-    let (ranked_files, manifest_info) =
-        rank_files_with_manifest(root_path, scanned_files, merged.ranking_weights.clone())?;
-
-    Ok((stats, ranked_files, manifest_info))
-}
-
-fn evaluate_index_state(
-    index_db_path: Option<&Path>,
-    root_path: &Path,
-    merged: &crate::domain::Config,
-) -> IndexState {
-    let Some(db_path) = index_db_path else {
-        return IndexState {
-            kind: IndexFreshness::Missing,
-            db_path: None,
-            reason: Some("index.sqlite not found".to_string()),
-        };
-    };
-
-    let conn = match rusqlite::Connection::open(db_path) {
-        Ok(conn) => conn,
-        Err(err) => {
-            return IndexState {
-                kind: IndexFreshness::Error,
-                db_path: Some(db_path.to_path_buf()),
-                reason: Some(format!("failed opening index db: {err}")),
-            };
-        }
-    };
-
-    let metadata = load_index_metadata_map(&conn);
-    let expected_hash = export_index_config_hash(merged);
-    let stored_hash = metadata.get("config_hash").cloned().unwrap_or_default();
-    if stored_hash != expected_hash {
-        return IndexState {
-            kind: IndexFreshness::Stale,
-            db_path: Some(db_path.to_path_buf()),
-            reason: Some("config hash mismatch".to_string()),
-        };
-    }
-
-    let current_commit = git2::Repository::discover(root_path).ok().and_then(|repo| {
-        let head = repo.head().ok()?;
-        head.target().map(|oid| oid.to_string())
-    });
-    let stored_commit =
-        metadata.get("git_commit").cloned().unwrap_or_else(|| "unknown".to_string());
-    if stored_commit != "unknown" && current_commit.as_deref() != Some(stored_commit.as_str()) {
-        return IndexState {
-            kind: IndexFreshness::Stale,
-            db_path: Some(db_path.to_path_buf()),
-            reason: Some("git commit mismatch".to_string()),
-        };
-    }
-
-    IndexState {
-        kind: IndexFreshness::Fresh,
-        db_path: Some(db_path.to_path_buf()),
-        reason: Some("metadata matches current config and commit".to_string()),
-    }
-}
-
-fn load_files_and_stats_from_index(
-    db_path: &Path,
-    root_path: &Path,
-) -> Result<(crate::domain::ScanStats, Vec<crate::domain::FileInfo>)> {
-    let conn = rusqlite::Connection::open(db_path)
-        .with_context(|| format!("Failed to open index database at {}", db_path.display()))?;
-    let metadata = load_index_metadata_map(&conn);
-
-    let mut stmt = conn.prepare(
-        "SELECT path, language, extension, size_bytes, priority, token_estimate FROM files",
+    preflight.check(&stats)?;
+    warn_dominant_directory(&scanned_files, merged.dominant_dir_fraction);
+    let (mut ranked_files, manifest_info) = rank_files_with_manifest(
+        root_path,
+        scanned_files,
+        merged.ranking_weights.clone(),
+        &merged.fixture_dirs,
+        &merged.language_weight_multipliers,
+        encodings,
     )?;
-    let rows = stmt.query_map([], |row| {
-        let rel_path: String = row.get(0)?;
-        let language: String = row.get(1)?;
-        let extension: String = row.get(2)?;
-        let size_bytes: i64 = row.get(3)?;
-        let priority: f64 = row.get(4)?;
-        let token_estimate: i64 = row.get(5)?;
-        Ok((rel_path, language, extension, size_bytes, priority, token_estimate))
-    })?;
-
-    let mut files = Vec::new();
-    let mut languages_detected: HashMap<String, usize> = HashMap::new();
-    let mut total_bytes_included = 0_u64;
-    for row in rows {
-        let (relative_path, language, extension, size_bytes, priority, token_estimate) = row?;
-        total_bytes_included = total_bytes_included.saturating_add(size_bytes.max(0) as u64);
-        *languages_detected.entry(language.clone()).or_insert(0) += 1;
-        files.push(crate::domain::FileInfo {
-            path: root_path.join(&relative_path),
-            relative_path: relative_path.clone(),
-            size_bytes: size_bytes.max(0) as u64,
-            extension,
-            language,
-            id: format!("idx:{relative_path}"),
-            priority,
-            token_estimate: token_estimate.max(0) as usize,
-            tags: BTreeSet::new(),
-            is_readme: false,
-            is_config: false,
-            is_doc: false,
-        });
-    }
-
-    let files_scanned =
-        metadata.get("files_scanned").and_then(|v| v.parse::<usize>().ok()).unwrap_or(files.len());
-
-    let stats = crate::domain::ScanStats {
-        files_scanned,
-        files_included: files.len(),
-        total_bytes_scanned: total_bytes_included,
-        total_bytes_included,
-        languages_detected,
-        ..crate::domain::ScanStats::default()
-    };
-    Ok((stats, files))
-}
+    apply_recency_weight(&mut ranked_files, merged.recency_weight);
 
-fn load_index_metadata_map(conn: &rusqlite::Connection) -> HashMap<String, String> {
-    let mut out = HashMap::new();
-    if let Ok(mut stmt) = conn.prepare("SELECT key, value FROM metadata") {
-        if let Ok(rows) = stmt.query_map([], |row| {
-            let key: String = row.get(0)?;
-            let value: String = row.get(1)?;
-            Ok((key, value))
-        }) {
-            for (key, value) in rows.flatten() {
-                out.insert(key, value);
-            }
-        }
-    }
-    out
+    Ok((stats, ranked_files, manifest_info))
 }
 
-fn export_index_config_hash(config: &crate::domain::Config) -> String {
-    let payload = json!({
-        "include_extensions": config.include_extensions,
-        "exclude_globs": config.exclude_globs,
-        "max_file_bytes": config.max_file_bytes,
-        "max_total_bytes": config.max_total_bytes,
-        "respect_gitignore": config.respect_gitignore,
-        "follow_symlinks": config.follow_symlinks,
-        "skip_minified": config.skip_minified,
-        "chunk_tokens": config.chunk_tokens,
-        "chunk_overlap": config.chunk_overlap,
-        "min_chunk_tokens": config.min_chunk_tokens,
-    });
-    stable_json_hash(&payload)
+/// `path` relative to the repository root. Relative paths are resolved against the root,
+/// not the working directory; anything outside the root is taken as already
+/// repository-relative.
+fn repo_relative_path(path: &Path, root_path: &Path) -> String {
+    let canonical_root = root_path.canonicalize().unwrap_or_else(|_| root_path.to_path_buf());
+    let relative = root_path
+        .join(path)
+        .canonicalize()
+        .ok()
+        .and_then(|abs| abs.strip_prefix(&canonical_root).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    relative.trim_start_matches("./").to_string()
 }
 
 fn resolve_index_db_path(root_path: &Path, merged: &crate::domain::Config) -> Option<PathBuf> {
@@ -1543,7 +1921,7 @@ fn resolve_index_db_path(root_path: &Path, merged: &crate::domain::Config) -> Op
     let cached = remote_index_cache_db_path(
         merged.repo_url.as_deref(),
         merged.ref_.as_deref(),
-        &export_index_config_hash(merged),
+        &config_hash(merged),
     )
     .filter(|path| path.exists());
 
@@ -1572,17 +1950,6 @@ fn find_index_db(root_path: &Path) -> Option<PathBuf> {
     }
 }
 
-fn query_graph_stats(db_path: &Path) -> Option<(usize, usize)> {
-    let conn = rusqlite::Connection::open(db_path).ok()?;
-    let symbols = conn
-        .query_row("SELECT COUNT(*) FROM symbol_chunks", [], |row| row.get::<_, i64>(0))
-        .ok()? as usize;
-    let edges = conn
-        .query_row("SELECT COUNT(*) FROM file_imports", [], |row| row.get::<_, i64>(0))
-        .ok()? as usize;
-    Some((symbols, edges))
-}
-
 fn extract_workspace_members(
     manifest_info: &std::collections::HashMap<String, serde_json::Value>,
 ) -> Vec<String> {
@@ -1601,454 +1968,200 @@ fn extract_workspace_members(
     members
 }
 
-fn process_file_for_export(
-    file: &mut crate::domain::FileInfo,
-    use_index_first: bool,
-    lazy_loader: Option<&LazyChunkLoader>,
-    redactor: Option<&Redactor>,
-    chunk_tokens: usize,
-    chunk_overlap: usize,
-    stats: &mut crate::domain::ScanStats,
-) -> Result<Option<Vec<Chunk>>> {
-    if use_index_first {
-        if let Some(index_chunks) =
-            process_export_file_from_index(file, lazy_loader, redactor, stats)?
-        {
-            return Ok(Some(index_chunks));
-        }
-    }
-
-    process_export_file(file, redactor, chunk_tokens, chunk_overlap, stats)
-}
-
-fn process_export_file_from_index(
-    file: &mut crate::domain::FileInfo,
-    lazy_loader: Option<&LazyChunkLoader>,
-    redactor: Option<&Redactor>,
-    stats: &mut crate::domain::ScanStats,
-) -> Result<Option<Vec<Chunk>>> {
-    let Some(loader) = lazy_loader else {
-        return Ok(None);
-    };
-    let mut file_chunks = loader.load_chunks_for_file(&file.relative_path);
-    if file_chunks.is_empty() {
-        return Ok(None);
-    }
-
-    if let Some(r) = redactor {
-        let filename = file.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if !r.is_file_allowlisted(filename, &file.relative_path) {
-            let mut rule_file_sets: BTreeMap<String, HashSet<String>> = BTreeMap::new();
-            for chunk in &mut file_chunks {
-                let original = chunk.content.clone();
-                let outcome = r.redact_with_language_report(
-                    &chunk.content,
-                    &file.language,
-                    &file.extension,
-                    filename,
-                    &file.relative_path,
-                );
-                if outcome.content != original {
-                    chunk.content = outcome.content;
-                    chunk.tags.insert("redacted".to_string());
-                    stats.redacted_chunks += 1;
-                    for (rule, count) in &outcome.counts {
-                        *stats.redaction_counts.entry(rule.clone()).or_insert(0) += count;
-                        rule_file_sets
-                            .entry(rule.clone())
-                            .or_default()
-                            .insert(file.relative_path.clone());
-                    }
-                }
-            }
-            if !rule_file_sets.is_empty() {
-                stats.redacted_files += 1;
-                for (rule, file_set) in rule_file_sets {
-                    *stats.redaction_file_counts.entry(rule).or_insert(0) += file_set.len();
-                }
+/// Distinct `def:`/`type:` symbols per file across its chunks.
+fn symbol_counts_by_file(chunks: &[Chunk]) -> HashMap<String, usize> {
+    let mut symbols: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for chunk in chunks {
+        for tag in &chunk.tags {
+            if tag.starts_with("def:") || tag.starts_with("type:") {
+                symbols.entry(chunk.path.as_str()).or_default().insert(tag.as_str());
             }
         }
     }
-
-    file.token_estimate = file_chunks.iter().map(|c| c.token_estimate).sum();
-    Ok(Some(file_chunks))
+    symbols.into_iter().map(|(path, tags)| (path.to_string(), tags.len())).collect()
 }
 
-fn process_export_file(
-    file: &mut crate::domain::FileInfo,
-    redactor: Option<&Redactor>,
-    chunk_tokens: usize,
-    chunk_overlap: usize,
-    stats: &mut crate::domain::ScanStats,
-) -> Result<Option<Vec<Chunk>>> {
-    let (content, _enc) = match read_file_safe(&file.path, None, None) {
-        Ok(r) => r,
-        Err(_) => return Ok(None),
-    };
+/// How long `--include-diagnostics` waits for rust-analyzer to finish checking.
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(60);
 
-    let redacted_content = if let Some(r) = redactor {
-        let filename = file.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if r.is_file_allowlisted(filename, &file.relative_path) {
-            content
-        } else {
-            use std::collections::{BTreeMap, HashSet};
-            let outcome = r.redact_with_language_report(
-                &content,
-                &file.language,
-                &file.extension,
-                filename,
-                &file.relative_path,
-            );
-            if outcome.content != content {
-                let mut rule_file_sets: BTreeMap<String, HashSet<String>> = BTreeMap::new();
-                for (rule, count) in &outcome.counts {
-                    *stats.redaction_counts.entry(rule.clone()).or_insert(0) += count;
-                    rule_file_sets
-                        .entry(rule.clone())
-                        .or_default()
-                        .insert(file.relative_path.clone());
-                }
-                stats.redacted_files += 1;
-                for (rule, file_set) in rule_file_sets {
-                    *stats.redaction_file_counts.entry(rule).or_insert(0) += file_set.len();
-                }
-                outcome.content
-            } else {
-                content
+/// `--include-diagnostics`: tag the chunks covering each rust-analyzer diagnostic with
+/// `diagnostic:<severity>` and record the diagnostics for the report and pack.
+///
+/// Only Rust files are checked; without rust-analyzer this warns and leaves chunks as-is.
+fn apply_diagnostics(root_path: &Path, chunks: &mut [Chunk], stats: &mut crate::domain::ScanStats) {
+    let paths: Vec<String> = chunks
+        .iter()
+        .filter(|c| c.language == "rust")
+        .map(|c| c.path.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    if !rust_analyzer::is_available() {
+        eprintln!("warning: --include-diagnostics skipped (rust-analyzer not found in PATH)");
+        return;
+    }
+    let diagnostics =
+        match rust_analyzer::collect_diagnostics(root_path, &paths, DIAGNOSTICS_TIMEOUT) {
+            Ok(diagnostics) => diagnostics,
+            Err(err) => {
+                eprintln!("warning: rust-analyzer diagnostics unavailable: {err}");
+                return;
             }
-        }
-    } else {
-        content
-    };
-
-    let mut file_chunks = chunk_content(file, &redacted_content, chunk_tokens, chunk_overlap)?;
-    let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
-    file.token_estimate = file_tokens;
+        };
 
-    if redactor.is_some() {
-        for chunk in &mut file_chunks {
-            if chunk.content.contains("[REDACTED") || chunk.content.contains("_REDACTED]") {
-                chunk.tags.insert("redacted".to_string());
-                stats.redacted_chunks += 1;
-            }
+    for diagnostic in &diagnostics {
+        let tag = format!("diagnostic:{}", diagnostic.severity);
+        for chunk in chunks.iter_mut().filter(|c| {
+            c.path == diagnostic.path
+                && c.start_line <= diagnostic.line
+                && diagnostic.line <= c.end_line
+        }) {
+            chunk.tags.insert(tag.clone());
         }
     }
-
-    Ok(Some(file_chunks))
-}
-
-fn sort_group(
-    chunk: &Chunk,
-    seed_ids: &std::collections::BTreeSet<String>,
-    stitched: &std::collections::HashMap<String, StitchTier>,
-) -> u8 {
-    if seed_ids.contains(&chunk.id) {
-        return 0;
-    }
-    match stitched.get(&chunk.id) {
-        Some(StitchTier::Definition) => 1,
-        Some(StitchTier::Callee) => 2,
-        Some(StitchTier::Caller) => 3,
-        Some(StitchTier::CrossCrate) => 4,
-        None => 5,
-    }
+    let errors = diagnostics.iter().filter(|d| d.severity == "error").count();
+    println!(
+        "info: rust-analyzer reported {} diagnostic(s) ({} error(s)) in {} file(s)",
+        diagnostics.len(),
+        errors,
+        diagnostics.iter().map(|d| d.path.as_str()).collect::<HashSet<_>>().len()
+    );
+    stats.diagnostics = diagnostics
+        .into_iter()
+        .map(|d| {
+            HashMap::from([
+                ("path".to_string(), json!(d.path)),
+                ("line".to_string(), json!(d.line)),
+                ("severity".to_string(), json!(d.severity)),
+                ("message".to_string(), json!(d.message)),
+                ("code".to_string(), json!(d.code)),
+            ])
+        })
+        .collect();
 }
 
-fn sort_chunks_for_stitch_story(
+/// Set each chunk's byte range from its line range in the raw file bytes, i.e. before
+/// decoding, redaction or any other rewrite of the chunk content. UTF-16 files get none.
+fn attach_byte_offsets(
     chunks: &mut [Chunk],
-    seed_ids: &std::collections::BTreeSet<String>,
-    stitched: &std::collections::HashMap<String, StitchTier>,
+    files: &[crate::domain::FileInfo],
+    encodings: &EncodingOverrides,
 ) {
-    chunks.sort_by(|a, b| {
-        let a_key = sort_group(a, seed_ids, stitched);
-        let b_key = sort_group(b, seed_ids, stitched);
-        a_key
-            .cmp(&b_key)
-            .then_with(|| b.priority.partial_cmp(&a.priority).unwrap_or(std::cmp::Ordering::Equal))
-            .then_with(|| a.path.cmp(&b.path))
-            .then_with(|| a.start_line.cmp(&b.start_line))
-            .then_with(|| a.id.cmp(&b.id))
-    });
-}
-
-fn parse_mode(mode: Option<&str>) -> Result<OutputMode> {
-    match mode.unwrap_or("both").to_ascii_lowercase().as_str() {
-        "prompt" => Ok(OutputMode::Prompt),
-        "rag" => Ok(OutputMode::Rag),
-        "contribution" => Ok(OutputMode::Contribution),
-        "pr-context" | "pr_context" | "prcontext" => Ok(OutputMode::PrContext),
-        "both" => Ok(OutputMode::Both),
-        invalid => {
-            anyhow::bail!("Invalid mode '{invalid}'. Use: prompt|rag|contribution|pr-context|both")
-        }
-    }
-}
-
-fn default_contribution_globs() -> Vec<String> {
-    [
-        "examples/**",
-        ".github/PULL_REQUEST_TEMPLATE*",
-        ".github/ISSUE_TEMPLATE/**",
-        ".github/workflows/**",
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect()
-}
-
-fn default_contribution_paths() -> Vec<String> {
-    [
-        "README.md",
-        "CONTRIBUTING.md",
-        "CONTRIBUTING.rst",
-        "SECURITY.md",
-        "CODE_OF_CONDUCT.md",
-        "Cargo.toml",
-        "pyproject.toml",
-        "package.json",
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect()
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PinTier {
-    Tier0,
-    Tier1,
-    Tier2,
-}
-
-impl PinTier {
-    fn as_str(self) -> &'static str {
-        match self {
-            Self::Tier0 => "tier0",
-            Self::Tier1 => "tier1",
-            Self::Tier2 => "tier2",
+    let paths: HashMap<&str, &Path> =
+        files.iter().map(|f| (f.relative_path.as_str(), f.path.as_path())).collect();
+    let mut line_starts: HashMap<String, Option<(Vec<usize>, usize)>> = HashMap::new();
+    for chunk in chunks.iter_mut() {
+        let entry = line_starts.entry(chunk.path.clone()).or_insert_with(|| {
+            let path = paths.get(chunk.path.as_str())?;
+            let bytes = fs::read(path).ok()?;
+            if !encodings.has_byte_newlines(&chunk.path, &bytes) {
+                return None;
+            }
+            Some((line_start_offsets(&bytes), bytes.len()))
+        });
+        if let Some((starts, len)) = entry {
+            chunk.byte_range =
+                Some(line_range_bytes(starts, *len, chunk.start_line, chunk.end_line));
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct PinPlan {
-    tiers: HashMap<String, PinTier>,
-    reasons: HashMap<String, String>,
+/// One `--explain-budget` line: the path, the drop reason and, for budget drops, how much
+/// of the budget was used at that point, e.g. `src/big.rs: bytes_limit, dropped at
+/// 19,980/20,000 bytes`.
+fn explain_dropped_file(record: &HashMap<String, serde_json::Value>) -> String {
+    let path = record.get("path").and_then(|v| v.as_str()).unwrap_or("?");
+    let reason = record.get("reason").and_then(|v| v.as_str()).unwrap_or("?");
+    let mut line = format!("{path}: {reason}");
+    let number = |key: &str| record.get(key).and_then(|v| v.as_u64());
+    if let (Some(used), Some(limit)) = (number("budget_used"), number("budget_limit")) {
+        let unit = record.get("budget_unit").and_then(|v| v.as_str()).unwrap_or("");
+        line.push_str(&format!(
+            ", dropped at {}/{} {unit}",
+            format_with_commas(used),
+            format_with_commas(limit)
+        ));
+    }
+    if let Some(tokens) = number("tokens") {
+        line.push_str(&format!(" ({} tokens dropped)", format_with_commas(tokens)));
+    }
+    if record.contains_key("stubbed") {
+        line.push_str(", kept as stub");
+    }
+    line
 }
 
-impl PinPlan {
-    fn protected_paths(&self) -> HashSet<String> {
-        self.tiers
-            .iter()
-            .filter_map(|(path, tier)| {
-                if matches!(tier, PinTier::Tier0 | PinTier::Tier1) {
-                    Some(path.clone())
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn tier_for(&self, path: &str) -> Option<PinTier> {
-        self.tiers.get(path).copied()
-    }
-
-    fn reason_for(&self, path: &str) -> Option<&str> {
-        self.reasons.get(path).map(String::as_str)
-    }
-
-    fn promote(&mut self, path: &str, tier: PinTier, reason: String) {
-        let should_update = match self.tiers.get(path) {
-            None => true,
-            Some(existing) => tier_rank(tier) < tier_rank(*existing),
+/// Print each redaction the export would make, one `path:line  rule  preview` row per
+/// match, without chunking or writing anything.
+fn preview_redaction(
+    redactor: &Redactor,
+    files: &[crate::domain::FileInfo],
+    encodings: &EncodingOverrides,
+) -> Result<()> {
+    let mut total = 0usize;
+    let mut redacted_files = 0usize;
+    let mut allowlisted = 0usize;
+    for file in files {
+        let encoding = encodings.encoding_for(&file.relative_path);
+        let Ok((content, _)) = read_file_safe(&file.path, None, encoding) else {
+            continue;
         };
-        if should_update {
-            self.tiers.insert(path.to_string(), tier);
-            self.reasons.insert(path.to_string(), reason);
-        }
-    }
-}
-
-fn tier_rank(tier: PinTier) -> u8 {
-    match tier {
-        PinTier::Tier0 => 0,
-        PinTier::Tier1 => 1,
-        PinTier::Tier2 => 2,
-    }
-}
-
-fn build_pin_plan(
-    root_path: &Path,
-    ranked_files: &[crate::domain::FileInfo],
-    explicit_paths: &[String],
-    explicit_globs: &[String],
-    invariant_keywords: &[String],
-) -> Result<PinPlan> {
-    const MAX_GLOB_PROTECTED: usize = 80;
-
-    let mut plan = PinPlan { tiers: HashMap::new(), reasons: HashMap::new() };
-    let normalized_paths: HashSet<String> =
-        explicit_paths.iter().map(|p| normalize_rel_path(p)).filter(|p| !p.is_empty()).collect();
-
-    for file in ranked_files {
-        let path = file.relative_path.as_str();
-        if normalized_paths.contains(path) {
-            plan.promote(path, PinTier::Tier0, "explicit_path".to_string());
-        }
-        if is_tier0_contract_path(path) {
-            plan.promote(path, PinTier::Tier0, "auto_contract_path".to_string());
-        }
-    }
-
-    let globset = build_globset(explicit_globs)?;
-    if let Some(globset) = globset {
-        let mut matches: Vec<&crate::domain::FileInfo> =
-            ranked_files.iter().filter(|f| globset.is_match(&f.relative_path)).collect();
-        matches.sort_by(|a, b| {
-            b.priority
-                .partial_cmp(&a.priority)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.relative_path.cmp(&b.relative_path))
-        });
-
-        for (idx, file) in matches.into_iter().enumerate() {
-            if idx < MAX_GLOB_PROTECTED {
-                plan.promote(&file.relative_path, PinTier::Tier1, "explicit_glob".to_string());
-            } else {
-                plan.promote(
-                    &file.relative_path,
-                    PinTier::Tier2,
-                    "explicit_glob_rate_limited".to_string(),
-                );
-            }
-        }
-    }
-
-    for file in ranked_files {
-        if plan.tier_for(&file.relative_path) == Some(PinTier::Tier0) {
+        let filename =
+            Path::new(&file.relative_path).file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if redactor.is_file_allowlisted(filename, &file.relative_path) {
+            allowlisted += redactor.count_allowlisted_file_matches(&content);
             continue;
         }
-        let Some((score, evidence)) = invariant_score(root_path, file, invariant_keywords) else {
+        let outcome = redactor.redact_with_language_report(
+            &content,
+            &file.language,
+            &file.extension,
+            filename,
+            &file.relative_path,
+        );
+        allowlisted += outcome.suppressed_by_allowlist;
+        if outcome.locations.is_empty() {
             continue;
-        };
-        if score >= 9 {
-            plan.promote(
-                &file.relative_path,
-                PinTier::Tier1,
-                format!("invariant_score:{score} ({evidence})"),
-            );
-        } else if score >= 6 {
-            plan.promote(
-                &file.relative_path,
-                PinTier::Tier2,
-                format!("invariant_score:{score} ({evidence})"),
+        }
+        redacted_files += 1;
+        total += outcome.locations.len();
+        // The preview is the command's output, so it prints even with --quiet.
+        for location in &outcome.locations {
+            std::println!(
+                "{}:{}  {}  {}",
+                file.relative_path,
+                location.line,
+                location.rule,
+                location.preview
             );
         }
     }
-
-    Ok(plan)
-}
-
-fn normalize_rel_path(path: &str) -> String {
-    path.trim().trim_start_matches("./").replace('\\', "/")
-}
-
-fn is_tier0_contract_path(path: &str) -> bool {
-    let lower = path.to_ascii_lowercase();
-    let file = lower.rsplit('/').next().unwrap_or(&lower);
-    if ["readme.md", "contributing.md", "security.md", "code_of_conduct.md"].contains(&file) {
-        return true;
-    }
-    if lower == "cargo.toml" || lower.ends_with("/cargo.toml") {
-        return true;
+    let mut summary = format!("{total} redaction(s) in {redacted_files} file(s)");
+    if allowlisted > 0 {
+        summary.push_str(&format!(", {allowlisted} match(es) kept by allowlists"));
     }
-    lower.ends_with("schema.json")
-        || lower.ends_with("schema.yaml")
-        || lower.ends_with("schema.yml")
+    std::println!("{summary}; nothing written");
+    Ok(())
 }
 
-fn invariant_score(
-    root_path: &Path,
-    file: &crate::domain::FileInfo,
-    keywords: &[String],
-) -> Option<(usize, String)> {
-    let mut score = 0usize;
-    let mut evidence = Vec::new();
-    let path_lower = file.relative_path.to_ascii_lowercase();
-
-    if file.is_readme {
-        score += 2;
-        evidence.push("readme");
-    }
-    if file.is_config {
-        score += 3;
-        evidence.push("config");
-    }
-    if file.is_doc {
-        score += 2;
-        evidence.push("doc");
-    }
-    if file.tags.contains("entrypoint") {
-        score += 2;
-        evidence.push("entrypoint");
-    }
-
-    for (needle, weight) in [
-        ("schema", 3usize),
-        ("contract", 3),
-        ("invariant", 3),
-        ("api", 2),
-        ("error", 2),
-        ("architecture", 3),
-        ("example", 2),
-        ("safety", 3),
-    ] {
-        if path_lower.contains(needle) {
-            score += weight;
-            evidence.push(needle);
-        }
-    }
-
-    if !(file.is_doc || file.is_config || path_lower.contains("test")) && score < 6 {
-        return None;
-    }
-
-    if file.size_bytes <= 256_000 {
-        if let Ok((content, _)) = read_file_safe(&root_path.join(&file.relative_path), None, None) {
-            let lower = content.to_ascii_lowercase();
-            let mut keyword_hits = 0usize;
-            for keyword in keywords {
-                let k = keyword.to_ascii_lowercase();
-                if k.is_empty() {
-                    continue;
-                }
-                if lower.contains(&k) {
-                    keyword_hits += 1;
-                }
-            }
-            if keyword_hits > 0 {
-                score += keyword_hits.min(4);
-                evidence.push("keywords");
-            }
+fn parse_mode(mode: Option<&str>) -> Result<OutputMode> {
+    match mode.unwrap_or("both").to_ascii_lowercase().as_str() {
+        "prompt" => Ok(OutputMode::Prompt),
+        "rag" => Ok(OutputMode::Rag),
+        "contribution" => Ok(OutputMode::Contribution),
+        "pr-context" | "pr_context" | "prcontext" => Ok(OutputMode::PrContext),
+        "files-json" | "files_json" => Ok(OutputMode::FilesJson),
+        "map" => Ok(OutputMode::Map),
+        "both" => Ok(OutputMode::Both),
+        invalid => {
+            anyhow::bail!(
+                "Invalid mode '{invalid}'. Use: prompt|rag|contribution|pr-context|files-json|map|both"
+            )
         }
     }
-
-    if score == 0 {
-        None
-    } else {
-        Some((score, evidence.join("+")))
-    }
-}
-
-fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
-    if patterns.is_empty() {
-        return Ok(None);
-    }
-    let mut builder = GlobSetBuilder::new();
-    for pattern in patterns {
-        builder.add(Glob::new(pattern)?);
-    }
-    Ok(Some(builder.build()?))
 }
 
 fn parse_redaction_mode(mode: Option<&str>) -> Result<RedactionMode> {
@@ -2063,124 +2176,59 @@ fn parse_redaction_mode(mode: Option<&str>) -> Result<RedactionMode> {
     }
 }
 
-fn build_redactor(mode: RedactionMode, cfg: &crate::domain::RedactionConfig) -> Redactor {
-    match mode {
-        RedactionMode::Fast => Redactor::from_config(false, false, false, cfg),
-        RedactionMode::Standard => Redactor::from_config(true, false, false, cfg),
-        RedactionMode::Paranoid => Redactor::from_config(true, true, false, cfg),
-        RedactionMode::StructureSafe => Redactor::from_config(true, false, true, cfg),
-    }
+fn redact_display_paths(
+    redactor: &Redactor,
+    files: &[crate::domain::FileInfo],
+    chunks: &[Chunk],
+) -> (Vec<crate::domain::FileInfo>, Vec<Chunk>) {
+    let display_files = files
+        .iter()
+        .map(|file| {
+            let mut file = file.clone();
+            file.relative_path = redactor.redact_path(&file.relative_path);
+            file
+        })
+        .collect();
+    let display_chunks = chunks
+        .iter()
+        .map(|chunk| {
+            let mut chunk = chunk.clone();
+            chunk.path = redactor.redact_path(&chunk.path);
+            chunk
+        })
+        .collect();
+    (display_files, display_chunks)
 }
 
-fn apply_byte_budget(
-    ranked_files: Vec<crate::domain::FileInfo>,
-    max_total_bytes: Option<u64>,
-    stats: &mut crate::domain::ScanStats,
-    protected_paths: &HashSet<String>,
-) -> Vec<crate::domain::FileInfo> {
-    let Some(limit) = max_total_bytes else {
-        return ranked_files;
-    };
-
-    let mut selected = Vec::new();
-    let mut total = 0_u64;
-    for (idx, file) in ranked_files.iter().enumerate() {
-        if protected_paths.contains(&file.relative_path) {
-            total += file.size_bytes;
-            selected.push(file.clone());
-            continue;
+/// Mask the `path` of every `top_ranked_files`, `dropped_files` and `diagnostics` record.
+fn redact_record_paths(redactor: &Redactor, stats: &mut crate::domain::ScanStats) {
+    let records = stats
+        .top_ranked_files
+        .iter_mut()
+        .chain(stats.dropped_files.iter_mut())
+        .chain(stats.diagnostics.iter_mut());
+    for record in records {
+        if let Some(serde_json::Value::String(path)) = record.get_mut("path") {
+            *path = redactor.redact_path(path);
         }
-        // Python checks >= BEFORE adding the current file (cumulative of already-accepted bytes)
-        if total >= limit {
-            // Bulk-drop this file and all remaining files
-            for remaining in &ranked_files[idx..] {
-                if protected_paths.contains(&remaining.relative_path) {
-                    total += remaining.size_bytes;
-                    selected.push(remaining.clone());
-                    continue;
-                }
-                stats.files_dropped_budget += 1;
-                stats.dropped_files.push(std::collections::HashMap::from([
-                    ("path".to_string(), json!(remaining.relative_path)),
-                    ("reason".to_string(), json!("bytes_limit")),
-                    ("priority".to_string(), json!(remaining.priority)),
-                ]));
-            }
-            break;
+    }
+    for asset in &mut stats.referenced_assets {
+        asset.path = redactor.redact_path(&asset.path);
+        for path in &mut asset.referenced_by {
+            *path = redactor.redact_path(path);
         }
-        total += file.size_bytes;
-        selected.push(file.clone());
     }
-    stats.total_bytes_included = total;
-    selected
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        apply_guided_plan, build_pin_plan, most_imported_not_included, repo_name_for_output,
-        repo_name_from_remote_url, sort_chunks_for_stitch_story, ExportArgs, GuidedPlan, PinTier,
+        apply_guided_plan, repo_name_for_output, repo_name_from_remote_url, ExportArgs, GuidedPlan,
+        PackOrder,
     };
-    use crate::domain::{Chunk, Config, OutputMode};
-    use crate::rank::StitchTier;
-    use rusqlite::Connection;
-    use std::collections::{BTreeSet, HashMap};
+    use crate::domain::{Config, OutputMode};
     use std::path::Path;
 
-    fn mk_chunk(id: &str, priority: f64, path: &str, start_line: usize) -> Chunk {
-        Chunk {
-            id: id.to_string(),
-            path: path.to_string(),
-            language: "rust".to_string(),
-            start_line,
-            end_line: start_line,
-            content: "fn x() {}".to_string(),
-            priority,
-            tags: BTreeSet::new(),
-            token_estimate: 10,
-        }
-    }
-
-    #[test]
-    fn stitch_story_sort_orders_seed_then_tiers_then_rest() {
-        let mut chunks = vec![
-            mk_chunk("rest", 0.99, "z.rs", 1),
-            mk_chunk("callee_hi", 0.95, "b.rs", 2),
-            mk_chunk("seed_hi", 0.70, "a.rs", 2),
-            mk_chunk("caller", 0.90, "c.rs", 1),
-            mk_chunk("def_lo", 0.60, "d.rs", 10),
-            mk_chunk("seed_lo", 0.20, "a.rs", 1),
-            mk_chunk("def_hi", 0.85, "d.rs", 1),
-            mk_chunk("callee_lo", 0.30, "b.rs", 1),
-        ];
-
-        let seed_ids = BTreeSet::from(["seed_hi".to_string(), "seed_lo".to_string()]);
-        let stitched = HashMap::from([
-            ("def_hi".to_string(), StitchTier::Definition),
-            ("def_lo".to_string(), StitchTier::Definition),
-            ("callee_hi".to_string(), StitchTier::Callee),
-            ("callee_lo".to_string(), StitchTier::Callee),
-            ("caller".to_string(), StitchTier::Caller),
-        ]);
-
-        sort_chunks_for_stitch_story(&mut chunks, &seed_ids, &stitched);
-        let ordered: Vec<&str> = chunks.iter().map(|c| c.id.as_str()).collect();
-
-        assert_eq!(
-            ordered,
-            vec![
-                "seed_hi",
-                "seed_lo",
-                "def_hi",
-                "def_lo",
-                "callee_hi",
-                "callee_lo",
-                "caller",
-                "rest"
-            ]
-        );
-    }
-
     #[test]
     fn repo_name_from_remote_url_extracts_repo_segment() {
         assert_eq!(
@@ -2215,40 +2263,94 @@ mod tests {
             path: None,
             repo: None,
             ref_: None,
-            config: None,
+            config: Vec::new(),
             include_ext: None,
             exclude_glob: None,
+            exclude_paths_from: None,
+            coverage_file: None,
             max_file_bytes: None,
             max_total_bytes: None,
+            max_scan_files: None,
             no_gitignore: false,
             follow_symlinks: false,
             include_minified: false,
+            lfs_resolve: false,
             max_tokens: None,
+            max_tokens_per_file: None,
+            reserve_tokens: None,
+            head_lines: None,
             allow_over_budget: false,
             strict_budget: false,
+            stub_dropped: false,
             always_include_path: Vec::new(),
             always_include_glob: Vec::new(),
             invariant_keywords: Vec::new(),
             invariant_keywords_add: Vec::new(),
+            recency_weight: None,
             task: None,
+            symbol: None,
+            focus_file: None,
+            focus_depth: 2,
+            include_git_status: false,
+            annotate_ownership: false,
+            include_diagnostics: false,
+            asset_metadata: false,
+            seed_file: Vec::new(),
             no_semantic_rerank: false,
             semantic_model: None,
             rerank_top_k: None,
+            expand_seeds: None,
+            expand_depth: None,
+            expand_decay: Vec::new(),
             stitch_budget_fraction: None,
             stitch_top_n: None,
             chunk_tokens: None,
             chunk_overlap: None,
             min_chunk_tokens: None,
+            max_chunk_lines: None,
+            merge_adjacent: false,
+            merge_factor: None,
             mode: None,
             output_dir: None,
+            max_dropped_records: super::DEFAULT_MAX_DROPPED_RECORDS,
+            clean: false,
+            no_overwrite: false,
+            skip_if_unchanged: false,
             no_timestamp: false,
+            report_pretty: false,
+            report_compact: false,
+            toc: false,
+            group_by_directory: false,
+            annotate_splits: false,
+            order: PackOrder::Ranked,
+            emit_byte_offsets: false,
+            emit_file_deps: false,
+            report_unresolved_imports: false,
+            summarize_schemas: false,
+            profile: None,
+            dedup_license_headers: false,
             tree_depth: None,
+            tree_collapse_single: false,
+            tree_symbol_counts: false,
+            token_report: false,
+            explain_budget: false,
+            post_export_cmd: None,
+            archive: None,
+            redaction_rules: None,
             no_redact: false,
             redaction_mode: None,
+            redact_paths: false,
+            redaction_audit: None,
+            preview_redaction: false,
+            normalize_unicode: false,
             no_graph: false,
             quick: false,
+            yes: false,
             from_index: false,
             require_fresh_index: false,
+            require_index: false,
+            selection: None,
+            since: None,
         }
     }
 
@@ -2312,84 +2414,4 @@ mod tests {
         assert_eq!(cfg.stitch_top_n, 10);
         assert_eq!(cfg.rerank_top_k, 42);
     }
-
-    #[test]
-    fn pin_plan_marks_explicit_paths_as_tier0() {
-        let tmp = tempfile::TempDir::new().expect("tmp");
-        std::fs::write(tmp.path().join("README.md"), "# Readme\n").expect("write readme");
-        let file = crate::domain::FileInfo {
-            path: tmp.path().join("README.md"),
-            relative_path: "README.md".to_string(),
-            size_bytes: 8,
-            extension: ".md".to_string(),
-            language: "markdown".to_string(),
-            id: "id1".to_string(),
-            priority: 0.9,
-            token_estimate: 4,
-            tags: BTreeSet::new(),
-            is_readme: true,
-            is_config: false,
-            is_doc: true,
-        };
-        let plan = build_pin_plan(
-            tmp.path(),
-            &[file],
-            &["README.md".to_string()],
-            &[],
-            &["must".to_string()],
-        )
-        .expect("pin plan");
-
-        assert_eq!(plan.tier_for("README.md"), Some(PinTier::Tier0));
-    }
-
-    #[test]
-    fn most_imported_not_included_prefers_incoming_edges_from_included() {
-        let tmp = tempfile::TempDir::new().expect("tmp");
-        let db = tmp.path().join("index.sqlite");
-        let conn = Connection::open(&db).expect("open db");
-        conn.execute(
-            "CREATE TABLE file_imports (source_path TEXT NOT NULL, target_path TEXT NOT NULL)",
-            [],
-        )
-        .expect("create table");
-        conn.execute(
-            "INSERT INTO file_imports (source_path, target_path) VALUES ('src/a.rs','src/x.rs')",
-            [],
-        )
-        .expect("insert edge 1");
-        conn.execute(
-            "INSERT INTO file_imports (source_path, target_path) VALUES ('src/b.rs','src/x.rs')",
-            [],
-        )
-        .expect("insert edge 2");
-        conn.execute(
-            "INSERT INTO file_imports (source_path, target_path) VALUES ('tests/t.rs','src/y.rs')",
-            [],
-        )
-        .expect("insert edge 3");
-
-        let dropped_paths = vec!["src/x.rs".to_string(), "src/y.rs".to_string()];
-        let included_paths = std::collections::HashSet::from(["src/a.rs".to_string()]);
-        let dropped_entries = vec![
-            HashMap::from([
-                ("path".to_string(), serde_json::json!("src/x.rs")),
-                ("reason".to_string(), serde_json::json!("token_budget")),
-            ]),
-            HashMap::from([
-                ("path".to_string(), serde_json::json!("src/y.rs")),
-                ("reason".to_string(), serde_json::json!("token_budget")),
-            ]),
-        ];
-
-        let rows = most_imported_not_included(
-            Some(&db),
-            &dropped_paths,
-            &included_paths,
-            &dropped_entries,
-        );
-
-        assert_eq!(rows[0]["path"], serde_json::json!("src/x.rs"));
-        assert_eq!(rows[0]["incoming_edges_from_included"], serde_json::json!(1));
-    }
 }
@@ -0,0 +1,40 @@
+//! Graph-merge command: combine the symbol graphs of several indexes.
+
+use anyhow::{bail, Result};
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::graph::merge::{merge_graphs, GraphSource};
+use crate::utils::output::println;
+
+#[derive(Args)]
+pub struct GraphMergeArgs {
+    /// Index or symbol graph to merge, as PATH or NAME=PATH; NAME prefixes its paths
+    #[arg(long = "db", value_name = "[NAME=]FILE", required = true)]
+    pub dbs: Vec<String>,
+
+    /// SQLite file to write the merged graph to (replaced if it holds a graph already)
+    #[arg(long, value_name = "FILE")]
+    pub out: PathBuf,
+}
+
+pub fn run(args: GraphMergeArgs) -> Result<()> {
+    if args.dbs.len() < 2 {
+        bail!("graph-merge needs at least two --db databases");
+    }
+    let sources =
+        args.dbs.iter().map(|spec| GraphSource::parse(spec)).collect::<Result<Vec<_>>>()?;
+    let counts = merge_graphs(&sources, &args.out)?;
+
+    for (source, counts) in sources.iter().zip(&counts) {
+        println!(
+            "{}/  {} symbol(s), {} import edge(s) from {}",
+            source.namespace,
+            counts.symbols,
+            counts.imports,
+            source.path.display()
+        );
+    }
+    println!("Merged {} graphs into {}", sources.len(), args.out.display());
+    Ok(())
+}
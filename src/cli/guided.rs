@@ -211,24 +211,16 @@ fn build_focus_areas(ranked_files: &[FileInfo]) -> Vec<FocusArea> {
 #[cfg(test)]
 mod tests {
     use super::build_focus_areas;
+    use crate::domain::test_support::file_info;
     use crate::domain::FileInfo;
-    use std::collections::BTreeSet;
-    use std::path::PathBuf;
 
     fn file(path: &str, priority: f64, tags: &[&str]) -> FileInfo {
         FileInfo {
-            path: PathBuf::from(path),
-            relative_path: path.to_string(),
             size_bytes: 10,
-            extension: ".rs".to_string(),
-            language: "rust".to_string(),
-            id: path.to_string(),
             priority,
             token_estimate: 10,
-            tags: tags.iter().map(|t| t.to_string()).collect::<BTreeSet<_>>(),
-            is_readme: false,
-            is_config: false,
-            is_doc: false,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..file_info(path)
         }
     }
 
@@ -12,16 +12,21 @@ use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 
 use super::cache::remote_index_cache_db_path;
-use super::utils::parse_csv;
-use crate::chunk::{chunk_content, coalesce_small_chunks_with_max};
-use crate::config::{load_config, merge_cli_with_config, CliOverrides};
-use crate::domain::{Chunk, FileInfo, ScanStats};
+use super::utils::{parse_csv, warn_dominant_directory};
+use crate::chunk::{
+    chunk_content_with_tag_queries, coalesce_small_chunks_with_max,
+    code_chunker::{DefinitionKinds, ParseLimits},
+    MinChunkTokens,
+};
+use crate::config::{config_hash, load_config_files, merge_cli_with_config, CliOverrides};
+use crate::domain::{Chunk, ChunkOverlap, FileInfo, ScanStats, TagQuery};
 use crate::fetch::fetch_repository;
 use crate::graph::persist::persist_graph;
 use crate::lsp::rust_analyzer;
 use crate::rank::rank_files;
 use crate::scan::scanner::FileScanner;
-use crate::utils::read_file_safe;
+use crate::utils::output::println;
+use crate::utils::{read_file_safe, EncodingOverrides};
 
 #[derive(Args)]
 pub struct IndexArgs {
@@ -37,9 +42,9 @@ pub struct IndexArgs {
     #[arg(long, value_name = "REF")]
     pub ref_: Option<String>,
 
-    /// Path to config file (repo-context.toml or .r2p.yml)
+    /// Path to config file (repo-context.toml or .r2p.yml); repeat to merge several in order
     #[arg(short = 'c', long, value_name = "FILE")]
-    pub config: Option<PathBuf>,
+    pub config: Vec<PathBuf>,
 
     /// SQLite path for the index database
     #[arg(long, value_name = "FILE", default_value = ".repo-context/index.sqlite")]
@@ -77,23 +82,34 @@ pub struct IndexArgs {
     #[arg(long, value_name = "TOKENS")]
     pub chunk_tokens: Option<usize>,
 
-    /// Overlap tokens between adjacent chunks
-    #[arg(long, value_name = "TOKENS")]
-    pub chunk_overlap: Option<usize>,
+    /// Overlap between adjacent chunks, in tokens or as a percentage of the chunk size (e.g. 15%)
+    #[arg(long, value_name = "TOKENS|PCT%")]
+    pub chunk_overlap: Option<ChunkOverlap>,
 
     /// Coalesce chunks smaller than this
     #[arg(long, value_name = "TOKENS")]
     pub min_chunk_tokens: Option<usize>,
 
+    /// Split chunks spanning more lines than this, even when under the token size
+    #[arg(long, value_name = "LINES")]
+    pub max_chunk_lines: Option<usize>,
+
     /// Enrich index with rust-analyzer symbol references
     #[arg(long)]
     pub lsp: bool,
+
+    /// Store references from at most N chunks per symbol (the first N by path and line)
+    #[arg(long, value_name = "N")]
+    pub max_occurrences_per_symbol: Option<usize>,
 }
 
 pub fn run(args: IndexArgs) -> Result<()> {
     if args.path.is_some() && args.repo.is_some() {
         anyhow::bail!("Cannot specify both --path and --repo");
     }
+    if args.max_occurrences_per_symbol == Some(0) {
+        anyhow::bail!("--max-occurrences-per-symbol must be at least 1");
+    }
 
     let cwd = std::env::current_dir()?;
     let config_anchor = match args.path.as_ref() {
@@ -101,7 +117,7 @@ pub fn run(args: IndexArgs) -> Result<()> {
         _ => cwd.clone(),
     };
 
-    let file_config = load_config(&config_anchor, args.config.as_deref())?;
+    let file_config = load_config_files(&config_anchor, &args.config, None)?;
     let include_ext = parse_csv(&args.include_ext).map(|v| v.into_iter().collect());
     let exclude_glob = parse_csv(&args.exclude_glob).map(|v| v.into_iter().collect());
 
@@ -119,10 +135,11 @@ pub fn run(args: IndexArgs) -> Result<()> {
         chunk_tokens: args.chunk_tokens,
         chunk_overlap: args.chunk_overlap,
         min_chunk_tokens: args.min_chunk_tokens,
+        max_chunk_lines: args.max_chunk_lines,
         ..CliOverrides::default()
     };
     let merged = merge_cli_with_config(file_config, cli_overrides);
-    let config_hash = index_config_hash(&merged);
+    let config_hash = config_hash(&merged);
     let default_db = PathBuf::from(".repo-context/index.sqlite");
     let mut db_path = args.db.clone();
     if merged.repo_url.is_some() && args.db == default_db {
@@ -152,11 +169,14 @@ pub fn run(args: IndexArgs) -> Result<()> {
         .respect_gitignore(merged.respect_gitignore)
         .follow_symlinks(merged.follow_symlinks)
         .skip_minified(merged.skip_minified)
+        .lfs_resolve(merged.lfs_resolve)
         .include_extensions(merged.include_extensions.iter().cloned().collect())
-        .exclude_globs(merged.exclude_globs.iter().cloned().collect());
+        .exclude_globs(merged.exclude_globs.iter().cloned().collect())
+        .force_text_globs(merged.force_text_globs.clone());
 
     let scanned_files = scanner.scan()?;
     let mut stats = scanner.stats().clone();
+    warn_dominant_directory(&scanned_files, merged.dominant_dir_fraction);
     let ranked_files = rank_files(&root_path, scanned_files)?;
     let selected_files = apply_byte_budget(ranked_files, Some(merged.max_total_bytes), &mut stats);
 
@@ -174,9 +194,15 @@ pub fn run(args: IndexArgs) -> Result<()> {
         },
         IndexBuildOptions {
             chunk_tokens: merged.chunk_tokens,
-            chunk_overlap: merged.chunk_overlap,
-            min_chunk_tokens: merged.min_chunk_tokens,
+            chunk_overlap: merged.chunk_overlap.resolve(merged.chunk_tokens),
+            min_chunk_tokens: MinChunkTokens::from_config(&merged),
+            tag_queries: &merged.tag_queries,
+            parse_limits: ParseLimits::from_config(&merged),
+            definition_kinds: DefinitionKinds::from_config(&merged),
+            max_chunk_lines: merged.max_chunk_lines,
+            encodings: &EncodingOverrides::from_config(&merged)?,
             lsp_enabled: args.lsp,
+            max_occurrences_per_symbol: args.max_occurrences_per_symbol,
         },
     )?;
 
@@ -206,7 +232,7 @@ fn write_index(
     files: &[FileInfo],
     stats: &ScanStats,
     metadata_ctx: IndexMetadata,
-    build: IndexBuildOptions,
+    build: IndexBuildOptions<'_>,
 ) -> Result<IndexSummary> {
     if let Some(parent) = db_path.parent() {
         fs::create_dir_all(parent)?;
@@ -276,7 +302,8 @@ fn write_index(
             continue;
         }
 
-        let (content, _encoding) = match read_file_safe(&file.path, None, None) {
+        let encoding = build.encodings.encoding_for(&file.relative_path);
+        let (content, _encoding) = match read_file_safe(&file.path, None, encoding) {
             Ok(value) => value,
             Err(_) => {
                 files_unreadable += 1;
@@ -316,9 +343,22 @@ fn write_index(
         tx.execute("DELETE FROM symbol_edges WHERE to_chunk_id IN (SELECT id FROM chunks WHERE file_path = ?1)", params![path])?;
         tx.execute("DELETE FROM files WHERE path = ?1", params![path])?;
 
-        let raw_chunks = chunk_content(file, &content, build.chunk_tokens, build.chunk_overlap)?;
-        let file_chunks =
-            coalesce_small_chunks_with_max(raw_chunks, build.min_chunk_tokens, build.chunk_tokens);
+        let raw_chunks = chunk_content_with_tag_queries(
+            file,
+            &content,
+            build.chunk_tokens,
+            build.chunk_overlap,
+            build.tag_queries,
+            build.parse_limits,
+            build.definition_kinds,
+            build.max_chunk_lines,
+        )?;
+        let file_chunks = coalesce_small_chunks_with_max(
+            raw_chunks,
+            build.min_chunk_tokens,
+            build.chunk_tokens,
+            build.max_chunk_lines,
+        );
         let file_tokens = file_chunks.iter().map(|c| c.token_estimate).sum::<usize>();
 
         tx.execute(
@@ -354,7 +394,7 @@ fn write_index(
 
     tx.execute("DELETE FROM metadata", [])?;
 
-    let metadata = [
+    let mut metadata = vec![
         ("repo_root".to_string(), root_path.to_string_lossy().to_string()),
         ("files_scanned".to_string(), stats.files_scanned.to_string()),
         ("files_indexed".to_string(), files_indexed.to_string()),
@@ -375,6 +415,9 @@ fn write_index(
         ("config_hash".to_string(), metadata_ctx.config_hash),
         ("tool_version".to_string(), metadata_ctx.tool_version),
     ];
+    if let Some(cap) = build.max_occurrences_per_symbol {
+        metadata.push(("max_occurrences_per_symbol".to_string(), cap.to_string()));
+    }
     for (key, value) in metadata {
         tx.execute("INSERT INTO metadata (key, value) VALUES (?1, ?2)", params![key, value])?;
     }
@@ -385,7 +428,9 @@ fn write_index(
     let mut graph_symbols_indexed = 0usize;
     let mut graph_import_edges_indexed = 0usize;
     let all_chunks = load_all_chunks(&conn)?;
-    if let Ok((symbols, edges)) = persist_graph(&mut conn, &all_chunks) {
+    if let Ok((symbols, edges)) =
+        persist_graph(&mut conn, &all_chunks, build.max_occurrences_per_symbol)
+    {
         graph_symbols_indexed = symbols;
         graph_import_edges_indexed = edges;
     }
@@ -573,11 +618,17 @@ struct IndexSummary {
 }
 
 #[derive(Debug, Copy, Clone)]
-struct IndexBuildOptions {
+struct IndexBuildOptions<'a> {
     chunk_tokens: usize,
     chunk_overlap: usize,
-    min_chunk_tokens: usize,
+    min_chunk_tokens: MinChunkTokens,
+    tag_queries: &'a [TagQuery],
+    parse_limits: ParseLimits,
+    definition_kinds: DefinitionKinds,
+    max_chunk_lines: usize,
+    encodings: &'a EncodingOverrides,
     lsp_enabled: bool,
+    max_occurrences_per_symbol: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -600,24 +651,6 @@ fn discover_git_commit(root_path: &Path) -> Option<String> {
     Repository::discover(root_path).ok()?.head().ok()?.target().map(|oid| oid.to_string())
 }
 
-fn index_config_hash(config: &crate::domain::Config) -> String {
-    let payload = json!({
-        "include_extensions": config.include_extensions,
-        "exclude_globs": config.exclude_globs,
-        "max_file_bytes": config.max_file_bytes,
-        "max_total_bytes": config.max_total_bytes,
-        "respect_gitignore": config.respect_gitignore,
-        "follow_symlinks": config.follow_symlinks,
-        "skip_minified": config.skip_minified,
-        "chunk_tokens": config.chunk_tokens,
-        "chunk_overlap": config.chunk_overlap,
-        "min_chunk_tokens": config.min_chunk_tokens,
-    });
-    let mut hasher = Sha256::new();
-    hasher.update(serde_json::to_vec(&payload).unwrap_or_default());
-    format!("{:x}", hasher.finalize())
-}
-
 fn apply_byte_budget(
     ranked_files: Vec<FileInfo>,
     max_total_bytes: Option<u64>,
@@ -633,7 +666,7 @@ fn apply_byte_budget(
         if total >= limit {
             for remaining in &ranked_files[idx..] {
                 stats.files_dropped_budget += 1;
-                stats.dropped_files.push(HashMap::from([
+                stats.push_dropped_record(HashMap::from([
                     ("path".to_string(), json!(remaining.relative_path)),
                     ("reason".to_string(), json!("bytes_limit")),
                     ("priority".to_string(), json!(remaining.priority)),
@@ -671,6 +704,8 @@ fn load_all_chunks(conn: &Connection) -> Result<Vec<Chunk>> {
             token_estimate: row.get::<_, i64>(6)? as usize,
             tags,
             content: row.get(8)?,
+            doc: None,
+            byte_range: None,
         })
     })?;
 
@@ -1,13 +1,14 @@
 //! Info command implementation
 
 use anyhow::Result;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use serde_json::json;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
 use super::utils::parse_csv;
 use crate::chunk::code_chunker::supported_tree_sitter_languages;
-use crate::rank::rank_files;
+use crate::rank::FileRanker;
 use crate::scan::scanner::FileScanner;
 use crate::scan::tree::generate_tree;
 use crate::utils::format_with_commas;
@@ -41,6 +42,31 @@ pub struct InfoArgs {
     /// Include minified/bundled files
     #[arg(long)]
     pub include_minified: bool,
+
+    /// Output format: text or json
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: InfoFormat,
+
+    /// Explain the ranking category and weight behind the top N files (default 10)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+    pub explain_ranking: Option<usize>,
+}
+
+/// Keys of `ScanStats::to_report_value` that a scan without an export fills in.
+const INFO_STATS_KEYS: &[&str] = &[
+    "files_scanned",
+    "files_included",
+    "files_skipped",
+    "total_bytes_scanned",
+    "total_bytes_included",
+    "languages_detected",
+    "top_ignored_patterns",
+];
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum InfoFormat {
+    Text,
+    Json,
 }
 
 pub fn run(args: InfoArgs) -> Result<()> {
@@ -65,13 +91,73 @@ pub fn run(args: InfoArgs) -> Result<()> {
         scanner = scanner.exclude_globs(globs);
     }
 
-    let scanned_files = scanner.scan()?;
+    let mut ranked_files = scanner.scan()?;
     let stats = scanner.stats().clone();
 
-    let ranked_files = rank_files(&root, scanned_files)?;
+    let ranker =
+        FileRanker::new(&root, ranked_files.iter().map(|f| f.relative_path.clone()).collect());
+    ranker.rank_files(&mut ranked_files);
 
     // Repository name (just the directory name, matching Python's path.name)
     let repo_name = root.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    if args.format == InfoFormat::Json {
+        let entrypoints: Vec<&str> = ranked_files
+            .iter()
+            .filter(|f| f.tags.contains("entrypoint"))
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        let top_files: Vec<_> = ranked_files
+            .iter()
+            .take(10)
+            .map(|f| {
+                json!({
+                    "path": f.relative_path,
+                    "priority": (f.priority * 1000.0).round() / 1000.0,
+                })
+            })
+            .collect();
+        let manifest = ranker.get_manifest_info();
+        // Scan-side keys of report.json's `stats`; export-only counters are left out.
+        let report_stats = stats.to_report_value();
+        let mut scan_stats = serde_json::Map::new();
+        for key in INFO_STATS_KEYS {
+            if let Some(value) = report_stats.get(key) {
+                scan_stats.insert(key.to_string(), value.clone());
+            }
+        }
+        let mut doc = json!({
+            "repository": repo_name,
+            "path": root.display().to_string(),
+            "entrypoints": entrypoints,
+            "manifest": {
+                "name": manifest.get("name"),
+                "description": manifest.get("description"),
+            },
+            "top_files": top_files,
+            "stats": scan_stats,
+            "tree_sitter_languages": supported_tree_sitter_languages(),
+        });
+        if let Some(n) = args.explain_ranking {
+            doc["ranking_explanation"] = ranked_files
+                .iter()
+                .take(n)
+                .map(|f| {
+                    json!({
+                        "path": f.relative_path,
+                        "priority": (f.priority * 1000.0).round() / 1000.0,
+                        "category": f.priority_reason.as_ref().map(|r| r.category.as_str()),
+                        "weight": f.priority_reason.as_ref().map(|r| r.weight),
+                        "language_multiplier":
+                            f.priority_reason.as_ref().and_then(|r| r.language_multiplier),
+                    })
+                })
+                .collect();
+        }
+        println!("{}", serde_json::to_string_pretty(&doc)?);
+        return Ok(());
+    }
+
     println!("Repository: {}", repo_name);
 
     // Languages detected (matching Python cli.py:762-765)
@@ -106,6 +192,25 @@ pub fn run(args: InfoArgs) -> Result<()> {
         }
     }
 
+    if let Some(n) = args.explain_ranking {
+        println!("Ranking explanation:");
+        for f in ranked_files.iter().take(n) {
+            match &f.priority_reason {
+                Some(reason) => {
+                    let language = reason
+                        .language_multiplier
+                        .map(|m| format!(" x language {m:.2}"))
+                        .unwrap_or_default();
+                    println!(
+                        "  {}: {} (weight {:.2}{language}) -> priority {:.2}",
+                        f.relative_path, reason.category, reason.weight, f.priority
+                    )
+                }
+                None => println!("  {}: priority {:.2}", f.relative_path, f.priority),
+            }
+        }
+    }
+
     // Statistics block (matching Python cli.py:779-787)
     println!("Statistics:");
     println!("  Total files scanned: {}", stats.files_scanned);
@@ -0,0 +1,47 @@
+//! Languages command: list supported languages and what each one gets.
+
+use anyhow::Result;
+use clap::Args;
+use std::collections::BTreeMap;
+
+use crate::chunk::chunking_strategy;
+use crate::chunk::code_chunker::supported_tree_sitter_languages;
+use crate::domain::{LANGUAGE_EXTENSIONS, LANGUAGE_FILENAMES};
+use crate::graph::symbol_usage::supports_symbol_usages;
+
+#[derive(Args)]
+pub struct LanguagesArgs {}
+
+pub fn run(_args: LanguagesArgs) -> Result<()> {
+    print!("{}", render_languages());
+    Ok(())
+}
+
+/// One row per language: chunking strategy, symbol-usage support, and the extensions
+/// and filenames that map to it.
+fn render_languages() -> String {
+    let mut patterns: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (language, extensions) in LANGUAGE_EXTENSIONS {
+        patterns.entry(language).or_default().extend(extensions.iter().map(|e| e.to_string()));
+    }
+    for (filename, language) in LANGUAGE_FILENAMES {
+        patterns.entry(language).or_default().push(filename.to_string());
+    }
+    for language in supported_tree_sitter_languages() {
+        patterns.entry(language).or_default();
+    }
+
+    let mut out =
+        format!("{:<18} {:<12} {:<14} {}\n", "LANGUAGE", "CHUNKING", "SYMBOL USAGES", "FILES");
+    for (language, files) in &patterns {
+        let usages = if supports_symbol_usages(language) { "yes" } else { "no" };
+        out.push_str(&format!(
+            "{:<18} {:<12} {:<14} {}\n",
+            language,
+            chunking_strategy(language),
+            usages,
+            files.join(", ")
+        ));
+    }
+    out
+}
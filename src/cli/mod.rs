@@ -1,9 +1,10 @@
 //! Command-line interface for repo-context
 //!
-//! Provides `export`, `index`, and `info` subcommands.
+//! Provides `export`, `index`, `info`, `rank`, `validate-config` and related subcommands.
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use std::process::ExitCode;
 use tracing::Level;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -11,11 +12,18 @@ mod cache;
 mod codeintel;
 mod diff;
 mod export;
+mod graph_merge;
 mod guided;
 mod index;
 mod info;
+mod languages;
 mod query;
+mod rank;
+mod select;
+mod selection;
+mod style;
 mod utils;
+mod validate_config;
 
 /// Convert repositories into LLM-friendly context packs
 #[derive(Parser)]
@@ -29,6 +37,22 @@ pub struct Cli {
     /// Enable verbose logging (sets log level to DEBUG)
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Emit diagnostic logs as NDJSON on stderr
+    #[arg(long, global = true)]
+    json_logs: bool,
+
+    /// When to color summaries: auto (terminal without NO_COLOR), always or never
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = style::ColorMode::Auto)]
+    color: style::ColorMode,
+
+    /// Same as --color never
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Suppress status and summary output on stdout; errors still go to stderr
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -39,6 +63,9 @@ enum Commands {
     /// Display repository information without exporting
     Info(info::InfoArgs),
 
+    /// Scan and rank files, listing them by priority without chunking or rendering
+    Rank(rank::RankArgs),
+
     /// Build a local SQLite index for query-time retrieval
     Index(index::IndexArgs),
 
@@ -50,9 +77,22 @@ enum Commands {
 
     /// Compare two export outputs and show structural diffs
     Diff(diff::DiffArgs),
+
+    /// Merge the symbol graphs of several indexes, namespacing paths by repository
+    GraphMerge(graph_merge::GraphMergeArgs),
+
+    /// Interactively pick ranked files, then export the chosen subset
+    Select(select::SelectArgs),
+
+    /// Load a config file and report invalid values (regexes, globs, tag queries)
+    ValidateConfig(validate_config::ValidateConfigArgs),
+
+    /// List supported languages: chunking strategy, symbol usages, and file extensions
+    Languages(languages::LanguagesArgs),
 }
 
-pub fn run() -> Result<()> {
+/// Parse the command line and run the chosen subcommand; the code is what the process exits with.
+pub fn run() -> Result<ExitCode> {
     let cli = Cli::parse();
 
     // Wire verbose flag to the tracing log level.
@@ -62,17 +102,28 @@ pub fn run() -> Result<()> {
     } else {
         EnvFilter::from_default_env().add_directive(Level::WARN.into())
     };
-    let _ = tracing_subscriber::registry()
-        .with(fmt::layer().with_writer(std::io::stderr))
-        .with(filter)
-        .try_init();
+    let (text_layer, json_layer) = if cli.json_logs {
+        (None, Some(fmt::layer().json().with_writer(std::io::stderr)))
+    } else {
+        (Some(fmt::layer().with_writer(std::io::stderr)), None)
+    };
+    let _ =
+        tracing_subscriber::registry().with(text_layer).with(json_layer).with(filter).try_init();
+    style::init(if cli.no_color { style::ColorMode::Never } else { cli.color });
+    crate::utils::output::set_quiet(cli.quiet);
 
     match cli.command {
-        Commands::Export(args) => export::run(*args),
-        Commands::Info(args) => info::run(args),
-        Commands::Index(args) => index::run(args),
-        Commands::Query(args) => query::run(args),
-        Commands::Codeintel(args) => codeintel::run(args),
-        Commands::Diff(args) => diff::run(args),
+        Commands::Export(args) => return export::run(*args),
+        Commands::Info(args) => info::run(args)?,
+        Commands::Rank(args) => rank::run(args)?,
+        Commands::Index(args) => index::run(args)?,
+        Commands::Query(args) => query::run(args)?,
+        Commands::Codeintel(args) => codeintel::run(args)?,
+        Commands::Diff(args) => diff::run(args)?,
+        Commands::GraphMerge(args) => graph_merge::run(args)?,
+        Commands::Select(args) => return select::run(args),
+        Commands::ValidateConfig(args) => validate_config::run(args)?,
+        Commands::Languages(args) => languages::run(args)?,
     }
+    Ok(ExitCode::SUCCESS)
 }
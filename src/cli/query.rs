@@ -1,12 +1,15 @@
 //! Query command implementation
 
+use anstream::{eprintln, print, println};
 use anyhow::{Context, Result};
 use clap::{Args, ValueEnum};
 use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::json;
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
 
+use super::style::{DIM, HEADING, PATH, WARNING};
 use crate::lsp::rust_analyzer;
 
 #[derive(Args)]
@@ -23,6 +26,10 @@ pub struct QueryArgs {
     #[arg(short = 'n', long, value_name = "COUNT", default_value_t = 20)]
     pub limit: usize,
 
+    /// Drop matches scoring below this (0.0-1.0) after all boosts
+    #[arg(long, value_name = "FLOAT")]
+    pub min_score: Option<f64>,
+
     /// Optional LSP backend for Rust symbol discovery
     #[arg(long, value_name = "MODE", default_value = "auto")]
     pub lsp_backend: LspBackend,
@@ -30,6 +37,24 @@ pub struct QueryArgs {
     /// Expand results into definition/callers/tests/docs sections
     #[arg(long)]
     pub expand: bool,
+
+    /// Print each match's full chunk content instead of a one-line summary
+    #[arg(long)]
+    pub full: bool,
+
+    /// Pull in the sibling chunks of each match's definition so whole functions are returned
+    #[arg(long)]
+    pub expand_definitions: bool,
+
+    /// Output format: text or json
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: QueryFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    Text,
+    Json,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -55,6 +80,12 @@ pub fn run(args: QueryArgs) -> Result<()> {
         );
     }
 
+    if let Some(min_score) = args.min_score {
+        if !(0.0..=1.0).contains(&min_score) {
+            anyhow::bail!("--min-score must be between 0.0 and 1.0, got {min_score}");
+        }
+    }
+
     let tokens = tokenize(&args.task);
     if tokens.is_empty() {
         anyhow::bail!("Task query is empty after tokenization");
@@ -136,7 +167,57 @@ pub fn run(args: QueryArgs) -> Result<()> {
         related_test_paths = outcome.related_test_paths;
     }
 
-    let mut rows: Vec<SearchRow> = scored.into_values().collect();
+    let mut rows = top_rows(scored, args.min_score, args.limit);
+
+    if rows.is_empty() && args.format == QueryFormat::Text {
+        match args.min_score {
+            Some(min_score) => println!(
+                "{WARNING}No matches scored {min_score} or higher. Try a lower --min-score.{WARNING:#}"
+            ),
+            None => println!("{WARNING}No matches found. Try broadening the query.{WARNING:#}"),
+        }
+        return Ok(());
+    }
+
+    if args.expand {
+        let expanded = expand_symbol_context(&conn, &tokens, &rows, args.limit)?;
+        match args.format {
+            QueryFormat::Json => {
+                let doc = expanded_json(&args.task, &expanded, args.full);
+                println!("{}", serde_json::to_string_pretty(&doc)?);
+            }
+            QueryFormat::Text => print_expanded_results(&args.task, &expanded),
+        }
+        return Ok(());
+    }
+
+    if args.expand_definitions {
+        rows = expand_definitions(&conn, &rows)?;
+    }
+
+    let related_tests: Vec<String> =
+        related_test_paths.into_iter().take(args.limit.max(1)).collect();
+    match args.format {
+        QueryFormat::Json => {
+            let doc = matches_json(&args.task, &rows, &related_tests, args.full);
+            println!("{}", serde_json::to_string_pretty(&doc)?);
+        }
+        QueryFormat::Text => {
+            print!("{}", render_matches(&args.task, &rows, &related_tests, args.full))
+        }
+    }
+
+    Ok(())
+}
+
+/// Best-scoring rows first, without those below `min_score`, capped at `limit`.
+fn top_rows(
+    scored: HashMap<String, SearchRow>,
+    min_score: Option<f64>,
+    limit: usize,
+) -> Vec<SearchRow> {
+    let mut rows: Vec<SearchRow> =
+        scored.into_values().filter(|row| min_score.is_none_or(|min| row.score >= min)).collect();
     rows.sort_by(|a, b| {
         b.score
             .partial_cmp(&a.score)
@@ -145,32 +226,151 @@ pub fn run(args: QueryArgs) -> Result<()> {
             .then_with(|| a.start_line.cmp(&b.start_line))
             .then_with(|| a.chunk_id.cmp(&b.chunk_id))
     });
-    rows.truncate(args.limit.max(1));
+    rows.truncate(limit.max(1));
+    rows
+}
 
-    if rows.is_empty() {
-        println!("No matches found. Try broadening the query.");
-        return Ok(());
-    }
+/// Replace each match with every chunk of the definition it belongs to.
+///
+/// Large definitions are split into several chunks that all carry the same `def:` symbol.
+/// Siblings come from the `symbols` table and are limited to the contiguous run of chunks
+/// around the match, so a same-named definition elsewhere in the file is not pulled in.
+/// Siblings inherit the match's score; a chunk reached from several matches is listed once.
+fn expand_definitions(conn: &Connection, rows: &[SearchRow]) -> Result<Vec<SearchRow>> {
+    let mut symbol_stmt =
+        conn.prepare("SELECT DISTINCT symbol FROM symbols WHERE chunk_id = ?1 AND kind = 'def'")?;
+    let mut sibling_stmt = conn.prepare(
+        "
+        SELECT DISTINCT c.id, c.file_path, c.start_line, c.end_line, c.content
+        FROM symbols s
+        JOIN chunks c ON c.id = s.chunk_id
+        WHERE s.symbol = ?1 AND s.kind = 'def' AND c.file_path = ?2
+        ",
+    )?;
 
-    if args.expand {
-        let expanded = expand_symbol_context(&conn, &tokens, &rows, args.limit)?;
-        print_expanded_results(&args.task, &expanded);
-        return Ok(());
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    for row in rows {
+        let symbols = symbol_stmt
+            .query_map(params![row.chunk_id], |r| r.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut siblings: HashMap<String, SearchRow> = HashMap::new();
+        for symbol in &symbols {
+            let fetched = sibling_stmt.query_map(params![symbol, row.path], |r| {
+                Ok(SearchRow {
+                    chunk_id: r.get(0)?,
+                    path: r.get(1)?,
+                    start_line: r.get::<_, i64>(2)? as usize,
+                    end_line: r.get::<_, i64>(3)? as usize,
+                    content: r.get(4)?,
+                    score: row.score,
+                })
+            })?;
+            for sibling in fetched {
+                let sibling = sibling?;
+                siblings.insert(sibling.chunk_id.clone(), sibling);
+            }
+        }
+
+        let mut siblings: Vec<SearchRow> = siblings.into_values().collect();
+        siblings.sort_by_key(|s| (s.start_line, s.end_line));
+        let Some(pos) = siblings.iter().position(|s| s.chunk_id == row.chunk_id) else {
+            if seen.insert(row.chunk_id.clone()) {
+                out.push(row.clone());
+            }
+            continue;
+        };
+
+        let mut first = pos;
+        while first > 0 && siblings[first].start_line <= siblings[first - 1].end_line + 1 {
+            first -= 1;
+        }
+        let mut last = pos;
+        while last + 1 < siblings.len()
+            && siblings[last + 1].start_line <= siblings[last].end_line + 1
+        {
+            last += 1;
+        }
+
+        for sibling in siblings.drain(first..=last) {
+            if seen.insert(sibling.chunk_id.clone()) {
+                out.push(sibling);
+            }
+        }
     }
+    Ok(out)
+}
 
-    println!("Top matches for task: {}", args.task);
+fn render_matches(task: &str, rows: &[SearchRow], related_tests: &[String], full: bool) -> String {
+    let mut out = format!("{HEADING}Top matches for task: {task}{HEADING:#}\n");
     for row in rows {
-        println!("- {}:{}-{} (score {:.3})", row.path, row.start_line, row.end_line, row.score);
-        println!("  {}", summarize(&row.content));
+        if full {
+            out.push_str(&format!(
+                "\n{HEADING}== {}:{}-{} (score {:.3}) =={HEADING:#}\n",
+                row.path, row.start_line, row.end_line, row.score
+            ));
+            out.push_str(row.content.trim_end());
+            out.push('\n');
+        } else {
+            out.push_str(&format!(
+                "- {PATH}{}:{}-{}{PATH:#} {DIM}(score {:.3}){DIM:#}\n",
+                row.path, row.start_line, row.end_line, row.score
+            ));
+            out.push_str(&format!("  {}\n", summarize(&row.content)));
+        }
     }
-    if !related_test_paths.is_empty() {
-        println!("Related tests:");
-        for path in related_test_paths.into_iter().take(args.limit.max(1)) {
-            println!("- {}", path);
+    if !related_tests.is_empty() {
+        out.push_str(if full { "\n" } else { "" });
+        out.push_str(&format!("{HEADING}Related tests:{HEADING:#}\n"));
+        for path in related_tests {
+            out.push_str(&format!("- {path}\n"));
         }
     }
+    out
+}
 
-    Ok(())
+fn matches_json(
+    task: &str,
+    rows: &[SearchRow],
+    related_tests: &[String],
+    full: bool,
+) -> serde_json::Value {
+    json!({
+        "task": task,
+        "matches": rows_json(rows, full),
+        "related_tests": related_tests,
+    })
+}
+
+/// `--expand` sections as JSON, each listing matches in the same shape as `matches`.
+fn expanded_json(task: &str, expanded: &ExpandedContext, full: bool) -> serde_json::Value {
+    json!({
+        "task": task,
+        "definitions": rows_json(&expanded.definitions, full),
+        "callers": rows_json(&expanded.callers, full),
+        "tests": rows_json(&expanded.tests, full),
+        "config_docs": rows_json(&expanded.config_docs, full),
+    })
+}
+
+fn rows_json(rows: &[SearchRow], full: bool) -> Vec<serde_json::Value> {
+    rows.iter()
+        .map(|row| {
+            let mut value = json!({
+                "chunk_id": row.chunk_id,
+                "path": row.path,
+                "start_line": row.start_line,
+                "end_line": row.end_line,
+                "score": row.score,
+                "summary": summarize(&row.content),
+            });
+            if full {
+                value["content"] = json!(row.content);
+            }
+            value
+        })
+        .collect()
 }
 
 fn apply_lsp_boosts(
@@ -209,7 +409,7 @@ fn apply_lsp_boosts(
     let analysis = match rust_analyzer::analyze_workspace_symbols(&root, task, limit.max(1)) {
         Ok(analysis) => analysis,
         Err(err) => {
-            eprintln!("warning: rust-analyzer enrichment unavailable: {err}");
+            eprintln!("{WARNING}warning:{WARNING:#} rust-analyzer enrichment unavailable: {err}");
             return Ok(LspBoostOutcome::default());
         }
     };
@@ -232,7 +432,7 @@ fn apply_lsp_boosts(
         }
     }
 
-    for path in symbol_paths.into_iter().chain(reference_paths.into_iter()) {
+    for path in symbol_paths.into_iter().chain(reference_paths) {
         if scored.values().any(|row| row.path == path) {
             continue;
         }
@@ -442,7 +642,7 @@ fn expand_symbol_context(
 }
 
 fn print_expanded_results(task: &str, expanded: &ExpandedContext) {
-    println!("Expanded matches for task: {task}");
+    println!("{HEADING}Expanded matches for task: {task}{HEADING:#}");
     print_section("Definition", &expanded.definitions);
     print_section("Top Callers", &expanded.callers);
     print_section("Related Tests", &expanded.tests);
@@ -450,14 +650,17 @@ fn print_expanded_results(task: &str, expanded: &ExpandedContext) {
 }
 
 fn print_section(title: &str, rows: &[SearchRow]) {
-    println!("\n== {title} ==");
+    println!("\n{HEADING}== {title} =={HEADING:#}");
     if rows.is_empty() {
         println!("- none");
         return;
     }
 
     for row in rows {
-        println!("- {}:{}-{} (score {:.3})", row.path, row.start_line, row.end_line, row.score);
+        println!(
+            "- {PATH}{}:{}-{}{PATH:#} {DIM}(score {:.3}){DIM:#}",
+            row.path, row.start_line, row.end_line, row.score
+        );
         println!("  {}", summarize(&row.content));
     }
 }
@@ -636,10 +839,13 @@ fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
 
 #[cfg(test)]
 mod tests {
-    use super::{expand_symbol_context, symbol_query_terms, SearchRow};
+    use super::{
+        expand_symbol_context, expanded_json, matches_json, render_matches, symbol_query_terms,
+        top_rows, SearchRow,
+    };
     use crate::lsp::rust_analyzer::WorkspaceSymbol;
     use rusqlite::Connection;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
 
     #[test]
     fn symbol_query_terms_include_symbol_and_file_tokens() {
@@ -658,6 +864,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn min_score_drops_weak_matches() {
+        let row = |id: &str, score: f64| SearchRow {
+            chunk_id: id.to_string(),
+            path: format!("src/{id}.rs"),
+            start_line: 1,
+            end_line: 5,
+            content: format!("fn {id}() {{}}"),
+            score,
+        };
+        let scored = || {
+            HashMap::from([
+                ("strong".to_string(), row("strong", 0.8)),
+                ("weak".to_string(), row("weak", 0.3)),
+            ])
+        };
+
+        let ids = |rows: Vec<SearchRow>| rows.into_iter().map(|r| r.chunk_id).collect::<Vec<_>>();
+        assert_eq!(ids(top_rows(scored(), None, 20)), vec!["strong", "weak"]);
+        assert_eq!(ids(top_rows(scored(), Some(0.5), 20)), vec!["strong"]);
+    }
+
     #[test]
     fn expand_context_uses_definition_and_edges() {
         let conn = Connection::open_in_memory().expect("sqlite in-memory db");
@@ -752,5 +980,32 @@ mod tests {
         assert_eq!(expanded.definitions[0].chunk_id, "def1");
         assert_eq!(expanded.callers.len(), 1);
         assert_eq!(expanded.callers[0].chunk_id, "call1");
+
+        let doc = expanded_json("refresh token", &expanded, false);
+        assert_eq!(doc["definitions"][0]["chunk_id"], "def1");
+        assert_eq!(doc["callers"][0]["path"], "src/handler.rs");
+        assert!(doc["definitions"][0].get("content").is_none());
+    }
+
+    #[test]
+    fn full_output_includes_complete_chunk_content() {
+        let rows = vec![SearchRow {
+            chunk_id: "c1".to_string(),
+            path: "src/auth.rs".to_string(),
+            start_line: 3,
+            end_line: 5,
+            content: "fn refresh_token() {\n    rotate();\n}\n".to_string(),
+            score: 0.9,
+        }];
+
+        let full = render_matches("refresh", &rows, &[], true);
+        assert!(full.contains("== src/auth.rs:3-5 (score 0.900) =="));
+        assert!(full.contains("fn refresh_token() {\n    rotate();\n}\n"));
+        let brief = render_matches("refresh", &rows, &[], false);
+        assert!(!brief.contains("rotate();"));
+
+        let doc = matches_json("refresh", &rows, &[], true);
+        assert_eq!(doc["matches"][0]["content"], rows[0].content.as_str());
+        assert!(matches_json("refresh", &rows, &[], false)["matches"][0].get("content").is_none());
     }
 }
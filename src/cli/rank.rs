@@ -0,0 +1,111 @@
+//! Rank command: scan and rank files without chunking or rendering.
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use serde_json::json;
+use std::path::PathBuf;
+
+use super::utils::parse_csv;
+use crate::config::{load_config_files, merge_cli_with_config, CliOverrides};
+use crate::domain::FileInfo;
+use crate::rank::{apply_recency_weight, rank_files_with_manifest};
+use crate::scan::scanner::FileScanner;
+use crate::utils::EncodingOverrides;
+
+#[derive(Args)]
+pub struct RankArgs {
+    /// Local directory path to rank
+    #[arg(short, long, value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Path to config file (repo-context.toml or .r2p.yml); repeat to merge in order
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Vec<PathBuf>,
+
+    /// Include only these extensions (comma-separated)
+    #[arg(short = 'i', long, value_name = "EXTS")]
+    pub include_ext: Option<String>,
+
+    /// Exclude paths matching these globs (comma-separated)
+    #[arg(short = 'e', long, value_name = "GLOBS")]
+    pub exclude_glob: Option<String>,
+
+    /// Only list the top N files
+    #[arg(short = 'n', long, value_name = "COUNT")]
+    pub limit: Option<usize>,
+
+    /// Output format: text or json
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: RankFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RankFormat {
+    Text,
+    Json,
+}
+
+pub fn run(args: RankArgs) -> Result<()> {
+    let root = args.path.canonicalize()?;
+    if !root.is_dir() {
+        anyhow::bail!("Path is not a directory: {}", root.display());
+    }
+
+    let file_config = load_config_files(&root, &args.config, None)?;
+    let cli_overrides = CliOverrides {
+        include_extensions: parse_csv(&args.include_ext).map(|v| v.into_iter().collect()),
+        exclude_globs: parse_csv(&args.exclude_glob).map(|v| v.into_iter().collect()),
+        ..CliOverrides::default()
+    };
+    let merged = merge_cli_with_config(file_config, cli_overrides);
+
+    let mut scanner = FileScanner::new(root.clone())
+        .max_file_bytes(merged.max_file_bytes)
+        .respect_gitignore(merged.respect_gitignore)
+        .follow_symlinks(merged.follow_symlinks)
+        .skip_minified(merged.skip_minified)
+        .lfs_resolve(merged.lfs_resolve)
+        .editorconfig_hints(merged.editorconfig_hints)
+        .include_extensions(merged.include_extensions.iter().cloned().collect())
+        .exclude_globs(merged.exclude_globs.iter().cloned().collect())
+        .force_text_globs(merged.force_text_globs.clone());
+    let scanned_files = scanner.scan()?;
+    let (mut ranked_files, _) = rank_files_with_manifest(
+        &root,
+        scanned_files,
+        merged.ranking_weights.clone(),
+        &merged.fixture_dirs,
+        &merged.language_weight_multipliers,
+        &EncodingOverrides::from_config(&merged)?,
+    )?;
+    apply_recency_weight(&mut ranked_files, merged.recency_weight);
+    ranked_files.truncate(args.limit.unwrap_or(usize::MAX));
+
+    match args.format {
+        RankFormat::Json => {
+            let doc: Vec<_> = ranked_files.iter().map(ranked_file_json).collect();
+            println!("{}", serde_json::to_string_pretty(&doc)?);
+        }
+        RankFormat::Text => {
+            for file in &ranked_files {
+                let tags: Vec<&str> = file.tags.iter().map(String::as_str).collect();
+                let tags =
+                    if tags.is_empty() { String::new() } else { format!(" [{}]", tags.join(", ")) };
+                println!("{:.3}  {}{}", file.priority, file.relative_path, tags);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Files are not read here, so the token estimate comes from the size (4 bytes per token).
+fn ranked_file_json(file: &FileInfo) -> serde_json::Value {
+    json!({
+        "path": file.relative_path,
+        "priority": (file.priority * 1000.0).round() / 1000.0,
+        "tags": file.tags,
+        "language": file.language,
+        "token_estimate": file.size_bytes / 4,
+    })
+}
@@ -0,0 +1,235 @@
+//! Interactive file selection before export.
+
+use anyhow::Result;
+use clap::Args;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::export::{self, ExportArgs};
+use super::selection::{read_selection_file, write_selection_file, SelectionState};
+use super::utils::parse_csv;
+use crate::config::{load_config, merge_cli_with_config, CliOverrides};
+use crate::rank::{apply_recency_weight, rank_files_with_manifest};
+use crate::scan::scanner::FileScanner;
+use crate::utils::output::println;
+use crate::utils::EncodingOverrides;
+
+#[derive(Args)]
+pub struct SelectArgs {
+    /// Local directory path to select files from
+    #[arg(short, long, value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+
+    /// Path to config file (repo-context.toml or .r2p.yml)
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Include only these extensions (comma-separated)
+    #[arg(short = 'i', long, value_name = "EXTS")]
+    pub include_ext: Option<String>,
+
+    /// Exclude paths matching these globs (comma-separated)
+    #[arg(short = 'e', long, value_name = "GLOBS")]
+    pub exclude_glob: Option<String>,
+
+    /// Token budget used for preselection and passed to export
+    #[arg(short = 't', long, value_name = "TOKENS")]
+    pub max_tokens: Option<usize>,
+
+    /// Where to write the selection, relative to --path (reused as the starting state when
+    /// present)
+    #[arg(long, value_name = "FILE", default_value = ".repo-context/selection.txt")]
+    pub selection_file: PathBuf,
+
+    /// Output format passed to export
+    #[arg(short = 'm', long, value_name = "MODE")]
+    pub mode: Option<String>,
+
+    /// Directory for output files
+    #[arg(short = 'o', long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Only write the selection file; do not run export
+    #[arg(long)]
+    pub no_export: bool,
+
+    /// Passed to export: let always-include files exceed the token budget
+    #[arg(long)]
+    pub allow_over_budget: bool,
+
+    /// Passed to export: skip guided mode and use quick export defaults
+    #[arg(long)]
+    pub quick: bool,
+}
+
+pub fn run(args: SelectArgs) -> Result<ExitCode> {
+    if !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+        anyhow::bail!(
+            "select requires an interactive terminal; use `export --selection <FILE>` instead"
+        );
+    }
+
+    let root = args.path.canonicalize()?;
+    if !root.is_dir() {
+        anyhow::bail!("Path is not a directory: {}", root.display());
+    }
+
+    let file_config = load_config(&root, args.config.as_deref())?;
+    let cli_overrides = CliOverrides {
+        include_extensions: parse_csv(&args.include_ext).map(|v| v.into_iter().collect()),
+        exclude_globs: parse_csv(&args.exclude_glob).map(|v| v.into_iter().collect()),
+        max_tokens: args.max_tokens,
+        ..CliOverrides::default()
+    };
+    let merged = merge_cli_with_config(file_config, cli_overrides);
+
+    let mut scanner = FileScanner::new(root.clone())
+        .max_file_bytes(merged.max_file_bytes)
+        .respect_gitignore(merged.respect_gitignore)
+        .follow_symlinks(merged.follow_symlinks)
+        .skip_minified(merged.skip_minified)
+        .lfs_resolve(merged.lfs_resolve)
+        .editorconfig_hints(merged.editorconfig_hints)
+        .include_extensions(merged.include_extensions.iter().cloned().collect())
+        .exclude_globs(merged.exclude_globs.iter().cloned().collect())
+        .force_text_globs(merged.force_text_globs.clone());
+    let scanned_files = scanner.scan()?;
+    let (mut ranked_files, _) = rank_files_with_manifest(
+        &root,
+        scanned_files,
+        merged.ranking_weights.clone(),
+        &merged.fixture_dirs,
+        &merged.language_weight_multipliers,
+        &EncodingOverrides::from_config(&merged)?,
+    )?;
+    apply_recency_weight(&mut ranked_files, merged.recency_weight);
+    if ranked_files.is_empty() {
+        anyhow::bail!("No files to select in {}", root.display());
+    }
+
+    let selection_file = root.join(&args.selection_file);
+    let mut state = SelectionState::new(&ranked_files, merged.max_tokens);
+    if selection_file.exists() {
+        let previous = read_selection_file(&selection_file, &root)?;
+        state = state.with_selected_paths(&previous);
+    }
+
+    let mut terminal = ratatui::init();
+    let confirmed = run_event_loop(&mut terminal, &mut state);
+    ratatui::restore();
+    if !confirmed? {
+        println!("Selection cancelled; nothing written.");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let selected = state.selected_paths();
+    if selected.is_empty() {
+        anyhow::bail!("No files selected");
+    }
+    write_selection_file(&selection_file, &selected)?;
+    println!(
+        "Wrote {} path(s) (~{} tokens) to {}",
+        selected.len(),
+        state.selected_tokens(),
+        selection_file.display()
+    );
+
+    if args.no_export {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    export::run(ExportArgs {
+        path: Some(root),
+        selection: Some(selection_file),
+        config: args.config.into_iter().collect(),
+        include_ext: args.include_ext,
+        exclude_glob: args.exclude_glob,
+        max_tokens: args.max_tokens,
+        allow_over_budget: args.allow_over_budget,
+        mode: args.mode,
+        output_dir: args.output_dir,
+        quick: args.quick,
+        yes: true,
+        ..ExportArgs::default()
+    })
+}
+
+/// Returns `Ok(true)` when the user confirms the selection, `Ok(false)` when cancelled.
+fn run_event_loop(terminal: &mut DefaultTerminal, state: &mut SelectionState) -> Result<bool> {
+    loop {
+        terminal.draw(|frame| draw(frame, state))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => state.move_up(),
+            KeyCode::Down | KeyCode::Char('j') => state.move_down(),
+            KeyCode::Char(' ') => state.toggle_current(),
+            KeyCode::Char('a') => state.set_all(true),
+            KeyCode::Char('n') => state.set_all(false),
+            KeyCode::Enter => return Ok(true),
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(false),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, state: &SelectionState) {
+    let [list_area, status_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+
+    let items: Vec<ListItem> = state
+        .items()
+        .iter()
+        .map(|item| {
+            let mark = if item.selected { "[x]" } else { "[ ]" };
+            ListItem::new(format!(
+                "{mark} {:>3}%  {:>7} tok  {}",
+                (item.priority * 100.0).round() as u64,
+                item.tokens,
+                item.path
+            ))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Select files — space: toggle, a/n: all/none, enter: export, q: cancel "),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default().with_selected(Some(state.cursor()));
+    frame.render_stateful_widget(list, list_area, &mut list_state);
+
+    let budget = match (state.budget(), state.remaining_budget()) {
+        (Some(limit), Some(remaining)) => format!(" | budget {limit} | remaining {remaining}"),
+        _ => String::new(),
+    };
+    let status_style = if state.over_budget() {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+    let status = Paragraph::new(Line::styled(
+        format!(
+            "{} of {} files | ~{} tokens{}",
+            state.selected_count(),
+            state.items().len(),
+            state.selected_tokens(),
+            budget
+        ),
+        status_style,
+    ))
+    .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(status, status_area);
+}
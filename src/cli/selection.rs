@@ -0,0 +1,244 @@
+//! Selection state for the interactive `select` command.
+//!
+//! Kept free of terminal I/O so toggling and budget accounting can be unit-tested.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::domain::FileInfo;
+use crate::utils::normalize_path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionItem {
+    pub path: String,
+    pub priority: f64,
+    pub tokens: usize,
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectionState {
+    items: Vec<SelectionItem>,
+    cursor: usize,
+    budget: Option<usize>,
+}
+
+impl SelectionState {
+    /// Build state from ranked files. With a budget, files are preselected in rank order
+    /// until the budget is reached; without one, everything starts selected.
+    pub fn new(files: &[FileInfo], budget: Option<usize>) -> Self {
+        let mut used = 0usize;
+        let items = files
+            .iter()
+            .map(|file| {
+                let tokens = estimate_file_tokens(file);
+                let selected = match budget {
+                    Some(limit) if used + tokens > limit => false,
+                    _ => {
+                        used += tokens;
+                        true
+                    }
+                };
+                SelectionItem {
+                    path: file.relative_path.clone(),
+                    priority: file.priority,
+                    tokens,
+                    selected,
+                }
+            })
+            .collect();
+        Self { items, cursor: 0, budget }
+    }
+
+    /// Mark exactly the given paths as selected (used to resume from a selection file).
+    pub fn with_selected_paths(mut self, paths: &[String]) -> Self {
+        let wanted: HashSet<&str> = paths.iter().map(String::as_str).collect();
+        for item in &mut self.items {
+            item.selected = wanted.contains(item.path.as_str());
+        }
+        self
+    }
+
+    pub fn items(&self) -> &[SelectionItem] {
+        &self.items
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn budget(&self) -> Option<usize> {
+        self.budget
+    }
+
+    pub fn move_up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.cursor + 1 < self.items.len() {
+            self.cursor += 1;
+        }
+    }
+
+    pub fn toggle(&mut self, idx: usize) {
+        if let Some(item) = self.items.get_mut(idx) {
+            item.selected = !item.selected;
+        }
+    }
+
+    pub fn toggle_current(&mut self) {
+        self.toggle(self.cursor);
+    }
+
+    pub fn set_all(&mut self, selected: bool) {
+        for item in &mut self.items {
+            item.selected = selected;
+        }
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.items.iter().filter(|item| item.selected).count()
+    }
+
+    pub fn selected_tokens(&self) -> usize {
+        self.items.iter().filter(|item| item.selected).map(|item| item.tokens).sum()
+    }
+
+    /// Tokens left under the budget; `None` when no budget is set.
+    pub fn remaining_budget(&self) -> Option<i64> {
+        self.budget.map(|limit| limit as i64 - self.selected_tokens() as i64)
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.remaining_budget().is_some_and(|remaining| remaining < 0)
+    }
+
+    /// Selected paths in rank order.
+    pub fn selected_paths(&self) -> Vec<String> {
+        self.items.iter().filter(|item| item.selected).map(|item| item.path.clone()).collect()
+    }
+}
+
+fn estimate_file_tokens(file: &FileInfo) -> usize {
+    if file.token_estimate > 0 {
+        file.token_estimate
+    } else {
+        (file.size_bytes / 4) as usize
+    }
+}
+
+/// Read a selection file: one path per line, `#` starts a comment, in file order.
+///
+/// Relative paths are taken relative to `root`; absolute ones are made relative to it and
+/// dropped when they lie outside.
+pub fn read_selection_file(path: &Path, root: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read selection file {}", path.display()))?;
+    let mut seen = HashSet::new();
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| selection_entry(line, root))
+        .filter(|line| seen.insert(line.clone()))
+        .collect())
+}
+
+fn selection_entry(line: &str, root: &Path) -> Option<String> {
+    let entry = Path::new(line);
+    if entry.is_absolute() {
+        let entry = entry.canonicalize().unwrap_or_else(|_| entry.to_path_buf());
+        let relative = entry.strip_prefix(root).ok()?;
+        return Some(normalize_path(&relative.to_string_lossy()));
+    }
+    Some(normalize_path(line).trim_start_matches("./").to_string())
+}
+
+pub fn write_selection_file(path: &Path, paths: &[String]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = String::from("# repo-context selection (one path per line, in order)\n");
+    for p in paths {
+        content.push_str(p);
+        content.push('\n');
+    }
+    fs::write(path, content)
+        .with_context(|| format!("failed to write selection file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_selection_file, write_selection_file, SelectionState};
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+
+    fn mk_file(path: &str, priority: f64, size_bytes: u64) -> FileInfo {
+        FileInfo { size_bytes, priority, ..file_info(path) }
+    }
+
+    #[test]
+    fn budget_preselects_files_in_rank_order() {
+        let files = vec![
+            mk_file("README.md", 1.0, 400),
+            mk_file("src/main.rs", 0.85, 800),
+            mk_file("src/lib.rs", 0.75, 200),
+        ];
+        let state = SelectionState::new(&files, Some(160));
+
+        assert_eq!(state.selected_paths(), vec!["README.md", "src/lib.rs"]);
+        assert_eq!(state.selected_tokens(), 150);
+        assert_eq!(state.remaining_budget(), Some(10));
+        assert!(!state.over_budget());
+    }
+
+    #[test]
+    fn toggling_updates_tokens_and_budget() {
+        let files = vec![mk_file("a.rs", 0.9, 400), mk_file("b.rs", 0.8, 400)];
+        let mut state = SelectionState::new(&files, Some(150));
+        assert_eq!(state.selected_count(), 1);
+
+        state.move_down();
+        state.toggle_current();
+        assert_eq!(state.selected_tokens(), 200);
+        assert!(state.over_budget());
+
+        state.move_up();
+        state.toggle_current();
+        assert_eq!(state.selected_paths(), vec!["b.rs"]);
+        assert_eq!(state.remaining_budget(), Some(50));
+
+        state.set_all(false);
+        assert_eq!(state.selected_tokens(), 0);
+    }
+
+    #[test]
+    fn selection_file_round_trips() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let path = tmp.path().join("nested/selection.txt");
+        let paths = vec!["src/lib.rs".to_string(), "README.md".to_string()];
+
+        write_selection_file(&path, &paths).expect("write selection");
+        assert_eq!(read_selection_file(&path, tmp.path()).expect("read selection"), paths);
+    }
+
+    #[test]
+    fn selection_file_keeps_order_and_resolves_absolute_paths_against_root() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let root = tmp.path().canonicalize().expect("canonical root");
+        let path = root.join("selection.txt");
+        let content = format!(
+            "# picked\n./src/z.rs\n{}\nsrc\\a.rs\n/elsewhere/b.rs\nsrc/z.rs\n",
+            root.join("README.md").display()
+        );
+        std::fs::write(&path, content).expect("write selection");
+
+        assert_eq!(
+            read_selection_file(&path, &root).expect("read selection"),
+            vec!["src/z.rs", "README.md", "src/a.rs"]
+        );
+    }
+}
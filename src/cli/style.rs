@@ -0,0 +1,32 @@
+//! Terminal colors for command summaries (`--color`).
+//!
+//! Output goes through `anstream`, which strips these styles when color is off, so
+//! callers can style unconditionally.
+
+use anstream::ColorChoice;
+use anstyle::{AnsiColor, Color, Style};
+use clap::ValueEnum;
+
+pub const HEADING: Style = Style::new().bold();
+pub const SUCCESS: Style = Style::new().bold().fg_color(Some(Color::Ansi(AnsiColor::Green)));
+pub const WARNING: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow)));
+pub const PATH: Style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Cyan)));
+pub const DIM: Style = Style::new().dimmed();
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset
+    Auto,
+    Always,
+    Never,
+}
+
+/// Apply the color mode to all later `anstream` output.
+pub fn init(mode: ColorMode) {
+    let choice = match mode {
+        ColorMode::Auto => ColorChoice::Auto,
+        ColorMode::Always => ColorChoice::Always,
+        ColorMode::Never => ColorChoice::Never,
+    };
+    choice.write_global();
+}
@@ -1,5 +1,8 @@
 //! Shared CLI utilities.
 
+use crate::domain::FileInfo;
+use crate::scan::dominance::find_dominant_directory;
+
 /// Parse a comma-separated string into a `Vec<String>`, trimming whitespace and
 /// discarding empty segments.  Returns `None` when `value` is `None`.
 pub fn parse_csv(value: &Option<String>) -> Option<Vec<String>> {
@@ -22,3 +25,18 @@ pub fn parse_csv_multi(values: &[String]) -> Vec<String> {
         .map(|part| part.to_string())
         .collect()
 }
+
+/// Warn on stderr when one directory dominates the scan, e.g. a vendored tree that slipped
+/// past the excludes. Advisory only: nothing is dropped.
+pub fn warn_dominant_directory(files: &[FileInfo], max_fraction: f64) {
+    if let Some(dir) = find_dominant_directory(files, max_fraction) {
+        eprintln!(
+            "warning: {}/ holds {} of {} scanned files ({:.0}% of bytes); if it is vendored or generated, exclude it (e.g. --exclude-glob '{}/**')",
+            dir.path,
+            dir.files,
+            files.len(),
+            dir.byte_share * 100.0,
+            dir.path
+        );
+    }
+}
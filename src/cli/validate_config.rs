@@ -0,0 +1,68 @@
+//! Validate-config command implementation
+
+use anyhow::Result;
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::config::load_config;
+use crate::config::loader::{discover_config, REPO_CONFIG_PATH};
+use crate::config::validate::validate_config;
+use crate::utils::output::println;
+
+#[derive(Args)]
+pub struct ValidateConfigArgs {
+    /// Config file to validate (default: the file `export` would discover)
+    #[arg(short = 'c', long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Repository root used for config discovery
+    #[arg(short, long, value_name = "PATH", default_value = ".")]
+    pub path: PathBuf,
+}
+
+pub fn run(args: ValidateConfigArgs) -> Result<()> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let repo_config = args.path.join(REPO_CONFIG_PATH);
+    if repo_config.is_file() {
+        files.push(repo_config);
+    }
+    if let Some(file) = args.config.clone().or_else(|| discover_config(&args.path)) {
+        files.push(file);
+    }
+    if files.is_empty() {
+        println!("No config file found under {}; defaults apply.", args.path.display());
+        return Ok(());
+    }
+
+    let mut failed = 0usize;
+    for file in &files {
+        if !check_file(&args.path, file) {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} config file(s) invalid", files.len());
+    }
+    Ok(())
+}
+
+/// Load and validate one file, printing its result; true when it is valid.
+fn check_file(root: &Path, file: &Path) -> bool {
+    let config = match load_config(root, Some(file)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("✗ {}: {}", file.display(), format!("{err:#}").trim_end());
+            return false;
+        }
+    };
+    let issues = validate_config(&config);
+    if issues.is_empty() {
+        println!("✓ {}: valid", file.display());
+        return true;
+    }
+    eprintln!("✗ {}: {} invalid value(s)", file.display(), issues.len());
+    for issue in issues {
+        eprintln!("  {}: {}", issue.field, issue.message);
+    }
+    false
+}
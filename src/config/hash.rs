@@ -0,0 +1,116 @@
+//! Config hash recorded by `index` and checked by `export --from-index`.
+
+use serde_json::json;
+use std::collections::BTreeSet;
+
+use crate::domain::{
+    default_definition_kinds, default_max_chunk_lines, default_tree_sitter_max_bytes, Config,
+};
+use crate::utils::stable_json_hash;
+
+/// Hash of the config keys that shape an index's files and chunks.
+pub fn config_hash(config: &Config) -> String {
+    // Sets are sorted: `HashSet` iteration order differs between processes, so hashing
+    // them as-is would leave every index stale.
+    let mut payload = json!({
+        "include_extensions": config.include_extensions.iter().collect::<BTreeSet<_>>(),
+        "exclude_globs": config.exclude_globs.iter().collect::<BTreeSet<_>>(),
+        "max_file_bytes": config.max_file_bytes,
+        "max_total_bytes": config.max_total_bytes,
+        "respect_gitignore": config.respect_gitignore,
+        "follow_symlinks": config.follow_symlinks,
+        "skip_minified": config.skip_minified,
+        "chunk_tokens": config.chunk_tokens,
+        "chunk_overlap": config.chunk_overlap,
+        "min_chunk_tokens": config.min_chunk_tokens,
+    });
+    // Later keys are only hashed when changed from their default, so indexes built before
+    // they existed stay fresh.
+    let optional = [
+        ("tag_queries", json!(config.tag_queries), config.tag_queries.is_empty()),
+        (
+            "encoding_overrides",
+            json!(config.encoding_overrides),
+            config.encoding_overrides.is_empty(),
+        ),
+        ("force_text_globs", json!(config.force_text_globs), config.force_text_globs.is_empty()),
+        (
+            "definition_kinds",
+            json!(config.definition_kinds),
+            config.definition_kinds == default_definition_kinds(),
+        ),
+        (
+            "max_chunk_lines",
+            json!(config.max_chunk_lines),
+            config.max_chunk_lines == default_max_chunk_lines(),
+        ),
+        (
+            "tree_sitter_max_bytes",
+            json!(config.tree_sitter_max_bytes),
+            config.tree_sitter_max_bytes == default_tree_sitter_max_bytes(),
+        ),
+        (
+            "tree_sitter_timeout_ms",
+            json!(config.tree_sitter_timeout_ms),
+            config.tree_sitter_timeout_ms == 0,
+        ),
+        (
+            "min_chunk_tokens_code",
+            json!(config.min_chunk_tokens_code),
+            config.min_chunk_tokens_code.is_none(),
+        ),
+        (
+            "min_chunk_tokens_doc",
+            json!(config.min_chunk_tokens_doc),
+            config.min_chunk_tokens_doc.is_none(),
+        ),
+        (
+            "min_chunk_tokens_config",
+            json!(config.min_chunk_tokens_config),
+            config.min_chunk_tokens_config.is_none(),
+        ),
+    ];
+    for (key, value, is_default) in optional {
+        if !is_default {
+            payload[key] = value;
+        }
+    }
+    stable_json_hash(&payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::config_hash;
+    use crate::domain::{Config, TagQuery};
+    use std::collections::HashSet;
+
+    #[test]
+    fn config_hash_ignores_set_order_and_tracks_optional_keys() {
+        let base = Config::default();
+        let mut reordered = Config::default();
+        let mut extensions: Vec<String> = base.include_extensions.iter().cloned().collect();
+        extensions.reverse();
+        reordered.include_extensions = extensions.into_iter().collect::<HashSet<_>>();
+        assert_eq!(config_hash(&reordered), config_hash(&base));
+
+        let changes: [fn(&mut Config); 6] = [
+            |c| {
+                c.tag_queries = vec![TagQuery {
+                    language: "rust".to_string(),
+                    query: "(function_item name: (identifier) @name)".to_string(),
+                    tag_prefix: "fn".to_string(),
+                }]
+            },
+            |c| c.encoding_overrides = vec![("*.txt".to_string(), "latin1".to_string())],
+            |c| c.max_chunk_lines += 1,
+            |c| c.tree_sitter_max_bytes = 1000,
+            |c| c.tree_sitter_timeout_ms = 500,
+            |c| c.min_chunk_tokens_doc = Some(10),
+        ];
+        for change in changes {
+            let mut changed = Config::default();
+            change(&mut changed);
+            assert_ne!(config_hash(&changed), config_hash(&base));
+        }
+    }
+}
@@ -1,12 +1,31 @@
 //! Config file loading
 
+use super::profile::Profile;
 use crate::domain::Config;
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+/// Repository-committed defaults, relative to the repository root.
+pub const REPO_CONFIG_PATH: &str = ".repo-to-prompt/config.toml";
+
+/// Load config for `repo_root`.
+///
+/// A repository-committed `.repo-to-prompt/config.toml` forms the lowest layer; keys set in
+/// the discovered (or explicit) config file override it key by key.
 pub fn load_config(repo_root: &Path, config_path: Option<&Path>) -> Result<Config> {
+    load_config_with_profile(repo_root, config_path, None)
+}
+
+/// [`load_config`] with a built-in `profile` beneath the repository-committed defaults.
+pub fn load_config_with_profile(
+    repo_root: &Path,
+    config_path: Option<&Path>,
+    profile: Option<Profile>,
+) -> Result<Config> {
     let config_path_provided = config_path.is_some();
+    let layered = layered_defaults(repo_root, profile);
+    let base = layered.as_ref();
 
     let discovered = match config_path {
         Some(path) => Some(path.to_path_buf()),
@@ -14,7 +33,7 @@ pub fn load_config(repo_root: &Path, config_path: Option<&Path>) -> Result<Confi
     };
 
     let Some(config_file) = discovered else {
-        return Ok(Config::default());
+        return Ok(config_from_base(base));
     };
 
     let content = fs::read_to_string(&config_file)
@@ -25,34 +44,34 @@ pub fn load_config(repo_root: &Path, config_path: Option<&Path>) -> Result<Confi
     // Python lines 380-395: Parse config, but silently return default on error
     // if auto-discovered (not explicitly provided by user).
     let parsed = match ext.as_str() {
-        "toml" => match parse_toml_config(&content, &config_file) {
+        "toml" => match parse_toml_config(&content, &config_file, base) {
             Ok(cfg) => cfg,
             Err(e) => {
                 if config_path_provided {
                     return Err(e);
                 }
-                // Auto-discovered: silently warn and return default
+                // Auto-discovered: silently warn and fall back to the committed defaults
                 tracing::warn!(
                     "Failed to parse auto-discovered config {}: {}",
                     config_file.display(),
                     e
                 );
-                return Ok(Config::default());
+                return Ok(config_from_base(base));
             }
         },
-        "yaml" | "yml" => match parse_yaml_config(&content, &config_file) {
+        "yaml" | "yml" => match parse_yaml_config(&content, &config_file, base) {
             Ok(cfg) => cfg,
             Err(e) => {
                 if config_path_provided {
                     return Err(e);
                 }
-                // Auto-discovered: silently warn and return default
+                // Auto-discovered: silently warn and fall back to the committed defaults
                 tracing::warn!(
                     "Failed to parse auto-discovered config {}: {}",
                     config_file.display(),
                     e
                 );
-                return Ok(Config::default());
+                return Ok(config_from_base(base));
             }
         },
         other => {
@@ -66,30 +85,151 @@ pub fn load_config(repo_root: &Path, config_path: Option<&Path>) -> Result<Confi
             }
             // Auto-discovered: silently ignore
             tracing::warn!("{}", err);
-            return Ok(Config::default());
+            return Ok(config_from_base(base));
         }
     };
 
     Ok(parsed)
 }
 
-/// Parse TOML config, supporting nested [repo-context] or [r2p] sections.
+/// [`load_config_with_profile`] for any number of explicit config files, merged in order:
+/// a key set in a later file overrides the same key from earlier ones.
+///
+/// With no files the config is discovered as usual.
+pub fn load_config_files(
+    repo_root: &Path,
+    config_paths: &[PathBuf],
+    profile: Option<Profile>,
+) -> Result<Config> {
+    let [first, second, rest @ ..] = config_paths else {
+        return load_config_with_profile(
+            repo_root,
+            config_paths.first().map(|p| p.as_path()),
+            profile,
+        );
+    };
+
+    let mut layered = layered_defaults(repo_root, profile);
+    for config_file in [first, second].into_iter().chain(rest) {
+        let upper = read_config_value(config_file)?;
+        layered = Some(match layered {
+            Some(base) => overlay(&base, upper),
+            None => upper,
+        });
+    }
+    let merged = layered.unwrap_or_else(|| toml::Value::Table(toml::Table::new()));
+    merged.try_into().context("Invalid merged config")
+}
+
+/// The `--profile` preset with the repository-committed defaults layered over it.
+fn layered_defaults(repo_root: &Path, profile: Option<Profile>) -> Option<toml::Value> {
+    match (profile.map(Profile::defaults), load_repo_defaults(repo_root)) {
+        (Some(preset), Some(repo)) => Some(overlay(&preset, repo)),
+        (preset, repo) => preset.or(repo),
+    }
+}
+
+/// Read one explicit TOML or YAML config file as a raw table, checking that it is a
+/// valid config on its own so errors name the offending file.
+fn read_config_value(config_file: &Path) -> Result<toml::Value> {
+    let content = fs::read_to_string(config_file)
+        .with_context(|| format!("Failed reading config file: {}", config_file.display()))?;
+    let ext = config_file.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    let value = match ext.as_str() {
+        "toml" => parse_toml_value(&content, config_file)?,
+        "yaml" | "yml" => {
+            let raw: serde_yaml::Value = serde_yaml::from_str(&content)
+                .with_context(|| format!("Invalid YAML syntax: {}", config_file.display()))?;
+            let section = raw.get("repo-context").or_else(|| raw.get("r2p")).unwrap_or(&raw);
+            toml::Value::try_from(section)
+                .with_context(|| format!("Invalid YAML config: {}", config_file.display()))?
+        }
+        other => anyhow::bail!(
+            "Unsupported config extension '.{}' for file {}",
+            other,
+            config_file.display()
+        ),
+    };
+    value
+        .clone()
+        .try_into::<Config>()
+        .with_context(|| format!("Invalid config: {}", config_file.display()))?;
+    Ok(value)
+}
+
+/// Read `.repo-to-prompt/config.toml` under `repo_root` as a raw table.
+///
+/// Like auto-discovered configs, an unreadable or invalid file only warns.
+fn load_repo_defaults(repo_root: &Path) -> Option<toml::Value> {
+    let path = repo_root.join(REPO_CONFIG_PATH);
+    let content = fs::read_to_string(&path).ok()?;
+    let value = parse_toml_value(&content, &path).and_then(|value| {
+        value
+            .clone()
+            .try_into::<Config>()
+            .with_context(|| format!("Invalid TOML config: {}", path.display()))
+            .map(|_| value)
+    });
+    match value {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!("Failed to parse repository config {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn config_from_base(base: Option<&toml::Value>) -> Config {
+    base.and_then(|value| value.clone().try_into().ok()).unwrap_or_default()
+}
+
+/// Overlay `upper` onto `base`: tables merge recursively, any other value replaces.
+fn overlay(base: &toml::Value, upper: toml::Value) -> toml::Value {
+    match (base, upper) {
+        (toml::Value::Table(base), toml::Value::Table(upper)) => {
+            let mut merged = base.clone();
+            for (key, value) in upper {
+                let value = match merged.get(&key) {
+                    Some(existing) => overlay(existing, value),
+                    None => value,
+                };
+                merged.insert(key, value);
+            }
+            toml::Value::Table(merged)
+        }
+        (_, upper) => upper,
+    }
+}
+
+/// Parse TOML to a generic value, unwrapping a nested [repo-context] or [r2p] section.
 ///
 /// Matches Python's _parse_toml behavior (lines 262-267).
-fn parse_toml_config(content: &str, config_file: &Path) -> Result<Config> {
+fn parse_toml_value(content: &str, config_file: &Path) -> Result<toml::Value> {
     // Parse to generic value first
     let raw: toml::Value = toml::from_str(content)
         .with_context(|| format!("Invalid TOML syntax: {}", config_file.display()))?;
 
     // Check for nested section (Python lines 263-266)
     // Prefer repo-context, then r2p.
-    let config_val = if let Some(nested) = raw.get("repo-context") {
+    Ok(if let Some(nested) = raw.get("repo-context") {
         nested.clone()
     } else if let Some(nested) = raw.get("r2p") {
         nested.clone()
     } else {
         raw
-    };
+    })
+}
+
+/// Parse TOML config, layered over the repository-committed defaults when present.
+fn parse_toml_config(
+    content: &str,
+    config_file: &Path,
+    base: Option<&toml::Value>,
+) -> Result<Config> {
+    let mut config_val = parse_toml_value(content, config_file)?;
+    if let Some(base) = base {
+        config_val = overlay(base, config_val);
+    }
 
     // Deserialize to Config
     config_val.try_into().with_context(|| format!("Invalid TOML config: {}", config_file.display()))
@@ -98,7 +238,11 @@ fn parse_toml_config(content: &str, config_file: &Path) -> Result<Config> {
 /// Parse YAML config, supporting nested repo-context or r2p sections.
 ///
 /// Matches Python's _parse_yaml behavior (lines 295-300).
-fn parse_yaml_config(content: &str, config_file: &Path) -> Result<Config> {
+fn parse_yaml_config(
+    content: &str,
+    config_file: &Path,
+    base: Option<&toml::Value>,
+) -> Result<Config> {
     // Parse to generic value first
     let raw: serde_yaml::Value = serde_yaml::from_str(content)
         .with_context(|| format!("Invalid YAML syntax: {}", config_file.display()))?;
@@ -113,12 +257,22 @@ fn parse_yaml_config(content: &str, config_file: &Path) -> Result<Config> {
         raw
     };
 
+    // Layering happens on TOML values, the format of the committed defaults
+    if let Some(base) = base {
+        let upper = toml::Value::try_from(&config_val)
+            .with_context(|| format!("Invalid YAML config: {}", config_file.display()))?;
+        return overlay(base, upper)
+            .try_into()
+            .with_context(|| format!("Invalid YAML config: {}", config_file.display()));
+    }
+
     // Deserialize to Config
     serde_yaml::from_value(config_val)
         .with_context(|| format!("Invalid YAML config: {}", config_file.display()))
 }
 
-fn discover_config(repo_root: &Path) -> Option<std::path::PathBuf> {
+/// First config file found under `repo_root`, in lookup order.
+pub fn discover_config(repo_root: &Path) -> Option<std::path::PathBuf> {
     let candidates = [
         // New names (preferred)
         "repo-context.toml",
@@ -168,6 +322,20 @@ mod tests {
         assert!(!cfg.respect_gitignore);
     }
 
+    #[test]
+    fn test_load_toml_tag_queries() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::write(
+            tmp.path().join("repo-context.toml"),
+            "[repo-context]\nchunk_tokens = 400\n\n[[repo-context.tag_queries]]\nlanguage = 'python'\nquery = '(function_definition name: (identifier) @name)'\ntag_prefix = 'resolver'\n",
+        )
+        .expect("write");
+
+        let cfg = load_config(tmp.path(), None).expect("config");
+        assert_eq!(cfg.tag_queries.len(), 1);
+        assert_eq!(cfg.tag_queries[0].tag_prefix, "resolver");
+    }
+
     // --- Test 1: Explicit config with invalid type for include_extensions ---
     #[test]
     fn test_explicit_config_invalid_type_returns_err() {
@@ -271,4 +439,82 @@ mod tests {
         assert!(globs.contains("build"), "should contain build");
         assert!(globs.contains("node_modules"), "should contain node_modules");
     }
+
+    #[test]
+    fn test_repo_committed_config_is_lowest_layer() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::create_dir(tmp.path().join(".repo-to-prompt")).expect("mkdir");
+        fs::write(tmp.path().join(REPO_CONFIG_PATH), "max_tokens = 12000\nmax_file_bytes = 2048\n")
+            .expect("write");
+
+        let cfg = load_config(tmp.path(), None).expect("config");
+        assert_eq!(cfg.max_tokens, Some(12000));
+        assert_eq!(cfg.max_file_bytes, 2048);
+
+        fs::write(tmp.path().join("r2p.yml"), "max_file_bytes: 4096\n").expect("write");
+        let cfg = load_config(tmp.path(), None).expect("config");
+        assert_eq!(cfg.max_tokens, Some(12000), "unset keys fall through to the repo layer");
+        assert_eq!(cfg.max_file_bytes, 4096, "the discovered file overrides the repo layer");
+    }
+
+    #[test]
+    fn test_profile_is_overridden_by_config_file() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::create_dir_all(tmp.path().join("src")).expect("mkdir src");
+        fs::create_dir_all(tmp.path().join("node_modules/left-pad")).expect("mkdir node_modules");
+        fs::write(tmp.path().join("src/index.ts"), "export const a = 1;\n").expect("write ts");
+        fs::write(tmp.path().join("src/index.d.ts"), "export declare const a: number;\n")
+            .expect("write d.ts");
+        fs::write(tmp.path().join("src/tool.py"), "print('py')\n").expect("write py");
+        fs::write(tmp.path().join("node_modules/left-pad/index.js"), "module.exports = 1;\n")
+            .expect("write dependency");
+
+        let cfg = load_config_with_profile(tmp.path(), None, Some(Profile::Node)).expect("config");
+        let mut scanner = crate::scan::FileScanner::new(tmp.path().to_path_buf())
+            .include_extensions(cfg.include_extensions.iter().cloned().collect())
+            .exclude_globs(cfg.exclude_globs.iter().cloned().collect());
+        let scanned = scanner.scan().expect("scan");
+        let paths: Vec<&str> = scanned.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/index.ts"]);
+
+        fs::write(tmp.path().join("r2p.toml"), "include_extensions = [\".ts\"]\n").expect("write");
+        let cfg = load_config_with_profile(tmp.path(), None, Some(Profile::Node)).expect("config");
+        assert_eq!(cfg.include_extensions.len(), 1);
+        assert!(cfg.exclude_globs.contains("*.d.ts"), "unset keys keep the profile value");
+    }
+
+    #[test]
+    fn test_multiple_config_files_merge_in_order() {
+        let tmp = TempDir::new().expect("tmp");
+        let base = tmp.path().join("base.toml");
+        let local = tmp.path().join("local.yml");
+        fs::write(&base, "max_tokens = 12000\nchunk_tokens = 600\n").expect("write base");
+        fs::write(&local, "max_file_bytes: 4096\nchunk_tokens: 900\n").expect("write local");
+
+        let cfg =
+            load_config_files(tmp.path(), &[base.clone(), local.clone()], None).expect("config");
+        assert_eq!(cfg.max_tokens, Some(12000));
+        assert_eq!(cfg.max_file_bytes, 4096);
+        assert_eq!(cfg.chunk_tokens, 900, "the later file wins on shared keys");
+
+        let cfg = load_config_files(tmp.path(), &[local, base], None).expect("config");
+        assert_eq!(cfg.chunk_tokens, 600);
+    }
+
+    #[test]
+    fn test_chunk_overlap_percentage_scales_with_chunk_tokens() {
+        use crate::domain::ChunkOverlap;
+
+        let tmp = TempDir::new().expect("tmp");
+        let path = tmp.path().join("repo-context.toml");
+        fs::write(&path, "chunk_tokens = 800\nchunk_overlap = \"15%\"\n").expect("write config");
+
+        let cfg = load_config(tmp.path(), Some(&path)).expect("config");
+        assert_eq!(cfg.chunk_overlap, ChunkOverlap::Percent(15.0));
+        assert_eq!(cfg.chunk_overlap.resolve(cfg.chunk_tokens), 120);
+
+        assert_eq!("15%".parse::<ChunkOverlap>(), Ok(ChunkOverlap::Percent(15.0)));
+        assert_eq!("120".parse::<ChunkOverlap>(), Ok(ChunkOverlap::Tokens(120)));
+        assert!("15 percent".parse::<ChunkOverlap>().is_err());
+    }
 }
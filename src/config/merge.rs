@@ -1,6 +1,6 @@
 //! CLI argument merging with config
 
-use crate::domain::Config;
+use crate::domain::{ChunkOverlap, Config};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
@@ -16,16 +16,23 @@ pub struct CliOverrides {
     pub respect_gitignore: Option<bool>,
     pub follow_symlinks: Option<bool>,
     pub skip_minified: Option<bool>,
+    pub lfs_resolve: Option<bool>,
     pub max_tokens: Option<usize>,
+    pub max_tokens_per_file: Option<f64>,
+    pub reserve_tokens: Option<usize>,
     pub task_query: Option<String>,
     pub semantic_rerank: Option<bool>,
     pub rerank_top_k: Option<usize>,
     pub semantic_model: Option<String>,
+    pub expand_seeds: Option<usize>,
+    pub expand_depth: Option<usize>,
+    pub expand_decay: Option<Vec<f64>>,
     pub stitch_budget_fraction: Option<f64>,
     pub stitch_top_n: Option<usize>,
     pub chunk_tokens: Option<usize>,
-    pub chunk_overlap: Option<usize>,
+    pub chunk_overlap: Option<ChunkOverlap>,
     pub min_chunk_tokens: Option<usize>,
+    pub max_chunk_lines: Option<usize>,
     pub mode: Option<crate::domain::OutputMode>,
     pub output_dir: Option<PathBuf>,
     pub tree_depth: Option<usize>,
@@ -34,6 +41,7 @@ pub struct CliOverrides {
     pub always_include_patterns: Option<Vec<String>>,
     pub always_include_paths: Option<Vec<String>>,
     pub invariant_keywords: Option<Vec<String>>,
+    pub recency_weight: Option<f64>,
 }
 
 pub fn merge_cli_with_config(mut base_config: Config, cli: CliOverrides) -> Config {
@@ -71,10 +79,19 @@ pub fn merge_cli_with_config(mut base_config: Config, cli: CliOverrides) -> Conf
     if let Some(skip_minified) = cli.skip_minified {
         base_config.skip_minified = skip_minified;
     }
+    if let Some(lfs_resolve) = cli.lfs_resolve {
+        base_config.lfs_resolve = lfs_resolve;
+    }
 
     if let Some(max_tokens) = cli.max_tokens {
         base_config.max_tokens = Some(max_tokens);
     }
+    if let Some(max_tokens_per_file) = cli.max_tokens_per_file {
+        base_config.max_tokens_per_file = Some(max_tokens_per_file);
+    }
+    if let Some(reserve_tokens) = cli.reserve_tokens {
+        base_config.reserve_tokens = reserve_tokens;
+    }
     if let Some(task_query) = cli.task_query {
         base_config.task_query = Some(task_query);
     }
@@ -87,6 +104,15 @@ pub fn merge_cli_with_config(mut base_config: Config, cli: CliOverrides) -> Conf
     if let Some(semantic_model) = cli.semantic_model {
         base_config.semantic_model = Some(semantic_model);
     }
+    if let Some(expand_seeds) = cli.expand_seeds {
+        base_config.expand_seeds = expand_seeds;
+    }
+    if let Some(expand_depth) = cli.expand_depth {
+        base_config.expand_depth = expand_depth;
+    }
+    if let Some(expand_decay) = cli.expand_decay {
+        base_config.expand_decay = expand_decay;
+    }
     if let Some(stitch_budget_fraction) = cli.stitch_budget_fraction {
         base_config.stitch_budget_fraction = stitch_budget_fraction;
     }
@@ -102,6 +128,9 @@ pub fn merge_cli_with_config(mut base_config: Config, cli: CliOverrides) -> Conf
     if let Some(min_chunk_tokens) = cli.min_chunk_tokens {
         base_config.min_chunk_tokens = min_chunk_tokens;
     }
+    if let Some(max_chunk_lines) = cli.max_chunk_lines {
+        base_config.max_chunk_lines = max_chunk_lines;
+    }
 
     if let Some(mode) = cli.mode {
         base_config.mode = mode;
@@ -127,6 +156,9 @@ pub fn merge_cli_with_config(mut base_config: Config, cli: CliOverrides) -> Conf
     if let Some(invariant_keywords) = cli.invariant_keywords {
         base_config.invariant_keywords = invariant_keywords;
     }
+    if let Some(recency_weight) = cli.recency_weight {
+        base_config.recency_weight = recency_weight;
+    }
 
     base_config
 }
@@ -1,10 +1,14 @@
 //! Configuration loading and merging
 //!
 //! Handles loading from config files, environment variables, and CLI arguments
-//! with proper precedence (CLI > Env > File > Defaults).
+//! with proper precedence (CLI > Env > File > repo-committed `.repo-to-prompt/config.toml` > `--profile` > Defaults).
 
+pub mod hash;
 pub mod loader;
 pub mod merge;
+pub mod profile;
+pub mod validate;
 
-pub use loader::load_config;
+pub use hash::config_hash;
+pub use loader::{load_config, load_config_files};
 pub use merge::{merge_cli_with_config, CliOverrides};
@@ -0,0 +1,74 @@
+//! Built-in stack presets (`--profile`).
+//!
+//! A profile is a partial config table layered beneath the repository-committed and
+//! discovered config files, so any key those files set still wins, as do CLI flags.
+
+use crate::domain::default_exclude_globs;
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Profile {
+    Rust,
+    Python,
+    Node,
+    Go,
+}
+
+impl Profile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Rust => "rust",
+            Profile::Python => "python",
+            Profile::Node => "node",
+            Profile::Go => "go",
+        }
+    }
+
+    /// The preset's config keys as a TOML table.
+    pub fn defaults(self) -> toml::Value {
+        let (extensions, extra_excludes, weights): (&[&str], &[&str], &[(&str, f64)]) = match self {
+            Profile::Rust => (
+                &[".rs", ".toml", ".md", ".proto", ".yaml", ".yml"],
+                &["**/target/**"],
+                &[("core_source", 0.80)],
+            ),
+            Profile::Python => (
+                &[".py", ".pyi", ".pyx", ".toml", ".cfg", ".ini", ".md", ".rst", ".yaml", ".yml"],
+                &["**/__pycache__/**", "**/.venv/**", "**/site-packages/**"],
+                &[("core_source", 0.80)],
+            ),
+            Profile::Node => (
+                &[".js", ".jsx", ".mjs", ".cjs", ".ts", ".tsx", ".json", ".md", ".yaml", ".yml"],
+                &["**/node_modules/**", "*.d.ts", ".next/**", ".nuxt/**", ".turbo/**"],
+                &[("core_source", 0.85), ("test", 0.45)],
+            ),
+            Profile::Go => (
+                &[".go", ".mod", ".proto", ".md", ".yaml", ".yml"],
+                &["**/vendor/**", "*.pb.go"],
+                &[("core_source", 0.80)],
+            ),
+        };
+
+        let mut excludes: Vec<String> = default_exclude_globs().into_iter().collect();
+        excludes.extend(extra_excludes.iter().map(|g| g.to_string()));
+        excludes.sort();
+        excludes.dedup();
+
+        let mut table = toml::Table::new();
+        table.insert(
+            "include_extensions".to_string(),
+            toml::Value::Array(extensions.iter().map(|e| toml::Value::from(*e)).collect()),
+        );
+        table.insert(
+            "exclude_globs".to_string(),
+            toml::Value::Array(excludes.into_iter().map(toml::Value::from).collect()),
+        );
+        table.insert(
+            "ranking_weights".to_string(),
+            toml::Value::Table(
+                weights.iter().map(|(key, w)| (key.to_string(), toml::Value::from(*w))).collect(),
+            ),
+        );
+        toml::Value::Table(table)
+    }
+}
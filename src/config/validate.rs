@@ -0,0 +1,260 @@
+//! Config validation (`validate-config`).
+//!
+//! Deserialization already rejects wrong types; these checks cover values that only fail
+//! later, such as custom redaction regexes, globs and tag queries.
+
+use crate::chunk::code_chunker::validate_tag_query;
+use crate::domain::{Config, DEFINITION_KINDS};
+use crate::render::resolve_section_order;
+use encoding_rs::Encoding;
+use globset::Glob;
+use regex::Regex;
+
+/// One invalid config value, addressed by its dotted field path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+/// Check the values of a deserialized config that are otherwise only used (and would
+/// only fail) during an export.
+pub fn validate_config(config: &Config) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    for (idx, rule) in config.redaction.custom_rules.iter().enumerate() {
+        if let Err(err) = Regex::new(&rule.pattern) {
+            let field = format!("redaction.custom_rules[{idx}].pattern");
+            let message = match rule.name.as_deref() {
+                Some(name) => format!("rule '{name}': {err}"),
+                None => err.to_string(),
+            };
+            issues.push(ConfigIssue::new(field, message));
+        }
+    }
+
+    for (idx, rule) in config.redaction.custom_rules.iter().enumerate() {
+        let prefix = format!("redaction.custom_rules[{idx}]");
+        check_regexes(&format!("{prefix}.allowlist_regexes"), &rule.allowlist_regexes, &mut issues);
+        check_regexes(
+            &format!("{prefix}.allowlist_path_regexes"),
+            &rule.allowlist_path_regexes,
+            &mut issues,
+        );
+    }
+    check_regexes("redaction.allowlist_regexes", &config.redaction.allowlist_regexes, &mut issues);
+    check_regexes(
+        "redaction.allowlist_path_regexes",
+        &config.redaction.allowlist_path_regexes,
+        &mut issues,
+    );
+    if config.redaction.structure_safe_placeholder.contains(['"', '\'', '`', '\\', '\n']) {
+        issues.push(ConfigIssue::new(
+            "redaction.structure_safe_placeholder",
+            "must not contain quotes, backslashes or newlines",
+        ));
+    }
+
+    let mut exclude_globs: Vec<&String> = config.exclude_globs.iter().collect();
+    exclude_globs.sort();
+    check_globs("exclude_globs", exclude_globs, &mut issues);
+    check_globs("always_include_patterns", &config.always_include_patterns, &mut issues);
+    check_globs("force_text_globs", &config.force_text_globs, &mut issues);
+    check_globs(
+        "per_path_token_budgets",
+        config.per_path_token_budgets.iter().map(|(pattern, _)| pattern),
+        &mut issues,
+    );
+
+    for (idx, query) in config.tag_queries.iter().enumerate() {
+        if let Err(err) = validate_tag_query(&query.language, &query.query) {
+            issues.push(ConfigIssue::new(format!("tag_queries[{idx}].query"), err));
+        }
+    }
+
+    if let Err(err) = resolve_section_order(&config.section_order) {
+        issues.push(ConfigIssue::new("section_order", err));
+    }
+
+    for (idx, factor) in config.expand_decay.iter().enumerate() {
+        if !(0.0..=1.0).contains(factor) {
+            issues.push(ConfigIssue::new(
+                format!("expand_decay[{idx}]"),
+                format!("must be within 0.0-1.0, got {factor}"),
+            ));
+        }
+    }
+    if !(0.0..=1.0).contains(&config.recency_weight) {
+        issues.push(ConfigIssue::new(
+            "recency_weight",
+            format!("must be within 0.0-1.0, got {}", config.recency_weight),
+        ));
+    }
+    let mut multipliers: Vec<_> = config.language_weight_multipliers.iter().collect();
+    multipliers.sort_by(|a, b| a.0.cmp(b.0));
+    for (language, multiplier) in multipliers {
+        if !multiplier.is_finite() || *multiplier < 0.0 {
+            issues.push(ConfigIssue::new(
+                format!("language_weight_multipliers.{language}"),
+                format!("must be a non-negative number, got {multiplier}"),
+            ));
+        }
+    }
+    if !(config.dominant_dir_fraction > 0.0 && config.dominant_dir_fraction <= 1.0) {
+        issues.push(ConfigIssue::new(
+            "dominant_dir_fraction",
+            format!("must be within (0.0, 1.0], got {}", config.dominant_dir_fraction),
+        ));
+    }
+    if let Some(cap) = config.max_tokens_per_file {
+        if !cap.is_finite() || cap <= 0.0 {
+            issues.push(ConfigIssue::new(
+                "max_tokens_per_file",
+                format!("must be positive, got {cap}"),
+            ));
+        } else if cap <= 1.0 && config.max_tokens.is_none() {
+            issues.push(ConfigIssue::new(
+                "max_tokens_per_file",
+                format!("a fraction ({cap}) requires max_tokens"),
+            ));
+        }
+    }
+    if let Some(max_tokens) = config.max_tokens {
+        if config.reserve_tokens >= max_tokens {
+            issues.push(ConfigIssue::new(
+                "reserve_tokens",
+                format!(
+                    "must be smaller than max_tokens ({max_tokens}), got {}",
+                    config.reserve_tokens
+                ),
+            ));
+        }
+    }
+    if config.chunk_overlap.resolve(config.chunk_tokens) >= config.chunk_tokens {
+        issues.push(ConfigIssue::new(
+            "chunk_overlap",
+            format!(
+                "must be smaller than chunk_tokens ({}), got {}",
+                config.chunk_tokens, config.chunk_overlap
+            ),
+        ));
+    }
+    if config.max_chunk_lines == 0 {
+        issues.push(ConfigIssue::new("max_chunk_lines", "must be at least 1"));
+    }
+
+    for (idx, kind) in config.definition_kinds.iter().enumerate() {
+        if !DEFINITION_KINDS.contains(&kind.as_str()) {
+            issues.push(ConfigIssue::new(
+                format!("definition_kinds[{idx}]"),
+                format!("unknown kind '{kind}'; expected one of {}", DEFINITION_KINDS.join(", ")),
+            ));
+        }
+    }
+
+    for (idx, (pattern, encoding)) in config.encoding_overrides.iter().enumerate() {
+        if let Err(err) = Glob::new(pattern) {
+            issues.push(ConfigIssue::new(
+                format!("encoding_overrides[{idx}]"),
+                format!("'{pattern}': {err}"),
+            ));
+        } else if Encoding::for_label(encoding.as_bytes()).is_none() {
+            issues.push(ConfigIssue::new(
+                format!("encoding_overrides[{idx}]"),
+                format!("unknown encoding '{encoding}'"),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn check_regexes(field: &str, patterns: &[String], issues: &mut Vec<ConfigIssue>) {
+    for (idx, pattern) in patterns.iter().enumerate() {
+        if let Err(err) = Regex::new(pattern) {
+            issues.push(ConfigIssue::new(format!("{field}[{idx}]"), format!("'{pattern}': {err}")));
+        }
+    }
+}
+
+fn check_globs<'a>(
+    field: &str,
+    patterns: impl IntoIterator<Item = &'a String>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (idx, pattern) in patterns.into_iter().enumerate() {
+        if let Err(err) = Glob::new(pattern) {
+            issues.push(ConfigIssue::new(format!("{field}[{idx}]"), format!("'{pattern}': {err}")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_config;
+    use crate::domain::{Config, CustomRedactionRule, TagQuery};
+
+    #[test]
+    fn reports_each_invalid_value_by_field() {
+        let mut config = Config::default();
+        config.redaction.custom_rules = vec![
+            CustomRedactionRule {
+                name: Some("ok".to_string()),
+                pattern: "token_[0-9]+".to_string(),
+                replacement: "[X]".to_string(),
+                allowlist_regexes: Vec::new(),
+                stopwords: Vec::new(),
+                allowlist_path_regexes: Vec::new(),
+                allowlists: Vec::new(),
+            },
+            CustomRedactionRule {
+                name: Some("broken".to_string()),
+                pattern: "([a-z".to_string(),
+                replacement: "[X]".to_string(),
+                allowlist_regexes: Vec::new(),
+                stopwords: Vec::new(),
+                allowlist_path_regexes: Vec::new(),
+                allowlists: Vec::new(),
+            },
+        ];
+        config.redaction.structure_safe_placeholder = "it's {marker}".to_string();
+        config.always_include_patterns = vec!["src/**".to_string(), "src/[a-".to_string()];
+        config.force_text_globs = vec!["proto/[x".to_string()];
+        config.tag_queries = vec![TagQuery {
+            language: "python".to_string(),
+            query: "(not_a_node) @x".to_string(),
+            tag_prefix: "x".to_string(),
+        }];
+        config.section_order = vec!["files".to_string(), "sidebar".to_string()];
+        config.max_chunk_lines = 0;
+        config.definition_kinds = vec!["function".to_string(), "macro".to_string()];
+        config.encoding_overrides = vec![
+            ("legacy/**".to_string(), "latin1".to_string()),
+            ("docs/**".to_string(), "klingon".to_string()),
+        ];
+
+        let fields: Vec<String> =
+            validate_config(&config).into_iter().map(|issue| issue.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "redaction.custom_rules[1].pattern",
+                "redaction.structure_safe_placeholder",
+                "always_include_patterns[1]",
+                "force_text_globs[0]",
+                "tag_queries[0].query",
+                "section_order",
+                "max_chunk_lines",
+                "definition_kinds[1]",
+                "encoding_overrides[1]",
+            ]
+        );
+        assert!(validate_config(&Config::default()).is_empty());
+    }
+}
@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 /// Current report schema version (matches Python implementation)
 pub const REPORT_SCHEMA_VERSION: &str = "1.0.0";
 
@@ -18,6 +21,11 @@ pub enum OutputMode {
     Contribution,
     #[serde(rename = "pr-context")]
     PrContext,
+    /// JSON array of whole files instead of chunks
+    #[serde(rename = "files-json")]
+    FilesJson,
+    /// One summary line per file instead of file contents
+    Map,
     #[default]
     Both,
 }
@@ -33,6 +41,77 @@ pub enum RedactionMode {
     StructureSafe,
 }
 
+/// Overlap between adjacent chunks: an absolute token count, or a percentage of the
+/// chunk size (`"15%"`) resolved when chunking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChunkOverlap {
+    Tokens(usize),
+    Percent(f64),
+}
+
+impl ChunkOverlap {
+    /// Overlap in tokens for chunks of `chunk_tokens`.
+    pub fn resolve(self, chunk_tokens: usize) -> usize {
+        match self {
+            ChunkOverlap::Tokens(tokens) => tokens,
+            ChunkOverlap::Percent(pct) => (chunk_tokens as f64 * pct / 100.0).round() as usize,
+        }
+    }
+}
+
+impl std::str::FromStr for ChunkOverlap {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(pct) = value.strip_suffix('%') {
+            let pct: f64 =
+                pct.trim().parse().map_err(|_| format!("invalid overlap percentage: {value}"))?;
+            if !pct.is_finite() || pct < 0.0 {
+                return Err(format!("overlap percentage must be non-negative, got {value}"));
+            }
+            return Ok(ChunkOverlap::Percent(pct));
+        }
+        value
+            .parse()
+            .map(ChunkOverlap::Tokens)
+            .map_err(|_| format!("expected a token count or a percentage like 15%, got {value}"))
+    }
+}
+
+impl std::fmt::Display for ChunkOverlap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkOverlap::Tokens(tokens) => write!(f, "{tokens}"),
+            ChunkOverlap::Percent(pct) => write!(f, "{pct}%"),
+        }
+    }
+}
+
+impl Serialize for ChunkOverlap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ChunkOverlap::Tokens(tokens) => serializer.serialize_u64(*tokens as u64),
+            ChunkOverlap::Percent(_) => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkOverlap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Tokens(usize),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Tokens(tokens) => Ok(ChunkOverlap::Tokens(tokens)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 /// Information about a scanned file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -77,6 +156,32 @@ pub struct FileInfo {
     /// Whether this is documentation
     #[serde(default)]
     pub is_doc: bool,
+
+    /// Last modification time (Unix seconds), when known
+    #[serde(default)]
+    pub modified_at: Option<i64>,
+
+    /// Ranking category and weight that produced `priority`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_reason: Option<PriorityReason>,
+}
+
+/// Why the ranker assigned a file its base priority
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorityReason {
+    /// Matched category, named after the `RankingWeights` field (e.g. `readme`)
+    pub category: String,
+
+    /// Weight configured for that category
+    pub weight: f64,
+
+    /// `language_weight_multipliers` entry the weight was scaled by, if not 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_multiplier: Option<f64>,
+
+    /// Priority added by `--coverage-file` for a well-covered file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage_boost: Option<f64>,
 }
 
 /// A chunk of file content
@@ -111,6 +216,36 @@ pub struct Chunk {
     /// Estimated tokens in chunk
     #[serde(default)]
     pub token_estimate: usize,
+
+    /// Leading docstring/doc-comment of the definition this chunk covers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+
+    /// `[start, end)` offsets of the chunk's line range in the raw bytes of the original
+    /// file, before decoding or redaction (only set with `--emit-byte-offsets`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_range: Option<(usize, usize)>,
+}
+
+/// A binary file named by a string literal in included code (`--asset-metadata`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferencedAsset {
+    /// Repository-relative path
+    pub path: String,
+    pub size_bytes: u64,
+    /// Hex SHA-256 of the file's bytes
+    pub sha256: String,
+    /// Files whose chunks reference it, sorted
+    pub referenced_by: Vec<String>,
+}
+
+/// An import in an included chunk that matches no scanned file (`--report-unresolved-imports`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedImport {
+    /// File whose chunk holds the import
+    pub from: String,
+    /// Module or path as written, e.g. `requests` or `../lib/util`
+    pub reference: String,
 }
 
 /// Statistics from scanning and processing
@@ -142,13 +277,22 @@ pub struct ScanStats {
     #[serde(default)]
     pub files_skipped_glob: usize,
 
+    /// Files skipped as unresolved Git LFS pointers
+    #[serde(default)]
+    pub files_skipped_lfs: usize,
+
     /// Files skipped due to filters (legacy, kept for compatibility)
     #[serde(default)]
     pub files_skipped: usize,
 
-    /// Files dropped due to budget limits
+    /// Files dropped due to budget limits, not counting stubbed ones
     pub files_dropped_budget: usize,
 
+    /// Budget-dropped files kept as a first-chunk stub (`--stub-dropped`); their
+    /// `dropped_files` records are marked `"stubbed": true`
+    #[serde(default)]
+    pub files_stubbed: usize,
+
     /// Total bytes scanned
     pub total_bytes_scanned: u64,
 
@@ -181,6 +325,18 @@ pub struct ScanStats {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dropped_files: Vec<HashMap<String, serde_json::Value>>,
 
+    /// `dropped_files` was cut to `--max-dropped-records`; the counters stay exact.
+    #[serde(default)]
+    pub dropped_files_truncated: bool,
+
+    /// Most `dropped_files` records to keep (`--max-dropped-records`); unlimited if unset.
+    #[serde(skip)]
+    pub max_dropped_records: Option<usize>,
+
+    /// Drops past `max_dropped_records`, counted but not listed in `dropped_files`.
+    #[serde(skip)]
+    pub dropped_files_omitted: usize,
+
     /// Redaction counts by rule name
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub redaction_counts: BTreeMap<String, usize>,
@@ -201,6 +357,54 @@ pub struct ScanStats {
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub redaction_file_counts: BTreeMap<String, usize>,
 
+    /// Would-be redactions left in place by `allowlist_strings` or `allowlist_patterns`.
+    #[serde(default)]
+    pub redactions_suppressed_by_allowlist: usize,
+
+    /// Files whose tree-sitter parse had errors and were chunked heuristically.
+    #[serde(default)]
+    pub files_parse_fallback: usize,
+
+    /// Files too large or too slow for tree-sitter that were line-chunked instead.
+    #[serde(default)]
+    pub files_parse_skipped: usize,
+
+    /// Files whose leading license header was replaced with a pointer.
+    #[serde(default)]
+    pub license_header_files_elided: usize,
+
+    /// Bytes removed by eliding repeated license headers.
+    #[serde(default)]
+    pub license_header_bytes_saved: usize,
+
+    /// Estimated tokens removed by eliding repeated license headers.
+    #[serde(default)]
+    pub license_header_tokens_saved: usize,
+
+    /// Estimated tokens per category tag (`--token-report`), with untagged chunks under
+    /// `lang:<language>`; a chunk counts toward each of its categories, so the values can
+    /// add up to more than `total_tokens_estimated`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tokens_by_tag: BTreeMap<String, usize>,
+
+    /// rust-analyzer diagnostics for included files (`--include-diagnostics`), each with
+    /// `path`, `line`, `severity`, `message` and optional `code`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<HashMap<String, serde_json::Value>>,
+
+    /// Binary files referenced by included code (`--asset-metadata`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_assets: Vec<ReferencedAsset>,
+
+    /// Files past gitignore and exclude globs that a later scan filter skipped (extension,
+    /// size, LFS, binary or minified); the only candidates for `referenced_assets`.
+    #[serde(skip)]
+    pub skipped_file_paths: BTreeSet<String>,
+
+    /// Imports that resolve to no scanned file (`--report-unresolved-imports`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved_imports: Vec<UnresolvedImport>,
+
     /// Number of chunks added by thread stitching.
     #[serde(default)]
     pub stitched_chunks: usize,
@@ -209,6 +413,14 @@ pub struct ScanStats {
     #[serde(default)]
     pub pinned_only_mode: bool,
 
+    /// Raw `max_tokens` limit before `reserve_tokens` is taken off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+
+    /// Tokens held back from `max_tokens` (`--reserve-tokens`).
+    #[serde(default)]
+    pub reserved_tokens: usize,
+
     /// Tokens by which protected pins exceeded max_tokens.
     #[serde(default)]
     pub pinned_overflow_tokens: usize,
@@ -219,6 +431,28 @@ pub struct ScanStats {
 }
 
 impl ScanStats {
+    /// List a dropped file, or only count it once `max_dropped_records` are listed, and
+    /// return its index when listed. Drops are recorded in rank order, so the
+    /// highest-priority ones are listed.
+    pub fn push_dropped_record(
+        &mut self,
+        record: HashMap<String, serde_json::Value>,
+    ) -> Option<usize> {
+        if self.max_dropped_records.is_some_and(|max| self.dropped_files.len() >= max) {
+            self.dropped_files_omitted += 1;
+            self.dropped_files_truncated = true;
+            None
+        } else {
+            self.dropped_files.push(record);
+            Some(self.dropped_files.len() - 1)
+        }
+    }
+
+    /// Dropped files, listed or not.
+    pub fn dropped_files_total(&self) -> usize {
+        self.dropped_files.len() + self.dropped_files_omitted
+    }
+
     /// Produce a JSON value matching Python's report schema.
     ///
     /// Python nests the per-category skip counts under a `"files_skipped"` object
@@ -269,6 +503,52 @@ impl ScanStats {
         if self.redacted_chunks > 0 {
             value["redacted_chunks"] = serde_json::json!(self.redacted_chunks);
         }
+        if self.license_header_files_elided > 0 {
+            value["license_header_files_elided"] =
+                serde_json::json!(self.license_header_files_elided);
+            value["license_header_bytes_saved"] =
+                serde_json::json!(self.license_header_bytes_saved);
+            value["license_header_tokens_saved"] =
+                serde_json::json!(self.license_header_tokens_saved);
+        }
+        if self.files_skipped_lfs > 0 {
+            value["files_skipped"]["lfs"] = serde_json::json!(self.files_skipped_lfs);
+        }
+        if self.files_stubbed > 0 {
+            value["files_stubbed"] = serde_json::json!(self.files_stubbed);
+        }
+        if self.dropped_files_truncated {
+            value["dropped_files_truncated"] = serde_json::json!(true);
+        }
+        if self.files_parse_fallback > 0 {
+            value["files_parse_fallback"] = serde_json::json!(self.files_parse_fallback);
+        }
+        if self.files_parse_skipped > 0 {
+            value["files_parse_skipped"] = serde_json::json!(self.files_parse_skipped);
+        }
+        if self.redactions_suppressed_by_allowlist > 0 {
+            value["redactions_suppressed_by_allowlist"] =
+                serde_json::json!(self.redactions_suppressed_by_allowlist);
+        }
+        if !self.tokens_by_tag.is_empty() {
+            value["tokens_by_tag"] = serde_json::json!(self.tokens_by_tag);
+        }
+        if self.reserved_tokens > 0 {
+            value["token_budget"] = serde_json::json!({
+                "max_tokens":           self.max_tokens,
+                "reserved_tokens":      self.reserved_tokens,
+                "effective_max_tokens": self.max_tokens.map(|max| max.saturating_sub(self.reserved_tokens)),
+            });
+        }
+        if !self.diagnostics.is_empty() {
+            value["diagnostics"] = serde_json::json!(self.diagnostics);
+        }
+        if !self.referenced_assets.is_empty() {
+            value["referenced_assets"] = serde_json::json!(self.referenced_assets);
+        }
+        if !self.unresolved_imports.is_empty() {
+            value["unresolved_imports"] = serde_json::json!(self.unresolved_imports);
+        }
 
         value
     }
@@ -285,6 +565,18 @@ pub struct RedactionConfig {
     #[serde(default)]
     pub allowlist_strings: Vec<String>,
 
+    /// Matches (or capture groups) matching one of these regexes are never redacted
+    #[serde(default)]
+    pub allowlist_regexes: Vec<String>,
+
+    /// Matches containing one of these words (case-insensitive) are never redacted
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+
+    /// Relative-path regexes for files to skip redaction entirely (like `allowlist_patterns`)
+    #[serde(default)]
+    pub allowlist_path_regexes: Vec<String>,
+
     /// Custom regex rules: each entry has `name`, `pattern`, optional `replacement`
     #[serde(default)]
     pub custom_rules: Vec<CustomRedactionRule>,
@@ -308,6 +600,11 @@ pub struct RedactionConfig {
     /// Enable structure-safe redaction for source files (default: true)
     #[serde(default = "default_true_redaction")]
     pub structure_safe_redaction: bool,
+
+    /// Literal text for structure-safe redactions; `{marker}` is the bracketed marker's
+    /// name (`SECRET_REDACTED`) and `{rule}` the rule name
+    #[serde(default = "default_structure_safe_placeholder")]
+    pub structure_safe_placeholder: String,
 }
 
 /// One custom redaction rule from the config file.
@@ -317,6 +614,31 @@ pub struct CustomRedactionRule {
     pub pattern: String,
     #[serde(default = "default_custom_replacement")]
     pub replacement: String,
+    /// Matches (or capture groups) matching one of these regexes are left in place
+    #[serde(default)]
+    pub allowlist_regexes: Vec<String>,
+    /// Matches containing one of these words (case-insensitive) are left in place
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+    /// Files whose relative path matches one of these regexes are skipped by this rule
+    #[serde(default)]
+    pub allowlist_path_regexes: Vec<String>,
+    /// Allowlists whose conditions must all hold (gitleaks `condition = "AND"`)
+    #[serde(default)]
+    pub allowlists: Vec<CustomRedactionAllowlist>,
+}
+
+/// An allowlist of a custom rule that spares a match only when every non-empty
+/// condition holds: the file path matches one of `path_regexes`, the match one of
+/// `regexes`, and it contains one of `stopwords`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomRedactionAllowlist {
+    #[serde(default)]
+    pub regexes: Vec<String>,
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+    #[serde(default)]
+    pub path_regexes: Vec<String>,
 }
 
 /// Entropy detection settings.
@@ -344,12 +666,16 @@ impl Default for RedactionConfig {
         Self {
             allowlist_patterns: Vec::new(),
             allowlist_strings: Vec::new(),
+            allowlist_regexes: Vec::new(),
+            stopwords: Vec::new(),
+            allowlist_path_regexes: Vec::new(),
             custom_rules: Vec::new(),
             entropy: EntropyConfig::default(),
             paranoid: ParanoidConfig::default(),
             safe_file_patterns: default_safe_file_patterns(),
             source_safe_patterns: default_source_safe_patterns(),
             structure_safe_redaction: true,
+            structure_safe_placeholder: default_structure_safe_placeholder(),
         }
     }
 }
@@ -369,6 +695,9 @@ impl Default for ParanoidConfig {
 fn default_true_redaction() -> bool {
     true
 }
+fn default_structure_safe_placeholder() -> String {
+    "{marker}".to_string()
+}
 fn default_custom_replacement() -> String {
     "[CUSTOM_REDACTED]".to_string()
 }
@@ -423,6 +752,19 @@ fn default_source_safe_patterns() -> Vec<String> {
     ]
 }
 
+/// A user-defined tree-sitter query; every capture in a chunk adds `<tag_prefix>:<text>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagQuery {
+    /// Language the query applies to (e.g. `python`, `typescript`)
+    pub language: String,
+
+    /// Query source in tree-sitter `.scm` syntax
+    pub query: String,
+
+    /// Prefix for tags produced by this query
+    pub tag_prefix: String,
+}
+
 /// Configurable weights for file ranking — mirrors Python's RankingWeights.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RankingWeights {
@@ -452,6 +794,8 @@ pub struct RankingWeights {
     pub lock_file: f64,
     #[serde(default = "w_vendored")]
     pub vendored: f64,
+    #[serde(default = "w_fixture")]
+    pub fixture: f64,
 }
 
 impl Default for RankingWeights {
@@ -470,6 +814,7 @@ impl Default for RankingWeights {
             generated: w_generated(),
             lock_file: w_lock_file(),
             vendored: w_vendored(),
+            fixture: w_fixture(),
         }
     }
 }
@@ -513,6 +858,9 @@ fn w_lock_file() -> f64 {
 fn w_vendored() -> f64 {
     0.10
 }
+fn w_fixture() -> f64 {
+    0.25
+}
 
 /// Custom deserializer for extensions: normalizes to dot-prefixed format.
 ///
@@ -665,6 +1013,14 @@ pub struct Config {
     #[serde(default = "default_max_total_bytes")]
     pub max_total_bytes: u64,
 
+    /// Warn (and confirm on a TTY) when a scan sees more files than this
+    #[serde(default = "default_scan_warn_files")]
+    pub scan_warn_files: usize,
+
+    /// Warn (and confirm on a TTY) when a scan sees more bytes than this
+    #[serde(default = "default_scan_warn_bytes")]
+    pub scan_warn_bytes: u64,
+
     #[serde(default = "default_true")]
     pub respect_gitignore: bool,
 
@@ -674,9 +1030,30 @@ pub struct Config {
     #[serde(default = "default_true")]
     pub skip_minified: bool,
 
+    /// Read Git LFS pointer files from `.git/lfs/objects` when the object is present
+    #[serde(default)]
+    pub lfs_resolve: bool,
+
+    /// Apply `language = ...` hints from a root `.editorconfig` to otherwise-`text` files
+    #[serde(default = "default_true")]
+    pub editorconfig_hints: bool,
+
     // Token budget
     pub max_tokens: Option<usize>,
 
+    /// Per-file token cap: absolute tokens, or a fraction of `max_tokens` when <= 1.0.
+    #[serde(default)]
+    pub max_tokens_per_file: Option<f64>,
+
+    /// `(glob, tokens)` pairs: files matching a glob share that many tokens, on top of
+    /// `max_tokens`; a file counts against the first glob it matches.
+    #[serde(default)]
+    pub per_path_token_budgets: Vec<(String, usize)>,
+
+    /// Tokens held back from `max_tokens` for text sent after the pack (e.g. a prompt).
+    #[serde(default)]
+    pub reserve_tokens: usize,
+
     /// Optional task description used for retrieval-driven reranking.
     #[serde(default)]
     pub task_query: Option<String>,
@@ -693,6 +1070,18 @@ pub struct Config {
     #[serde(default)]
     pub semantic_model: Option<String>,
 
+    /// Number of top lexical files used as dependency-expansion seeds.
+    #[serde(default = "default_expand_seeds")]
+    pub expand_seeds: usize,
+
+    /// How many dependency-graph hops expansion follows from each seed.
+    #[serde(default = "default_expand_depth")]
+    pub expand_depth: usize,
+
+    /// Score decay per expansion level; missing levels halve the previous factor.
+    #[serde(default = "default_expand_decay")]
+    pub expand_decay: Vec<f64>,
+
     /// Fraction of token budget reserved for stitched context.
     #[serde(default = "default_stitch_budget_fraction")]
     pub stitch_budget_fraction: f64,
@@ -705,16 +1094,54 @@ pub struct Config {
     #[serde(default = "default_chunk_tokens")]
     pub chunk_tokens: usize,
 
+    /// Token count, or a percentage of `chunk_tokens` such as `"15%"`
     #[serde(default = "default_chunk_overlap")]
-    pub chunk_overlap: usize,
+    pub chunk_overlap: ChunkOverlap,
 
     #[serde(default = "default_min_chunk_tokens")]
     pub min_chunk_tokens: usize,
 
+    /// Most lines a chunk may span, whatever its token count
+    #[serde(default = "default_max_chunk_lines")]
+    pub max_chunk_lines: usize,
+
+    /// Per-class overrides of `min_chunk_tokens` for code, prose docs, and config files
+    #[serde(default)]
+    pub min_chunk_tokens_code: Option<usize>,
+
+    #[serde(default)]
+    pub min_chunk_tokens_doc: Option<usize>,
+
+    #[serde(default)]
+    pub min_chunk_tokens_config: Option<usize>,
+
+    /// Custom tree-sitter queries whose captures become chunk tags
+    #[serde(default)]
+    pub tag_queries: Vec<TagQuery>,
+
+    /// Files larger than this skip tree-sitter parsing and are line-chunked
+    #[serde(default = "default_tree_sitter_max_bytes")]
+    pub tree_sitter_max_bytes: usize,
+
+    /// Abandon a tree-sitter parse after this many milliseconds and line-chunk the file
+    /// (0 = no limit)
+    #[serde(default)]
+    pub tree_sitter_timeout_ms: u64,
+
+    /// Kinds of top-level definition that start a chunk and get symbol tags (`function`,
+    /// `method`, `type`, `impl`, `module`, `const`, `var`); others stay in the chunk above.
+    #[serde(default = "default_definition_kinds")]
+    pub definition_kinds: Vec<String>,
+
     // Output options
     #[serde(default)]
     pub mode: OutputMode,
 
+    /// Context pack sections in render order (e.g. `["header", "files", "tree"]`);
+    /// empty keeps the built-in layout
+    #[serde(default)]
+    pub section_order: Vec<String>,
+
     #[serde(default = "default_output_dir")]
     pub output_dir: PathBuf,
 
@@ -743,6 +1170,35 @@ pub struct Config {
     #[serde(default, alias = "weights")]
     pub ranking_weights: RankingWeights,
 
+    /// Blend of file recency into priority (0.0 = ranking only, 1.0 = recency only).
+    #[serde(default)]
+    pub recency_weight: f64,
+
+    /// Per-language priority scale (e.g. `css = 0.5`), capped at 1.0; unlisted languages
+    /// keep their ranked priority.
+    #[serde(default)]
+    pub language_weight_multipliers: HashMap<String, f64>,
+
+    /// Directory names whose files are ranked as low-weight test data/fixtures.
+    #[serde(default = "default_fixture_dirs")]
+    pub fixture_dirs: Vec<String>,
+
+    /// Warn when one directory holds more than this fraction of the scanned files and
+    /// bytes (1.0 disables the warning).
+    #[serde(default = "default_dominant_dir_fraction")]
+    pub dominant_dir_fraction: f64,
+
+    /// `(glob, encoding)` pairs: files matching a glob are decoded with that charset
+    /// (an `encoding_rs` label such as `latin1` or `shift_jis`) instead of auto-detected.
+    #[serde(default)]
+    pub encoding_overrides: Vec<(String, String)>,
+
+    /// Globs for files that are always treated as text: binary detection is skipped and
+    /// they are decoded as UTF-8, with invalid bytes replaced, unless an encoding override
+    /// matches.
+    #[serde(default)]
+    pub force_text_globs: Vec<String>,
+
     /// Redaction configuration loaded from [redaction] section
     #[serde(default, alias = "redact")]
     pub redaction: RedactionConfig,
@@ -758,20 +1214,39 @@ impl Default for Config {
             exclude_globs: default_exclude_globs(),
             max_file_bytes: default_max_file_bytes(),
             max_total_bytes: default_max_total_bytes(),
+            scan_warn_files: default_scan_warn_files(),
+            scan_warn_bytes: default_scan_warn_bytes(),
             respect_gitignore: true,
             follow_symlinks: false,
             skip_minified: true,
+            lfs_resolve: false,
+            editorconfig_hints: true,
             max_tokens: None,
+            max_tokens_per_file: None,
+            per_path_token_budgets: Vec::new(),
+            reserve_tokens: 0,
             task_query: None,
             semantic_rerank: true,
             rerank_top_k: default_rerank_top_k(),
             semantic_model: None,
+            expand_seeds: default_expand_seeds(),
+            expand_depth: default_expand_depth(),
+            expand_decay: default_expand_decay(),
             stitch_budget_fraction: default_stitch_budget_fraction(),
             stitch_top_n: default_stitch_top_n(),
             chunk_tokens: default_chunk_tokens(),
             chunk_overlap: default_chunk_overlap(),
             min_chunk_tokens: default_min_chunk_tokens(),
+            max_chunk_lines: default_max_chunk_lines(),
+            min_chunk_tokens_code: None,
+            min_chunk_tokens_doc: None,
+            min_chunk_tokens_config: None,
+            tag_queries: Vec::new(),
+            tree_sitter_max_bytes: default_tree_sitter_max_bytes(),
+            tree_sitter_timeout_ms: 0,
+            definition_kinds: default_definition_kinds(),
             mode: OutputMode::Both,
+            section_order: Vec::new(),
             output_dir: default_output_dir(),
             tree_depth: default_tree_depth(),
             redact_secrets: true,
@@ -780,6 +1255,12 @@ impl Default for Config {
             always_include_paths: Vec::new(),
             invariant_keywords: default_invariant_keywords(),
             ranking_weights: RankingWeights::default(),
+            recency_weight: 0.0,
+            language_weight_multipliers: HashMap::new(),
+            fixture_dirs: default_fixture_dirs(),
+            dominant_dir_fraction: default_dominant_dir_fraction(),
+            encoding_overrides: Vec::new(),
+            force_text_globs: Vec::new(),
             redaction: RedactionConfig::default(),
         }
     }
@@ -798,14 +1279,38 @@ fn default_max_total_bytes() -> u64 {
     20_000_000 // 20 MB
 }
 
+fn default_scan_warn_files() -> usize {
+    50_000
+}
+
+fn default_scan_warn_bytes() -> u64 {
+    2_000_000_000 // 2 GB
+}
+
 fn default_chunk_tokens() -> usize {
     800
 }
 
+pub fn default_max_chunk_lines() -> usize {
+    400
+}
+
 fn default_rerank_top_k() -> usize {
     200
 }
 
+fn default_expand_seeds() -> usize {
+    5
+}
+
+fn default_expand_depth() -> usize {
+    2
+}
+
+fn default_expand_decay() -> Vec<f64> {
+    vec![0.6, 0.3]
+}
+
 fn default_stitch_budget_fraction() -> f64 {
     0.30
 }
@@ -814,14 +1319,26 @@ fn default_stitch_top_n() -> usize {
     20
 }
 
-fn default_chunk_overlap() -> usize {
-    120
+fn default_chunk_overlap() -> ChunkOverlap {
+    ChunkOverlap::Tokens(120)
 }
 
 fn default_min_chunk_tokens() -> usize {
     200
 }
 
+pub fn default_tree_sitter_max_bytes() -> usize {
+    512 * 1024
+}
+
+/// Every definition kind the code chunker recognizes.
+pub const DEFINITION_KINDS: &[&str] =
+    &["function", "method", "type", "impl", "module", "const", "var"];
+
+pub fn default_definition_kinds() -> Vec<String> {
+    DEFINITION_KINDS.iter().map(|s| s.to_string()).collect()
+}
+
 fn default_output_dir() -> PathBuf {
     PathBuf::from("./out")
 }
@@ -921,6 +1438,18 @@ pub fn default_include_extensions() -> HashSet<String> {
     .collect()
 }
 
+pub fn default_dominant_dir_fraction() -> f64 {
+    0.5
+}
+
+/// Directory names treated as test data/fixtures by the ranker.
+pub fn default_fixture_dirs() -> Vec<String> {
+    ["testdata", "test_data", "fixtures", "__fixtures__", "__snapshots__"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
 pub fn default_exclude_globs() -> HashSet<String> {
     [
         // Build outputs
@@ -974,61 +1503,64 @@ pub fn default_exclude_globs() -> HashSet<String> {
     .collect()
 }
 
+/// Extensions mapped to each language by [`get_language`].
+pub const LANGUAGE_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("python", &[".py", ".pyi", ".pyx"]),
+    ("javascript", &[".js", ".jsx", ".mjs", ".cjs"]),
+    ("typescript", &[".ts", ".tsx"]),
+    ("go", &[".go"]),
+    ("java", &[".java"]),
+    ("kotlin", &[".kt", ".kts"]),
+    ("rust", &[".rs"]),
+    ("c", &[".c", ".h"]),
+    ("cpp", &[".cpp", ".hpp", ".cc", ".cxx"]),
+    ("csharp", &[".cs"]),
+    ("ruby", &[".rb"]),
+    ("php", &[".php"]),
+    ("swift", &[".swift"]),
+    ("scala", &[".scala"]),
+    ("bash", &[".sh", ".bash"]),
+    ("zsh", &[".zsh"]),
+    ("markdown", &[".md"]),
+    ("restructuredtext", &[".rst"]),
+    ("asciidoc", &[".adoc"]),
+    ("text", &[".txt"]),
+    ("yaml", &[".yaml", ".yml"]),
+    ("toml", &[".toml"]),
+    ("json", &[".json"]),
+    ("ini", &[".ini", ".cfg"]),
+    ("html", &[".html"]),
+    ("css", &[".css"]),
+    ("scss", &[".scss"]),
+    ("less", &[".less"]),
+    ("vue", &[".vue"]),
+    ("svelte", &[".svelte"]),
+    ("sql", &[".sql"]),
+    ("dockerfile", &[".dockerfile"]),
+    ("graphql", &[".graphql"]),
+    ("protobuf", &[".proto"]),
+];
+
+/// Extensionless filenames (lowercased) recognized by [`get_language`].
+pub const LANGUAGE_FILENAMES: &[(&str, &str)] =
+    &[("dockerfile", "dockerfile"), ("makefile", "makefile"), ("rakefile", "ruby")];
+
 /// Get language from file extension or special filename.
 pub fn get_language(extension: &str, filename: &str) -> String {
     let ext = extension.to_lowercase();
-    let lang = match ext.as_str() {
-        ".py" | ".pyi" | ".pyx" => "python",
-        ".js" | ".jsx" | ".mjs" | ".cjs" => "javascript",
-        ".ts" | ".tsx" => "typescript",
-        ".go" => "go",
-        ".java" => "java",
-        ".kt" | ".kts" => "kotlin",
-        ".rs" => "rust",
-        ".c" | ".h" => "c",
-        ".cpp" | ".hpp" | ".cc" | ".cxx" => "cpp",
-        ".cs" => "csharp",
-        ".rb" => "ruby",
-        ".php" => "php",
-        ".swift" => "swift",
-        ".scala" => "scala",
-        ".sh" | ".bash" => "bash",
-        ".zsh" => "zsh",
-        ".md" => "markdown",
-        ".rst" => "restructuredtext",
-        ".adoc" => "asciidoc",
-        ".txt" => "text",
-        ".yaml" | ".yml" => "yaml",
-        ".toml" => "toml",
-        ".json" => "json",
-        ".ini" | ".cfg" => "ini",
-        ".html" => "html",
-        ".css" => "css",
-        ".scss" => "scss",
-        ".less" => "less",
-        ".vue" => "vue",
-        ".svelte" => "svelte",
-        ".sql" => "sql",
-        ".dockerfile" => "dockerfile",
-        ".graphql" => "graphql",
-        ".proto" => "protobuf",
-        _ => {
-            // Special filenames (no extension or empty extension)
-            let name = filename.to_lowercase();
-            if name == "dockerfile" {
-                return "dockerfile".to_string();
-            }
-            if name == "makefile" {
-                return "makefile".to_string();
-            }
-            if name == "rakefile" {
-                return "ruby".to_string();
-            }
-            if ext.is_empty() && name.ends_with("rc") {
-                return "shell".to_string();
-            }
-            "text"
-        }
-    };
-    lang.to_string()
+    if let Some((lang, _)) =
+        LANGUAGE_EXTENSIONS.iter().find(|(_, exts)| exts.contains(&ext.as_str()))
+    {
+        return lang.to_string();
+    }
+
+    // Special filenames (no extension or empty extension)
+    let name = filename.to_lowercase();
+    if let Some((_, lang)) = LANGUAGE_FILENAMES.iter().find(|(file, _)| *file == name) {
+        return lang.to_string();
+    }
+    if ext.is_empty() && name.ends_with("rc") {
+        return "shell".to_string();
+    }
+    "text".to_string()
 }
@@ -0,0 +1,57 @@
+//! `FileInfo` and `Chunk` builders for unit tests.
+//!
+//! Tests override the fields they care about with struct update syntax, e.g.
+//! `Chunk { priority: 0.9, ..chunk("src/lib.rs", "fn a() {}") }`.
+
+use super::{get_language, Chunk, FileInfo};
+use crate::utils::estimate_tokens;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// A file at `relative_path` with id, extension and language derived from the path.
+pub(crate) fn file_info(relative_path: &str) -> FileInfo {
+    let (extension, language) = extension_and_language(relative_path);
+    FileInfo {
+        path: PathBuf::from(relative_path),
+        relative_path: relative_path.to_string(),
+        size_bytes: 0,
+        extension,
+        language,
+        id: relative_path.to_string(),
+        priority: 0.5,
+        token_estimate: 0,
+        tags: BTreeSet::new(),
+        is_readme: false,
+        is_config: false,
+        is_doc: false,
+        modified_at: None,
+        priority_reason: None,
+    }
+}
+
+/// A chunk holding `content` from line 1 of `path`, with id `path` and the language of `path`.
+pub(crate) fn chunk(path: &str, content: &str) -> Chunk {
+    let (_, language) = extension_and_language(path);
+    Chunk {
+        id: path.to_string(),
+        path: path.to_string(),
+        language,
+        start_line: 1,
+        end_line: content.lines().count().max(1),
+        content: content.to_string(),
+        priority: 0.5,
+        tags: BTreeSet::new(),
+        token_estimate: estimate_tokens(content),
+        doc: None,
+        byte_range: None,
+    }
+}
+
+fn extension_and_language(path: &str) -> (String, String) {
+    let path = Path::new(path);
+    let extension =
+        path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let language = get_language(&extension, &file_name);
+    (extension, language)
+}
@@ -0,0 +1,345 @@
+//! Coverage report for `report.json`: dropped files that included files import, public
+//! API coverage and hot paths from tests and examples.
+
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::domain::{Chunk, FileInfo, ScanStats};
+use crate::rank::{dependency_graph, symbol_definitions};
+use crate::redact::Redactor;
+use crate::utils::{read_file_safe, EncodingOverrides};
+
+/// The `coverage` section of `report.json`: what the pack leaves out and how much of the
+/// public API it covers. Paths are matched on the real tree and masked with
+/// `path_redactor` on the way out. Files are read with the config's `encodings`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_coverage_report(
+    root_path: &Path,
+    selected_files: &[FileInfo],
+    chunks: &[Chunk],
+    stats: &ScanStats,
+    provenance: &serde_json::Value,
+    index_db_path: Option<&Path>,
+    path_redactor: Option<&Redactor>,
+    encodings: &EncodingOverrides,
+) -> serde_json::Value {
+    let dropped_paths: Vec<String> = stats
+        .dropped_files
+        .iter()
+        .filter_map(|entry| entry.get("path").and_then(|v| v.as_str()).map(|p| p.to_string()))
+        .collect();
+    let included_paths: HashSet<String> =
+        selected_files.iter().map(|f| f.relative_path.clone()).collect();
+    let most_imported_not_included = most_imported_not_included(
+        index_db_path,
+        &dropped_paths,
+        &included_paths,
+        &stats.dropped_files,
+    );
+
+    let public_api = public_api_coverage(root_path, selected_files, stats, encodings);
+    let hot_paths = hot_paths_from_tests_examples(chunks);
+    let mut missing_context_todos = Vec::new();
+    for entry in stats.dropped_files.iter().take(15) {
+        if let Some(path) = entry.get("path").and_then(|v| v.as_str()) {
+            let reason = entry.get("reason").and_then(|v| v.as_str()).unwrap_or("unknown");
+            missing_context_todos.push(json!({"path": path, "reason": reason}));
+        }
+    }
+
+    if stats.pinned_only_mode {
+        missing_context_todos.push(json!({
+            "path": "<export>",
+            "reason": "pinned-only fallback activated; increase --max-tokens for broader coverage"
+        }));
+    }
+
+    let mut most_imported_not_included = most_imported_not_included;
+    let mut hot_paths = hot_paths;
+    if let Some(redactor) = path_redactor {
+        let entries = most_imported_not_included
+            .iter_mut()
+            .chain(hot_paths.iter_mut())
+            .chain(missing_context_todos.iter_mut());
+        for entry in entries {
+            if let Some(serde_json::Value::String(path)) = entry.get_mut("path") {
+                *path = redactor.redact_path(path);
+            }
+        }
+    }
+
+    json!({
+        "most_imported_not_included": most_imported_not_included,
+        "public_api_surface_coverage": public_api,
+        "hot_paths_from_tests_examples": hot_paths,
+        "missing_context_todos": missing_context_todos,
+        "fingerprint": provenance.get("fingerprint").cloned().unwrap_or(json!(null)),
+    })
+}
+
+fn most_imported_not_included(
+    index_db_path: Option<&Path>,
+    dropped_paths: &[String],
+    included_paths: &HashSet<String>,
+    dropped_entries: &[HashMap<String, serde_json::Value>],
+) -> Vec<serde_json::Value> {
+    if dropped_paths.is_empty() {
+        return Vec::new();
+    }
+
+    let dropped_set: HashSet<String> = dropped_paths.iter().cloned().collect();
+    let mut dropped_reason: HashMap<String, String> = HashMap::new();
+    let mut dropped_priority: HashMap<String, serde_json::Value> = HashMap::new();
+    for entry in dropped_entries {
+        let Some(path) = entry.get("path").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if let Some(reason) = entry.get("reason").and_then(|v| v.as_str()) {
+            dropped_reason.insert(path.to_string(), reason.to_string());
+        }
+        if let Some(priority) = entry.get("priority") {
+            dropped_priority.insert(path.to_string(), priority.clone());
+        }
+    }
+
+    let mut inbound_all: HashMap<String, usize> = HashMap::new();
+    let mut inbound_from_included: HashMap<String, usize> = HashMap::new();
+
+    if let Some(db_path) = index_db_path {
+        if let Ok(conn) = rusqlite::Connection::open(db_path) {
+            if let Ok(mut stmt) = conn.prepare("SELECT source_path, target_path FROM file_imports")
+            {
+                if let Ok(rows) = stmt.query_map([], |row| {
+                    let source: String = row.get(0)?;
+                    let target: String = row.get(1)?;
+                    Ok((source, target))
+                }) {
+                    for (source, target) in rows.flatten() {
+                        if !dropped_set.contains(&target) {
+                            continue;
+                        }
+                        *inbound_all.entry(target.clone()).or_insert(0) += 1;
+                        if included_paths.contains(&source) {
+                            *inbound_from_included.entry(target).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, usize, usize)> = dropped_set
+        .iter()
+        .map(|path| {
+            (
+                path.clone(),
+                *inbound_all.get(path).unwrap_or(&0),
+                *inbound_from_included.get(path).unwrap_or(&0),
+            )
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| b.1.cmp(&a.1)).then_with(|| a.0.cmp(&b.0)));
+
+    ranked
+        .into_iter()
+        .take(10)
+        .map(|(path, inbound, inbound_included)| {
+            let reason =
+                dropped_reason.get(&path).cloned().unwrap_or_else(|| "unknown".to_string());
+            let priority = dropped_priority.get(&path).cloned().unwrap_or(json!(null));
+            json!({
+                "path": path,
+                "reason": reason,
+                "priority": priority,
+                "incoming_edges": inbound,
+                "incoming_edges_from_included": inbound_included,
+            })
+        })
+        .collect()
+}
+
+fn public_api_coverage(
+    root_path: &Path,
+    files: &[FileInfo],
+    stats: &ScanStats,
+    encodings: &EncodingOverrides,
+) -> serde_json::Value {
+    let included_pub_items = count_pub_items_in_files(files, encodings);
+
+    let mut estimated_total_pub_items = included_pub_items;
+    let mut dropped_rs_files = 0usize;
+    for dropped in &stats.dropped_files {
+        let Some(path) = dropped.get("path").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if !path.ends_with(".rs") {
+            continue;
+        }
+        dropped_rs_files += 1;
+        let encoding = encodings.encoding_for(path);
+        if let Ok((content, _)) = read_file_safe(&root_path.join(path), None, encoding) {
+            estimated_total_pub_items += count_pub_items_in_content(&content);
+        }
+    }
+
+    let coverage_ratio = if estimated_total_pub_items == 0 {
+        1.0
+    } else {
+        (included_pub_items as f64) / (estimated_total_pub_items as f64)
+    };
+
+    json!({
+        "language": "rust",
+        "included_pub_items": included_pub_items,
+        "estimated_total_pub_items": estimated_total_pub_items,
+        "coverage_ratio": ((coverage_ratio * 1000.0).round() / 1000.0),
+        "dropped_rust_files_considered": dropped_rs_files,
+        "notes": "Coverage counts Rust public items in included files and estimates dropped Rust files from disk when readable.",
+    })
+}
+
+fn count_pub_items_in_files(files: &[FileInfo], encodings: &EncodingOverrides) -> usize {
+    let mut included_pub_items = 0usize;
+    for file in files {
+        if file.extension != ".rs" {
+            continue;
+        }
+        let encoding = encodings.encoding_for(&file.relative_path);
+        if let Ok((content, _)) = read_file_safe(&file.path, None, encoding) {
+            included_pub_items += count_pub_items_in_content(&content);
+        }
+    }
+    included_pub_items
+}
+
+fn count_pub_items_in_content(content: &str) -> usize {
+    content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            trimmed.starts_with("pub ")
+                || trimmed.starts_with("pub(")
+                || trimmed.starts_with("pub(crate)")
+        })
+        .count()
+}
+
+fn hot_paths_from_tests_examples(chunks: &[Chunk]) -> Vec<serde_json::Value> {
+    let known_files: HashSet<String> = chunks.iter().map(|c| c.path.clone()).collect();
+    if known_files.is_empty() {
+        return Vec::new();
+    }
+    let defs = symbol_definitions(chunks);
+    let graph = dependency_graph(chunks, &known_files, &defs);
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (source, targets) in &graph {
+        if !is_test_or_example_path(source) {
+            continue;
+        }
+        for target in targets {
+            if is_test_or_example_path(target) {
+                continue;
+            }
+            *counts.entry(target.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+        .into_iter()
+        .take(10)
+        .map(|(path, refs)| json!({"path": path, "incoming_from_tests_examples": refs}))
+        .collect()
+}
+
+fn is_test_or_example_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.starts_with("tests/")
+        || lower.contains("/tests/")
+        || lower.starts_with("examples/")
+        || lower.contains("/examples/")
+        || lower.contains("_test")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_pub_items_in_files, most_imported_not_included};
+    use crate::domain::test_support::file_info;
+    use crate::domain::{Config, FileInfo};
+    use crate::utils::EncodingOverrides;
+    use rusqlite::Connection;
+    use std::collections::HashMap;
+
+    #[test]
+    fn pub_items_are_counted_in_override_charsets() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let path = tmp.path().join("lib.rs");
+        let utf16: Vec<u8> = "pub fn a() {}\npub struct B;\n"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        std::fs::write(&path, utf16).expect("write lib");
+        let files = [FileInfo { path, size_bytes: 56, ..file_info("legacy/lib.rs") }];
+
+        assert_eq!(count_pub_items_in_files(&files, &EncodingOverrides::default()), 0);
+        let config = Config {
+            encoding_overrides: vec![("legacy/**".to_string(), "utf-16le".to_string())],
+            ..Config::default()
+        };
+        let encodings = EncodingOverrides::from_config(&config).expect("overrides");
+        assert_eq!(count_pub_items_in_files(&files, &encodings), 2);
+    }
+
+    #[test]
+    fn most_imported_not_included_prefers_incoming_edges_from_included() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        let db = tmp.path().join("index.sqlite");
+        let conn = Connection::open(&db).expect("open db");
+        conn.execute(
+            "CREATE TABLE file_imports (source_path TEXT NOT NULL, target_path TEXT NOT NULL)",
+            [],
+        )
+        .expect("create table");
+        conn.execute(
+            "INSERT INTO file_imports (source_path, target_path) VALUES ('src/a.rs','src/x.rs')",
+            [],
+        )
+        .expect("insert edge 1");
+        conn.execute(
+            "INSERT INTO file_imports (source_path, target_path) VALUES ('src/b.rs','src/x.rs')",
+            [],
+        )
+        .expect("insert edge 2");
+        conn.execute(
+            "INSERT INTO file_imports (source_path, target_path) VALUES ('tests/t.rs','src/y.rs')",
+            [],
+        )
+        .expect("insert edge 3");
+
+        let dropped_paths = vec!["src/x.rs".to_string(), "src/y.rs".to_string()];
+        let included_paths = std::collections::HashSet::from(["src/a.rs".to_string()]);
+        let dropped_entries = vec![
+            HashMap::from([
+                ("path".to_string(), serde_json::json!("src/x.rs")),
+                ("reason".to_string(), serde_json::json!("token_budget")),
+            ]),
+            HashMap::from([
+                ("path".to_string(), serde_json::json!("src/y.rs")),
+                ("reason".to_string(), serde_json::json!("token_budget")),
+            ]),
+        ];
+
+        let rows = most_imported_not_included(
+            Some(&db),
+            &dropped_paths,
+            &included_paths,
+            &dropped_entries,
+        );
+
+        assert_eq!(rows[0]["path"], serde_json::json!("src/x.rs"));
+        assert_eq!(rows[0]["incoming_edges_from_included"], serde_json::json!(1));
+    }
+}
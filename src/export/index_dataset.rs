@@ -0,0 +1,184 @@
+//! Reading `repo-context index` databases for export: freshness checks and the file list
+//! they recorded.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::config::config_hash;
+use crate::domain::{Config, FileInfo, ScanStats};
+
+/// How the index database relates to the current config and commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IndexFreshness {
+    Fresh,
+    Stale,
+    Missing,
+    Error,
+}
+
+impl IndexFreshness {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Fresh => "fresh",
+            Self::Stale => "stale",
+            Self::Missing => "missing",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct IndexState {
+    pub(crate) kind: IndexFreshness,
+    pub(crate) db_path: Option<PathBuf>,
+    pub(crate) reason: Option<String>,
+}
+
+/// Whether the index at `index_db_path` was built with the current config and commit.
+pub(crate) fn evaluate_index_state(
+    index_db_path: Option<&Path>,
+    root_path: &Path,
+    merged: &Config,
+) -> IndexState {
+    let Some(db_path) = index_db_path else {
+        return IndexState {
+            kind: IndexFreshness::Missing,
+            db_path: None,
+            reason: Some("index.sqlite not found".to_string()),
+        };
+    };
+
+    let conn = match rusqlite::Connection::open(db_path) {
+        Ok(conn) => conn,
+        Err(err) => {
+            return IndexState {
+                kind: IndexFreshness::Error,
+                db_path: Some(db_path.to_path_buf()),
+                reason: Some(format!("failed opening index db: {err}")),
+            };
+        }
+    };
+
+    let metadata = load_index_metadata_map(&conn);
+    let expected_hash = config_hash(merged);
+    let stored_hash = metadata.get("config_hash").cloned().unwrap_or_default();
+    if stored_hash != expected_hash {
+        return IndexState {
+            kind: IndexFreshness::Stale,
+            db_path: Some(db_path.to_path_buf()),
+            reason: Some("config hash mismatch".to_string()),
+        };
+    }
+
+    let current_commit = git2::Repository::discover(root_path).ok().and_then(|repo| {
+        let head = repo.head().ok()?;
+        head.target().map(|oid| oid.to_string())
+    });
+    let stored_commit =
+        metadata.get("git_commit").cloned().unwrap_or_else(|| "unknown".to_string());
+    if stored_commit != "unknown" && current_commit.as_deref() != Some(stored_commit.as_str()) {
+        return IndexState {
+            kind: IndexFreshness::Stale,
+            db_path: Some(db_path.to_path_buf()),
+            reason: Some("git commit mismatch".to_string()),
+        };
+    }
+
+    IndexState {
+        kind: IndexFreshness::Fresh,
+        db_path: Some(db_path.to_path_buf()),
+        reason: Some("metadata matches current config and commit".to_string()),
+    }
+}
+
+/// Selected files and scan stats as recorded by `repo-context index`.
+pub(crate) fn load_files_and_stats_from_index(
+    db_path: &Path,
+    root_path: &Path,
+) -> Result<(ScanStats, Vec<FileInfo>)> {
+    let conn = rusqlite::Connection::open(db_path)
+        .with_context(|| format!("Failed to open index database at {}", db_path.display()))?;
+    let metadata = load_index_metadata_map(&conn);
+
+    let mut stmt = conn.prepare(
+        "SELECT path, language, extension, size_bytes, priority, token_estimate, mtime FROM files",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let rel_path: String = row.get(0)?;
+        let language: String = row.get(1)?;
+        let extension: String = row.get(2)?;
+        let size_bytes: i64 = row.get(3)?;
+        let priority: f64 = row.get(4)?;
+        let token_estimate: i64 = row.get(5)?;
+        let mtime: Option<i64> = row.get(6)?;
+        Ok((rel_path, language, extension, size_bytes, priority, token_estimate, mtime))
+    })?;
+
+    let mut files = Vec::new();
+    let mut languages_detected: HashMap<String, usize> = HashMap::new();
+    let mut total_bytes_included = 0_u64;
+    for row in rows {
+        let (relative_path, language, extension, size_bytes, priority, token_estimate, mtime) =
+            row?;
+        total_bytes_included = total_bytes_included.saturating_add(size_bytes.max(0) as u64);
+        *languages_detected.entry(language.clone()).or_insert(0) += 1;
+        files.push(FileInfo {
+            path: root_path.join(&relative_path),
+            relative_path: relative_path.clone(),
+            size_bytes: size_bytes.max(0) as u64,
+            extension,
+            language,
+            id: format!("idx:{relative_path}"),
+            priority,
+            token_estimate: token_estimate.max(0) as usize,
+            tags: BTreeSet::new(),
+            is_readme: false,
+            is_config: false,
+            is_doc: false,
+            modified_at: mtime,
+            priority_reason: None,
+        });
+    }
+
+    let files_scanned =
+        metadata.get("files_scanned").and_then(|v| v.parse::<usize>().ok()).unwrap_or(files.len());
+
+    let stats = ScanStats {
+        files_scanned,
+        files_included: files.len(),
+        total_bytes_scanned: total_bytes_included,
+        total_bytes_included,
+        languages_detected,
+        ..ScanStats::default()
+    };
+    Ok((stats, files))
+}
+
+fn load_index_metadata_map(conn: &rusqlite::Connection) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    if let Ok(mut stmt) = conn.prepare("SELECT key, value FROM metadata") {
+        if let Ok(rows) = stmt.query_map([], |row| {
+            let key: String = row.get(0)?;
+            let value: String = row.get(1)?;
+            Ok((key, value))
+        }) {
+            for (key, value) in rows.flatten() {
+                out.insert(key, value);
+            }
+        }
+    }
+    out
+}
+
+/// Symbol and import-edge counts of the symbol graph at `db_path`.
+pub(crate) fn query_graph_stats(db_path: &Path) -> Option<(usize, usize)> {
+    let conn = rusqlite::Connection::open(db_path).ok()?;
+    let symbols = conn
+        .query_row("SELECT COUNT(*) FROM symbol_chunks", [], |row| row.get::<_, i64>(0))
+        .ok()? as usize;
+    let edges = conn
+        .query_row("SELECT COUNT(*) FROM file_imports", [], |row| row.get::<_, i64>(0))
+        .ok()? as usize;
+    Some((symbols, edges))
+}
@@ -0,0 +1,228 @@
+//! Library entry point for exports that stay in memory.
+//!
+//! [`export_to_strings`] runs the same chunking, budgeting, reranking and rendering as
+//! `repo-context export`, but over files the caller has already loaded, and returns the
+//! rendered artifacts instead of writing them. `cli::export::run` scans, fetches and
+//! persists around the shared [`pipeline`].
+
+pub(crate) mod coverage;
+pub(crate) mod index_dataset;
+pub(crate) mod pins;
+pub(crate) mod pipeline;
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::domain::{Config, FileInfo, ScanStats};
+use crate::render::{render_context_pack, render_jsonl, report_value, resolve_section_order};
+use crate::render::{PackRenderOptions, ReportOptions};
+use crate::scan::tree::generate_tree_from_paths;
+use pipeline::{apply_byte_budget, build_pack, report_config, PackSources};
+
+/// Default for `--max-dropped-records`.
+pub const DEFAULT_MAX_DROPPED_RECORDS: usize = 1000;
+
+/// A file to export together with its (already decoded) content.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub info: FileInfo,
+    pub content: String,
+}
+
+/// What an in-memory export produces; nothing is written to disk.
+#[derive(Debug, Clone)]
+pub struct ExportArtifacts {
+    /// Rendered `context_pack.md`
+    pub context_pack: String,
+    /// Rendered `chunks.jsonl`
+    pub jsonl: String,
+    pub stats: ScanStats,
+    /// `report.json` as a value
+    pub report: Value,
+}
+
+/// Export settings that are command-line flags rather than `Config` fields.
+///
+/// `Default` matches `repo-context export` without those flags, except that the
+/// timestamp is left out so identical inputs give byte-identical artifacts.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Name shown in the pack header and at the root of the tree
+    pub repo_name: String,
+    /// Keep only the first N lines of each file (`--head-lines`)
+    pub head_lines: Option<usize>,
+    pub normalize_unicode: bool,
+    pub summarize_schemas: bool,
+    pub stub_dropped: bool,
+    /// Merge adjacent chunks up to this multiple of `chunk_tokens` (`--merge-adjacent`)
+    pub merge_adjacent: Option<f64>,
+    pub allow_over_budget: bool,
+    pub strict_budget: bool,
+    pub dedup_license_headers: bool,
+    pub token_report: bool,
+    pub max_dropped_records: usize,
+    /// Repository-relative files to rerank around (`--seed-file`)
+    pub seed_files: Vec<String>,
+    pub include_timestamp: bool,
+    pub toc: bool,
+    pub group_by_directory: bool,
+    pub annotate_splits: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            repo_name: "repo".to_string(),
+            head_lines: None,
+            normalize_unicode: false,
+            summarize_schemas: false,
+            stub_dropped: false,
+            merge_adjacent: None,
+            allow_over_budget: false,
+            strict_budget: false,
+            dedup_license_headers: false,
+            token_report: false,
+            max_dropped_records: DEFAULT_MAX_DROPPED_RECORDS,
+            seed_files: Vec::new(),
+            include_timestamp: false,
+            toc: false,
+            group_by_directory: false,
+            annotate_splits: false,
+        }
+    }
+}
+
+/// Export `files` with default [`ExportOptions`], returning the rendered artifacts.
+///
+/// Files are budgeted in `priority` order (ties by path), so rank them first, e.g. with
+/// [`FileRanker`](crate::rank::FileRanker), when the budget may drop some.
+pub fn export_to_strings(config: &Config, files: Vec<SourceFile>) -> Result<ExportArtifacts> {
+    export_to_strings_with_options(config, files, &ExportOptions::default())
+}
+
+/// Like [`export_to_strings`], with the command-line-only settings spelled out.
+pub fn export_to_strings_with_options(
+    config: &Config,
+    files: Vec<SourceFile>,
+    options: &ExportOptions,
+) -> Result<ExportArtifacts> {
+    let section_order = resolve_section_order(&config.section_order)
+        .map_err(|err| anyhow::anyhow!("Invalid section_order: {err}"))?;
+
+    let mut contents: HashMap<String, String> = HashMap::with_capacity(files.len());
+    let mut infos: Vec<FileInfo> = Vec::with_capacity(files.len());
+    for SourceFile { info, content } in files {
+        contents.insert(info.relative_path.clone(), content);
+        infos.push(info);
+    }
+    infos.sort_by(|a, b| {
+        b.priority
+            .partial_cmp(&a.priority)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    let mut stats = ScanStats {
+        files_scanned: infos.len(),
+        files_included: infos.len(),
+        total_bytes_included: infos.iter().map(|f| f.size_bytes).sum(),
+        max_dropped_records: Some(options.max_dropped_records),
+        ..Default::default()
+    };
+    let selected =
+        apply_byte_budget(infos, Some(config.max_total_bytes), &mut stats, &HashSet::new());
+
+    let pack = build_pack(config, selected, stats, options, &contents, &PackSources::default())?;
+
+    let highlight: HashSet<String> =
+        pack.files.iter().filter(|f| f.priority >= 0.8).map(|f| f.relative_path.clone()).collect();
+    let tree = generate_tree_from_paths(
+        &options.repo_name,
+        pack.files.iter().map(|f| f.relative_path.as_str()),
+        config.tree_depth,
+        &highlight,
+    );
+    let context_pack = render_context_pack(
+        Path::new(&options.repo_name),
+        &pack.files,
+        &pack.chunks,
+        &contents,
+        &pack.stats,
+        &tree,
+        PackRenderOptions {
+            include_timestamp: options.include_timestamp,
+            task_query: config.task_query.as_deref(),
+            include_toc: options.toc,
+            group_by_directory: options.group_by_directory,
+            annotate_splits: options.annotate_splits,
+            section_order: &section_order,
+            ..Default::default()
+        },
+    );
+    let jsonl = render_jsonl(&pack.chunks);
+
+    let report_config = report_config(config, options, &section_order, &pack);
+    let report = report_value(
+        &pack.stats,
+        &pack.files,
+        &[],
+        &report_config,
+        ReportOptions { include_timestamp: options.include_timestamp, ..Default::default() },
+    )?;
+
+    Ok(ExportArtifacts { context_pack, jsonl, stats: pack.stats, report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_to_strings, SourceFile};
+    use crate::domain::test_support::file_info;
+    use crate::domain::{Config, FileInfo};
+
+    fn source(path: &str, priority: f64, content: &str) -> SourceFile {
+        SourceFile {
+            info: FileInfo {
+                // Never read: contents come from `SourceFile::content`.
+                path: format!("/nonexistent/{path}").into(),
+                size_bytes: content.len() as u64,
+                priority,
+                ..file_info(path)
+            },
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn exports_in_memory_files_deterministically() {
+        let files = || {
+            vec![
+                source("src/lib.rs", 0.7, "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n"),
+                SourceFile {
+                    info: FileInfo { is_readme: true, ..source("README.md", 0.9, "").info },
+                    content: "# Demo\n\nAdds numbers.\n".to_string(),
+                },
+            ]
+        };
+        let config = Config::default();
+
+        let first = export_to_strings(&config, files()).expect("export");
+        let second = export_to_strings(&config, files().into_iter().rev().collect())
+            .expect("export reversed");
+
+        assert_eq!(first.context_pack, second.context_pack);
+        assert_eq!(first.jsonl, second.jsonl);
+        assert_eq!(first.report, second.report);
+        assert!(first.context_pack.contains("pub fn add"));
+        assert!(first.context_pack.contains("└── lib.rs"));
+        // The README excerpt comes from the given content, not from disk.
+        assert!(first.context_pack.contains("**README Excerpt:**\n\n# Demo\n\nAdds numbers."));
+        assert_eq!(first.jsonl.lines().count(), first.stats.chunks_created);
+        assert_eq!(first.report["stats"]["files_included"], 2);
+        assert!(first.report.get("generated_at").is_none());
+        // The same pipeline settings the CLI reports.
+        assert_eq!(first.report["config"]["chunk_tokens"], config.chunk_tokens);
+        assert_eq!(first.report["config"]["section_order"][0], "header");
+        assert_eq!(first.report["config"]["stub_dropped"], false);
+    }
+}
@@ -0,0 +1,326 @@
+//! Protected files of contribution and pr-context exports, in tiers that decide how the
+//! pipeline budgets them.
+
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::pipeline::{build_globset, Placement};
+use crate::domain::FileInfo;
+use crate::utils::{read_file_safe, EncodingOverrides};
+
+/// Globs contribution and pr-context exports always include.
+pub(crate) fn default_contribution_globs() -> Vec<String> {
+    [
+        "examples/**",
+        ".github/PULL_REQUEST_TEMPLATE*",
+        ".github/ISSUE_TEMPLATE/**",
+        ".github/workflows/**",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// Paths contribution and pr-context exports always include.
+pub(crate) fn default_contribution_paths() -> Vec<String> {
+    [
+        "README.md",
+        "CONTRIBUTING.md",
+        "CONTRIBUTING.rst",
+        "SECURITY.md",
+        "CODE_OF_CONDUCT.md",
+        "Cargo.toml",
+        "pyproject.toml",
+        "package.json",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PinTier {
+    Tier0,
+    Tier1,
+    Tier2,
+}
+
+impl PinTier {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Tier0 => "tier0",
+            Self::Tier1 => "tier1",
+            Self::Tier2 => "tier2",
+        }
+    }
+}
+
+/// Pin tier and the reason for it, per repository-relative path.
+#[derive(Debug, Clone)]
+pub(crate) struct PinPlan {
+    tiers: HashMap<String, PinTier>,
+    reasons: HashMap<String, String>,
+}
+
+impl PinPlan {
+    pub(crate) fn protected_paths(&self) -> HashSet<String> {
+        self.tiers
+            .iter()
+            .filter_map(|(path, tier)| {
+                if matches!(tier, PinTier::Tier0 | PinTier::Tier1) {
+                    Some(path.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// How the export pipeline budgets each pinned path.
+    pub(crate) fn placements(&self) -> HashMap<String, Placement> {
+        self.tiers
+            .iter()
+            .map(|(path, tier)| {
+                let placement = match tier {
+                    PinTier::Tier0 | PinTier::Tier1 => Placement::Always,
+                    PinTier::Tier2 => Placement::Advisory,
+                };
+                (path.clone(), placement)
+            })
+            .collect()
+    }
+
+    pub(crate) fn tier_for(&self, path: &str) -> Option<PinTier> {
+        self.tiers.get(path).copied()
+    }
+
+    pub(crate) fn reason_for(&self, path: &str) -> Option<&str> {
+        self.reasons.get(path).map(String::as_str)
+    }
+
+    fn promote(&mut self, path: &str, tier: PinTier, reason: String) {
+        let should_update = match self.tiers.get(path) {
+            None => true,
+            Some(existing) => tier_rank(tier) < tier_rank(*existing),
+        };
+        if should_update {
+            self.tiers.insert(path.to_string(), tier);
+            self.reasons.insert(path.to_string(), reason);
+        }
+    }
+}
+
+fn tier_rank(tier: PinTier) -> u8 {
+    match tier {
+        PinTier::Tier0 => 0,
+        PinTier::Tier1 => 1,
+        PinTier::Tier2 => 2,
+    }
+}
+
+/// Pin explicit paths and contract files (tier 0), explicit globs (tier 1, or tier 2 past
+/// the first 80 matches) and files that score high on invariant keywords.
+pub(crate) fn build_pin_plan(
+    root_path: &Path,
+    ranked_files: &[FileInfo],
+    encodings: &EncodingOverrides,
+    explicit_paths: &[String],
+    explicit_globs: &[String],
+    invariant_keywords: &[String],
+) -> Result<PinPlan> {
+    const MAX_GLOB_PROTECTED: usize = 80;
+
+    let mut plan = PinPlan { tiers: HashMap::new(), reasons: HashMap::new() };
+    let normalized_paths: HashSet<String> =
+        explicit_paths.iter().map(|p| normalize_rel_path(p)).filter(|p| !p.is_empty()).collect();
+
+    for file in ranked_files {
+        let path = file.relative_path.as_str();
+        if normalized_paths.contains(path) {
+            plan.promote(path, PinTier::Tier0, "explicit_path".to_string());
+        }
+        if is_tier0_contract_path(path) {
+            plan.promote(path, PinTier::Tier0, "auto_contract_path".to_string());
+        }
+    }
+
+    let globset = build_globset(explicit_globs)?;
+    if let Some(globset) = globset {
+        let mut matches: Vec<&FileInfo> =
+            ranked_files.iter().filter(|f| globset.is_match(&f.relative_path)).collect();
+        matches.sort_by(|a, b| {
+            b.priority
+                .partial_cmp(&a.priority)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.relative_path.cmp(&b.relative_path))
+        });
+
+        for (idx, file) in matches.into_iter().enumerate() {
+            if idx < MAX_GLOB_PROTECTED {
+                plan.promote(&file.relative_path, PinTier::Tier1, "explicit_glob".to_string());
+            } else {
+                plan.promote(
+                    &file.relative_path,
+                    PinTier::Tier2,
+                    "explicit_glob_rate_limited".to_string(),
+                );
+            }
+        }
+    }
+
+    for file in ranked_files {
+        if plan.tier_for(&file.relative_path) == Some(PinTier::Tier0) {
+            continue;
+        }
+        let Some((score, evidence)) =
+            invariant_score(root_path, file, invariant_keywords, encodings)
+        else {
+            continue;
+        };
+        if score >= 9 {
+            plan.promote(
+                &file.relative_path,
+                PinTier::Tier1,
+                format!("invariant_score:{score} ({evidence})"),
+            );
+        } else if score >= 6 {
+            plan.promote(
+                &file.relative_path,
+                PinTier::Tier2,
+                format!("invariant_score:{score} ({evidence})"),
+            );
+        }
+    }
+
+    Ok(plan)
+}
+
+fn normalize_rel_path(path: &str) -> String {
+    path.trim().trim_start_matches("./").replace('\\', "/")
+}
+
+fn is_tier0_contract_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    let file = lower.rsplit('/').next().unwrap_or(&lower);
+    if ["readme.md", "contributing.md", "security.md", "code_of_conduct.md"].contains(&file) {
+        return true;
+    }
+    if lower == "cargo.toml" || lower.ends_with("/cargo.toml") {
+        return true;
+    }
+    lower.ends_with("schema.json")
+        || lower.ends_with("schema.yaml")
+        || lower.ends_with("schema.yml")
+}
+
+fn invariant_score(
+    root_path: &Path,
+    file: &FileInfo,
+    keywords: &[String],
+    encodings: &EncodingOverrides,
+) -> Option<(usize, String)> {
+    let mut score = 0usize;
+    let mut evidence = Vec::new();
+    let path_lower = file.relative_path.to_ascii_lowercase();
+
+    if file.is_readme {
+        score += 2;
+        evidence.push("readme");
+    }
+    if file.is_config {
+        score += 3;
+        evidence.push("config");
+    }
+    if file.is_doc {
+        score += 2;
+        evidence.push("doc");
+    }
+    if file.tags.contains("entrypoint") {
+        score += 2;
+        evidence.push("entrypoint");
+    }
+
+    for (needle, weight) in [
+        ("schema", 3usize),
+        ("contract", 3),
+        ("invariant", 3),
+        ("api", 2),
+        ("error", 2),
+        ("architecture", 3),
+        ("example", 2),
+        ("safety", 3),
+    ] {
+        if path_lower.contains(needle) {
+            score += weight;
+            evidence.push(needle);
+        }
+    }
+
+    if !(file.is_doc || file.is_config || path_lower.contains("test")) && score < 6 {
+        return None;
+    }
+
+    if file.size_bytes <= 256_000 {
+        let encoding = encodings.encoding_for(&file.relative_path);
+        if let Ok((content, _)) =
+            read_file_safe(&root_path.join(&file.relative_path), None, encoding)
+        {
+            let lower = content.to_ascii_lowercase();
+            let mut keyword_hits = 0usize;
+            for keyword in keywords {
+                let k = keyword.to_ascii_lowercase();
+                if k.is_empty() {
+                    continue;
+                }
+                if lower.contains(&k) {
+                    keyword_hits += 1;
+                }
+            }
+            if keyword_hits > 0 {
+                score += keyword_hits.min(4);
+                evidence.push("keywords");
+            }
+        }
+    }
+
+    if score == 0 {
+        None
+    } else {
+        Some((score, evidence.join("+")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_pin_plan, PinTier};
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+    use crate::utils::EncodingOverrides;
+
+    #[test]
+    fn pin_plan_marks_explicit_paths_as_tier0() {
+        let tmp = tempfile::TempDir::new().expect("tmp");
+        std::fs::write(tmp.path().join("README.md"), "# Readme\n").expect("write readme");
+        let file = FileInfo {
+            path: tmp.path().join("README.md"),
+            size_bytes: 8,
+            priority: 0.9,
+            token_estimate: 4,
+            is_readme: true,
+            is_doc: true,
+            ..file_info("README.md")
+        };
+        let plan = build_pin_plan(
+            tmp.path(),
+            &[file],
+            &EncodingOverrides::default(),
+            &["README.md".to_string()],
+            &[],
+            &["must".to_string()],
+        )
+        .expect("pin plan");
+
+        assert_eq!(plan.tier_for("README.md"), Some(PinTier::Tier0));
+    }
+}
@@ -0,0 +1,1315 @@
+//! Chunking, token budgeting and reranking shared by `repo-context export` and
+//! [`export_to_strings`](super::export_to_strings).
+//!
+//! The pipeline takes ranked files and yields the chunks that make up the pack. It never
+//! writes anything; file contents come from a [`ContentSource`], so the CLI reads the
+//! working tree while library callers hand over content they already hold.
+
+use anstream::eprintln;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use super::ExportOptions;
+use crate::analysis::async_boundary::detect_async_boundaries;
+use crate::analysis::license_header::{HeaderElision, LicenseHeaderDedup};
+use crate::chunk::{
+    chunk_content_with_tag_queries, coalesce_small_chunks_with_max,
+    code_chunker::{DefinitionKinds, ParseLimits, PARSE_FALLBACK_TAG, PARSE_SKIPPED_TAG},
+    merge_adjacent_chunks,
+    schema::summarize_schema,
+    MinChunkTokens,
+};
+use crate::domain::{
+    Chunk, Config, FileInfo, OutputMode, RedactionConfig, RedactionMode, ScanStats,
+};
+use crate::graph::lazy_loader::LazyChunkLoader;
+use crate::rank::{
+    max_chunk_priority_by_file, rerank_chunks, stitch_thread_bundles, ExpansionOptions, StitchTier,
+};
+use crate::redact::Redactor;
+use crate::render::context_pack::PackSection;
+use crate::rerank::{build_reranker, normalize_scores};
+use crate::utils::unicode::{has_suspicious_unicode, normalize_unicode};
+use crate::utils::{estimate_tokens, stable_hash, ContentSource};
+
+/// Tag on the first-chunk stand-ins for budget-dropped files (`--stub-dropped`).
+const STUB_TAG: &str = "stub";
+
+/// Stubs rank below the file's real chunks would have.
+const STUB_PRIORITY_FACTOR: f64 = 0.5;
+
+/// How a pinned file is charged against the token budget; other files are budgeted in
+/// rank order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placement {
+    /// Chunked first and kept even over budget (tier-0/1 pins, always-include globs).
+    Always,
+    /// Budgeted ahead of other files (tier-2 contribution pins).
+    Advisory,
+}
+
+/// Inputs only the CLI has: an index to read chunks from and contribution-mode pins.
+#[derive(Default)]
+pub(crate) struct PackSources<'a> {
+    pub lazy_loader: Option<&'a LazyChunkLoader>,
+    /// Take each file's chunks from the index when it has them.
+    pub chunks_from_index: bool,
+    /// Placement for pinned paths; other files follow `always_include_patterns`.
+    pub pins: HashMap<String, Placement>,
+    pub workspace_members: Vec<String>,
+    /// Record every redaction in `Pack::redaction_audit` (`--redaction-audit`).
+    pub redaction_audit: bool,
+}
+
+/// One redaction made while chunking: the file, the rule that fired and the file line the
+/// match starts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RedactionAuditEntry {
+    pub path: String,
+    pub rule: String,
+    pub line: usize,
+}
+
+/// Chunks chosen for the pack, with the files and stats that describe them.
+pub(crate) struct Pack {
+    /// Selected files, re-sorted by final priority when reranking ran.
+    pub files: Vec<FileInfo>,
+    pub chunks: Vec<Chunk>,
+    pub stats: ScanStats,
+    pub reranking_mode: Option<String>,
+    /// Seed files found among the exported chunks.
+    pub seed_files: Vec<String>,
+    pub per_file_token_cap: Option<usize>,
+    pub stitch_tokens_used: usize,
+    pub stitched_unavailable_chunks: usize,
+    /// Dropped-file records before `max_dropped_records` capped them.
+    pub dropped_total: usize,
+    /// Redactions inside the kept chunks, when `PackSources::redaction_audit` asked for them.
+    pub redaction_audit: Vec<RedactionAuditEntry>,
+}
+
+/// Chunk, budget and rerank `files` (in rank order) into the chunks of a pack.
+pub(crate) fn build_pack(
+    config: &Config,
+    mut files: Vec<FileInfo>,
+    mut stats: ScanStats,
+    options: &ExportOptions,
+    content: &dyn ContentSource,
+    sources: &PackSources<'_>,
+) -> Result<Pack> {
+    let contribution_mode = matches!(config.mode, OutputMode::Contribution | OutputMode::PrContext);
+    let chunk_tokens = config.chunk_tokens;
+    stats.max_dropped_records = Some(options.max_dropped_records);
+    let mut chunking = FileChunking {
+        content,
+        lazy_loader: sources.lazy_loader,
+        chunks_from_index: sources.chunks_from_index,
+        redactor: config
+            .redact_secrets
+            .then(|| build_redactor(config.redaction_mode, &config.redaction)),
+        config,
+        chunk_overlap: config.chunk_overlap.resolve(chunk_tokens),
+        parse_limits: ParseLimits::from_config(config),
+        definition_kinds: DefinitionKinds::from_config(config),
+        head_lines: options.head_lines,
+        redaction_audit: sources.redaction_audit.then(Vec::new),
+    };
+    let always_include =
+        if contribution_mode { None } else { build_globset(&config.always_include_patterns)? };
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut always_indices = Vec::new();
+    let mut advisory_indices = Vec::new();
+    let mut normal_indices = Vec::new();
+    for (idx, file) in files.iter().enumerate() {
+        match sources.pins.get(&file.relative_path) {
+            Some(Placement::Always) => always_indices.push(idx),
+            Some(Placement::Advisory) => advisory_indices.push(idx),
+            _ if always_include.as_ref().is_some_and(|g| g.is_match(&file.relative_path)) => {
+                always_indices.push(idx)
+            }
+            _ => normal_indices.push(idx),
+        }
+    }
+
+    // Headers are elided before budgeting so the savings make room for more files.
+    let mut license_headers = options.dedup_license_headers.then(LicenseHeaderDedup::default);
+
+    let mut always_tokens = 0usize;
+    for idx in always_indices {
+        if let Some(mut file_chunks) = chunking.process(&mut files[idx], &mut stats)? {
+            apply_unicode_hygiene(&mut files[idx], &mut file_chunks, options.normalize_unicode);
+            if options.summarize_schemas {
+                apply_schema_summary(&mut files[idx], &mut file_chunks);
+            }
+            let elision = license_headers
+                .as_ref()
+                .map_or(HeaderElision::Unchanged, |dedup| dedup.elide(&mut file_chunks));
+            if let Some(dedup) = license_headers.as_mut() {
+                dedup.keep(elision, &files[idx].relative_path, &mut stats);
+            }
+            let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
+            always_tokens += file_tokens;
+            chunks.extend(file_chunks);
+        }
+    }
+
+    // `--reserve-tokens` comes off the top, so every budget below sees the smaller limit.
+    if let Some(max_tokens) = config.max_tokens {
+        if config.reserve_tokens >= max_tokens {
+            anyhow::bail!(
+                "reserve_tokens ({}) must be smaller than max_tokens ({max_tokens})",
+                config.reserve_tokens
+            );
+        }
+    }
+    let budget_max_tokens = config.max_tokens.map(|max| max.saturating_sub(config.reserve_tokens));
+    stats.max_tokens = config.max_tokens;
+    if config.max_tokens.is_some() {
+        stats.reserved_tokens = config.reserve_tokens;
+    }
+
+    let mut pinned_only_mode = false;
+    if let Some(max_tokens) = budget_max_tokens {
+        if always_tokens > max_tokens {
+            let overflow = always_tokens.saturating_sub(max_tokens);
+            stats.pinned_overflow_tokens = overflow;
+            if contribution_mode {
+                if options.strict_budget {
+                    anyhow::bail!(
+                        "protected pin files require {always_tokens} tokens but max_tokens={max_tokens}; increase budget or remove --strict-budget"
+                    );
+                }
+                pinned_only_mode = true;
+                stats.pinned_only_mode = true;
+                eprintln!(
+                    "info: protected pins exceed max_tokens by {overflow}; writing pinned-only contribution pack"
+                );
+            } else if !options.allow_over_budget {
+                anyhow::bail!(
+                    "always-include files require {always_tokens} tokens but max_tokens={max_tokens}; use --allow-over-budget to proceed"
+                );
+            }
+        }
+    }
+
+    let mut normal_tokens = 0usize;
+    // Chunks of files dropped for the token budget, which stitching may load back.
+    let mut budget_dropped_chunks = 0usize;
+    let mut remaining_budget = budget_max_tokens.map(|max| max.saturating_sub(always_tokens));
+    if let (Some(max_tokens), Some(rest)) = (budget_max_tokens, remaining_budget) {
+        if always_tokens > max_tokens {
+            eprintln!(
+                "Warning: always-include files use {} tokens above max_tokens={} (remaining budget: {})",
+                always_tokens.saturating_sub(max_tokens),
+                max_tokens,
+                rest
+            );
+            remaining_budget = Some(0);
+        }
+    }
+
+    let per_file_token_cap =
+        resolve_per_file_token_cap(config.max_tokens_per_file, budget_max_tokens)?;
+    let mut path_budgets = PathTokenBudgets::from_config(config)?;
+    let mut budgeted_indices = Vec::new();
+    if !pinned_only_mode {
+        budgeted_indices.extend(advisory_indices);
+        budgeted_indices.extend(normal_indices);
+    }
+
+    let mut stubs: Vec<(Chunk, HeaderElision, usize, Option<usize>)> = Vec::new();
+    for idx in budgeted_indices {
+        let Some(mut file_chunks) = chunking.process(&mut files[idx], &mut stats)? else {
+            continue;
+        };
+        apply_unicode_hygiene(&mut files[idx], &mut file_chunks, options.normalize_unicode);
+        if options.summarize_schemas {
+            apply_schema_summary(&mut files[idx], &mut file_chunks);
+        }
+        let elision = license_headers
+            .as_ref()
+            .map_or(HeaderElision::Unchanged, |dedup| dedup.elide(&mut file_chunks));
+
+        if let Some(cap) = per_file_token_cap {
+            let (kept, capped) = split_chunks_at_token_cap(file_chunks, cap);
+            if !capped.is_empty() {
+                let kept_tokens: usize = kept.iter().map(|c| c.token_estimate).sum();
+                let mut record = HashMap::from([
+                    ("path".to_string(), json!(files[idx].relative_path)),
+                    ("reason".to_string(), json!("per_file_token_cap")),
+                    (
+                        "priority".to_string(),
+                        json!((files[idx].priority * 1000.0).round() / 1000.0),
+                    ),
+                    (
+                        "tokens".to_string(),
+                        json!(capped.iter().map(|c| c.token_estimate).sum::<usize>()),
+                    ),
+                    ("chunks".to_string(), json!(capped.len())),
+                ]);
+                record.extend(budget_state("tokens", kept_tokens as u64, cap as u64));
+                stats.push_dropped_record(record);
+                files[idx].token_estimate = kept_tokens;
+            }
+            if kept.is_empty() {
+                continue;
+            }
+            file_chunks = kept;
+        }
+
+        let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
+        let path_budget = path_budgets.matching(&files[idx].relative_path);
+        if let Some((glob, used, limit)) =
+            path_budget.and_then(|slot| path_budgets.overflow(slot, file_tokens))
+        {
+            stats.files_dropped_budget += 1;
+            let mut record = HashMap::from([
+                ("path".to_string(), json!(files[idx].relative_path)),
+                ("reason".to_string(), json!(format!("path_token_budget:{glob}"))),
+                ("priority".to_string(), json!((files[idx].priority * 1000.0).round() / 1000.0)),
+                ("tokens".to_string(), json!(file_tokens)),
+                ("chunks".to_string(), json!(file_chunks.len())),
+            ]);
+            record.extend(budget_state("tokens", used as u64, limit as u64));
+            stats.push_dropped_record(record);
+            continue;
+        }
+        if let Some(budget) = remaining_budget {
+            if normal_tokens + file_tokens > budget {
+                stats.files_dropped_budget += 1;
+                let advisory =
+                    sources.pins.get(&files[idx].relative_path) == Some(&Placement::Advisory);
+                let mut record = HashMap::from([
+                    ("path".to_string(), json!(files[idx].relative_path)),
+                    (
+                        "reason".to_string(),
+                        json!(if advisory { "token_budget_tier2" } else { "token_budget" }),
+                    ),
+                    (
+                        "priority".to_string(),
+                        json!((files[idx].priority * 1000.0).round() / 1000.0),
+                    ),
+                    ("tokens".to_string(), json!(file_tokens)),
+                    ("chunks".to_string(), json!(file_chunks.len())),
+                ]);
+                // Measured against the whole budget, so always-include tokens count as used.
+                let max_tokens = budget_max_tokens.unwrap_or(budget);
+                record.extend(budget_state(
+                    "tokens",
+                    (max_tokens.saturating_sub(budget) + normal_tokens) as u64,
+                    max_tokens as u64,
+                ));
+                let record = stats.push_dropped_record(record);
+                if !advisory {
+                    budget_dropped_chunks += file_chunks.len();
+                }
+                if options.stub_dropped {
+                    if let Some(first) = file_chunks.into_iter().min_by_key(|c| c.start_line) {
+                        stubs.push((first, elision, idx, record));
+                    }
+                }
+                continue;
+            }
+        }
+        if let Some(slot) = path_budget {
+            path_budgets.charge(slot, file_tokens);
+        }
+        normal_tokens += file_tokens;
+        if let Some(dedup) = license_headers.as_mut() {
+            dedup.keep(elision, &files[idx].relative_path, &mut stats);
+        }
+        chunks.extend(file_chunks);
+    }
+
+    // Stubs only take budget left over once every full file has been placed, and are
+    // charged to their path's budget like any other chunk.
+    for (mut stub, elision, idx, record) in stubs {
+        if remaining_budget.is_some_and(|budget| normal_tokens + stub.token_estimate > budget) {
+            continue;
+        }
+        let path_budget = path_budgets.matching(&files[idx].relative_path);
+        if path_budget
+            .is_some_and(|slot| path_budgets.overflow(slot, stub.token_estimate).is_some())
+        {
+            continue;
+        }
+        if let Some(slot) = path_budget {
+            path_budgets.charge(slot, stub.token_estimate);
+        }
+        if let Some(dedup) = license_headers.as_mut() {
+            dedup.keep(elision, &files[idx].relative_path, &mut stats);
+        }
+        stub.priority = ((stub.priority * STUB_PRIORITY_FACTOR) * 1000.0).round() / 1000.0;
+        stub.tags.insert(STUB_TAG.to_string());
+        normal_tokens += stub.token_estimate;
+        stats.files_dropped_budget -= 1;
+        stats.files_stubbed += 1;
+        if let Some(record) = record.and_then(|i| stats.dropped_files.get_mut(i)) {
+            record.insert("stubbed".to_string(), json!(true));
+        }
+        chunks.push(stub);
+    }
+
+    let max_chunk_lines = config.max_chunk_lines;
+    let min_chunk_tokens = MinChunkTokens::from_config(config);
+    chunks =
+        coalesce_small_chunks_with_max(chunks, min_chunk_tokens, chunk_tokens, max_chunk_lines);
+    if let Some(merge_factor) = options.merge_adjacent {
+        let merge_cap = (chunk_tokens as f64 * merge_factor).round() as usize;
+        chunks = merge_adjacent_chunks(chunks, merge_cap, max_chunk_lines);
+    }
+
+    let mut reranking_mode: Option<String> = None;
+    let mut stitch_tokens_used = 0usize;
+    let mut stitched_unavailable_chunks: usize = 0;
+    let seed_files = resolve_seed_files(&options.seed_files, &chunks);
+    if config.task_query.is_some() || !seed_files.is_empty() {
+        let expansion = ExpansionOptions {
+            seeds: config.expand_seeds,
+            depth: config.expand_depth,
+            decay: config.expand_decay.clone(),
+        };
+        let task_query = config.task_query.as_deref();
+        rerank_chunks(&mut chunks, task_query, &seed_files, 0.4, &expansion);
+        let mode = match (seed_files.is_empty(), task_query.is_some()) {
+            (false, false) => "seeds+deps",
+            (true, _) => "bm25+deps",
+            (false, true) => "seeds+bm25+deps",
+        };
+        reranking_mode = Some(mode.to_string());
+        sort_chunks_by_priority(&mut chunks);
+
+        let reason = if config.task_query.is_some() { "bm25" } else { "seed" };
+        for (idx, chunk) in chunks.iter_mut().enumerate() {
+            chunk.tags.insert(format!("reason:{reason}(rank={})", idx + 1));
+        }
+
+        if let (true, Some(task_query)) = (config.semantic_rerank, config.task_query.as_deref()) {
+            let reranker = build_reranker(config.semantic_model.as_deref());
+            let top_k = config.rerank_top_k.min(chunks.len());
+            let semantic_scores = reranker.rerank(task_query, &chunks[..top_k])?;
+            let normalized = normalize_scores(&semantic_scores);
+            for (chunk, score) in chunks[..top_k].iter_mut().zip(normalized) {
+                chunk.priority =
+                    (((chunk.priority * 0.6) + (score * 0.4)) * 1000.0).round() / 1000.0;
+                chunk.tags.insert(format!("reason:semantic(score={:.3})", score));
+            }
+            sort_chunks_by_priority(&mut chunks);
+            reranking_mode = Some(format!("bm25+{}", reranker.name()));
+        }
+
+        if let Some(max_tokens) = budget_max_tokens {
+            let effective_tokens = max_tokens.saturating_sub(always_tokens);
+            let budget =
+                ((effective_tokens as f64) * config.stitch_budget_fraction).round() as usize;
+            let stitch = stitch_thread_bundles(
+                &chunks,
+                config.stitch_top_n,
+                budget,
+                sources.lazy_loader,
+                &sources.workspace_members,
+            );
+            if !stitch.lazy_chunks.is_empty() {
+                chunks.extend(stitch.lazy_chunks.iter().cloned());
+            }
+            for chunk in &mut chunks {
+                if let Some(tier) = stitch.stitched.get(&chunk.id) {
+                    chunk.tags.insert(format!("stitch:{}", tier.as_str()));
+                    chunk.tags.insert(format!("reason:stitched({})", tier.as_str()));
+                }
+            }
+            stats.stitched_chunks = stitch.stitched.len();
+            stitch_tokens_used = stitch.tokens_used;
+            stitched_unavailable_chunks =
+                budget_dropped_chunks.saturating_sub(stitch.lazy_chunks.len());
+
+            sort_chunks_for_stitch_story(&mut chunks, &stitch.seed_ids, &stitch.stitched);
+        }
+
+        // A file takes its best final chunk priority, so the report, tree highlight and
+        // top_ranked_files all agree with the reranked chunk order.
+        let chunk_priorities = max_chunk_priority_by_file(&chunks);
+        for file in &mut files {
+            if let Some(priority) = chunk_priorities.get(&file.relative_path) {
+                file.priority = *priority;
+            }
+        }
+        files.sort_by(|a, b| {
+            b.priority
+                .partial_cmp(&a.priority)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.relative_path.cmp(&b.relative_path))
+        });
+
+        stats.top_ranked_files = files
+            .iter()
+            .take(20)
+            .map(|f| {
+                HashMap::from([
+                    ("path".to_string(), json!(f.relative_path)),
+                    ("priority".to_string(), json!(f.priority)),
+                ])
+            })
+            .collect();
+    }
+
+    for boundary in detect_async_boundaries(&chunks) {
+        if let Some(chunk) = chunks.iter_mut().find(|c| c.id == boundary.chunk_id) {
+            for pattern in boundary.patterns {
+                chunk.tags.insert(pattern.tag().to_string());
+            }
+        }
+    }
+
+    stats.chunks_created = chunks.len();
+    stats.total_tokens_estimated = chunks.iter().map(|c| c.token_estimate).sum();
+    if options.token_report {
+        stats.tokens_by_tag = tokens_by_tag(&files, &chunks);
+    }
+    let dropped_total = stats.dropped_files_total();
+    let redaction_audit = kept_redactions(chunking.redaction_audit.unwrap_or_default(), &chunks);
+
+    Ok(Pack {
+        files,
+        chunks,
+        stats,
+        reranking_mode,
+        seed_files,
+        per_file_token_cap,
+        stitch_tokens_used,
+        stitched_unavailable_chunks,
+        dropped_total,
+        redaction_audit,
+    })
+}
+
+/// The `config` block of report.json for the settings that shape `pack`: chunking,
+/// budgeting, reranking and pack layout. `cli::export` adds its scan and output flags.
+pub(crate) fn report_config(
+    config: &Config,
+    options: &ExportOptions,
+    section_order: &[PackSection],
+    pack: &Pack,
+) -> serde_json::Value {
+    let mut report = json!({
+        "chunk_overlap":        config.chunk_overlap,
+        "chunk_tokens":         config.chunk_tokens,
+        "max_chunk_lines":      config.max_chunk_lines,
+        "merge_adjacent":       options.merge_adjacent.is_some(),
+        "stitch_budget_fraction": config.stitch_budget_fraction,
+        "stitch_top_n":         config.stitch_top_n,
+        "max_tokens":           config.max_tokens,
+        "max_tokens_per_file":  pack.per_file_token_cap,
+        "reserve_tokens":       config.reserve_tokens,
+        "allow_over_budget":    options.allow_over_budget,
+        "strict_budget":        options.strict_budget,
+        "stub_dropped":         options.stub_dropped,
+        "max_total_bytes":      config.max_total_bytes,
+        "semantic_rerank":      config.semantic_rerank,
+        "semantic_model":       config.semantic_model,
+        "rerank_top_k":         config.rerank_top_k,
+        "expand_seeds":         config.expand_seeds,
+        "expand_depth":         config.expand_depth,
+        "expand_decay":         config.expand_decay,
+        "mode":                 config.mode,
+        "task_query":           config.task_query,
+        "reranking":            pack.reranking_mode,
+        "redact_secrets":       config.redact_secrets,
+        "normalize_unicode":    options.normalize_unicode,
+        "tree_depth":           config.tree_depth,
+    });
+    // Kept out of the literal above to stay under `json!`'s recursion limit.
+    report["toc"] = json!(options.toc);
+    report["group_by_directory"] = json!(options.group_by_directory);
+    report["annotate_splits"] = json!(options.annotate_splits);
+    report["summarize_schemas"] = json!(options.summarize_schemas);
+    report["dedup_license_headers"] = json!(options.dedup_license_headers);
+    report["token_report"] = json!(options.token_report);
+    report["head_lines"] = json!(options.head_lines);
+    report["seed_files"] = json!(pack.seed_files);
+    report["max_dropped_records"] = json!(options.max_dropped_records);
+    report["per_path_token_budgets"] = json!(config.per_path_token_budgets);
+    report["tree_sitter_max_bytes"] = json!(config.tree_sitter_max_bytes);
+    report["tree_sitter_timeout_ms"] = json!(config.tree_sitter_timeout_ms);
+    report["definition_kinds"] = json!(config.definition_kinds);
+    report["section_order"] = json!(section_order.iter().map(|s| s.name()).collect::<Vec<_>>());
+    report
+}
+
+/// Audit rows whose line falls inside a chunk that made it into the pack.
+fn kept_redactions(audit: Vec<RedactionAuditEntry>, chunks: &[Chunk]) -> Vec<RedactionAuditEntry> {
+    let mut ranges: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for chunk in chunks {
+        ranges.entry(chunk.path.as_str()).or_default().push((chunk.start_line, chunk.end_line));
+    }
+    audit
+        .into_iter()
+        .filter(|entry| {
+            ranges.get(entry.path.as_str()).is_some_and(|spans| {
+                spans.iter().any(|&(start, end)| (start..=end).contains(&entry.line))
+            })
+        })
+        .collect()
+}
+
+/// Keep the highest-ranked files within `max_total_bytes`; files in `protected_paths`
+/// are always kept and still count towards the total.
+pub(crate) fn apply_byte_budget(
+    ranked_files: Vec<FileInfo>,
+    max_total_bytes: Option<u64>,
+    stats: &mut ScanStats,
+    protected_paths: &HashSet<String>,
+) -> Vec<FileInfo> {
+    let Some(limit) = max_total_bytes else {
+        return ranked_files;
+    };
+
+    let mut selected = Vec::new();
+    let mut total = 0_u64;
+    for (idx, file) in ranked_files.iter().enumerate() {
+        if protected_paths.contains(&file.relative_path) {
+            total += file.size_bytes;
+            selected.push(file.clone());
+            continue;
+        }
+        // Python checks >= BEFORE adding the current file (cumulative of already-accepted bytes)
+        if total >= limit {
+            // Bulk-drop this file and all remaining files
+            for remaining in &ranked_files[idx..] {
+                if protected_paths.contains(&remaining.relative_path) {
+                    total += remaining.size_bytes;
+                    selected.push(remaining.clone());
+                    continue;
+                }
+                stats.files_dropped_budget += 1;
+                let mut record = HashMap::from([
+                    ("path".to_string(), json!(remaining.relative_path)),
+                    ("reason".to_string(), json!("bytes_limit")),
+                    ("priority".to_string(), json!(remaining.priority)),
+                ]);
+                record.extend(budget_state("bytes", total, limit));
+                stats.push_dropped_record(record);
+            }
+            break;
+        }
+        total += file.size_bytes;
+        selected.push(file.clone());
+    }
+    stats.total_bytes_included = total;
+    selected
+}
+
+/// Budget context for a dropped-file record: how much of `limit` (in `unit`) was already
+/// used when the file was dropped (`--explain-budget`).
+fn budget_state(unit: &str, used: u64, limit: u64) -> [(String, serde_json::Value); 3] {
+    [
+        ("budget_unit".to_string(), json!(unit)),
+        ("budget_used".to_string(), json!(used)),
+        ("budget_limit".to_string(), json!(limit)),
+    ]
+}
+
+pub(crate) fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+pub(crate) fn build_redactor(mode: RedactionMode, cfg: &RedactionConfig) -> Redactor {
+    match mode {
+        RedactionMode::Fast => Redactor::from_config(false, false, false, cfg),
+        RedactionMode::Standard => Redactor::from_config(true, false, false, cfg),
+        RedactionMode::Paranoid => Redactor::from_config(true, true, false, cfg),
+        RedactionMode::StructureSafe => Redactor::from_config(true, false, true, cfg),
+    }
+}
+
+/// Settings for turning one selected file into chunks, resolved once per export.
+struct FileChunking<'a> {
+    content: &'a dyn ContentSource,
+    lazy_loader: Option<&'a LazyChunkLoader>,
+    chunks_from_index: bool,
+    redactor: Option<Redactor>,
+    config: &'a Config,
+    chunk_overlap: usize,
+    parse_limits: ParseLimits,
+    definition_kinds: DefinitionKinds,
+    head_lines: Option<usize>,
+    redaction_audit: Option<Vec<RedactionAuditEntry>>,
+}
+
+impl FileChunking<'_> {
+    fn process(
+        &mut self,
+        file: &mut FileInfo,
+        stats: &mut ScanStats,
+    ) -> Result<Option<Vec<Chunk>>> {
+        // Indexed chunks cover whole files, so head truncation re-reads the file instead.
+        if self.chunks_from_index && self.head_lines.is_none() {
+            if let Some(index_chunks) = self.indexed_chunks(file, stats) {
+                return Ok(Some(index_chunks));
+            }
+        }
+
+        let file_chunks = self.chunk_content(file, stats)?;
+        if let Some(chunks) = &file_chunks {
+            if chunks.iter().any(|c| c.tags.contains(PARSE_FALLBACK_TAG)) {
+                stats.files_parse_fallback += 1;
+            }
+            if chunks.iter().any(|c| c.tags.contains(PARSE_SKIPPED_TAG)) {
+                stats.files_parse_skipped += 1;
+            }
+        }
+        Ok(file_chunks)
+    }
+
+    fn indexed_chunks(&mut self, file: &mut FileInfo, stats: &mut ScanStats) -> Option<Vec<Chunk>> {
+        let loader = self.lazy_loader?;
+        let mut file_chunks = loader.load_chunks_for_file(&file.relative_path);
+        if file_chunks.is_empty() {
+            return None;
+        }
+
+        if let Some(r) = self.redactor.as_ref() {
+            let filename =
+                Path::new(&file.relative_path).file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if r.is_file_allowlisted(filename, &file.relative_path) {
+                stats.redactions_suppressed_by_allowlist += file_chunks
+                    .iter()
+                    .map(|chunk| r.count_allowlisted_file_matches(&chunk.content))
+                    .sum::<usize>();
+            } else {
+                let mut rule_file_sets: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+                for chunk in &mut file_chunks {
+                    let original = chunk.content.clone();
+                    let outcome = r.redact_with_language_report(
+                        &chunk.content,
+                        &file.language,
+                        &file.extension,
+                        filename,
+                        &file.relative_path,
+                    );
+                    stats.redactions_suppressed_by_allowlist += outcome.suppressed_by_allowlist;
+                    if let Some(audit) = self.redaction_audit.as_mut() {
+                        audit.extend(outcome.locations.iter().map(|hit| RedactionAuditEntry {
+                            path: file.relative_path.clone(),
+                            rule: hit.rule.clone(),
+                            line: chunk.start_line + hit.line - 1,
+                        }));
+                    }
+                    if outcome.content != original {
+                        chunk.content = outcome.content;
+                        chunk.tags.insert("redacted".to_string());
+                        stats.redacted_chunks += 1;
+                        for (rule, count) in &outcome.counts {
+                            *stats.redaction_counts.entry(rule.clone()).or_insert(0) += count;
+                            rule_file_sets
+                                .entry(rule.clone())
+                                .or_default()
+                                .insert(file.relative_path.clone());
+                        }
+                    }
+                }
+                if !rule_file_sets.is_empty() {
+                    stats.redacted_files += 1;
+                    for (rule, file_set) in rule_file_sets {
+                        *stats.redaction_file_counts.entry(rule).or_insert(0) += file_set.len();
+                    }
+                }
+            }
+        }
+
+        file.token_estimate = file_chunks.iter().map(|c| c.token_estimate).sum();
+        Some(file_chunks)
+    }
+
+    fn chunk_content(
+        &mut self,
+        file: &mut FileInfo,
+        stats: &mut ScanStats,
+    ) -> Result<Option<Vec<Chunk>>> {
+        let Some(content) = self.content.read(file) else {
+            return Ok(None);
+        };
+
+        let redacted_content = if let Some(r) = self.redactor.as_ref() {
+            let filename =
+                Path::new(&file.relative_path).file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if r.is_file_allowlisted(filename, &file.relative_path) {
+                stats.redactions_suppressed_by_allowlist +=
+                    r.count_allowlisted_file_matches(&content);
+                content
+            } else {
+                let outcome = r.redact_with_language_report(
+                    &content,
+                    &file.language,
+                    &file.extension,
+                    filename,
+                    &file.relative_path,
+                );
+                stats.redactions_suppressed_by_allowlist += outcome.suppressed_by_allowlist;
+                if let Some(audit) = self.redaction_audit.as_mut() {
+                    audit.extend(outcome.locations.iter().map(|hit| RedactionAuditEntry {
+                        path: file.relative_path.clone(),
+                        rule: hit.rule.clone(),
+                        line: hit.line,
+                    }));
+                }
+                if outcome.content != content {
+                    let mut rule_file_sets: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+                    for (rule, count) in &outcome.counts {
+                        *stats.redaction_counts.entry(rule.clone()).or_insert(0) += count;
+                        rule_file_sets
+                            .entry(rule.clone())
+                            .or_default()
+                            .insert(file.relative_path.clone());
+                    }
+                    stats.redacted_files += 1;
+                    for (rule, file_set) in rule_file_sets {
+                        *stats.redaction_file_counts.entry(rule).or_insert(0) += file_set.len();
+                    }
+                    outcome.content
+                } else {
+                    content
+                }
+            }
+        } else {
+            content
+        };
+        // Truncate after redaction so a multi-line secret is never cut short of its pattern.
+        let (redacted_content, head_truncated) = match self.head_lines {
+            Some(limit) => truncate_to_head_lines(redacted_content, limit),
+            None => (redacted_content, false),
+        };
+
+        let mut file_chunks = chunk_content_with_tag_queries(
+            file,
+            &redacted_content,
+            self.config.chunk_tokens,
+            self.chunk_overlap,
+            &self.config.tag_queries,
+            self.parse_limits,
+            self.definition_kinds,
+            self.config.max_chunk_lines,
+        )?;
+        let file_tokens: usize = file_chunks.iter().map(|c| c.token_estimate).sum();
+        file.token_estimate = file_tokens;
+
+        if head_truncated {
+            for chunk in &mut file_chunks {
+                chunk.tags.insert("head-truncated".to_string());
+            }
+        }
+
+        if self.redactor.is_some() {
+            for chunk in &mut file_chunks {
+                if chunk.content.contains("[REDACTED") || chunk.content.contains("_REDACTED]") {
+                    chunk.tags.insert("redacted".to_string());
+                    stats.redacted_chunks += 1;
+                }
+            }
+        }
+
+        Ok(Some(file_chunks))
+    }
+}
+
+/// Seed files that made it into the pack; seeds outside it are reported and ignored.
+fn resolve_seed_files(seeds: &[String], chunks: &[Chunk]) -> Vec<String> {
+    let known: HashSet<&str> = chunks.iter().map(|c| c.path.as_str()).collect();
+    let mut resolved: Vec<String> = Vec::new();
+    for seed in seeds {
+        if !known.contains(seed.as_str()) {
+            eprintln!("Warning: --seed-file {seed} is not among the exported files; ignoring");
+        } else if !resolved.contains(seed) {
+            resolved.push(seed.clone());
+        }
+    }
+    resolved
+}
+
+fn sort_chunks_by_priority(chunks: &mut [Chunk]) {
+    chunks.sort_by(|a, b| {
+        b.priority
+            .partial_cmp(&a.priority)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.start_line.cmp(&b.start_line))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// `per_path_token_budgets` with the tokens each glob has used so far.
+struct PathTokenBudgets {
+    rules: Vec<(String, GlobMatcher, usize)>,
+    used: Vec<usize>,
+}
+
+impl PathTokenBudgets {
+    fn from_config(config: &Config) -> Result<Self> {
+        let mut rules = Vec::with_capacity(config.per_path_token_budgets.len());
+        for (pattern, tokens) in &config.per_path_token_budgets {
+            let glob = Glob::new(pattern)
+                .with_context(|| format!("Invalid per_path_token_budgets glob '{pattern}'"))?;
+            rules.push((pattern.clone(), glob.compile_matcher(), *tokens));
+        }
+        let used = vec![0; rules.len()];
+        Ok(Self { rules, used })
+    }
+
+    /// Index of the first budget whose glob matches `path`.
+    fn matching(&self, path: &str) -> Option<usize> {
+        self.rules.iter().position(|(_, matcher, _)| matcher.is_match(path))
+    }
+
+    /// The budget's glob, tokens used and limit when `tokens` more would exceed it.
+    fn overflow(&self, slot: usize, tokens: usize) -> Option<(&str, usize, usize)> {
+        let (pattern, _, limit) = &self.rules[slot];
+        (self.used[slot] + tokens > *limit).then_some((pattern.as_str(), self.used[slot], *limit))
+    }
+
+    fn charge(&mut self, slot: usize, tokens: usize) {
+        self.used[slot] += tokens;
+    }
+}
+
+/// Resolve `--max-tokens-per-file`: values <= 1.0 are a fraction of `max_tokens`.
+fn resolve_per_file_token_cap(
+    cap: Option<f64>,
+    max_tokens: Option<usize>,
+) -> Result<Option<usize>> {
+    let Some(cap) = cap else {
+        return Ok(None);
+    };
+    if !cap.is_finite() || cap <= 0.0 {
+        anyhow::bail!("--max-tokens-per-file must be positive, got {cap}");
+    }
+    if cap > 1.0 {
+        return Ok(Some(cap as usize));
+    }
+    let Some(max_tokens) = max_tokens else {
+        anyhow::bail!("--max-tokens-per-file as a fraction ({cap}) requires --max-tokens");
+    };
+    Ok(Some(((max_tokens as f64) * cap).floor().max(1.0) as usize))
+}
+
+/// Split a file's chunks into the leading run that fits within `cap` tokens and the rest.
+fn split_chunks_at_token_cap(chunks: Vec<Chunk>, cap: usize) -> (Vec<Chunk>, Vec<Chunk>) {
+    let mut used = 0usize;
+    let split_at = chunks
+        .iter()
+        .position(|chunk| {
+            used += chunk.token_estimate;
+            used > cap
+        })
+        .unwrap_or(chunks.len());
+    let mut kept = chunks;
+    let capped = kept.split_off(split_at);
+    (kept, capped)
+}
+
+/// Tag chunks containing zero-width/bidi control characters and, when requested,
+/// NFC-normalize their content (recomputing the id and token estimate afterwards).
+fn apply_unicode_hygiene(file: &mut FileInfo, file_chunks: &mut [Chunk], normalize: bool) {
+    let mut changed = false;
+    for chunk in file_chunks.iter_mut() {
+        if has_suspicious_unicode(&chunk.content) {
+            chunk.tags.insert("suspicious-unicode".to_string());
+        }
+        if normalize {
+            let normalized = normalize_unicode(&chunk.content);
+            if normalized != chunk.content {
+                chunk.id = stable_hash(&normalized, &chunk.path, chunk.start_line, chunk.end_line);
+                chunk.token_estimate = estimate_tokens(&normalized);
+                chunk.content = normalized;
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        file.token_estimate = file_chunks.iter().map(|c| c.token_estimate).sum();
+    }
+}
+
+/// Estimated tokens per category tag, counting both the chunk's own tags and its file's
+/// (`readme`, `config`, `entrypoint`, ...). Chunks with no category tag count under their
+/// language as `lang:<language>`, so ordinary source still lands in a useful bucket.
+///
+/// Symbol tags (`def:`, `type:` and other `kind:name` tags) would give one bucket per
+/// symbol, so only plain category tags are counted.
+fn tokens_by_tag(files: &[FileInfo], chunks: &[Chunk]) -> BTreeMap<String, usize> {
+    let file_tags: HashMap<&str, &BTreeSet<String>> =
+        files.iter().map(|f| (f.relative_path.as_str(), &f.tags)).collect();
+    let mut totals: BTreeMap<String, usize> = BTreeMap::new();
+    for chunk in chunks {
+        let categories: BTreeSet<&String> = chunk
+            .tags
+            .iter()
+            .chain(file_tags.get(chunk.path.as_str()).into_iter().flat_map(|t| t.iter()))
+            .filter(|tag| !tag.contains(':'))
+            .collect();
+        if categories.is_empty() {
+            let language = if chunk.language.is_empty() { "unknown" } else { &chunk.language };
+            *totals.entry(format!("lang:{language}")).or_insert(0) += chunk.token_estimate;
+        }
+        for tag in categories {
+            *totals.entry(tag.clone()).or_insert(0) += chunk.token_estimate;
+        }
+    }
+    totals
+}
+
+/// Replace a GraphQL/OpenAPI file's chunks with one compact summary chunk.
+fn apply_schema_summary(file: &mut FileInfo, file_chunks: &mut Vec<Chunk>) {
+    let Some(first) = file_chunks.first() else {
+        return;
+    };
+    let mut ordered: Vec<&Chunk> = file_chunks.iter().collect();
+    ordered.sort_by_key(|c| c.start_line);
+    // Reassemble the file, skipping lines repeated by chunk overlap.
+    let mut content = String::new();
+    let mut covered = 0usize;
+    for chunk in &ordered {
+        let skip = (covered + 1).saturating_sub(chunk.start_line);
+        for line in chunk.content.lines().skip(skip) {
+            content.push_str(line);
+            content.push('\n');
+        }
+        covered = covered.max(chunk.end_line);
+    }
+    let Some(summary) = summarize_schema(&first.language, &content) else {
+        return;
+    };
+    let end_line = ordered.iter().map(|c| c.end_line).max().unwrap_or(1);
+    let mut tags = ordered.iter().flat_map(|c| c.tags.iter().cloned()).collect::<BTreeSet<_>>();
+    tags.insert("schema-summary".to_string());
+    let chunk = Chunk {
+        id: stable_hash(&summary, &file.relative_path, 1, end_line),
+        path: file.relative_path.clone(),
+        language: first.language.clone(),
+        start_line: 1,
+        end_line,
+        priority: ordered.iter().map(|c| c.priority).fold(0.0, f64::max),
+        tags,
+        token_estimate: estimate_tokens(&summary),
+        doc: None,
+        byte_range: None,
+        content: summary,
+    };
+    file.token_estimate = chunk.token_estimate;
+    *file_chunks = vec![chunk];
+}
+
+/// Keep the first `limit` lines of `content`; the flag reports whether anything was cut.
+fn truncate_to_head_lines(mut content: String, limit: usize) -> (String, bool) {
+    let end: usize = content.split_inclusive('\n').take(limit).map(str::len).sum();
+    if end >= content.len() {
+        return (content, false);
+    }
+    content.truncate(end);
+    (content, true)
+}
+
+fn sort_group(
+    chunk: &Chunk,
+    seed_ids: &BTreeSet<String>,
+    stitched: &HashMap<String, StitchTier>,
+) -> u8 {
+    if seed_ids.contains(&chunk.id) {
+        return 0;
+    }
+    match stitched.get(&chunk.id) {
+        Some(StitchTier::Definition) => 1,
+        Some(StitchTier::Callee) => 2,
+        Some(StitchTier::Caller) => 3,
+        Some(StitchTier::CrossCrate) => 4,
+        None => 5,
+    }
+}
+
+fn sort_chunks_for_stitch_story(
+    chunks: &mut [Chunk],
+    seed_ids: &BTreeSet<String>,
+    stitched: &HashMap<String, StitchTier>,
+) {
+    chunks.sort_by(|a, b| {
+        let a_key = sort_group(a, seed_ids, stitched);
+        let b_key = sort_group(b, seed_ids, stitched);
+        a_key
+            .cmp(&b_key)
+            .then_with(|| b.priority.partial_cmp(&a.priority).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.start_line.cmp(&b.start_line))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_unicode_hygiene, build_pack, kept_redactions, resolve_per_file_token_cap,
+        sort_chunks_for_stitch_story, split_chunks_at_token_cap, tokens_by_tag, PackSources,
+        RedactionAuditEntry,
+    };
+    use crate::domain::test_support::{chunk, file_info};
+    use crate::domain::{Chunk, Config, FileInfo, ScanStats};
+    use crate::export::ExportOptions;
+    use crate::rank::StitchTier;
+    use crate::utils::stable_hash;
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    fn mk_chunk(id: &str, priority: f64, path: &str, start_line: usize) -> Chunk {
+        Chunk {
+            id: id.to_string(),
+            start_line,
+            end_line: start_line,
+            priority,
+            token_estimate: 10,
+            ..chunk(path, "fn x() {}")
+        }
+    }
+    fn mk_file(path: &str) -> FileInfo {
+        FileInfo { size_bytes: 20, token_estimate: 10, ..file_info(path) }
+    }
+
+    #[test]
+    fn tokens_by_tag_counts_file_categories_and_falls_back_to_language() {
+        let tagged = |id: &str, path: &str, tokens: usize, tags: &[&str]| {
+            let mut chunk = mk_chunk(id, 0.5, path, 1);
+            chunk.token_estimate = tokens;
+            chunk.tags = tags.iter().map(|t| t.to_string()).collect();
+            chunk
+        };
+        let mut main = mk_file("src/main.rs");
+        main.tags.insert("entrypoint".to_string());
+        let mut readme = mk_file("README.md");
+        readme.tags.insert("readme".to_string());
+        let files = vec![main, readme, mk_file("src/lib.rs")];
+        let chunks = vec![
+            tagged("a", "src/main.rs", 40, &["def:main"]),
+            tagged("b", "src/main.rs", 30, &["redacted"]),
+            tagged("c", "src/lib.rs", 20, &["redacted", "def:helper"]),
+            tagged("e", "src/lib.rs", 10, &["def:other"]),
+            tagged("d", "README.md", 25, &["section:Intro"]),
+        ];
+
+        let by_tag = tokens_by_tag(&files, &chunks);
+
+        let expected: BTreeMap<String, usize> =
+            [("entrypoint", 70), ("lang:rust", 10), ("readme", 25), ("redacted", 50)]
+                .into_iter()
+                .map(|(tag, tokens)| (tag.to_string(), tokens))
+                .collect();
+        assert_eq!(by_tag, expected);
+    }
+
+    #[test]
+    fn stitch_story_sort_orders_seed_then_tiers_then_rest() {
+        let mut chunks = vec![
+            mk_chunk("rest", 0.99, "z.rs", 1),
+            mk_chunk("callee_hi", 0.95, "b.rs", 2),
+            mk_chunk("seed_hi", 0.70, "a.rs", 2),
+            mk_chunk("caller", 0.90, "c.rs", 1),
+            mk_chunk("def_lo", 0.60, "d.rs", 10),
+            mk_chunk("seed_lo", 0.20, "a.rs", 1),
+            mk_chunk("def_hi", 0.85, "d.rs", 1),
+            mk_chunk("callee_lo", 0.30, "b.rs", 1),
+        ];
+
+        let seed_ids = BTreeSet::from(["seed_hi".to_string(), "seed_lo".to_string()]);
+        let stitched = HashMap::from([
+            ("def_hi".to_string(), StitchTier::Definition),
+            ("def_lo".to_string(), StitchTier::Definition),
+            ("callee_hi".to_string(), StitchTier::Callee),
+            ("callee_lo".to_string(), StitchTier::Callee),
+            ("caller".to_string(), StitchTier::Caller),
+        ]);
+
+        sort_chunks_for_stitch_story(&mut chunks, &seed_ids, &stitched);
+        let ordered: Vec<&str> = chunks.iter().map(|c| c.id.as_str()).collect();
+
+        assert_eq!(
+            ordered,
+            vec![
+                "seed_hi",
+                "seed_lo",
+                "def_hi",
+                "def_lo",
+                "callee_hi",
+                "callee_lo",
+                "caller",
+                "rest"
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_hygiene_tags_zero_width_and_normalizes_when_enabled() {
+        let mut file = mk_file("src/auth.rs");
+        let mut chunk = mk_chunk("c1", 0.5, "src/auth.rs", 1);
+        chunk.content = "let is_admin\u{200B} = true;".to_string();
+
+        let mut tagged_only = vec![chunk.clone()];
+        apply_unicode_hygiene(&mut file, &mut tagged_only, false);
+        assert!(tagged_only[0].tags.contains("suspicious-unicode"));
+        assert!(tagged_only[0].content.contains('\u{200B}'));
+
+        let mut normalized = vec![chunk];
+        apply_unicode_hygiene(&mut file, &mut normalized, true);
+        assert!(normalized[0].tags.contains("suspicious-unicode"));
+        assert_eq!(normalized[0].content, "let is_admin = true;");
+        assert_eq!(normalized[0].id, stable_hash("let is_admin = true;", "src/auth.rs", 1, 1));
+        assert_eq!(file.token_estimate, normalized[0].token_estimate);
+    }
+
+    #[test]
+    fn redaction_audit_keeps_only_rows_inside_packed_chunks() {
+        let entry = |path: &str, line: usize| RedactionAuditEntry {
+            path: path.to_string(),
+            rule: "openai_key".to_string(),
+            line,
+        };
+        let mut chunk = mk_chunk("c1", 0.5, "kept.py", 3);
+        chunk.end_line = 6;
+        let audit = vec![entry("kept.py", 4), entry("kept.py", 9), entry("dropped.py", 4)];
+
+        assert_eq!(kept_redactions(audit, &[chunk]), vec![entry("kept.py", 4)]);
+    }
+
+    #[test]
+    fn per_file_token_cap_keeps_leading_chunks_within_cap() {
+        let chunks: Vec<Chunk> =
+            (1..=5).map(|line| mk_chunk(&format!("c{line}"), 0.5, "big.rs", line)).collect();
+
+        let (kept, capped) = split_chunks_at_token_cap(chunks, 25);
+        assert_eq!(kept.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), vec!["c1", "c2"]);
+        assert_eq!(capped.len(), 3);
+
+        assert_eq!(resolve_per_file_token_cap(Some(0.2), Some(1000)).expect("fraction"), Some(200));
+        assert_eq!(resolve_per_file_token_cap(Some(300.0), None).expect("absolute"), Some(300));
+        assert!(resolve_per_file_token_cap(Some(0.2), None).is_err());
+        assert!(resolve_per_file_token_cap(Some(0.0), Some(1000)).is_err());
+    }
+
+    #[test]
+    fn per_path_token_budgets_drop_files_past_their_glob_budget() {
+        let body = "fn handler() { let value = compute(); store(value); }\n".repeat(8);
+        let mut contents = HashMap::new();
+        let files: Vec<FileInfo> =
+            ["frontend/a.rs", "frontend/b.rs", "backend/c.rs", "backend/d.rs"]
+                .iter()
+                .enumerate()
+                .map(|(idx, path)| {
+                    contents.insert(path.to_string(), body.clone());
+                    FileInfo {
+                        size_bytes: body.len() as u64,
+                        id: format!("id-{idx}"),
+                        priority: 0.9 - idx as f64 * 0.1,
+                        ..file_info(path)
+                    }
+                })
+                .collect();
+        let file_tokens = crate::utils::estimate_tokens(&body);
+        let config = Config {
+            semantic_rerank: false,
+            per_path_token_budgets: vec![
+                ("frontend/**".to_string(), file_tokens * 3 / 2),
+                ("backend/**".to_string(), file_tokens * 4),
+            ],
+            ..Config::default()
+        };
+
+        let pack = build_pack(
+            &config,
+            files,
+            ScanStats::default(),
+            &ExportOptions::default(),
+            &contents,
+            &PackSources::default(),
+        )
+        .expect("build pack");
+
+        let kept: BTreeSet<&str> = pack.chunks.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(kept, BTreeSet::from(["frontend/a.rs", "backend/c.rs", "backend/d.rs"]));
+        assert_eq!(pack.stats.dropped_files.len(), 1);
+        let dropped = &pack.stats.dropped_files[0];
+        assert_eq!(dropped["path"], "frontend/b.rs");
+        assert_eq!(dropped["reason"], "path_token_budget:frontend/**");
+        assert_eq!(pack.stats.files_dropped_budget, 1);
+    }
+
+    #[test]
+    fn stubs_stay_within_their_path_budget() {
+        let function =
+            |i: usize| format!("fn handler_{i}() {{ let value = compute(); store(value); }}\n\n");
+        let body: String = (0..6).map(function).collect();
+        let mut contents = HashMap::new();
+        let files: Vec<FileInfo> =
+            ["frontend/a.rs", "frontend/b.rs", "backend/c.rs", "backend/d.rs"]
+                .iter()
+                .enumerate()
+                .map(|(idx, path)| {
+                    contents.insert(path.to_string(), body.clone());
+                    FileInfo {
+                        size_bytes: body.len() as u64,
+                        id: format!("id-{idx}"),
+                        priority: 0.9 - idx as f64 * 0.1,
+                        ..file_info(path)
+                    }
+                })
+                .collect();
+        let config = Config {
+            semantic_rerank: false,
+            chunk_tokens: 20,
+            min_chunk_tokens: 1,
+            ..Config::default()
+        };
+        let options = ExportOptions { stub_dropped: true, ..ExportOptions::default() };
+        let build = |config: &Config| {
+            build_pack(
+                config,
+                files.clone(),
+                ScanStats::default(),
+                &options,
+                &contents,
+                &PackSources::default(),
+            )
+            .expect("build pack")
+        };
+        let unbudgeted = build(&config);
+        let tokens = |chunks: &[Chunk], prefix: &str| -> usize {
+            chunks.iter().filter(|c| c.path.starts_with(prefix)).map(|c| c.token_estimate).sum()
+        };
+        let file_tokens = tokens(&unbudgeted.chunks, "frontend/a.rs");
+        assert!(unbudgeted.chunks.iter().filter(|c| c.path == "frontend/a.rs").count() > 1);
+
+        // frontend/b.rs is over the frontend budget, so it is dropped without a stub;
+        // backend/d.rs only misses the overall budget and keeps its first chunk.
+        let budgeted = Config {
+            max_tokens: Some(file_tokens * 2 + file_tokens / 2),
+            per_path_token_budgets: vec![("frontend/**".to_string(), file_tokens * 3 / 2)],
+            ..config.clone()
+        };
+        let pack = build(&budgeted);
+
+        let stubbed: BTreeSet<&str> = pack
+            .chunks
+            .iter()
+            .filter(|c| c.tags.contains("stub"))
+            .map(|c| c.path.as_str())
+            .collect();
+        assert_eq!(stubbed, BTreeSet::from(["backend/d.rs"]));
+        assert!(tokens(&pack.chunks, "frontend/") <= file_tokens * 3 / 2);
+        assert!(!pack.chunks.iter().any(|c| c.path == "frontend/b.rs"));
+        assert_eq!(pack.stats.files_stubbed, 1);
+    }
+}
@@ -0,0 +1,126 @@
+//! Archive extraction (`--archive`)
+//!
+//! Extraction is done in-process with the `tar`, `flate2` and `zip` crates, which refuse
+//! entries that would land outside the destination (absolute paths, `..` components).
+
+use crate::fetch::RepoContext;
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+/// Extract `archive` into a temporary directory and use it as the repository root.
+///
+/// The root is named after the archive (`demo.tar.gz` → `demo`) so output names match an
+/// export of the extracted checkout. An archive whose only entry is one directory (as in
+/// GitHub release tarballs) uses that directory instead. Everything is removed on drop.
+pub fn extract_archive(archive: &Path) -> Result<RepoContext> {
+    let archive = archive
+        .canonicalize()
+        .with_context(|| format!("Archive not found: {}", archive.display()))?;
+    let file_name = archive.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let (kind, stem) = archive_kind(file_name).with_context(|| {
+        format!("Unsupported archive type (use .tar, .tar.gz, .tgz or .zip): {}", archive.display())
+    })?;
+
+    // Randomly named and private to this user, so nothing can be planted there beforehand.
+    let temp_dir = tempfile::Builder::new()
+        .prefix("repo-context-archive-")
+        .tempdir()
+        .context("Failed creating temp directory")?
+        .keep();
+    let dest = temp_dir.join(stem);
+    // Owns the temp dir from here on, so a failed extraction is cleaned up too.
+    let mut ctx = RepoContext::with_cleanup(dest.clone(), temp_dir);
+    fs::create_dir_all(&dest)
+        .with_context(|| format!("Failed creating temp directory: {}", dest.display()))?;
+
+    unpack(kind, &archive, &dest)
+        .with_context(|| format!("Failed extracting {}", archive.display()))?;
+
+    if let Some(inner) = sole_directory(&dest)? {
+        ctx.root_path = inner;
+    }
+    Ok(ctx)
+}
+
+fn unpack(kind: ArchiveKind, archive: &Path, dest: &Path) -> Result<()> {
+    let reader = BufReader::new(File::open(archive)?);
+    match kind {
+        ArchiveKind::Tar => tar::Archive::new(reader).unpack(dest)?,
+        ArchiveKind::TarGz => {
+            tar::Archive::new(flate2::read::GzDecoder::new(reader)).unpack(dest)?
+        }
+        ArchiveKind::Zip => zip::ZipArchive::new(reader)?.extract(dest)?,
+    }
+    Ok(())
+}
+
+/// Archive kind and the file name without its archive extension.
+fn archive_kind(file_name: &str) -> Option<(ArchiveKind, &str)> {
+    let lower = file_name.to_ascii_lowercase();
+    [
+        (".tar.gz", ArchiveKind::TarGz),
+        (".tgz", ArchiveKind::TarGz),
+        (".tar", ArchiveKind::Tar),
+        (".zip", ArchiveKind::Zip),
+    ]
+    .into_iter()
+    .find(|(ext, _)| lower.ends_with(ext) && lower.len() > ext.len())
+    .map(|(ext, kind)| (kind, &file_name[..file_name.len() - ext.len()]))
+}
+
+fn sole_directory(dir: &Path) -> Result<Option<PathBuf>> {
+    let entries: Vec<fs::DirEntry> = fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+    match entries.as_slice() {
+        [entry] if entry.file_type()?.is_dir() => Ok(Some(entry.path())),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_kind, extract_archive, ArchiveKind};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn archive_kind_strips_extension() {
+        assert_eq!(archive_kind("demo.tar.gz"), Some((ArchiveKind::TarGz, "demo")));
+        assert_eq!(archive_kind("demo-1.2.TGZ"), Some((ArchiveKind::TarGz, "demo-1.2")));
+        assert_eq!(archive_kind("demo.tar"), Some((ArchiveKind::Tar, "demo")));
+        assert_eq!(archive_kind("demo.zip"), Some((ArchiveKind::Zip, "demo")));
+        assert_eq!(archive_kind("demo.rar"), None);
+        assert_eq!(archive_kind(".zip"), None);
+    }
+
+    #[test]
+    fn extract_archive_skips_entries_outside_the_destination() {
+        let tmp = TempDir::new().expect("temp dir");
+        let archive = tmp.path().join("evil.tar");
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, body) in [("../escape.txt", "escaped"), ("kept.txt", "kept")] {
+            let mut header = tar::Header::new_old();
+            // `set_path` rejects `..`, so the name is written into the raw header.
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, body.as_bytes()).expect("append entry");
+        }
+        fs::write(&archive, builder.into_inner().expect("tar bytes")).expect("write archive");
+
+        let ctx = extract_archive(&archive).expect("extract");
+        assert_eq!(fs::read_to_string(ctx.root_path.join("kept.txt")).expect("kept"), "kept");
+        let temp_root = ctx.root_path.parent().expect("temp root");
+        assert!(!temp_root.join("escape.txt").exists());
+        assert!(temp_root.starts_with(std::env::temp_dir()));
+    }
+}
@@ -0,0 +1,99 @@
+//! Files changed on the current branch, for `export --since <REF>`.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// The commit `--since <REF>` compares against: the merge base of `base_ref` and `HEAD`
+/// (as in `git diff <ref>...HEAD`).
+///
+/// Resolved once per export so the file filter and the symbol diff agree.
+pub fn since_merge_base(root: &Path, base_ref: &str) -> Result<git2::Oid> {
+    let repo = git2::Repository::discover(root)
+        .with_context(|| format!("{} is not inside a git repository", root.display()))?;
+    let base = repo
+        .revparse_single(base_ref)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("unknown git ref '{base_ref}'"))?;
+    let head = repo.head().and_then(|head| head.peel_to_commit()).context("HEAD has no commit")?;
+    repo.merge_base(base.id(), head.id())
+        .with_context(|| format!("'{base_ref}' shares no history with HEAD"))
+}
+
+/// Root-relative paths changed since `merge_base` (see [`since_merge_base`]), plus
+/// uncommitted and untracked changes in the working tree.
+///
+/// Deleted files are listed too; callers intersect the set with the files they scanned.
+pub fn changed_files_since(root: &Path, merge_base: git2::Oid) -> Result<BTreeSet<String>> {
+    let repo = git2::Repository::discover(root)
+        .with_context(|| format!("{} is not inside a git repository", root.display()))?;
+    let workdir = repo.workdir().context("bare repositories have no working tree")?;
+    let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let prefix = root.strip_prefix(&workdir).unwrap_or(Path::new("")).to_path_buf();
+
+    let base_tree = repo.find_commit(merge_base)?.tree()?;
+
+    let mut options = git2::DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))?;
+
+    let mut changed = BTreeSet::new();
+    for delta in diff.deltas() {
+        for path in [delta.new_file().path(), delta.old_file().path()].into_iter().flatten() {
+            if let Ok(relative) = path.strip_prefix(&prefix) {
+                changed.insert(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changed_files_since, since_merge_base};
+    use std::collections::BTreeSet;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit_all(repo: &git2::Repository, message: &str) {
+        let mut index = repo.index().expect("index");
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).expect("add");
+        index.write().expect("write index");
+        let tree = repo.find_tree(index.write_tree().expect("tree")).expect("find tree");
+        let sig = git2::Signature::now("test", "test@example.com").expect("signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).expect("commit");
+    }
+
+    #[test]
+    fn lists_committed_and_uncommitted_changes_since_ref() {
+        let tmp = TempDir::new().expect("tmp");
+        let root = tmp.path();
+        let repo = git2::Repository::init(root).expect("init");
+        fs::create_dir_all(root.join("pkg")).expect("mkdir");
+        fs::write(root.join("pkg/a.py"), "A = 1\n").expect("write a");
+        fs::write(root.join("pkg/b.py"), "B = 1\n").expect("write b");
+        fs::write(root.join("top.py"), "T = 1\n").expect("write top");
+        commit_all(&repo, "base");
+        let base = repo.head().expect("head").peel_to_commit().expect("commit").id().to_string();
+
+        fs::write(root.join("pkg/a.py"), "A = 2\n").expect("edit a");
+        commit_all(&repo, "branch work");
+        fs::write(root.join("top.py"), "T = 2\n").expect("edit top");
+        fs::write(root.join("pkg/new.py"), "N = 1\n").expect("write new");
+
+        let merge_base = since_merge_base(root, &base).expect("merge base");
+        let changed = changed_files_since(root, merge_base).expect("changed");
+        assert_eq!(changed, BTreeSet::from(["pkg/a.py", "pkg/new.py", "top.py"].map(String::from)));
+
+        // Paths are relative to the exported directory, not the git workdir.
+        let scoped = changed_files_since(&root.join("pkg"), merge_base).expect("scoped");
+        assert_eq!(scoped, BTreeSet::from(["a.py", "new.py"].map(String::from)));
+
+        assert!(since_merge_base(root, "no-such-ref").is_err());
+        let plain = TempDir::new().expect("plain dir");
+        assert!(since_merge_base(plain.path(), "HEAD").is_err());
+    }
+}
@@ -6,18 +6,25 @@ use std::path::PathBuf;
 pub struct RepoContext {
     pub root_path: PathBuf,
     pub is_temp: bool,
+    /// Directory removed on drop instead of `root_path` (when the root is nested in it).
+    cleanup_path: Option<PathBuf>,
 }
 
 impl RepoContext {
     pub fn new(root_path: PathBuf, is_temp: bool) -> Self {
-        Self { root_path, is_temp }
+        Self { root_path, is_temp, cleanup_path: None }
+    }
+
+    /// A temporary root whose enclosing `cleanup_path` is removed on drop.
+    pub fn with_cleanup(root_path: PathBuf, cleanup_path: PathBuf) -> Self {
+        Self { root_path, is_temp: true, cleanup_path: Some(cleanup_path) }
     }
 }
 
 impl Drop for RepoContext {
     fn drop(&mut self) {
         if self.is_temp {
-            let _ = std::fs::remove_dir_all(&self.root_path);
+            let _ = std::fs::remove_dir_all(self.cleanup_path.as_ref().unwrap_or(&self.root_path));
         }
     }
 }
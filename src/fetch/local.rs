@@ -1,6 +1,7 @@
 //! Local path validation
 
 use crate::fetch::RepoContext;
+use crate::utils::output::println;
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 
@@ -3,6 +3,8 @@
 use anyhow::Result;
 use std::path::Path;
 
+pub mod archive;
+pub mod changed;
 pub mod context;
 pub mod github;
 pub mod huggingface;
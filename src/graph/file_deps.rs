@@ -0,0 +1,103 @@
+//! File-level import adjacency (`--emit-file-deps`).
+
+use crate::domain::{Chunk, FileInfo, UnresolvedImport};
+use crate::rank::{extract_import_references, resolve_reference};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Map each selected file to the selected files it imports.
+///
+/// Imports are read from the exported chunks and resolved the same way as the
+/// `file_imports` table of the symbol graph. Every selected file gets an entry, so files
+/// without imports map to an empty set.
+pub fn file_dependencies(
+    files: &[FileInfo],
+    chunks: &[Chunk],
+) -> BTreeMap<String, BTreeSet<String>> {
+    let known_files: HashSet<String> = files.iter().map(|f| f.relative_path.clone()).collect();
+    let mut deps: BTreeMap<String, BTreeSet<String>> =
+        known_files.iter().map(|path| (path.clone(), BTreeSet::new())).collect();
+
+    for chunk in chunks {
+        for reference in extract_import_references(&chunk.content) {
+            for target in resolve_reference(&reference, &chunk.path, &known_files) {
+                if target != chunk.path {
+                    deps.entry(chunk.path.clone()).or_default().insert(target);
+                }
+            }
+        }
+    }
+    deps
+}
+
+/// Languages whose imports [`resolve_reference`] knows how to map to files.
+const RESOLVED_LANGUAGES: &[&str] = &["python", "rust", "javascript", "typescript"];
+
+/// Import references in `chunks` that resolve to none of `known_files`, sorted and
+/// deduplicated (`--report-unresolved-imports`).
+///
+/// External packages (`import requests`, `use serde::Serialize`) land here as well as
+/// imports of files that were excluded from the scan. Only chunks in
+/// [`RESOLVED_LANGUAGES`] are checked, and crate-relative Rust paths (`crate::`,
+/// `super::`, `self::`) are internal by definition, so they are never reported.
+pub fn unresolved_imports(
+    chunks: &[Chunk],
+    known_files: &HashSet<String>,
+) -> Vec<UnresolvedImport> {
+    let mut unresolved = BTreeSet::new();
+    for chunk in chunks.iter().filter(|c| RESOLVED_LANGUAGES.contains(&c.language.as_str())) {
+        for reference in extract_import_references(&chunk.content) {
+            if is_crate_relative(&reference) {
+                continue;
+            }
+            if resolve_reference(&reference, &chunk.path, known_files).is_empty() {
+                unresolved.insert((chunk.path.clone(), reference));
+            }
+        }
+    }
+    unresolved.into_iter().map(|(from, reference)| UnresolvedImport { from, reference }).collect()
+}
+
+fn is_crate_relative(reference: &str) -> bool {
+    ["crate", "super", "self"].iter().any(|root| {
+        reference.strip_prefix(root).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unresolved_imports;
+    use crate::domain::test_support::chunk;
+    use std::collections::HashSet;
+
+    #[test]
+    fn external_packages_are_unresolved() {
+        let chunks = vec![
+            chunk("app/main.py", "import requests\nimport util\n"),
+            chunk("app/util.py", "import requests\nimport numpy as np\n"),
+            chunk(
+                "src/lib.rs",
+                "mod util;\nmod tests {\n    use super::*;\n}\nuse crate::util::helper;\nuse serde::Serialize;\n",
+            ),
+            chunk("docs/notes.md", "import the data from \"somewhere\" first\n"),
+        ];
+        let known = HashSet::from(
+            ["app/main.py", "app/util.py", "src/lib.rs", "src/util.rs", "docs/notes.md"]
+                .map(String::from),
+        );
+
+        let unresolved: Vec<(String, String)> = unresolved_imports(&chunks, &known)
+            .into_iter()
+            .map(|import| (import.from, import.reference))
+            .collect();
+        let pair = |from: &str, reference: &str| (from.to_string(), reference.to_string());
+        assert_eq!(
+            unresolved,
+            vec![
+                pair("app/main.py", "requests"),
+                pair("app/util.py", "numpy"),
+                pair("app/util.py", "requests"),
+                pair("src/lib.rs", "serde::Serialize"),
+            ]
+        );
+    }
+}
@@ -54,6 +54,8 @@ impl LazyChunkLoader {
                 token_estimate: row.get::<_, i64>(6)? as usize,
                 tags,
                 content: row.get(8)?,
+                doc: None,
+                byte_range: None,
             })
         }) {
             Ok(v) => v,
@@ -0,0 +1,236 @@
+//! Merge the symbol graphs of several indexes into one.
+//!
+//! Each source's paths become `<namespace>/<path>` and its chunk ids `<namespace>:<id>`, so
+//! files and chunks from different repositories never collide while symbol names stay shared
+//! for cross-repo lookups.
+
+use crate::graph::schema::open_or_create;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// One database to merge and the namespace its paths get.
+pub struct GraphSource {
+    pub namespace: String,
+    pub path: PathBuf,
+}
+
+impl GraphSource {
+    /// Parse `NAME=PATH` or `PATH`; without a name, the namespace is the last segment of
+    /// the indexed repository's name, else the file stem.
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some((name, path)) = spec.split_once('=') {
+            let name = name.trim();
+            if name.is_empty() || name.contains(['/', ':']) {
+                bail!("Invalid namespace {name:?} in --db {spec}; use letters, digits, '-' or '_'");
+            }
+            return Ok(Self { namespace: name.to_string(), path: PathBuf::from(path) });
+        }
+        let path = PathBuf::from(spec);
+        let namespace = repo_metadata_name(&path)
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+            .unwrap_or_else(|| "repo".to_string());
+        Ok(Self { namespace, path })
+    }
+}
+
+/// Rows copied from one source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeCounts {
+    pub symbols: usize,
+    pub imports: usize,
+}
+
+/// Write the union of the sources' graph tables to `out`, replacing any graph already there.
+pub fn merge_graphs(sources: &[GraphSource], out: &Path) -> Result<Vec<MergeCounts>> {
+    let mut seen = HashSet::new();
+    for source in sources {
+        if !seen.insert(source.namespace.as_str()) {
+            bail!(
+                "Two databases share the namespace '{}'; name them with --db NAME=PATH",
+                source.namespace
+            );
+        }
+        if !source.path.is_file() {
+            bail!("Graph database not found: {}", source.path.display());
+        }
+        if fs_same_file(&source.path, out) {
+            bail!("--out must differ from the merged databases ({})", out.display());
+        }
+    }
+
+    let mut conn = open_or_create(out)
+        .with_context(|| format!("Failed to open merged graph {}", out.display()))?;
+    for source in sources {
+        ensure_graph_tables(&source.path)?;
+    }
+
+    let tx = conn.transaction()?;
+    for table in ["symbol_chunks", "file_imports", "chunk_meta", "symbol_refs"] {
+        tx.execute(&format!("DELETE FROM {table}"), [])?;
+    }
+    tx.commit()?;
+
+    // SQLite cannot attach inside a transaction, so each source is attached first.
+    let mut counts = Vec::with_capacity(sources.len());
+    for source in sources {
+        conn.execute("ATTACH DATABASE ?1 AS src", [source.path.to_string_lossy()])?;
+        let copied = copy_namespaced(&mut conn, &source.namespace);
+        conn.execute("DETACH DATABASE src", [])?;
+        counts.push(copied?);
+    }
+    Ok(counts)
+}
+
+/// Copy the attached `src` graph into the main tables under `ns`.
+fn copy_namespaced(conn: &mut Connection, ns: &str) -> Result<MergeCounts> {
+    let tx = conn.transaction()?;
+    let symbols = tx.execute(
+        "INSERT OR IGNORE INTO symbol_chunks(symbol, chunk_id, kind, path)
+         SELECT symbol, ?1 || ':' || chunk_id, kind, ?1 || '/' || path FROM src.symbol_chunks",
+        params![ns],
+    )?;
+    let imports = tx.execute(
+        "INSERT OR IGNORE INTO file_imports(source_path, target_path)
+         SELECT ?1 || '/' || source_path, ?1 || '/' || target_path FROM src.file_imports",
+        params![ns],
+    )?;
+    tx.execute(
+        "INSERT OR IGNORE INTO chunk_meta(chunk_id, path, start_line, end_line, priority)
+         SELECT ?1 || ':' || chunk_id, ?1 || '/' || path, start_line, end_line, priority
+         FROM src.chunk_meta",
+        params![ns],
+    )?;
+    tx.execute(
+        "INSERT OR IGNORE INTO symbol_refs(symbol, chunk_id, ref_kind)
+         SELECT symbol, ?1 || ':' || chunk_id, ref_kind FROM src.symbol_refs",
+        params![ns],
+    )?;
+    tx.commit()?;
+    Ok(MergeCounts { symbols, imports })
+}
+
+/// Fail early, naming the file, when a source lacks the graph tables.
+fn ensure_graph_tables(path: &Path) -> Result<()> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    for table in ["symbol_chunks", "file_imports", "chunk_meta", "symbol_refs"] {
+        let present: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+                [table],
+                |row| row.get(0),
+            )
+            .with_context(|| format!("{} is not a SQLite database", path.display()))?;
+        if !present {
+            bail!("{} has no {table} table; is it an index or symbol graph?", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Last path segment of the repository (URL or root path) an index records in its metadata.
+fn repo_metadata_name(path: &Path) -> Option<String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    let repo: String = conn
+        .query_row("SELECT value FROM metadata WHERE key = 'repo_url'", [], |row| row.get(0))
+        .ok()?;
+    let name = repo.trim_end_matches(['/', '\\']).rsplit(['/', '\\']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn fs_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn seed(path: &Path, symbol: &str, file: &str, import: &str) {
+        let conn = open_or_create(path).expect("create graph");
+        conn.execute(
+            "INSERT INTO symbol_chunks(symbol, chunk_id, kind, path) VALUES(?1, 'c1', 'def', ?2)",
+            params![symbol, file],
+        )
+        .expect("insert symbol");
+        conn.execute(
+            "INSERT INTO file_imports(source_path, target_path) VALUES(?1, ?2)",
+            params![file, import],
+        )
+        .expect("insert import");
+        conn.execute(
+            "INSERT INTO chunk_meta(chunk_id, path, start_line, end_line, priority)
+             VALUES('c1', ?1, 1, 5, 0.5)",
+            params![file],
+        )
+        .expect("insert chunk meta");
+        conn.execute(
+            "INSERT INTO symbol_refs(symbol, chunk_id, ref_kind) VALUES(?1, 'c1', 'call')",
+            params![symbol],
+        )
+        .expect("insert ref");
+    }
+
+    #[test]
+    fn merged_graph_namespaces_both_sources() {
+        let tmp = TempDir::new().expect("tmp");
+        let a = tmp.path().join("a.sqlite");
+        let b = tmp.path().join("b.sqlite");
+        seed(&a, "parse_config", "src/config.rs", "src/util.rs");
+        seed(&b, "load", "src/config.rs", "src/io.rs");
+        let out = tmp.path().join("merged.sqlite");
+
+        let sources = vec![
+            GraphSource::parse(a.to_str().expect("path")).expect("parse a"),
+            GraphSource::parse(&format!("backend={}", b.display())).expect("parse b"),
+        ];
+        let counts = merge_graphs(&sources, &out).expect("merge");
+        assert_eq!(counts, vec![MergeCounts { symbols: 1, imports: 1 }; 2]);
+
+        let conn = Connection::open(&out).expect("open merged");
+        let mut stmt = conn
+            .prepare("SELECT symbol, chunk_id, path FROM symbol_chunks ORDER BY symbol")
+            .expect("prepare");
+        let rows: Vec<(String, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .expect("query")
+            .map(|row| row.expect("row"))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("load".into(), "backend:c1".into(), "backend/src/config.rs".into()),
+                ("parse_config".into(), "a:c1".into(), "a/src/config.rs".into()),
+            ]
+        );
+        let imports: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM file_imports WHERE source_path IN ('a/src/config.rs', 'backend/src/config.rs')",
+                [],
+                |row| row.get(0),
+            )
+            .expect("count imports");
+        assert_eq!(imports, 2);
+
+        // Re-merging replaces the earlier result instead of accumulating rows.
+        merge_graphs(&sources[..1], &out).expect("merge again");
+        let symbols: i64 = conn
+            .query_row("SELECT COUNT(*) FROM symbol_chunks", [], |row| row.get(0))
+            .expect("count symbols");
+        assert_eq!(symbols, 1);
+
+        let clash = vec![
+            GraphSource::parse(a.to_str().expect("path")).expect("parse a"),
+            GraphSource::parse(&format!("a={}", b.display())).expect("parse b"),
+        ];
+        let err = merge_graphs(&clash, &out).expect_err("duplicate namespace");
+        assert!(err.to_string().contains("share the namespace"));
+    }
+}
@@ -1,6 +1,8 @@
 //! Persisted symbol and module graph.
 
+pub mod file_deps;
 pub mod lazy_loader;
+pub mod merge;
 pub mod persist;
 pub mod schema;
 pub mod symbol_usage;
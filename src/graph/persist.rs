@@ -5,9 +5,17 @@ use crate::graph::symbol_usage::{extract_symbol_usages, UsageKind};
 use crate::rank::{extract_import_references, resolve_reference, symbol_definitions};
 use anyhow::Result;
 use rusqlite::{params, Connection};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-pub fn persist_graph(conn: &mut Connection, chunks: &[Chunk]) -> Result<(usize, usize)> {
+/// Rebuild the graph tables from `chunks`.
+///
+/// With `max_occurrences_per_symbol`, each symbol keeps references from at most that many
+/// chunks, the first ones by path and line. Returns `(symbols, import edges)` written.
+pub fn persist_graph(
+    conn: &mut Connection,
+    chunks: &[Chunk],
+    max_occurrences_per_symbol: Option<usize>,
+) -> Result<(usize, usize)> {
     let tx = conn.transaction()?;
 
     tx.execute("DELETE FROM symbol_chunks", [])?;
@@ -39,8 +47,11 @@ pub fn persist_graph(conn: &mut Connection, chunks: &[Chunk]) -> Result<(usize,
     }
 
     let defs = symbol_definitions(chunks);
+    let mut ordered: Vec<&Chunk> = chunks.iter().collect();
+    ordered.sort_by(|a, b| (&a.path, a.start_line, &a.id).cmp(&(&b.path, b.start_line, &b.id)));
+    let mut occurrence_chunks: HashMap<String, HashSet<&str>> = HashMap::new();
     let mut edge_count = 0usize;
-    for chunk in chunks {
+    for chunk in ordered {
         for reference in extract_import_references(&chunk.content) {
             for target in resolve_reference(&reference, &chunk.path, &known_files) {
                 if target == chunk.path {
@@ -65,6 +76,13 @@ pub fn persist_graph(conn: &mut Connection, chunks: &[Chunk]) -> Result<(usize,
         }
         for (symbol, ref_kind) in usages {
             if defs.contains_key(&symbol) {
+                let seen = occurrence_chunks.entry(symbol.clone()).or_default();
+                if !seen.contains(chunk.id.as_str()) {
+                    if max_occurrences_per_symbol.is_some_and(|cap| seen.len() >= cap) {
+                        continue;
+                    }
+                    seen.insert(chunk.id.as_str());
+                }
                 tx.execute(
                     "INSERT OR REPLACE INTO symbol_refs(symbol, chunk_id, ref_kind) VALUES(?1, ?2, ?3)",
                     params![symbol, chunk.id, ref_kind.as_str()],
@@ -24,14 +24,25 @@ impl UsageKind {
     }
 }
 
-pub fn extract_symbol_usages(content: &str, language: &str) -> Vec<(String, UsageKind)> {
-    let ts_language: Language = match language {
+/// Whether [`extract_symbol_usages`] understands `language`; others yield no usages.
+pub fn supports_symbol_usages(language: &str) -> bool {
+    usage_grammar(language).is_some()
+}
+
+fn usage_grammar(language: &str) -> Option<Language> {
+    Some(match language {
         "python" => tree_sitter_python::LANGUAGE.into(),
         "rust" => tree_sitter_rust::LANGUAGE.into(),
         "javascript" => tree_sitter_javascript::LANGUAGE.into(),
         "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
         "go" => tree_sitter_go::LANGUAGE.into(),
-        _ => return Vec::new(),
+        _ => return None,
+    })
+}
+
+pub fn extract_symbol_usages(content: &str, language: &str) -> Vec<(String, UsageKind)> {
+    let Some(ts_language) = usage_grammar(language) else {
+        return Vec::new();
     };
 
     let mut parser = Parser::new();
@@ -78,11 +89,9 @@ fn collect_rust(node: Node<'_>, content: &str, out: &mut BTreeSet<(String, Usage
                 }
             }
         }
-        "type_identifier" => {
-            if !is_definition_name_node(node) {
-                if let Some(sym) = symbol_text(node, content) {
-                    out.insert((sym, UsageKind::TypeUse));
-                }
+        "type_identifier" if !is_definition_name_node(node) => {
+            if let Some(sym) = symbol_text(node, content) {
+                out.insert((sym, UsageKind::TypeUse));
             }
         }
         _ => {}
@@ -8,6 +8,7 @@ pub mod chunk;
 pub mod cli;
 pub mod config;
 pub mod domain;
+pub mod export;
 pub mod fetch;
 pub mod graph;
 pub mod lsp;
@@ -2,10 +2,12 @@
 
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use std::collections::BTreeSet;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 pub fn is_available() -> bool {
     Command::new("rust-analyzer")
@@ -49,6 +51,154 @@ pub struct SymbolReference {
     pub line: u32,
 }
 
+/// A diagnostic rust-analyzer published for a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiagnostic {
+    pub path: String,
+    /// 1-based line the diagnostic starts on
+    pub line: usize,
+    /// `error`, `warning`, `info` or `hint`
+    pub severity: &'static str,
+    pub message: String,
+    pub code: Option<String>,
+}
+
+/// How long to keep listening for diagnostics once rust-analyzer reports it is idle.
+const DIAGNOSTICS_GRACE: Duration = Duration::from_secs(2);
+
+/// Open `paths` (relative to `root`) in rust-analyzer and collect the diagnostics it
+/// publishes for them.
+///
+/// Listening stops shortly after the server reports it is quiescent, or at `timeout`.
+/// Only the latest diagnostics per file are kept, as LSP clients do.
+pub fn collect_diagnostics(
+    root: &Path,
+    paths: &[String],
+    timeout: Duration,
+) -> Result<Vec<FileDiagnostic>> {
+    if !is_available() {
+        anyhow::bail!("rust-analyzer is not available in PATH");
+    }
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut conn = LspConnection::spawn("rust-analyzer")?;
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let init = json!({
+        "processId": null,
+        "rootUri": file_uri(&root)?,
+        "capabilities": {
+            "textDocument": { "publishDiagnostics": {} },
+            "experimental": { "serverStatusNotification": true },
+        },
+        "trace": "off",
+    });
+    let _ = conn.request("initialize", init)?;
+    conn.notify("initialized", json!({}))?;
+
+    for path in paths {
+        let full_path = root.join(path);
+        let Ok(text) = std::fs::read_to_string(&full_path) else {
+            continue;
+        };
+        let params = json!({
+            "textDocument": {
+                "uri": file_uri(&full_path)?,
+                "languageId": "rust",
+                "version": 1,
+                "text": text,
+            }
+        });
+        conn.notify("textDocument/didOpen", params)?;
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut quiescent_at: Option<Instant> = None;
+    let mut by_uri: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for message in conn.notifications.drain(..) {
+        record_published_diagnostics(&message, &mut by_uri);
+    }
+    loop {
+        let now = Instant::now();
+        let stop = quiescent_at.map_or(deadline, |at| (at + DIAGNOSTICS_GRACE).min(deadline));
+        if now >= stop {
+            break;
+        }
+        let message = match conn.incoming.recv_timeout(stop - now) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        match message.get("method").and_then(Value::as_str) {
+            Some("textDocument/publishDiagnostics") => {
+                record_published_diagnostics(&message, &mut by_uri);
+            }
+            Some("experimental/serverStatus") => {
+                let quiescent =
+                    message.pointer("/params/quiescent").and_then(Value::as_bool).unwrap_or(false);
+                if quiescent && quiescent_at.is_none() {
+                    quiescent_at = Some(Instant::now());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let _ = conn.request("shutdown", json!(null));
+    let _ = conn.notify("exit", json!(null));
+
+    let mut diagnostics = Vec::new();
+    for (uri, items) in by_uri {
+        let Some(path) = file_uri_to_path(&uri) else {
+            continue;
+        };
+        let Ok(rel) = path.strip_prefix(&root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        for item in items {
+            let line = item.pointer("/range/start/line").and_then(Value::as_u64).unwrap_or(0);
+            let severity = match item.get("severity").and_then(Value::as_u64) {
+                Some(1) => "error",
+                Some(2) => "warning",
+                Some(3) => "info",
+                Some(4) => "hint",
+                _ => "error",
+            };
+            let code = item.get("code").and_then(|code| match code {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            });
+            diagnostics.push(FileDiagnostic {
+                path: rel.clone(),
+                line: line as usize + 1,
+                severity,
+                message: item.get("message").and_then(Value::as_str).unwrap_or("").to_string(),
+                code,
+            });
+        }
+    }
+    diagnostics.sort_by(|a, b| (&a.path, a.line, &a.message).cmp(&(&b.path, b.line, &b.message)));
+    Ok(diagnostics)
+}
+
+fn record_published_diagnostics(message: &Value, by_uri: &mut BTreeMap<String, Vec<Value>>) {
+    if message.get("method").and_then(Value::as_str) != Some("textDocument/publishDiagnostics") {
+        return;
+    }
+    let Some(uri) = message.pointer("/params/uri").and_then(Value::as_str) else {
+        return;
+    };
+    let items = message
+        .pointer("/params/diagnostics")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    by_uri.insert(uri.to_string(), items);
+}
+
 pub fn analyze_workspace_symbols(
     root: &Path,
     query: &str,
@@ -296,7 +446,10 @@ fn is_windows_drive_path(s: &str) -> bool {
 struct LspConnection {
     child: Child,
     stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    /// Messages read from the server's stdout by a background thread
+    incoming: Receiver<Value>,
+    /// Diagnostics published while waiting for a response
+    notifications: Vec<Value>,
     next_id: i64,
 }
 
@@ -311,7 +464,16 @@ impl LspConnection {
 
         let stdin = child.stdin.take().context("Failed to open LSP stdin")?;
         let stdout = child.stdout.take().context("Failed to open LSP stdout")?;
-        Ok(Self { child, stdin, stdout: BufReader::new(stdout), next_id: 1 })
+        let (sender, incoming) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(message) = read_lsp_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { child, stdin, incoming, notifications: Vec::new(), next_id: 1 })
     }
 
     fn notify(&mut self, method: &str, params: Value) -> Result<()> {
@@ -329,6 +491,11 @@ impl LspConnection {
             let incoming = self.read_message()?;
             let msg_id = incoming.get("id").and_then(Value::as_i64);
             if msg_id != Some(id) {
+                if incoming.get("method").and_then(Value::as_str)
+                    == Some("textDocument/publishDiagnostics")
+                {
+                    self.notifications.push(incoming);
+                }
                 continue;
             }
 
@@ -348,30 +515,34 @@ impl LspConnection {
     }
 
     fn read_message(&mut self) -> Result<Value> {
-        let mut content_length = None::<usize>;
+        self.incoming.recv().map_err(|_| anyhow::anyhow!("LSP process ended unexpectedly"))
+    }
+}
 
-        loop {
-            let mut line = String::new();
-            let read = self.stdout.read_line(&mut line)?;
-            if read == 0 {
-                anyhow::bail!("LSP process ended unexpectedly");
-            }
-            let trimmed = line.trim_end();
-            if trimmed.is_empty() {
-                break;
-            }
-            let lower = trimmed.to_ascii_lowercase();
-            if let Some(rest) = lower.strip_prefix("content-length:") {
-                content_length = rest.trim().parse::<usize>().ok();
-            }
-        }
+fn read_lsp_message(reader: &mut impl BufRead) -> Result<Value> {
+    let mut content_length = None::<usize>;
 
-        let len = content_length.context("Missing Content-Length in LSP response")?;
-        let mut buf = vec![0u8; len];
-        self.stdout.read_exact(&mut buf)?;
-        let value: Value = serde_json::from_slice(&buf)?;
-        Ok(value)
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            anyhow::bail!("LSP process ended unexpectedly");
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            break;
+        }
+        let lower = trimmed.to_ascii_lowercase();
+        if let Some(rest) = lower.strip_prefix("content-length:") {
+            content_length = rest.trim().parse::<usize>().ok();
+        }
     }
+
+    let len = content_length.context("Missing Content-Length in LSP response")?;
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    let value: Value = serde_json::from_slice(&buf)?;
+    Ok(value)
 }
 
 impl Drop for LspConnection {
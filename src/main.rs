@@ -4,22 +4,8 @@
 //! for large language model prompting and RAG (Retrieval-Augmented Generation) workflows.
 
 use anyhow::Result;
+use std::process::ExitCode;
 
-mod analysis;
-mod chunk;
-mod cli;
-mod config;
-mod domain;
-mod fetch;
-mod graph;
-mod lsp;
-mod rank;
-mod redact;
-mod render;
-mod rerank;
-mod scan;
-mod utils;
-
-fn main() -> Result<()> {
-    cli::run()
+fn main() -> Result<ExitCode> {
+    repo_context::cli::run()
 }
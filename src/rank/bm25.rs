@@ -6,7 +6,25 @@ use std::collections::{HashMap, HashSet};
 const K1: f64 = 1.5;
 const B: f64 = 0.75;
 
+/// Tags naming a symbol the chunk defines.
+const SYMBOL_TAG_PREFIXES: &[&str] = &["def:", "type:", "impl:"];
+
+/// How many times each symbol-tag term counts in `score_query_against_chunks`.
+pub const DEFAULT_TAG_TERM_WEIGHT: usize = 3;
+
 pub fn score_query_against_chunks(chunks: &[Chunk], query: &str) -> Vec<f64> {
+    score_query_against_chunks_weighted(chunks, query, DEFAULT_TAG_TERM_WEIGHT)
+}
+
+/// BM25 over chunk content plus the names in its `def:`/`type:`/`impl:` tags.
+///
+/// Each tag term is counted `tag_term_weight` times, so a chunk defining `refresh_token`
+/// outranks one that only mentions "refresh token"; `0` scores content alone.
+pub fn score_query_against_chunks_weighted(
+    chunks: &[Chunk],
+    query: &str,
+    tag_term_weight: usize,
+) -> Vec<f64> {
     if chunks.is_empty() {
         return Vec::new();
     }
@@ -21,7 +39,12 @@ pub fn score_query_against_chunks(chunks: &[Chunk], query: &str) -> Vec<f64> {
     let mut total_len = 0usize;
 
     for chunk in chunks {
-        let tokens = tokenize(&chunk.content);
+        let mut tokens = tokenize(&chunk.content);
+        if tag_term_weight > 0 {
+            for term in symbol_tag_terms(chunk) {
+                tokens.extend(std::iter::repeat_n(term, tag_term_weight));
+            }
+        }
         total_len += tokens.len();
 
         let unique: HashSet<String> = tokens.iter().cloned().collect();
@@ -63,49 +86,103 @@ pub fn score_query_against_chunks(chunks: &[Chunk], query: &str) -> Vec<f64> {
         .collect()
 }
 
+/// Terms for the symbols a chunk's tags name: the whole identifier plus its
+/// snake_case, camelCase and `scope.name` parts (`def:Auth.refreshToken` yields
+/// `auth`, `refreshtoken`, `refresh`, `token`).
+fn symbol_tag_terms(chunk: &Chunk) -> Vec<String> {
+    let mut terms = Vec::new();
+    for tag in &chunk.tags {
+        let Some(name) = SYMBOL_TAG_PREFIXES.iter().find_map(|prefix| tag.strip_prefix(prefix))
+        else {
+            continue;
+        };
+        let mut words = String::with_capacity(name.len() * 2);
+        let mut prev_lower = false;
+        for c in name.chars() {
+            if !c.is_alphanumeric() || (c.is_uppercase() && prev_lower) {
+                words.push(' ');
+            }
+            if c.is_alphanumeric() {
+                words.push(c);
+            }
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+        let mut tag_terms = tokenize(name);
+        tag_terms.extend(tokenize(&words));
+        tag_terms.sort();
+        tag_terms.dedup();
+        terms.extend(tag_terms);
+    }
+    terms
+}
+
+/// Split `text` into lowercase terms.
+///
+/// Latin-script words split on non-alphanumerics. CJK text has no word separators, so
+/// each run of CJK characters becomes overlapping bigrams (a lone character stays a
+/// unigram), which lets a CJK query match any text containing the same phrase.
 fn tokenize(text: &str) -> Vec<String> {
-    text.split(|c: char| !c.is_alphanumeric() && c != '_')
-        .filter_map(|token| {
-            let lower = token.trim().to_ascii_lowercase();
-            if lower.len() >= 2 {
-                Some(lower)
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let mut rest = word;
+        while let Some(first) = rest.chars().next() {
+            let cjk = is_cjk(first);
+            let end = rest.find(|c: char| is_cjk(c) != cjk).unwrap_or(rest.len());
+            let (run, tail) = rest.split_at(end);
+            if cjk {
+                push_cjk_bigrams(run, &mut tokens);
             } else {
-                None
+                let lower = run.to_ascii_lowercase();
+                if lower.len() >= 2 {
+                    tokens.push(lower);
+                }
             }
-        })
-        .collect()
+            rest = tail;
+        }
+    }
+    tokens
+}
+
+fn push_cjk_bigrams(run: &str, tokens: &mut Vec<String>) {
+    let chars: Vec<char> = run.chars().collect();
+    if chars.len() == 1 {
+        tokens.push(run.to_string());
+        return;
+    }
+    tokens.extend(chars.windows(2).map(|pair| pair.iter().collect::<String>()));
+}
+
+/// Han ideographs, kana and Hangul.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF       // Hiragana, Katakana
+            | 0x3400..=0x4DBF // CJK Extension A
+            | 0x4E00..=0x9FFF // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF // Hangul syllables
+            | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+            | 0x20000..=0x2FA1F // Extensions B-F, compatibility supplement
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::score_query_against_chunks;
-    use crate::domain::Chunk;
-    use std::collections::BTreeSet;
+    use super::{score_query_against_chunks, score_query_against_chunks_weighted, tokenize};
+    use crate::domain::{test_support, Chunk};
 
     #[test]
     fn bm25_scores_relevant_chunk_higher() {
         let chunks = vec![
             Chunk {
                 id: "1".to_string(),
-                path: "src/auth.rs".to_string(),
-                language: "rust".to_string(),
-                start_line: 1,
-                end_line: 10,
-                content: "fn refresh_token() { oauth refresh token logic }".to_string(),
-                priority: 0.5,
-                tags: BTreeSet::new(),
-                token_estimate: 20,
+                ..test_support::chunk(
+                    "src/auth.rs",
+                    "fn refresh_token() { oauth refresh token logic }",
+                )
             },
             Chunk {
                 id: "2".to_string(),
-                path: "src/math.rs".to_string(),
-                language: "rust".to_string(),
-                start_line: 1,
-                end_line: 10,
-                content: "fn add(a: i32, b: i32) -> i32 { a + b }".to_string(),
-                priority: 0.5,
-                tags: BTreeSet::new(),
-                token_estimate: 20,
+                ..test_support::chunk("src/math.rs", "fn add(a: i32, b: i32) -> i32 { a + b }")
             },
         ];
 
@@ -113,4 +190,45 @@ mod tests {
         assert_eq!(scores.len(), 2);
         assert!(scores[0] > scores[1]);
     }
+
+    #[test]
+    fn symbol_tag_match_outranks_equal_content_match() {
+        let chunk = |id: &str, content: &str, tags: &[&str]| Chunk {
+            id: id.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..test_support::chunk(&format!("src/{id}.rs"), content)
+        };
+        let chunks = vec![
+            chunk("comment", "// refresh token later\nfn rotate(user: User) {}", &[]),
+            chunk("def", "fn handler(user: User) -> Session {}", &["def:refresh_token"]),
+            chunk("math", "fn add(a: i32, b: i32) -> i32 {}", &["def:add"]),
+        ];
+
+        let scores = score_query_against_chunks(&chunks, "refresh token");
+        assert!(scores[1] > scores[0], "{scores:?}");
+        assert_eq!(scores[2], 0.0);
+
+        let unweighted = score_query_against_chunks_weighted(&chunks, "refresh token", 0);
+        assert_eq!(unweighted[1], 0.0);
+        assert!(unweighted[0] > 0.0);
+    }
+
+    #[test]
+    fn cjk_query_matches_chunk_with_same_phrase() {
+        let chunk = |id: &str, content: &str| Chunk {
+            id: id.to_string(),
+            ..test_support::chunk(&format!("src/{id}.py"), content)
+        };
+        let chunks = vec![
+            chunk("math", "# 计算两个数的和\ndef add(a, b):\n    return a + b\n"),
+            chunk("auth", "# 刷新用户的访问令牌\ndef refresh(user):\n    return rotate(user)\n"),
+        ];
+
+        let scores = score_query_against_chunks(&chunks, "访问令牌");
+        assert!(scores[1] > 0.0);
+        assert!(scores[1] > scores[0]);
+
+        assert_eq!(tokenize("令牌token刷新"), vec!["令牌", "token", "刷新"]);
+        assert_eq!(tokenize("a 字 refresh_token"), vec!["字", "refresh_token"]);
+    }
 }
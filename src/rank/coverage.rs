@@ -0,0 +1,265 @@
+//! Coverage-based ranking boost (`--coverage-file`)
+//!
+//! Well-tested files tend to be the core of a codebase, so line coverage from an LCOV
+//! (`lcov.info`) or Cobertura (`coverage.xml`) report is used as an importance signal.
+
+use crate::domain::FileInfo;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Line rate at which a file counts as covered and gets boosted.
+const COVERED_LINE_RATE: f64 = 0.5;
+/// Priority added to a fully covered file; scaled down by its line rate.
+const COVERAGE_BOOST: f64 = 0.1;
+const COVERED_TAG: &str = "covered";
+
+/// Read a coverage report into line rates (0.0-1.0) keyed by the paths it names.
+///
+/// XML content is read as Cobertura, anything else as LCOV. Paths under `root_path`
+/// are made relative to it; Cobertura file names are first joined to the report's
+/// `<source>` directories.
+pub fn load_coverage(path: &Path, root_path: &Path) -> Result<HashMap<String, f64>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read coverage file: {}", path.display()))?;
+    if content.trim_start().starts_with('<') {
+        return parse_cobertura(&content, root_path)
+            .with_context(|| format!("Invalid Cobertura XML: {}", path.display()));
+    }
+    Ok(parse_lcov(&content)
+        .into_iter()
+        .map(|(file, rate)| (normalize_coverage_path(&file, root_path), rate))
+        .collect())
+}
+
+fn parse_lcov(content: &str) -> HashMap<String, f64> {
+    let mut rates = HashMap::new();
+    let mut current: Option<String> = None;
+    let (mut found, mut hit) = (None::<usize>, None::<usize>);
+    let (mut da_lines, mut da_hit) = (0usize, 0usize);
+    for line in content.lines().map(str::trim) {
+        if let Some(file) = line.strip_prefix("SF:") {
+            current = Some(file.to_string());
+            (found, hit, da_lines, da_hit) = (None, None, 0, 0);
+        } else if let Some(n) = line.strip_prefix("LF:") {
+            found = n.trim().parse().ok();
+        } else if let Some(n) = line.strip_prefix("LH:") {
+            hit = n.trim().parse().ok();
+        } else if let Some(rest) = line.strip_prefix("DA:") {
+            da_lines += 1;
+            let hits = rest.split(',').nth(1).and_then(|h| h.trim().parse::<u64>().ok());
+            if hits.unwrap_or(0) > 0 {
+                da_hit += 1;
+            }
+        } else if line == "end_of_record" {
+            if let Some(file) = current.take() {
+                // Prefer the LF/LH summary; fall back to counting DA records.
+                let (total, covered) = match (found, hit) {
+                    (Some(f), Some(h)) => (f, h),
+                    _ => (da_lines, da_hit),
+                };
+                if total > 0 {
+                    rates.insert(file, (covered as f64 / total as f64).min(1.0));
+                }
+            }
+        }
+    }
+    rates
+}
+
+/// Cobertura reports one `<class>` per class; a file's rate is the mean over its classes.
+///
+/// Class file names are relative to one of the `<source>` directories; the first source
+/// under `root_path` names the file, and without one the name is taken as repo-relative.
+fn parse_cobertura(content: &str, root_path: &Path) -> Result<HashMap<String, f64>> {
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    let doc = roxmltree::Document::parse_with_options(content, options)?;
+    let sources: Vec<&str> = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("source"))
+        .filter_map(|node| node.text())
+        .map(str::trim)
+        .filter(|source| !source.is_empty())
+        .collect();
+
+    let mut sums: HashMap<String, (f64, usize)> = HashMap::new();
+    for class in doc.descendants().filter(|node| node.has_tag_name("class")) {
+        let (Some(file), Some(rate)) = (class.attribute("filename"), class.attribute("line-rate"))
+        else {
+            continue;
+        };
+        let Ok(rate) = rate.trim().parse::<f64>() else {
+            continue;
+        };
+        let path = sources
+            .iter()
+            .map(|source| normalize_coverage_path(&format!("{source}/{file}"), root_path))
+            .find(|path| !Path::new(path).is_absolute())
+            .unwrap_or_else(|| normalize_coverage_path(file, root_path));
+        let entry = sums.entry(path).or_insert((0.0, 0));
+        entry.0 += rate.clamp(0.0, 1.0);
+        entry.1 += 1;
+    }
+    Ok(sums.into_iter().map(|(file, (sum, count))| (file, sum / count as f64)).collect())
+}
+
+fn normalize_coverage_path(file: &str, root_path: &Path) -> String {
+    let file = file.replace('\\', "/");
+    let root = root_path.to_string_lossy().replace('\\', "/");
+    let relative = file
+        .strip_prefix(root.trim_end_matches('/'))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(&file);
+    relative.trim_start_matches("./").to_string()
+}
+
+/// Boost and tag files whose line rate reaches [`COVERED_LINE_RATE`], then re-sort; the
+/// boost is recorded on the file's `priority_reason`.
+///
+/// Report paths are relative to the repository root, or to a directory inside it: a
+/// path matches the one file it equals or, failing that, the only file ending in it at a
+/// segment boundary. Returns the number of files boosted.
+pub fn apply_coverage_boost(files: &mut [FileInfo], coverage: &HashMap<String, f64>) -> usize {
+    if coverage.is_empty() {
+        return 0;
+    }
+
+    let paths: Vec<&str> = files.iter().map(|f| f.relative_path.as_str()).collect();
+    let mut rates: HashMap<String, f64> = HashMap::new();
+    for (path, rate) in coverage {
+        if let Some(file) = file_for_coverage_path(path, &paths) {
+            let entry = rates.entry(file.to_string()).or_insert(*rate);
+            *entry = entry.max(*rate);
+        }
+    }
+
+    let mut boosted = 0usize;
+    for file in files.iter_mut() {
+        let Some(&rate) = rates.get(&file.relative_path) else {
+            continue;
+        };
+        if rate < COVERED_LINE_RATE {
+            continue;
+        }
+        let priority = (file.priority + COVERAGE_BOOST * rate).min(1.0);
+        let priority = (priority * 1000.0).round() / 1000.0;
+        if let Some(reason) = file.priority_reason.as_mut() {
+            reason.coverage_boost = Some(((priority - file.priority) * 1000.0).round() / 1000.0);
+        }
+        file.priority = priority;
+        file.tags.insert(COVERED_TAG.to_string());
+        boosted += 1;
+    }
+
+    files.sort_by(|a, b| {
+        b.priority
+            .partial_cmp(&a.priority)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    boosted
+}
+
+/// The repository file a report path names: an exact match, else the only file whose
+/// path ends with it at a segment boundary. Ambiguous suffixes match nothing.
+fn file_for_coverage_path<'a>(report_path: &str, files: &[&'a str]) -> Option<&'a str> {
+    if let Some(file) = files.iter().find(|file| **file == report_path) {
+        return Some(file);
+    }
+    let suffix = format!("/{report_path}");
+    let mut matches = files.iter().filter(|file| file.ends_with(&suffix));
+    match (matches.next(), matches.next()) {
+        (Some(file), None) => Some(file),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_coverage_boost, load_coverage, parse_cobertura, COVERED_TAG};
+    use crate::domain::test_support::file_info;
+    use crate::domain::{FileInfo, PriorityReason};
+    use std::collections::HashMap;
+    use std::fs;
+
+    fn file(path: &str, priority: f64) -> FileInfo {
+        FileInfo { size_bytes: 10, priority, ..file_info(path) }
+    }
+
+    #[test]
+    fn covered_file_outranks_uncovered_sibling() {
+        let root = tempfile::TempDir::new().expect("temp dir");
+        let lcov = format!(
+            "TN:\nSF:{root}/src/billing.py\nDA:1,4\nDA:2,4\nDA:3,1\nDA:4,0\nLF:4\nLH:3\nend_of_record\n\
+             SF:{root}/src/legacy.py\nDA:1,0\nDA:2,0\nLF:2\nLH:0\nend_of_record\n",
+            root = root.path().display()
+        );
+        let lcov_path = root.path().join("lcov.info");
+        fs::write(&lcov_path, lcov).expect("write lcov");
+
+        let coverage = load_coverage(&lcov_path, root.path()).expect("parse lcov");
+        assert_eq!(coverage.get("src/billing.py"), Some(&0.75));
+        assert_eq!(coverage.get("src/legacy.py"), Some(&0.0));
+
+        let mut files = vec![file("src/legacy.py", 0.7), file("src/billing.py", 0.7)];
+        assert_eq!(apply_coverage_boost(&mut files, &coverage), 1);
+        assert_eq!(files[0].relative_path, "src/billing.py");
+        assert!(files[0].priority > files[1].priority);
+        assert!(files[0].tags.contains(COVERED_TAG));
+        assert!(!files[1].tags.contains(COVERED_TAG));
+    }
+
+    #[test]
+    fn cobertura_rates_are_averaged_per_file_under_their_source() {
+        let root = tempfile::TempDir::new().expect("temp dir");
+        let xml = format!(
+            r#"<?xml version="1.0" ?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+<coverage><sources><source>/ci/elsewhere</source><source>{root}/src</source></sources>
+<packages><package name="app"><classes>
+    <class name="A&gt;B" filename="app/models.py" line-rate="0.9"></class>
+    <class line-rate="0.5" name="B" filename="app/models.py"/>
+</classes></package></packages></coverage>"#,
+            root = root.path().display()
+        );
+        let rates = parse_cobertura(&xml, root.path()).expect("parse cobertura");
+        let rate = rates.get("src/app/models.py").copied().expect("models.py rate");
+        assert!((rate - 0.7).abs() < 1e-9);
+        assert!(parse_cobertura("<coverage><class", root.path()).is_err());
+    }
+
+    #[test]
+    fn report_paths_match_repo_files_by_unique_suffix_only() {
+        let mut reason_file = file("src/app/models.py", 0.5);
+        reason_file.priority_reason = Some(PriorityReason {
+            category: "source_code".to_string(),
+            weight: 0.5,
+            language_multiplier: None,
+            coverage_boost: None,
+        });
+        let mut files = vec![
+            reason_file,
+            file("models.py", 0.5),
+            file("a/util.py", 0.5),
+            file("b/util.py", 0.5),
+        ];
+        let coverage: HashMap<String, f64> = [
+            // Relative to `src/`: the one file ending in it matches.
+            ("app/models.py".to_string(), 1.0),
+            // A longer path does not match the shorter repo file `models.py`.
+            ("vendor/models.py".to_string(), 1.0),
+            // Two files end in `util.py`, so neither is boosted.
+            ("util.py".to_string(), 1.0),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(apply_coverage_boost(&mut files, &coverage), 1);
+        let boosted = &files[0];
+        assert_eq!(boosted.relative_path, "src/app/models.py");
+        assert_eq!(boosted.priority, 0.6);
+        assert_eq!(boosted.priority_reason.as_ref().and_then(|r| r.coverage_boost), Some(0.1));
+        assert!(files[1..].iter().all(|f| !f.tags.contains(COVERED_TAG)));
+    }
+}
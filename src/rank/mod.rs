@@ -2,12 +2,14 @@
 
 use crate::domain::{Chunk, FileInfo, RankingWeights};
 use crate::graph::lazy_loader::LazyChunkLoader;
+use crate::utils::EncodingOverrides;
 use anyhow::Result;
 use serde_json::Value as JsonValue;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 pub mod bm25;
+pub mod coverage;
 pub mod ranker;
 
 pub use ranker::FileRanker;
@@ -48,37 +50,111 @@ pub struct StitchedBundle {
     pub lazy_chunks: Vec<Chunk>,
 }
 
+/// Tunables for the dependency expansion step of task reranking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpansionOptions {
+    /// Number of top lexical files expanded from
+    pub seeds: usize,
+    /// Dependency-graph hops followed from each seed
+    pub depth: usize,
+    /// Score multiplier per level; levels past the end halve the previous factor
+    pub decay: Vec<f64>,
+}
+
+impl Default for ExpansionOptions {
+    fn default() -> Self {
+        Self { seeds: 5, depth: 2, decay: vec![0.6, 0.3] }
+    }
+}
+
+/// Rerank toward a task query.
 pub fn rerank_chunks_by_task(
     chunks: &mut [Chunk],
     query: &str,
     relevance_weight: f64,
+    expansion_options: &ExpansionOptions,
+) -> HashMap<String, f64> {
+    rerank_chunks(chunks, Some(query), &[], relevance_weight, expansion_options)
+}
+
+/// Rerank toward `seed_files` as though they were the only lexical hits of a task query.
+pub fn rerank_chunks_by_seed_files(
+    chunks: &mut [Chunk],
+    seed_files: &[String],
+    relevance_weight: f64,
+    expansion_options: &ExpansionOptions,
+) -> HashMap<String, f64> {
+    rerank_chunks(chunks, None, seed_files, relevance_weight, expansion_options)
+}
+
+/// Rerank toward a task query, seed files or both, blending each chunk's priority once.
+///
+/// A chunk's relevance is 1.0 in a seed file and otherwise its BM25 score against the
+/// query, normalized to the best match. Seeds are all expanded through the dependency
+/// graph, and the symbols they define join the query text, so their callers and tests
+/// are boosted too.
+pub fn rerank_chunks(
+    chunks: &mut [Chunk],
+    query: Option<&str>,
+    seed_files: &[String],
+    relevance_weight: f64,
+    expansion_options: &ExpansionOptions,
 ) -> HashMap<String, f64> {
     let weight = relevance_weight.clamp(0.0, 1.0);
-    let lexical_scores = bm25::score_query_against_chunks(chunks, query);
+    let seeds: HashSet<&str> = seed_files.iter().map(String::as_str).collect();
+    let lexical_scores = match query {
+        Some(query) => bm25::score_query_against_chunks(chunks, query),
+        None => vec![0.0; chunks.len()],
+    };
 
     let mut max_score = 0.0_f64;
     for score in &lexical_scores {
         max_score = max_score.max(*score);
     }
 
-    let mut file_scores: HashMap<String, f64> = HashMap::new();
     let mut lexical_by_file: HashMap<String, f64> = HashMap::new();
-
-    for (chunk, lexical) in chunks.iter_mut().zip(lexical_scores.into_iter()) {
-        let normalized = if max_score > 0.0 { lexical / max_score } else { 0.0 };
-        let blended = (chunk.priority * (1.0 - weight)) + (normalized * weight);
+    for (chunk, lexical) in chunks.iter_mut().zip(lexical_scores) {
+        let relevance = if seeds.contains(chunk.path.as_str()) {
+            1.0
+        } else if max_score > 0.0 {
+            lexical / max_score
+        } else {
+            0.0
+        };
+        let blended = (chunk.priority * (1.0 - weight)) + (relevance * weight);
         chunk.priority = (blended * 1000.0).round() / 1000.0;
-        file_scores
-            .entry(chunk.path.clone())
-            .and_modify(|existing| *existing = existing.max(chunk.priority))
-            .or_insert(chunk.priority);
         lexical_by_file
             .entry(chunk.path.clone())
-            .and_modify(|existing| *existing = existing.max(normalized))
-            .or_insert(normalized);
+            .and_modify(|existing| *existing = existing.max(relevance))
+            .or_insert(relevance);
     }
 
-    let expansion = dependency_expansion_scores(chunks, &lexical_by_file);
+    let mut seed_symbols: Vec<String> = symbol_definitions(chunks)
+        .into_iter()
+        .filter(|(_, files)| files.iter().any(|f| seeds.contains(f.as_str())))
+        .map(|(symbol, _)| symbol)
+        .collect();
+    seed_symbols.sort();
+    // Every seed is expanded; with a query, its top hits are expanded as well.
+    let expanded = match query {
+        Some(_) if !seeds.is_empty() => expansion_options.seeds + seeds.len(),
+        _ => expansion_options.seeds.max(seeds.len()),
+    };
+    let options = ExpansionOptions { seeds: expanded, ..expansion_options.clone() };
+    let query_terms: Vec<String> =
+        query.into_iter().map(str::to_string).chain(seed_symbols).collect();
+    expand_and_score_files(chunks, &lexical_by_file, &query_terms.join(" "), &options)
+}
+
+/// Apply dependency and symbol expansion to lexically scored chunks; returns the best
+/// chunk priority per file.
+fn expand_and_score_files(
+    chunks: &mut [Chunk],
+    lexical_by_file: &HashMap<String, f64>,
+    query: &str,
+    expansion_options: &ExpansionOptions,
+) -> HashMap<String, f64> {
+    let expansion = dependency_expansion_scores(chunks, lexical_by_file, expansion_options);
     for chunk in chunks.iter_mut() {
         if let Some(expanded) = expansion.get(&chunk.path) {
             let boosted = (chunk.priority * 0.8) + (expanded * 0.2);
@@ -94,14 +170,18 @@ pub fn rerank_chunks_by_task(
         }
     }
 
-    file_scores.clear();
-    for chunk in chunks.iter() {
+    max_chunk_priority_by_file(chunks)
+}
+
+/// Best chunk priority per file path.
+pub fn max_chunk_priority_by_file(chunks: &[Chunk]) -> HashMap<String, f64> {
+    let mut file_scores: HashMap<String, f64> = HashMap::new();
+    for chunk in chunks {
         file_scores
             .entry(chunk.path.clone())
             .and_modify(|existing| *existing = existing.max(chunk.priority))
             .or_insert(chunk.priority);
     }
-
     file_scores
 }
 
@@ -352,6 +432,7 @@ pub fn stitch_thread_bundles(
 fn dependency_expansion_scores(
     chunks: &[Chunk],
     lexical_by_file: &HashMap<String, f64>,
+    options: &ExpansionOptions,
 ) -> HashMap<String, f64> {
     let known_files: HashSet<String> = chunks.iter().map(|c| c.path.clone()).collect();
     if known_files.is_empty() {
@@ -368,24 +449,25 @@ fn dependency_expansion_scores(
     });
 
     let mut expanded: HashMap<String, f64> = HashMap::new();
-    for (seed, score) in seeds.into_iter().take(5) {
+    for (seed, score) in seeds.into_iter().take(options.seeds) {
         expanded.entry(seed.clone()).and_modify(|v| *v = v.max(*score)).or_insert(*score);
 
-        if let Some(neighbors) = graph.get(seed) {
-            for neighbor in neighbors {
-                let value = (score * 0.6).min(1.0);
-                expanded.entry(neighbor.clone()).and_modify(|v| *v = v.max(value)).or_insert(value);
-
-                if let Some(level2) = graph.get(neighbor) {
-                    for neighbor2 in level2 {
-                        let value2 = (score * 0.3).min(1.0);
-                        expanded
-                            .entry(neighbor2.clone())
-                            .and_modify(|v| *v = v.max(value2))
-                            .or_insert(value2);
-                    }
+        let mut frontier: BTreeSet<&String> = BTreeSet::from([seed]);
+        let mut factor = 1.0_f64;
+        for level in 0..options.depth {
+            factor = options.decay.get(level).copied().unwrap_or(factor * 0.5);
+            let value = (score * factor).min(1.0);
+            let mut next: BTreeSet<&String> = BTreeSet::new();
+            for node in frontier {
+                for neighbor in graph.get(node).into_iter().flatten() {
+                    expanded
+                        .entry(neighbor.clone())
+                        .and_modify(|v| *v = v.max(value))
+                        .or_insert(value);
+                    next.insert(neighbor);
                 }
             }
+            frontier = next;
         }
     }
 
@@ -513,30 +595,180 @@ pub(crate) fn dependency_graph(
     symbol_defs: &HashMap<String, HashSet<String>>,
 ) -> HashMap<String, BTreeSet<String>> {
     let mut graph: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for (source, targets) in directed_dependencies(chunks, known_files, symbol_defs) {
+        for target in targets {
+            graph.entry(target.clone()).or_default().insert(source.clone());
+            graph.entry(source.clone()).or_default().insert(target);
+        }
+    }
+    graph
+}
 
+/// Edges from each file to the files it imports or whose symbols it mentions; the
+/// directed form of [`dependency_graph`].
+fn directed_dependencies(
+    chunks: &[Chunk],
+    known_files: &HashSet<String>,
+    symbol_defs: &HashMap<String, HashSet<String>>,
+) -> HashMap<String, BTreeSet<String>> {
+    let mut graph = import_dependencies(chunks, known_files);
+    for chunk in chunks {
+        for token in tokenize(&chunk.content) {
+            if let Some(def_files) = symbol_defs.get(&token) {
+                for target in def_files {
+                    if target != &chunk.path {
+                        graph.entry(chunk.path.clone()).or_default().insert(target.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+/// Edges from each file to the files it imports.
+fn import_dependencies(
+    chunks: &[Chunk],
+    known_files: &HashSet<String>,
+) -> HashMap<String, BTreeSet<String>> {
+    let mut graph: HashMap<String, BTreeSet<String>> = HashMap::new();
     for chunk in chunks {
         for reference in extract_import_references(&chunk.content) {
             for target in resolve_reference(&reference, &chunk.path, known_files) {
                 if target != chunk.path {
-                    graph.entry(chunk.path.clone()).or_default().insert(target.clone());
-                    graph.entry(target).or_default().insert(chunk.path.clone());
+                    graph.entry(chunk.path.clone()).or_default().insert(target);
                 }
             }
         }
+    }
+    graph
+}
 
-        for token in tokenize(&chunk.content) {
-            if let Some(def_files) = symbol_defs.get(&token) {
-                for target in def_files {
-                    if target != &chunk.path {
-                        graph.entry(chunk.path.clone()).or_default().insert(target.clone());
-                        graph.entry(target.clone()).or_default().insert(chunk.path.clone());
+/// Paths of `chunks` ordered so each file follows the files it imports (`--order
+/// topological`).
+///
+/// Import cycles are collapsed into strongly connected components, which are placed in
+/// dependency order like single files; the files of one component are ordered by path.
+/// Among components whose dependencies are all placed, the one with the smallest path
+/// goes first.
+pub fn topological_file_order(chunks: &[Chunk]) -> Vec<String> {
+    let known_files: HashSet<String> = chunks.iter().map(|c| c.path.clone()).collect();
+    let imports = import_dependencies(chunks, &known_files);
+    let files: Vec<String> = known_files.into_iter().collect::<BTreeSet<_>>().into_iter().collect();
+    let index: HashMap<&str, usize> =
+        files.iter().enumerate().map(|(i, path)| (path.as_str(), i)).collect();
+    let edges: Vec<Vec<usize>> = files
+        .iter()
+        .map(|path| {
+            imports
+                .get(path)
+                .into_iter()
+                .flatten()
+                .filter_map(|target| index.get(target.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    // Components come out of Tarjan's algorithm with their files in path order.
+    let components = strongly_connected_components(&edges);
+    let mut component_of = vec![0; files.len()];
+    for (c, members) in components.iter().enumerate() {
+        for &file in members {
+            component_of[file] = c;
+        }
+    }
+    let mut unplaced_deps = vec![0usize; components.len()];
+    let mut importers: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); components.len()];
+    for (file, targets) in edges.iter().enumerate() {
+        for &target in targets {
+            let (from, to) = (component_of[file], component_of[target]);
+            if from != to && importers[to].insert(from) {
+                unplaced_deps[from] += 1;
+            }
+        }
+    }
+
+    // Ready components keyed by their smallest file index, i.e. their smallest path.
+    let mut ready: BTreeSet<(usize, usize)> = (0..components.len())
+        .filter(|&c| unplaced_deps[c] == 0)
+        .map(|c| (components[c][0], c))
+        .collect();
+    let mut order = Vec::with_capacity(files.len());
+    while let Some((_, c)) = ready.pop_first() {
+        order.extend(components[c].iter().map(|&file| files[file].clone()));
+        for &importer in &importers[c] {
+            unplaced_deps[importer] -= 1;
+            if unplaced_deps[importer] == 0 {
+                ready.insert((components[importer][0], importer));
+            }
+        }
+    }
+    order
+}
+
+/// Tarjan's strongly connected components of the graph `edges[node] -> targets`; each
+/// component lists its nodes in ascending order.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.lowlink[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for &target in &self.edges[node] {
+                match self.index[target] {
+                    None => {
+                        self.visit(target);
+                        self.lowlink[node] = self.lowlink[node].min(self.lowlink[target]);
                     }
+                    Some(target_index) if self.on_stack[target] => {
+                        self.lowlink[node] = self.lowlink[node].min(target_index);
+                    }
+                    Some(_) => {}
                 }
             }
+            if Some(self.lowlink[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
         }
     }
 
-    graph
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; edges.len()],
+        lowlink: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
 }
 
 pub(crate) fn extract_import_references(content: &str) -> Vec<String> {
@@ -564,8 +796,9 @@ pub(crate) fn extract_import_references(content: &str) -> Vec<String> {
             }
         }
 
+        // `mod x;` loads a file; an inline `mod x { .. }` does not.
         if let Some(rest) = trimmed.strip_prefix("mod ") {
-            if let Some(module) = rest.split(';').next() {
+            if let Some(module) = rest.split(';').next().filter(|m| !m.contains('{')) {
                 refs.push(module.trim().to_string());
             }
         }
@@ -749,19 +982,64 @@ pub fn rank_files_with_manifest(
     root_path: &Path,
     mut files: Vec<FileInfo>,
     weights: RankingWeights,
+    fixture_dirs: &[String],
+    language_multipliers: &HashMap<String, f64>,
+    encodings: &EncodingOverrides,
 ) -> Result<(Vec<FileInfo>, HashMap<String, JsonValue>)> {
     let scanned_files: HashSet<String> = files.iter().map(|f| f.relative_path.clone()).collect();
-    let ranker = FileRanker::with_weights(root_path, scanned_files, weights);
+    let ranker = FileRanker::with_weights(root_path, scanned_files, weights)
+        .with_fixture_dirs(fixture_dirs.to_vec())
+        .with_language_multipliers(language_multipliers)
+        .with_encodings(encodings.clone());
     ranker.rank_files(&mut files);
     let manifest = ranker.get_manifest_info().clone();
     Ok((files, manifest))
 }
 
+/// Blend mtime-derived recency into file priorities and re-sort.
+///
+/// Recency is normalized over the repo's mtime range (newest = 1.0, oldest = 0.0;
+/// files without an mtime count as oldest): `priority = base * (1 - w) + recency * w`.
+pub fn apply_recency_weight(files: &mut [FileInfo], recency_weight: f64) {
+    let weight = recency_weight.clamp(0.0, 1.0);
+    if weight <= 0.0 {
+        return;
+    }
+
+    let mtimes: Vec<i64> = files.iter().filter_map(|f| f.modified_at).collect();
+    let (Some(&oldest), Some(&newest)) = (mtimes.iter().min(), mtimes.iter().max()) else {
+        return;
+    };
+    let span = (newest - oldest) as f64;
+
+    for file in files.iter_mut() {
+        let recency = match file.modified_at {
+            Some(mtime) if span > 0.0 => (mtime - oldest) as f64 / span,
+            Some(_) => 1.0,
+            None => 0.0,
+        };
+        let blended = file.priority * (1.0 - weight) + recency * weight;
+        file.priority = (blended * 1000.0).round() / 1000.0;
+    }
+
+    files.sort_by(|a, b| {
+        b.priority
+            .partial_cmp(&a.priority)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+}
+
 #[cfg(test)]
 mod tests {
-    use super::rerank_chunks_by_task;
-    use crate::domain::Chunk;
-    use std::collections::BTreeSet;
+    use super::{
+        apply_recency_weight, dependency_expansion_scores, rerank_chunks,
+        rerank_chunks_by_seed_files, rerank_chunks_by_task, topological_file_order,
+        ExpansionOptions,
+    };
+    use crate::domain::test_support::{self, chunk};
+    use crate::domain::{Chunk, FileInfo};
+    use std::collections::{BTreeSet, HashMap};
 
     #[test]
     fn reranking_expands_to_related_files() {
@@ -776,6 +1054,8 @@ mod tests {
                 priority: 0.5,
                 tags: BTreeSet::from(["def:refresh_token".to_string()]),
                 token_estimate: 10,
+                doc: None,
+                byte_range: None,
             },
             Chunk {
                 id: "2".to_string(),
@@ -788,10 +1068,17 @@ mod tests {
                 priority: 0.2,
                 tags: BTreeSet::new(),
                 token_estimate: 16,
+                doc: None,
+                byte_range: None,
             },
         ];
 
-        let scores = rerank_chunks_by_task(&mut chunks, "refresh token bug", 0.4);
+        let scores = rerank_chunks_by_task(
+            &mut chunks,
+            "refresh token bug",
+            0.4,
+            &ExpansionOptions::default(),
+        );
         assert!(scores.contains_key("src/auth.py"));
         assert!(scores.contains_key("tests/test_auth.py"));
         assert!(scores["tests/test_auth.py"] >= 0.12);
@@ -814,6 +1101,8 @@ mod tests {
                     "def:refresh_token".to_string(),
                 ]),
                 token_estimate: 16,
+                doc: None,
+                byte_range: None,
             },
             Chunk {
                 id: "2".to_string(),
@@ -826,6 +1115,8 @@ mod tests {
                 priority: 0.2,
                 tags: BTreeSet::new(),
                 token_estimate: 12,
+                doc: None,
+                byte_range: None,
             },
             Chunk {
                 id: "3".to_string(),
@@ -838,14 +1129,150 @@ mod tests {
                 priority: 0.1,
                 tags: BTreeSet::new(),
                 token_estimate: 12,
+                doc: None,
+                byte_range: None,
             },
         ];
 
-        let scores = rerank_chunks_by_task(&mut chunks, "fix refresh token bug", 0.4);
+        let scores = rerank_chunks_by_task(
+            &mut chunks,
+            "fix refresh token bug",
+            0.4,
+            &ExpansionOptions::default(),
+        );
         assert!(scores.contains_key("src/auth.py"));
         assert!(scores.contains_key("src/handler.py"));
         assert!(scores.contains_key("tests/test_auth.py"));
         assert!(scores["src/handler.py"] > 0.2);
         assert!(scores["tests/test_auth.py"] > 0.1);
     }
+
+    #[test]
+    fn seed_file_reranking_pulls_in_importers() {
+        let chunk = |id: &str, path: &str, content: &str, priority: f64, tags: &[&str]| Chunk {
+            id: id.to_string(),
+            priority,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..test_support::chunk(path, content)
+        };
+        let mut chunks = vec![
+            chunk("1", "src/auth.py", "def login(user):\n    return user\n", 0.4, &["def:login"]),
+            chunk(
+                "2",
+                "src/views.py",
+                "from src.auth import login\n\ndef index():\n    return login('u')\n",
+                0.3,
+                &["def:index"],
+            ),
+            chunk("3", "src/report.py", "def render():\n    return 'ok'\n", 0.5, &["def:render"]),
+        ];
+
+        let scores = rerank_chunks_by_seed_files(
+            &mut chunks,
+            &["src/auth.py".to_string()],
+            0.4,
+            &ExpansionOptions::default(),
+        );
+        assert!(scores["src/auth.py"] > scores["src/report.py"]);
+        assert!(scores["src/views.py"] > scores["src/report.py"]);
+    }
+
+    #[test]
+    fn seeds_and_task_query_are_blended_in_one_rerank() {
+        let chunk = |id: &str, path: &str, content: &str| Chunk {
+            id: id.to_string(),
+            ..test_support::chunk(path, content)
+        };
+        let mut chunks = vec![
+            chunk("1", "src/seed.py", "x = 1\n"),
+            chunk("2", "src/refresh.py", "token refresh handler\n"),
+            chunk("3", "src/other.py", "y = 2\n"),
+        ];
+
+        let scores = rerank_chunks(
+            &mut chunks,
+            Some("token refresh"),
+            &["src/seed.py".to_string()],
+            0.4,
+            &ExpansionOptions::default(),
+        );
+        assert_eq!(scores["src/seed.py"], scores["src/refresh.py"]);
+        assert!(scores["src/refresh.py"] > scores["src/other.py"]);
+    }
+
+    #[test]
+    fn expansion_depth_limits_neighbor_levels() {
+        let chunk = |id: &str, path: &str, content: &str, def: &str| Chunk {
+            id: id.to_string(),
+            tags: BTreeSet::from([format!("def:{def}")]),
+            ..test_support::chunk(path, content)
+        };
+        let chunks = vec![
+            chunk("1", "src/alpha.py", "def alpha_fn():\n    pass\n", "alpha_fn"),
+            chunk("2", "src/beta.py", "def beta_fn():\n    return alpha_fn()\n", "beta_fn"),
+            chunk("3", "src/gamma.py", "def gamma_fn():\n    return beta_fn()\n", "gamma_fn"),
+        ];
+        let lexical = HashMap::from([("src/alpha.py".to_string(), 1.0)]);
+
+        let deep = dependency_expansion_scores(&chunks, &lexical, &ExpansionOptions::default());
+        assert_eq!(deep.get("src/beta.py"), Some(&0.6));
+        assert_eq!(deep.get("src/gamma.py"), Some(&0.3));
+
+        let shallow = ExpansionOptions { depth: 1, ..ExpansionOptions::default() };
+        let shallow = dependency_expansion_scores(&chunks, &lexical, &shallow);
+        assert_eq!(shallow.get("src/beta.py"), Some(&0.6));
+        assert!(!shallow.contains_key("src/gamma.py"));
+    }
+
+    fn recency_file(path: &str, priority: f64, modified_at: i64) -> FileInfo {
+        FileInfo { priority, modified_at: Some(modified_at), ..test_support::file_info(path) }
+    }
+
+    #[test]
+    fn recency_weight_lets_recent_test_outrank_old_readme() {
+        let mut files = vec![
+            recency_file("README.md", 1.0, 1_600_000_000),
+            recency_file("tests/test_api.py", 0.5, 1_700_000_000),
+        ];
+
+        apply_recency_weight(&mut files, 0.0);
+        assert_eq!(files[0].relative_path, "README.md");
+
+        apply_recency_weight(&mut files, 0.8);
+        assert_eq!(files[0].relative_path, "tests/test_api.py");
+        assert_eq!(files[0].priority, 0.9);
+        assert_eq!(files[1].priority, 0.2);
+    }
+
+    #[test]
+    fn topological_order_places_imports_first_and_orders_cycles_by_path() {
+        let chunks = vec![
+            chunk("app.py", "import models\nimport views\n"),
+            chunk("views.py", "import models\n"),
+            chunk("models.py", "import base\n"),
+            chunk("base.py", "x = 1\n"),
+            // b imports a and a imports b: the cycle is placed as one unit, by path.
+            chunk("cycle_b.py", "import cycle_a\n"),
+            chunk("cycle_a.py", "import cycle_b\n"),
+        ];
+
+        assert_eq!(
+            topological_file_order(&chunks),
+            vec!["base.py", "cycle_a.py", "cycle_b.py", "models.py", "views.py", "app.py"]
+        );
+
+        // A file downstream of a cycle follows the whole cycle, even with a smaller path.
+        let chunks = vec![
+            chunk("a.py", "import cycle_x\n"),
+            chunk("cycle_x.py", "import cycle_y\n"),
+            chunk("cycle_y.py", "import cycle_x\n"),
+        ];
+        assert_eq!(topological_file_order(&chunks), vec!["cycle_x.py", "cycle_y.py", "a.py"]);
+
+        // Mentioning a symbol defined elsewhere is not an import edge.
+        let mut defines = chunk("b.py", "def helper():\n    pass\n");
+        defines.tags.insert("def:helper".to_string());
+        let chunks = vec![defines, chunk("a.py", "helper()\n")];
+        assert_eq!(topological_file_order(&chunks), vec!["a.py", "b.py"]);
+    }
 }
@@ -1,9 +1,10 @@
 //! File ranker implementation with manifest-aware entrypoint detection.
 
-use crate::domain::{FileInfo, RankingWeights};
+use crate::domain::{default_fixture_dirs, FileInfo, PriorityReason, RankingWeights};
 use crate::fetch::workspace::discover_workspace_graph;
 use crate::utils::{
     is_likely_generated, is_lock_file, is_vendored, normalize_path, read_file_safe,
+    EncodingOverrides,
 };
 use serde_json::Value as JsonValue;
 use std::collections::{HashMap, HashSet};
@@ -22,6 +23,10 @@ const IMPORTANT_DOC_FILES: &[&str] = &[
     "documentation/index.md",
 ];
 
+/// Compose file names, in the order `docker compose` looks for them.
+const COMPOSE_FILES: &[&str] =
+    &["compose.yaml", "compose.yml", "docker-compose.yaml", "docker-compose.yml"];
+
 const CONTRIBUTION_DOC_PREFIXES: &[&str] =
     &["contributing", "code_of_conduct", "security", "authors", "maintainers"];
 
@@ -42,19 +47,44 @@ const IMPORTANT_CONFIG_FILES: &[&str] = &[
     "setup.cfg",
 ];
 
+/// Suffixes protoc plugins append to a `.proto` file's stem, longest first.
+const PROTO_OUTPUT_SUFFIXES: &[&str] = &[
+    "_grpc.pb.go",
+    ".pb.go",
+    "_pb2_grpc.py",
+    "_pb2.pyi",
+    "_pb2.py",
+    ".pb.cc",
+    ".pb.h",
+    "_grpc_pb.js",
+    "_grpc_pb.d.ts",
+    "_pb.js",
+    "_pb.d.ts",
+];
+
+/// Where `prisma generate` writes the client when the schema sets no `output`.
+const PRISMA_DEFAULT_OUTPUT: &str = "node_modules/.prisma/client/";
+
 pub struct FileRanker {
     root_path: PathBuf,
     scanned_files: HashSet<String>,
+    /// Lowercased stems of scanned `.proto` files
+    proto_stems: HashSet<String>,
+    /// Directory prefixes (with trailing `/`) that Prisma generators write to
+    prisma_output_dirs: Vec<String>,
     entrypoint_candidates: HashSet<String>,
     entrypoints: HashSet<String>,
     detected_languages: HashSet<String>,
     manifest_info: HashMap<String, JsonValue>,
     workspace_members: Vec<String>,
     weights: RankingWeights,
+    fixture_dirs: Vec<String>,
+    /// Lowercased language -> priority multiplier in `0.0..=1.0`
+    language_multipliers: HashMap<String, f64>,
+    encodings: EncodingOverrides,
 }
 
 impl FileRanker {
-    #[allow(dead_code)]
     pub fn new(root_path: &Path, scanned_files: HashSet<String>) -> Self {
         Self::with_weights(root_path, scanned_files, RankingWeights::default())
     }
@@ -63,61 +93,113 @@ impl FileRanker {
         scanned_files: HashSet<String>,
         weights: RankingWeights,
     ) -> Self {
+        let proto_stems = scanned_files
+            .iter()
+            .filter_map(|rel| {
+                let name = rel.rsplit('/').next().unwrap_or(rel).to_lowercase();
+                name.strip_suffix(".proto").map(str::to_string)
+            })
+            .collect();
+        let prisma_output_dirs = prisma_output_dirs(root_path, &scanned_files);
         let mut ranker = Self {
             root_path: root_path.to_path_buf(),
             scanned_files,
+            proto_stems,
+            prisma_output_dirs,
             entrypoint_candidates: HashSet::new(),
             entrypoints: HashSet::new(),
             detected_languages: HashSet::new(),
             manifest_info: HashMap::new(),
             workspace_members: Vec::new(),
             weights,
+            fixture_dirs: default_fixture_dirs(),
+            language_multipliers: HashMap::new(),
+            encodings: EncodingOverrides::default(),
         };
         ranker.load_manifests();
         ranker.validate_entrypoints();
         ranker
     }
 
+    /// Replace the directory names whose files rank as fixtures.
+    pub fn with_fixture_dirs(mut self, fixture_dirs: Vec<String>) -> Self {
+        self.fixture_dirs = fixture_dirs;
+        self
+    }
+
+    /// Charsets for files whose content is sampled while ranking.
+    pub fn with_encodings(mut self, encodings: EncodingOverrides) -> Self {
+        self.encodings = encodings;
+        self
+    }
+
+    /// Scale the priority of files in each listed language; multipliers above 1.0 count
+    /// as 1.0, so a language can only be demoted.
+    pub fn with_language_multipliers(mut self, multipliers: &HashMap<String, f64>) -> Self {
+        self.language_multipliers = multipliers
+            .iter()
+            .map(|(language, multiplier)| (language.to_lowercase(), multiplier.clamp(0.0, 1.0)))
+            .collect();
+        self
+    }
+
     pub fn rank_file(&self, file: &mut FileInfo) {
         let rel_normalized = normalize_path(&file.relative_path);
         let rel_lower = rel_normalized.to_lowercase();
-        let name = file.path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
+        // Classify by the repository path: `path` may point into the LFS object store
+        let rel_path = Path::new(&file.relative_path);
+        let name = rel_path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
 
         file.is_readme = name.starts_with("readme");
         file.is_config = is_config_file(&name, &rel_normalized);
         file.is_doc = is_doc_file(&name, &rel_normalized);
 
+        let encoding = self.encodings.encoding_for(&file.relative_path);
         let content_sample =
-            read_file_safe(&file.path, Some(2000), None).map(|(s, _)| s).unwrap_or_default();
+            read_file_safe(&file.path, Some(2000), encoding).map(|(s, _)| s).unwrap_or_default();
 
-        let mut priority: f64 = self.weights.default;
-        if file.is_readme {
-            priority = self.weights.readme;
+        let w = &self.weights;
+        let (category, priority) = if file.is_readme {
+            ("readme", w.readme)
         } else if is_contribution_doc(&rel_normalized, &name) {
-            priority = self.weights.contribution_doc;
+            ("contribution_doc", w.contribution_doc)
         } else if is_important_doc(&rel_normalized, &name) {
-            priority = self.weights.main_doc;
-        } else if is_vendored(&file.path) {
-            priority = self.weights.vendored;
-        } else if is_lock_file(&file.path) {
-            priority = self.weights.lock_file;
-        } else if is_likely_generated(&file.path, &content_sample) {
-            priority = self.weights.generated;
+            ("main_doc", w.main_doc)
+        } else if is_vendored(rel_path) {
+            ("vendored", w.vendored)
+        } else if is_fixture_path(&rel_lower, &self.fixture_dirs) {
+            ("fixture", w.fixture)
+        } else if is_lock_file(rel_path) {
+            ("lock_file", w.lock_file)
+        } else if is_likely_generated(rel_path, &content_sample)
+            || self.is_generator_output(&rel_lower, &name)
+        {
+            ("generated", w.generated)
         } else if is_ci_workflow(&rel_lower) || file.is_config {
-            priority = self.weights.config;
+            ("config", w.config)
         } else if self.entrypoints.contains(&rel_normalized) || is_common_entrypoint(&name) {
-            priority = self.weights.entrypoint;
+            ("entrypoint", w.entrypoint)
         } else if is_test_file(&name, &rel_lower) {
-            priority = self.weights.test;
+            ("test", w.test)
         } else if is_example_file(&rel_lower) {
-            priority = self.weights.example;
+            ("example", w.example)
         } else if is_core_source(&rel_lower) {
-            priority = self.weights.core_source;
+            ("core_source", w.core_source)
         } else if is_api_definition(&name) {
-            priority = self.weights.api_definition;
-        }
-
-        file.priority = priority;
+            ("api_definition", w.api_definition)
+        } else {
+            ("default", w.default)
+        };
+        let language_multiplier =
+            self.language_multipliers.get(&file.language.to_lowercase()).copied().unwrap_or(1.0);
+
+        file.priority = priority * language_multiplier;
+        file.priority_reason = Some(PriorityReason {
+            category: category.to_string(),
+            weight: priority,
+            language_multiplier: (language_multiplier != 1.0).then_some(language_multiplier),
+            coverage_boost: None,
+        });
 
         if file.is_readme {
             file.tags.insert("readme".to_string());
@@ -136,9 +218,24 @@ impl FileRanker {
         if self.entrypoints.contains(&rel_normalized) {
             file.tags.insert("entrypoint".to_string());
         }
-        if is_lock_file(&file.path) {
+        if is_lock_file(rel_path) {
             file.tags.insert("lock-file".to_string());
         }
+        if is_fixture_path(&rel_lower, &self.fixture_dirs) {
+            file.tags.insert("fixture".to_string());
+        }
+    }
+
+    /// Whether the file is a known output of a generator whose source is in the repo
+    /// (`api.proto` -> `api.pb.go`, `schema.prisma` -> its generator `output` directory).
+    fn is_generator_output(&self, rel_lower: &str, name: &str) -> bool {
+        let proto_output = PROTO_OUTPUT_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .is_some_and(|stem| self.proto_stems.contains(stem));
+        let prisma_output =
+            self.prisma_output_dirs.iter().any(|dir| rel_lower.starts_with(dir.as_str()));
+        proto_output || prisma_output
     }
 
     pub fn rank_files(&self, files: &mut [FileInfo]) {
@@ -179,6 +276,8 @@ impl FileRanker {
         self.parse_package_json();
         self.parse_go_mod();
         self.parse_cargo_toml();
+        self.parse_procfile();
+        self.parse_docker_compose();
 
         if self.root_path.join("setup.py").exists() {
             self.detected_languages.insert("python".to_string());
@@ -346,6 +445,53 @@ impl FileRanker {
         // src/main.rs or src/lib.rs as entrypoint candidates (ranker.py).
     }
 
+    /// `web: python app.py` — each process command may name the script it runs.
+    fn parse_procfile(&mut self) {
+        let Ok((content, _)) = read_file_safe(&self.root_path.join("Procfile"), None, None) else {
+            return;
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some((_, command)) = line.split_once(':') {
+                self.entrypoint_candidates.extend(command_script_paths(command));
+            }
+        }
+    }
+
+    /// Scripts named by each service's `command` or `entrypoint` in a compose file.
+    fn parse_docker_compose(&mut self) {
+        let Some(content) = COMPOSE_FILES
+            .iter()
+            .find_map(|name| read_file_safe(&self.root_path.join(name), None, None).ok())
+            .map(|(content, _)| content)
+        else {
+            return;
+        };
+        let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(&content) else {
+            return;
+        };
+        let Some(services) = value.get("services").and_then(serde_yaml::Value::as_mapping) else {
+            return;
+        };
+        for service in services.values() {
+            for key in ["command", "entrypoint"] {
+                let command = match service.get(key) {
+                    Some(serde_yaml::Value::String(command)) => command.clone(),
+                    Some(serde_yaml::Value::Sequence(args)) => args
+                        .iter()
+                        .filter_map(serde_yaml::Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    _ => continue,
+                };
+                self.entrypoint_candidates.extend(command_script_paths(&command));
+            }
+        }
+    }
+
     fn validate_entrypoints(&mut self) {
         for candidate in &self.entrypoint_candidates {
             if self.scanned_files.contains(candidate) || self.root_path.join(candidate).exists() {
@@ -355,6 +501,90 @@ impl FileRanker {
     }
 }
 
+/// Paths in a process command that look like files (`app.py`, `./bin/start`, `dist/server.js`).
+///
+/// Interpreters, flags, env assignments and `module:attr` specs are skipped; candidates are
+/// only kept once `validate_entrypoints` finds them among the scanned files.
+fn command_script_paths(command: &str) -> Vec<String> {
+    command
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .map(|token| token.trim_matches(['"', '\'', '(', ')']))
+        .filter(|token| !token.starts_with(['-', '$']) && !token.contains(['=', ':']))
+        .map(|token| token.trim_start_matches("./").trim_start_matches('/'))
+        .filter(|token| {
+            let name = token.rsplit('/').next().unwrap_or(token);
+            let has_extension = name.rsplit_once('.').is_some_and(|(stem, ext)| {
+                !stem.is_empty() && !ext.is_empty() && ext.chars().all(char::is_alphanumeric)
+            });
+            has_extension || (token.contains('/') && !token.ends_with('/'))
+        })
+        .map(normalize_path)
+        .collect()
+}
+
+/// Output directories declared by the repo's Prisma schemas, relative to the root.
+///
+/// `.prisma` is not scanned by default, so the conventional schema locations are
+/// checked on disk as well as any scanned schema.
+fn prisma_output_dirs(root_path: &Path, scanned_files: &HashSet<String>) -> Vec<String> {
+    let mut schemas: Vec<String> =
+        scanned_files.iter().filter(|rel| rel.ends_with(".prisma")).cloned().collect();
+    for rel in ["prisma/schema.prisma", "schema.prisma"] {
+        if root_path.join(rel).is_file() && !schemas.iter().any(|s| s == rel) {
+            schemas.push(rel.to_string());
+        }
+    }
+    schemas.sort();
+
+    let mut dirs = Vec::new();
+    for schema in &schemas {
+        let Ok((content, _)) = read_file_safe(&root_path.join(schema), None, None) else {
+            continue;
+        };
+        let schema_dir = schema.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        let mut declared = false;
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            if key.trim() != "output" {
+                continue;
+            }
+            let output = value.trim().trim_matches('"');
+            if let Some(dir) = resolve_relative_dir(schema_dir, output) {
+                dirs.push(dir);
+                declared = true;
+            }
+        }
+        if !declared {
+            dirs.push(PRISMA_DEFAULT_OUTPUT.to_string());
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Join `rel` onto `base` (both repository-relative), folding `.` and `..`. Returns the
+/// lowercased directory with a trailing `/`, or `None` when it escapes the root.
+fn resolve_relative_dir(base: &str, rel: &str) -> Option<String> {
+    let rel = normalize_path(rel);
+    let mut parts: Vec<&str> = Vec::new();
+    for part in base.split('/').chain(rel.split('/')) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(format!("{}/", parts.join("/").to_lowercase()))
+}
+
 fn is_common_entrypoint(name: &str) -> bool {
     matches!(
         name,
@@ -435,6 +665,13 @@ fn is_config_file(name: &str, rel: &str) -> bool {
     IMPORTANT_CONFIG_FILES.contains(&rel) || IMPORTANT_CONFIG_FILES.contains(&name)
 }
 
+fn is_fixture_path(rel_lower: &str, fixture_dirs: &[String]) -> bool {
+    let Some((dirs, _)) = rel_lower.rsplit_once('/') else {
+        return false;
+    };
+    dirs.split('/').any(|dir| fixture_dirs.iter().any(|fixture| fixture.eq_ignore_ascii_case(dir)))
+}
+
 fn is_api_definition(name: &str) -> bool {
     ["api", "interface", "types", "models", "schema"].iter().any(|needle| name.contains(needle))
 }
@@ -443,7 +680,7 @@ fn is_api_definition(name: &str) -> bool {
 mod tests {
     use super::{FileRanker, JsonValue};
     use crate::domain::FileInfo;
-    use std::collections::{BTreeSet, HashSet};
+    use std::collections::{BTreeSet, HashMap, HashSet};
     use std::fs;
     use tempfile::TempDir;
 
@@ -461,6 +698,8 @@ mod tests {
             is_readme: false,
             is_config: false,
             is_doc: false,
+            modified_at: None,
+            priority_reason: None,
         }
     }
 
@@ -481,6 +720,27 @@ mod tests {
         assert!(ranker.get_entrypoints().contains("repo_context/cli.py"));
     }
 
+    #[test]
+    fn detects_procfile_and_compose_entrypoints() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::write(tmp.path().join("Procfile"), "web: python app.py --port $PORT\n")
+            .expect("write procfile");
+        fs::write(
+            tmp.path().join("docker-compose.yml"),
+            "services:\n  worker:\n    command: [\"node\", \"./jobs/worker.js\"]\n  \
+             api:\n    entrypoint: sh -c 'gunicorn api:app'\n",
+        )
+        .expect("write compose");
+
+        let scanned = HashSet::from(["app.py".to_string(), "jobs/worker.js".to_string()]);
+        let ranker = FileRanker::new(tmp.path(), scanned);
+
+        let entrypoints = ranker.get_entrypoints();
+        assert!(entrypoints.contains("app.py"));
+        assert!(entrypoints.contains("jobs/worker.js"));
+        assert_eq!(entrypoints.len(), 2);
+    }
+
     #[test]
     fn readme_ranks_higher_than_test() {
         let tmp = TempDir::new().expect("tmp");
@@ -501,6 +761,34 @@ mod tests {
         assert!(readme.priority > test_file.priority);
     }
 
+    #[test]
+    fn fixture_directory_ranks_below_core_source() {
+        let tmp = TempDir::new().expect("tmp");
+        let core_path = tmp.path().join("src/parser.py");
+        let fixture_path = tmp.path().join("src/testdata/parser.py");
+        fs::create_dir_all(tmp.path().join("src/testdata")).expect("mkdir testdata");
+        fs::write(&core_path, "def parse(): pass\n").expect("write core");
+        fs::write(&fixture_path, "def parse(): pass\n").expect("write fixture");
+
+        let scanned =
+            HashSet::from(["src/parser.py".to_string(), "src/testdata/parser.py".to_string()]);
+        let ranker = FileRanker::new(tmp.path(), scanned.clone());
+
+        let mut core = make_file(&core_path, "src/parser.py", ".py", "python");
+        let mut fixture = make_file(&fixture_path, "src/testdata/parser.py", ".py", "python");
+        ranker.rank_file(&mut core);
+        ranker.rank_file(&mut fixture);
+
+        assert!(fixture.priority < core.priority);
+        assert!(fixture.tags.contains("fixture"));
+        assert!(!core.tags.contains("fixture"));
+
+        let ranker = FileRanker::new(tmp.path(), scanned).with_fixture_dirs(Vec::new());
+        let mut fixture = make_file(&fixture_path, "src/testdata/parser.py", ".py", "python");
+        ranker.rank_file(&mut fixture);
+        assert!(!fixture.tags.contains("fixture"));
+    }
+
     #[test]
     fn contribution_doc_ranks_higher_than_config() {
         let tmp = TempDir::new().expect("tmp");
@@ -543,4 +831,101 @@ mod tests {
             .and_then(JsonValue::as_array)
             .is_some());
     }
+
+    #[test]
+    fn proto_outputs_rank_as_generated_when_source_is_present() {
+        let tmp = TempDir::new().expect("tmp");
+        let scanned = HashSet::from([
+            "proto/user.proto".to_string(),
+            "api/user.pb.go".to_string(),
+            "api/user_grpc.pb.go".to_string(),
+            "api/order.pb.go".to_string(),
+        ]);
+        let ranker = FileRanker::new(tmp.path(), scanned);
+
+        let mut files = vec![
+            make_file(&tmp.path().join("api/user.pb.go"), "api/user.pb.go", ".go", "go"),
+            make_file(&tmp.path().join("api/user_grpc.pb.go"), "api/user_grpc.pb.go", ".go", "go"),
+            make_file(&tmp.path().join("api/order.pb.go"), "api/order.pb.go", ".go", "go"),
+        ];
+        ranker.rank_files(&mut files);
+        let category = |rel: &str| {
+            let file = files.iter().find(|f| f.relative_path == rel).expect("ranked file");
+            file.priority_reason.as_ref().expect("reason").category.clone()
+        };
+
+        assert_eq!(category("api/user.pb.go"), "generated");
+        assert_eq!(category("api/user_grpc.pb.go"), "generated");
+        // No order.proto in the repo, and no generated header
+        assert_ne!(category("api/order.pb.go"), "generated");
+    }
+
+    #[test]
+    fn prisma_schema_output_directory_ranks_as_generated() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::create_dir_all(tmp.path().join("prisma")).expect("mkdir");
+        fs::write(
+            tmp.path().join("prisma/schema.prisma"),
+            "generator client {\n  provider = \"prisma-client-js\"\n  output   = \"../src/db/client\"\n}\n",
+        )
+        .expect("write schema");
+
+        let ranker = FileRanker::new(tmp.path(), HashSet::new());
+        let mut files = vec![
+            make_file(
+                &tmp.path().join("src/db/client/index.ts"),
+                "src/db/client/index.ts",
+                ".ts",
+                "typescript",
+            ),
+            make_file(
+                &tmp.path().join("src/db/queries.ts"),
+                "src/db/queries.ts",
+                ".ts",
+                "typescript",
+            ),
+        ];
+        ranker.rank_files(&mut files);
+        let category = |rel: &str| {
+            let file = files.iter().find(|f| f.relative_path == rel).expect("ranked file");
+            file.priority_reason.as_ref().expect("reason").category.clone()
+        };
+
+        assert_eq!(category("src/db/client/index.ts"), "generated");
+        assert_ne!(category("src/db/queries.ts"), "generated");
+    }
+
+    #[test]
+    fn language_multiplier_demotes_files_of_that_language() {
+        let tmp = TempDir::new().expect("tmp");
+        fs::create_dir_all(tmp.path().join("web")).expect("mkdir web");
+        let css_path = tmp.path().join("web/site.css");
+        let js_path = tmp.path().join("web/site.js");
+        fs::write(&css_path, "body { margin: 0; }\n").expect("write css");
+        fs::write(&js_path, "export const x = 1;\n").expect("write js");
+        let scanned = HashSet::from(["web/site.css".to_string(), "web/site.js".to_string()]);
+
+        let rank = |ranker: &FileRanker| {
+            let mut css = make_file(&css_path, "web/site.css", ".css", "css");
+            let mut js = make_file(&js_path, "web/site.js", ".js", "javascript");
+            ranker.rank_file(&mut css);
+            ranker.rank_file(&mut js);
+            (css, js)
+        };
+
+        let (css, js) = rank(&FileRanker::new(tmp.path(), scanned.clone()));
+        assert_eq!(css.priority_reason, js.priority_reason);
+        assert_eq!(css.priority, js.priority);
+
+        let multipliers =
+            HashMap::from([("CSS".to_string(), 0.5), ("javascript".to_string(), 3.0)]);
+        let ranker = FileRanker::new(tmp.path(), scanned).with_language_multipliers(&multipliers);
+        let (demoted, unchanged) = rank(&ranker);
+        assert!(demoted.priority < unchanged.priority);
+        assert_eq!(demoted.priority, css.priority * 0.5);
+        assert_eq!(unchanged.priority, js.priority);
+        let multiplier = |file: &FileInfo| file.priority_reason.as_ref()?.language_multiplier;
+        assert_eq!(multiplier(&demoted), Some(0.5));
+        assert_eq!(multiplier(&unchanged), None);
+    }
 }
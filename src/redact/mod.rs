@@ -1,6 +1,7 @@
 //! Secret redaction with entropy detection
 
 pub mod entropy;
+pub mod gitleaks;
 pub mod redactor;
 pub mod rules;
 
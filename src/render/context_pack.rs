@@ -1,8 +1,9 @@
 //! Context pack Markdown rendering
 
+use crate::analysis::codeowners::OWNER_TAG_PREFIX;
 use crate::analysis::pr::PrContextReport;
 use crate::domain::{Chunk, FileInfo, ScanStats};
-use crate::utils::{format_with_commas, read_file_safe};
+use crate::utils::{format_with_commas, line_comment_prefix, ContentSource};
 use chrono::Utc;
 use serde_json::Value as JsonValue;
 use std::collections::{BTreeSet, HashMap, HashSet};
@@ -11,18 +12,158 @@ use std::path::Path;
 use super::guardrails::{build_claims, build_missing_pieces, render_guardrails};
 use super::pr_context::render_pr_context;
 
-#[allow(clippy::too_many_arguments)]
+/// A top-level section of the context pack, as named in the `section_order` config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackSection {
+    Header,
+    Toc,
+    Contribution,
+    Overview,
+    Tree,
+    KeyFiles,
+    CodeMap,
+    AsyncTopology,
+    Diagnostics,
+    ReferencedAssets,
+    Files,
+    Guardrails,
+    PrContext,
+    RedactionSummary,
+}
+
+impl PackSection {
+    pub const ALL: [PackSection; 14] = [
+        PackSection::Header,
+        PackSection::Toc,
+        PackSection::Contribution,
+        PackSection::Overview,
+        PackSection::Tree,
+        PackSection::KeyFiles,
+        PackSection::CodeMap,
+        PackSection::AsyncTopology,
+        PackSection::Diagnostics,
+        PackSection::ReferencedAssets,
+        PackSection::Files,
+        PackSection::Guardrails,
+        PackSection::PrContext,
+        PackSection::RedactionSummary,
+    ];
+
+    /// The layout used when `section_order` is unset; `redaction_summary` is opt-in.
+    pub const DEFAULT_ORDER: [PackSection; 13] = [
+        PackSection::Header,
+        PackSection::Toc,
+        PackSection::Contribution,
+        PackSection::Overview,
+        PackSection::Tree,
+        PackSection::KeyFiles,
+        PackSection::CodeMap,
+        PackSection::AsyncTopology,
+        PackSection::Diagnostics,
+        PackSection::ReferencedAssets,
+        PackSection::Files,
+        PackSection::Guardrails,
+        PackSection::PrContext,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PackSection::Header => "header",
+            PackSection::Toc => "toc",
+            PackSection::Contribution => "contribution",
+            PackSection::Overview => "overview",
+            PackSection::Tree => "tree",
+            PackSection::KeyFiles => "key_files",
+            PackSection::CodeMap => "code_map",
+            PackSection::AsyncTopology => "async_topology",
+            PackSection::Diagnostics => "diagnostics",
+            PackSection::ReferencedAssets => "referenced_assets",
+            PackSection::Files => "files",
+            PackSection::Guardrails => "guardrails",
+            PackSection::PrContext => "pr_context",
+            PackSection::RedactionSummary => "redaction_summary",
+        }
+    }
+}
+
+/// Resolve `section_order` names. An empty list is the default layout; sections left
+/// out are not rendered, and repeats after the first are ignored.
+pub fn resolve_section_order(names: &[String]) -> Result<Vec<PackSection>, String> {
+    if names.is_empty() {
+        return Ok(PackSection::DEFAULT_ORDER.to_vec());
+    }
+    let mut order = Vec::with_capacity(names.len());
+    for name in names {
+        let section =
+            PackSection::ALL.into_iter().find(|s| s.name() == name.as_str()).ok_or_else(|| {
+                let valid: Vec<&str> = PackSection::ALL.iter().map(|s| s.name()).collect();
+                format!("unknown section '{name}' (expected one of: {})", valid.join(", "))
+            })?;
+        if !order.contains(&section) {
+            order.push(section);
+        }
+    }
+    Ok(order)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PackRenderOptions<'a> {
+    pub include_timestamp: bool,
+    pub task_query: Option<&'a str>,
+    pub pr_context: Option<&'a PrContextReport>,
+    /// Project name, description and scripts read from the repository's manifest
+    pub manifest_info: Option<&'a HashMap<String, JsonValue>>,
+    /// Add a table of contents linking each file heading
+    pub include_toc: bool,
+    /// Render files under one heading per directory
+    pub group_by_directory: bool,
+    /// Mark where a file's content continues in a later chunk
+    pub annotate_splits: bool,
+    /// Paths rendered first, in this order (e.g. a selection file's)
+    pub file_order: Option<&'a [String]>,
+    pub section_order: &'a [PackSection],
+}
+
+impl Default for PackRenderOptions<'_> {
+    fn default() -> Self {
+        Self {
+            include_timestamp: false,
+            task_query: None,
+            pr_context: None,
+            manifest_info: None,
+            include_toc: false,
+            group_by_directory: false,
+            annotate_splits: false,
+            file_order: None,
+            section_order: &PackSection::DEFAULT_ORDER,
+        }
+    }
+}
+
 pub fn render_context_pack(
     root_path: &Path,
     files: &[FileInfo],
     chunks: &[Chunk],
+    content: &dyn ContentSource,
     stats: &ScanStats,
     tree: &str,
-    manifest_info: &HashMap<String, JsonValue>,
-    task_query: Option<&str>,
-    pr_context: Option<&PrContextReport>,
-    include_timestamp: bool,
+    options: PackRenderOptions<'_>,
 ) -> String {
+    let PackRenderOptions {
+        include_timestamp,
+        task_query,
+        pr_context,
+        manifest_info,
+        include_toc,
+        group_by_directory,
+        annotate_splits,
+        file_order,
+        section_order,
+    } = options;
+    let no_manifest = HashMap::new();
+    let manifest_info = manifest_info.unwrap_or(&no_manifest);
+    // Sections render in the default layout, then are assembled in `section_order`.
+    let mut sections: HashMap<PackSection, String> = HashMap::new();
     let mut out = String::new();
 
     // ── Header ──────────────────────────────────────────────────────────────
@@ -49,6 +190,52 @@ pub fn render_context_pack(
         out.push_str(&format!("> Task Context: {}\n", task.trim()));
     }
     out.push_str("\n---\n\n");
+    sections.insert(PackSection::Header, std::mem::take(&mut out));
+
+    let file_priorities: HashMap<&str, f64> =
+        files.iter().map(|f| (f.relative_path.as_str(), f.priority)).collect();
+    let mut chunks_by_file: HashMap<&str, Vec<&Chunk>> = HashMap::new();
+    for chunk in chunks {
+        chunks_by_file.entry(chunk.path.as_str()).or_default().push(chunk);
+    }
+
+    // Group chunks by file path, sorted by file priority then path.
+    let mut sorted_paths: Vec<&str> = chunks_by_file.keys().copied().collect();
+    sorted_paths.sort_by(|a, b| {
+        let pa = file_priorities.get(a).copied().unwrap_or(0.0);
+        let pb = file_priorities.get(b).copied().unwrap_or(0.0);
+        pb.partial_cmp(&pa).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.cmp(b))
+    });
+    if let Some(order) = file_order {
+        // Stable: paths missing from `order` follow it in priority order.
+        let position: HashMap<&str, usize> =
+            order.iter().enumerate().map(|(i, path)| (path.as_str(), i)).collect();
+        sorted_paths.sort_by_key(|path| position.get(path).copied().unwrap_or(usize::MAX));
+    }
+    if group_by_directory {
+        // Stable: files keep their priority order within each directory.
+        sorted_paths.sort_by_key(|path| directory_of(path));
+    }
+
+    if include_toc && !sorted_paths.is_empty() {
+        out.push_str("## 🧭 Table of Contents\n\n");
+        let mut slug_counts: HashMap<String, usize> = HashMap::new();
+        // Diagnostics headings are the same `path` headings; when they come first, GitHub
+        // gives them the plain slugs and the file headings the suffixed ones.
+        let position = |section| section_order.iter().position(|s| *s == section);
+        let files_at = position(PackSection::Files);
+        if position(PackSection::Diagnostics).is_some_and(|at| files_at.is_some_and(|f| at < f)) {
+            for path in diagnostic_paths(stats) {
+                unique_slug(&github_slug(path), &mut slug_counts);
+            }
+        }
+        for path in &sorted_paths {
+            let anchor = unique_slug(&github_slug(path), &mut slug_counts);
+            out.push_str(&format!("- [`{}`](#{})\n", path, anchor));
+        }
+        out.push('\n');
+    }
+    sections.insert(PackSection::Toc, std::mem::take(&mut out));
 
     let mut contribution_files: Vec<&FileInfo> = files
         .iter()
@@ -66,8 +253,8 @@ pub fn render_context_pack(
 
         let mut suggested_commands: Vec<String> = Vec::new();
         for file in &contribution_files {
-            if let Ok((content, _)) = read_file_safe(&file.path, Some(10_000), None) {
-                for line in content.lines() {
+            if let Some(text) = content.read(file) {
+                for line in head_bytes(&text, 10_000).lines() {
                     let trimmed = line.trim();
                     if trimmed.is_empty() || trimmed.len() > 120 {
                         continue;
@@ -138,6 +325,7 @@ pub fn render_context_pack(
         }
         out.push('\n');
     }
+    sections.insert(PackSection::Contribution, std::mem::take(&mut out));
 
     // ── Repository Overview ──────────────────────────────────────────────────
     out.push_str("## 📋 Repository Overview\n\n");
@@ -177,6 +365,31 @@ pub fn render_context_pack(
         }
     }
 
+    // Owners from `--annotate-ownership`, busiest first
+    let mut owner_counts: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        for owner in file.tags.iter().filter_map(|tag| tag.strip_prefix(OWNER_TAG_PREFIX)) {
+            *owner_counts.entry(owner).or_default() += 1;
+        }
+    }
+    if !owner_counts.is_empty() {
+        let mut owners: Vec<(&str, usize)> = owner_counts.into_iter().collect();
+        owners.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        out.push_str("\n**Owners:**\n");
+        for (owner, count) in owners.iter().take(10) {
+            let noun = if *count == 1 { "file" } else { "files" };
+            out.push_str(&format!("- `{owner}` ({count} {noun})\n"));
+        }
+        let unowned = files
+            .iter()
+            .filter(|f| !f.tags.iter().any(|tag| tag.starts_with(OWNER_TAG_PREFIX)))
+            .count();
+        if unowned > 0 {
+            let noun = if unowned == 1 { "file" } else { "files" };
+            out.push_str(&format!("- unowned ({unowned} {noun})\n"));
+        }
+    }
+
     // Available Commands from package.json scripts (matches Python _render_overview lines 159-168)
     if let Some(JsonValue::Object(scripts)) = manifest_info.get("scripts") {
         let known_cmds = ["build", "test", "start", "dev", "lint"];
@@ -199,8 +412,8 @@ pub fn render_context_pack(
     // README excerpt — find the highest-priority readme file and show first 15 meaningful lines.
     // Matches Python renderer.py lines 170-190.
     if let Some(readme) = files.iter().find(|f| f.is_readme) {
-        if let Ok((content, _)) = read_file_safe(&readme.path, Some(4000), None) {
-            let readme_lines: Vec<&str> = content.lines().collect();
+        if let Some(text) = content.read(readme) {
+            let readme_lines: Vec<&str> = head_bytes(&text, 4000).lines().collect();
             let total_readme_lines = readme_lines.len();
             let mut excerpt_lines: Vec<&str> = Vec::new();
             let mut in_content = false;
@@ -231,12 +444,14 @@ pub fn render_context_pack(
         }
     }
     out.push('\n');
+    sections.insert(PackSection::Overview, std::mem::take(&mut out));
 
     // ── Directory Tree ───────────────────────────────────────────────────────
     out.push_str("## 📁 Directory Structure\n\n");
     out.push_str("```\n");
     out.push_str(tree);
     out.push_str("\n```\n\n*⭐ = Important file*\n\n");
+    sections.insert(PackSection::Tree, std::mem::take(&mut out));
 
     // ── Key Files ────────────────────────────────────────────────────────────
     // Python groups into Documentation / Configuration / Entrypoints (5 per category).
@@ -260,6 +475,7 @@ pub fn render_context_pack(
         }
     }
     out.push('\n');
+    sections.insert(PackSection::KeyFiles, std::mem::take(&mut out));
 
     // ── Code Map ────────────────────────────────────────────────────────────
     // Group files by language, skip pure text/markdown, top 5 languages.
@@ -319,47 +535,66 @@ pub fn render_context_pack(
         }
         out.push('\n');
     }
+    sections.insert(PackSection::CodeMap, std::mem::take(&mut out));
 
     if let Some(async_section) = render_async_topology(chunks) {
-        out.push_str(&async_section);
+        sections.insert(PackSection::AsyncTopology, async_section);
+    }
+    if !stats.diagnostics.is_empty() {
+        sections.insert(PackSection::Diagnostics, render_diagnostics(stats));
+    }
+    if !stats.referenced_assets.is_empty() {
+        sections.insert(PackSection::ReferencedAssets, render_referenced_assets(stats));
     }
 
     // ── File Contents ────────────────────────────────────────────────────────
     out.push_str("## 📄 File Contents\n\n");
 
-    // Group chunks by file path, sorted by file priority then path.
-    let file_priorities: HashMap<&str, f64> =
-        files.iter().map(|f| (f.relative_path.as_str(), f.priority)).collect();
-    let mut chunks_by_file: HashMap<&str, Vec<&Chunk>> = HashMap::new();
-    for chunk in chunks {
-        chunks_by_file.entry(chunk.path.as_str()).or_default().push(chunk);
-    }
-
-    let mut sorted_paths: Vec<&&str> = chunks_by_file.keys().collect();
-    sorted_paths.sort_by(|a, b| {
-        let pa = file_priorities.get(**a).copied().unwrap_or(0.0);
-        let pb = file_priorities.get(**b).copied().unwrap_or(0.0);
-        pb.partial_cmp(&pa).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.cmp(b))
-    });
-
+    let mut current_dir: Option<&str> = None;
     for path in sorted_paths {
+        if group_by_directory && current_dir != Some(directory_of(path)) {
+            let dir = directory_of(path);
+            if dir.is_empty() {
+                out.push_str("### 📂 (root)\n\n");
+            } else {
+                out.push_str(&format!("### 📂 `{}/`\n\n", dir));
+            }
+            current_dir = Some(dir);
+        }
         let file_chunks = chunks_by_file.get(path).unwrap();
         let mut sorted_chunks: Vec<&&Chunk> = file_chunks.iter().collect();
         sorted_chunks.sort_by(|a, b| a.start_line.cmp(&b.start_line).then_with(|| a.id.cmp(&b.id)));
 
         let lang = sorted_chunks.first().map(|c| c.language.as_str()).unwrap_or("text");
-        let priority = file_priorities.get(*path).copied().unwrap_or(0.5);
+        let priority = file_priorities.get(path).copied().unwrap_or(0.5);
 
         // Per-file header with metadata
-        out.push_str(&format!("### `{}`\n\n", path));
+        let heading = if group_by_directory { "####" } else { "###" };
+        out.push_str(&format!("{} `{}`\n\n", heading, path));
+        let git_status = sorted_chunks
+            .first()
+            .and_then(|c| c.tags.iter().find_map(|tag| tag.strip_prefix("git:")))
+            .map(|status| format!(" | Git: {status}"))
+            .unwrap_or_default();
+        let owners: Vec<&str> = sorted_chunks
+            .first()
+            .map(|c| c.tags.iter().filter_map(|tag| tag.strip_prefix(OWNER_TAG_PREFIX)).collect())
+            .unwrap_or_default();
+        let owners = if owners.is_empty() {
+            String::new()
+        } else {
+            format!(" | Owners: {}", owners.join(", "))
+        };
         out.push_str(&format!(
-            "*Priority: {:.0}% | Language: {} | Chunks: {}*\n\n",
+            "*Priority: {:.0}% | Language: {} | Chunks: {}{}{}*\n\n",
             priority * 100.0,
             lang,
-            file_chunks.len()
+            file_chunks.len(),
+            git_status,
+            owners
         ));
 
-        for chunk in sorted_chunks {
+        for (idx, chunk) in sorted_chunks.into_iter().enumerate() {
             let mut notes: Vec<String> = chunk
                 .tags
                 .iter()
@@ -386,24 +621,157 @@ pub fn render_context_pack(
                 ));
             }
             out.push_str(&format!("```{}\n", chunk.language));
+            if annotate_splits && idx > 0 {
+                out.push_str(&split_marker(&chunk.language, chunk.start_line, chunk.end_line));
+                out.push('\n');
+            }
             let trimmed = chunk.content.trim_end();
             out.push_str(trimmed);
             out.push('\n');
             out.push_str("```\n\n");
         }
     }
+    sections.insert(PackSection::Files, std::mem::take(&mut out));
 
     let claims = build_claims(chunks);
     let missing = build_missing_pieces(chunks, stats);
-    out.push_str(&render_guardrails(&claims, &missing));
+    sections.insert(PackSection::Guardrails, render_guardrails(&claims, &missing));
 
     if let Some(report) = pr_context {
-        out.push_str(&render_pr_context(report));
+        sections.insert(PackSection::PrContext, render_pr_context(report));
+    }
+    if section_order.contains(&PackSection::RedactionSummary) {
+        sections.insert(PackSection::RedactionSummary, render_redaction_summary(stats));
     }
 
+    section_order.iter().filter_map(|section| sections.remove(section)).collect()
+}
+
+/// `--include-diagnostics`: one line per rust-analyzer diagnostic, grouped by file.
+fn render_diagnostics(stats: &ScanStats) -> String {
+    let mut out = String::from("## 🩺 Diagnostics\n\n");
+    let mut current_path: Option<&str> = None;
+    for diagnostic in &stats.diagnostics {
+        let field = |key: &str| diagnostic.get(key).and_then(JsonValue::as_str).unwrap_or("");
+        let path = field("path");
+        if current_path != Some(path) {
+            if current_path.is_some() {
+                out.push('\n');
+            }
+            out.push_str(&format!("### `{path}`\n\n"));
+            current_path = Some(path);
+        }
+        let line = diagnostic.get("line").and_then(JsonValue::as_u64).unwrap_or(0);
+        let code = match field("code") {
+            "" => String::new(),
+            code => format!(" [{code}]"),
+        };
+        out.push_str(&format!(
+            "- L{line} **{}**{code}: {}\n",
+            field("severity"),
+            field("message").lines().next().unwrap_or("")
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// `--asset-metadata`: binary files the included code names, without their bytes.
+fn render_referenced_assets(stats: &ScanStats) -> String {
+    let mut out = String::from("## 🖼️ Referenced Assets\n\n");
+    out.push_str("| Path | Size | SHA-256 | Referenced by |\n");
+    out.push_str("|------|------|---------|---------------|\n");
+    for asset in &stats.referenced_assets {
+        let referenced_by: Vec<String> =
+            asset.referenced_by.iter().map(|path| format!("`{path}`")).collect();
+        out.push_str(&format!(
+            "| `{}` | {} bytes | `{}` | {} |\n",
+            asset.path,
+            format_with_commas(asset.size_bytes),
+            asset.sha256,
+            referenced_by.join(", ")
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// Redactions by rule, from the export's scan stats (`redaction_summary` section).
+fn render_redaction_summary(stats: &ScanStats) -> String {
+    let mut out = String::from("## 🔒 Redaction Summary\n\n");
+    if stats.redaction_counts.is_empty() {
+        out.push_str("No secrets were redacted.\n\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "{} redaction(s) in {} file(s):\n\n",
+        stats.redaction_counts.values().sum::<usize>(),
+        stats.redacted_files
+    ));
+    for (rule, count) in &stats.redaction_counts {
+        out.push_str(&format!("- `{rule}`: {count}\n"));
+    }
+    out.push('\n');
     out
 }
 
+/// The file heading of each group in the diagnostics section, in order.
+fn diagnostic_paths(stats: &ScanStats) -> Vec<&str> {
+    let mut paths: Vec<&str> = stats
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.get("path").and_then(JsonValue::as_str).unwrap_or(""))
+        .collect();
+    paths.dedup();
+    paths
+}
+
+/// Comment line marking where a file continues in a new chunk (`--annotate-splits`).
+fn split_marker(language: &str, start_line: usize, end_line: usize) -> String {
+    let text = format!("--- chunk boundary (lines {start_line}-{end_line}) ---");
+    match language {
+        "markdown" | "html" | "xml" | "vue" | "svelte" => format!("<!-- {text} -->"),
+        "css" | "scss" => format!("/* {text} */"),
+        _ => format!("{} {text}", line_comment_prefix(language)),
+    }
+}
+
+/// The first `max_bytes` of `text`, cut back to a char boundary.
+fn head_bytes(text: &str, max_bytes: usize) -> &str {
+    let mut end = text.len().min(max_bytes);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Parent directory of a repository-relative path; empty for root files.
+fn directory_of(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Heading anchor as GitHub generates it: lowercase, drop punctuation, spaces to hyphens.
+fn github_slug(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// GitHub suffixes repeated slugs with `-1`, `-2`, ... in document order.
+fn unique_slug(slug: &str, counts: &mut HashMap<String, usize>) -> String {
+    let seen = counts.entry(slug.to_string()).or_insert(0);
+    let unique = if *seen == 0 { slug.to_string() } else { format!("{slug}-{seen}") };
+    *seen += 1;
+    unique
+}
+
 fn capitalize(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
@@ -594,3 +962,210 @@ fn build_dev_loop_checklist(
     out.truncate(8);
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        github_slug, render_context_pack, resolve_section_order, PackRenderOptions, PackSection,
+    };
+    use crate::domain::test_support::file_info;
+    use crate::domain::{test_support, Chunk, FileInfo, ScanStats};
+    use serde_json::Value as JsonValue;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn file(path: &str, priority: f64) -> FileInfo {
+        FileInfo { size_bytes: 10, priority, ..file_info(path) }
+    }
+
+    fn chunk(path: &str) -> Chunk {
+        test_support::chunk(path, "fn main() {}\n")
+    }
+
+    #[test]
+    fn github_slug_matches_heading_anchors() {
+        assert_eq!(github_slug("src/main.rs"), "srcmainrs");
+        assert_eq!(github_slug("docs/Getting Started.md"), "docsgetting-startedmd");
+        assert_eq!(github_slug("my_crate/build-info.rs"), "my_cratebuild-infors");
+    }
+
+    #[test]
+    fn toc_links_every_included_file() {
+        let files =
+            vec![file("src/main.rs", 0.9), file("src/lib.rs", 0.8), file("src-lib.rs", 0.1)];
+        let chunks: Vec<Chunk> = files.iter().map(|f| chunk(&f.relative_path)).collect();
+        let render = |toc| {
+            render_context_pack(
+                Path::new("/tmp/demo"),
+                &files,
+                &chunks,
+                &HashMap::new(),
+                &ScanStats::default(),
+                "",
+                PackRenderOptions { include_toc: toc, ..Default::default() },
+            )
+        };
+
+        let pack = render(true);
+        assert!(pack.contains("## 🧭 Table of Contents"));
+        assert!(pack.contains("- [`src/main.rs`](#srcmainrs)\n"));
+        assert!(pack.contains("- [`src/lib.rs`](#srclibrs)\n"));
+        assert!(pack.contains("- [`src-lib.rs`](#src-librs)\n"));
+        for f in &files {
+            assert!(pack.contains(&format!("### `{}`", f.relative_path)));
+        }
+        assert!(!render(false).contains("Table of Contents"));
+    }
+
+    #[test]
+    fn group_by_directory_renders_one_heading_per_directory() {
+        let files = vec![
+            file("src/auth/login.rs", 0.9),
+            file("src/main.rs", 0.8),
+            file("src/auth/token.rs", 0.7),
+            file("build.rs", 0.1),
+        ];
+        let chunks: Vec<Chunk> = files.iter().map(|f| chunk(&f.relative_path)).collect();
+        let pack = render_context_pack(
+            Path::new("/tmp/demo"),
+            &files,
+            &chunks,
+            &HashMap::new(),
+            &ScanStats::default(),
+            "",
+            PackRenderOptions { group_by_directory: true, ..Default::default() },
+        );
+
+        assert_eq!(pack.matches("### 📂 `src/auth/`").count(), 1);
+        let auth = pack.find("### 📂 `src/auth/`").unwrap();
+        let src = pack.find("### 📂 `src/`").unwrap();
+        let root = pack.find("### 📂 (root)").unwrap();
+        let login = pack.find("#### `src/auth/login.rs`").unwrap();
+        let token = pack.find("#### `src/auth/token.rs`").unwrap();
+        let main = pack.find("#### `src/main.rs`").unwrap();
+        assert!(root < src && src < main && main < auth && auth < login && login < token);
+    }
+
+    #[test]
+    fn annotate_splits_marks_each_continuation_chunk() {
+        let files = vec![file("src/lib.rs", 0.9)];
+        let part = |id: &str, start: usize, end: usize| Chunk {
+            id: id.to_string(),
+            start_line: start,
+            end_line: end,
+            content: format!("fn part_{id}() {{}}\n"),
+            ..chunk("src/lib.rs")
+        };
+        let chunks = vec![part("b", 41, 80), part("a", 1, 40), part("c", 81, 95)];
+        let render = |annotate| {
+            render_context_pack(
+                Path::new("/tmp/demo"),
+                &files,
+                &chunks,
+                &HashMap::new(),
+                &ScanStats::default(),
+                "",
+                PackRenderOptions { annotate_splits: annotate, ..Default::default() },
+            )
+        };
+
+        let pack = render(true);
+        assert_eq!(pack.matches("chunk boundary").count(), 2);
+        assert!(pack.contains("```rust\n// --- chunk boundary (lines 41-80) ---\nfn part_b() {}\n"));
+        assert!(pack.contains("```rust\n// --- chunk boundary (lines 81-95) ---\nfn part_c() {}\n"));
+        assert!(pack.contains("```rust\nfn part_a() {}\n"));
+        assert!(!render(false).contains("chunk boundary"));
+    }
+
+    #[test]
+    fn section_order_puts_files_before_tree() {
+        let files = vec![file("src/main.rs", 0.9)];
+        let chunks: Vec<Chunk> = files.iter().map(|f| chunk(&f.relative_path)).collect();
+        let render = |order: &[PackSection]| {
+            render_context_pack(
+                Path::new("/tmp/demo"),
+                &files,
+                &chunks,
+                &HashMap::new(),
+                &ScanStats::default(),
+                "demo/\n└── src/",
+                PackRenderOptions { section_order: order, ..Default::default() },
+            )
+        };
+
+        let default = render(&PackSection::DEFAULT_ORDER);
+        let tree = default.find("## 📁 Directory Structure").unwrap();
+        let contents = default.find("## 📄 File Contents").unwrap();
+        assert!(tree < contents);
+        assert!(!default.contains("Redaction Summary"));
+
+        let names: Vec<String> =
+            ["header", "files", "tree"].iter().map(|s| s.to_string()).collect();
+        let reordered = render(&resolve_section_order(&names).unwrap());
+        let header = reordered.find("# Repository Context Pack").unwrap();
+        let tree = reordered.find("## 📁 Directory Structure").unwrap();
+        let contents = reordered.find("## 📄 File Contents").unwrap();
+        assert!(header < contents && contents < tree);
+        assert!(!reordered.contains("## 📋 Repository Overview"));
+
+        let unknown = resolve_section_order(&["footer".to_string()]).unwrap_err();
+        assert!(unknown.contains("unknown section 'footer'"), "{unknown}");
+    }
+
+    #[test]
+    fn diagnostics_section_lists_each_diagnostic_by_file() {
+        let files = vec![file("src/main.rs", 0.9)];
+        let chunks: Vec<Chunk> = files.iter().map(|f| chunk(&f.relative_path)).collect();
+        let diagnostic = |line: u64, severity: &str, message: &str, code: JsonValue| {
+            HashMap::from([
+                ("path".to_string(), JsonValue::from("src/main.rs")),
+                ("line".to_string(), JsonValue::from(line)),
+                ("severity".to_string(), JsonValue::from(severity)),
+                ("message".to_string(), JsonValue::from(message)),
+                ("code".to_string(), code),
+            ])
+        };
+        let render_with = |stats: &ScanStats, include_toc: bool, order: &[PackSection]| {
+            render_context_pack(
+                Path::new("/tmp/demo"),
+                &files,
+                &chunks,
+                &HashMap::new(),
+                stats,
+                "",
+                PackRenderOptions { include_toc, section_order: order, ..Default::default() },
+            )
+        };
+        let render = |stats: &ScanStats| render_with(stats, false, &PackSection::DEFAULT_ORDER);
+
+        let stats = ScanStats {
+            diagnostics: vec![
+                diagnostic(2, "error", "expected expression", JsonValue::Null),
+                diagnostic(
+                    7,
+                    "warning",
+                    "unused variable: `x`",
+                    JsonValue::from("unused_variables"),
+                ),
+            ],
+            ..ScanStats::default()
+        };
+        let pack = render(&stats);
+        assert!(pack.contains("## 🩺 Diagnostics\n\n### `src/main.rs`\n\n"));
+        assert!(pack.contains("- L2 **error**: expected expression\n"));
+        assert!(pack.contains("- L7 **warning** [unused_variables]: unused variable: `x`\n"));
+        assert!(!render(&ScanStats::default()).contains("Diagnostics"));
+
+        // The diagnostics heading takes `#srcmainrs`; the file heading after it is `-1`.
+        let toc = render_with(&stats, true, &PackSection::DEFAULT_ORDER);
+        assert!(toc.contains("- [`src/main.rs`](#srcmainrs-1)\n"), "{toc}");
+        let files_first: Vec<PackSection> = PackSection::DEFAULT_ORDER
+            .iter()
+            .copied()
+            .filter(|section| *section != PackSection::Diagnostics)
+            .chain([PackSection::Diagnostics])
+            .collect();
+        let toc = render_with(&stats, true, &files_first);
+        assert!(toc.contains("- [`src/main.rs`](#srcmainrs)\n"), "{toc}");
+    }
+}
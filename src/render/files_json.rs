@@ -0,0 +1,122 @@
+//! Whole-file JSON array rendering (`--mode files-json`)
+
+use crate::domain::Chunk;
+use crate::utils::line_comment_prefix;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Render exported chunks as a JSON array of `{path, language, content}` objects.
+///
+/// Each file's chunks are stitched back together in line order, dropping lines repeated
+/// by chunk overlap. Files appear in the order of their first chunk; where chunks dropped
+/// by the budget leave a gap, a comment line in the file's language names the missing
+/// lines.
+pub fn render_files_json(chunks: &[Chunk]) -> String {
+    let mut order: Vec<&str> = Vec::new();
+    let mut by_path: HashMap<&str, Vec<&Chunk>> = HashMap::new();
+    for chunk in chunks {
+        by_path
+            .entry(chunk.path.as_str())
+            .or_insert_with(|| {
+                order.push(chunk.path.as_str());
+                Vec::new()
+            })
+            .push(chunk);
+    }
+
+    let files: Vec<BTreeMap<&str, Value>> = order
+        .into_iter()
+        .map(|path| {
+            let mut file_chunks = by_path.remove(path).unwrap_or_default();
+            file_chunks.sort_by_key(|c| (c.start_line, c.end_line));
+            let mut entry: BTreeMap<&str, Value> = BTreeMap::new();
+            entry.insert("content", Value::String(stitch_file_content(&file_chunks)));
+            entry.insert("language", Value::String(file_chunks[0].language.clone()));
+            entry.insert("path", Value::String(path.to_string()));
+            entry
+        })
+        .collect();
+
+    let mut out = serde_json::to_string_pretty(&files).unwrap_or_else(|_| "[]".to_string());
+    out.push('\n');
+    out
+}
+
+/// Concatenate chunks sorted by start line, skipping lines an earlier chunk already covered
+/// and marking lines no chunk covers.
+fn stitch_file_content(chunks: &[&Chunk]) -> String {
+    let mut content = String::new();
+    let mut last_line = 0usize;
+    for chunk in chunks {
+        if chunk.end_line <= last_line {
+            continue;
+        }
+        if chunk.start_line > last_line + 1 {
+            content.push_str(&format!(
+                "{} ... lines {}-{} not exported ...\n",
+                line_comment_prefix(&chunk.language),
+                last_line + 1,
+                chunk.start_line - 1
+            ));
+        }
+        let skip = (last_line + 1).saturating_sub(chunk.start_line);
+        for line in chunk.content.split_inclusive('\n').skip(skip) {
+            content.push_str(line);
+        }
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        last_line = chunk.end_line;
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_files_json;
+    use crate::domain::{test_support, Chunk};
+
+    fn chunk(path: &str, start_line: usize, end_line: usize, content: &str) -> Chunk {
+        Chunk {
+            id: format!("{path}:{start_line}"),
+            start_line,
+            end_line,
+            ..test_support::chunk(path, content)
+        }
+    }
+
+    #[test]
+    fn overlapping_chunks_are_stitched_once() {
+        let chunks = vec![
+            chunk("b.py", 1, 1, "b = 1\n"),
+            chunk("a.py", 3, 5, "x = 3\ny = 4\nz = 5\n"),
+            chunk("a.py", 1, 3, "a = 1\nb = 2\nx = 3\n"),
+        ];
+
+        let files: Vec<serde_json::Value> =
+            serde_json::from_str(&render_files_json(&chunks)).expect("json array");
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["path"], "b.py");
+        assert_eq!(files[1]["path"], "a.py");
+        assert_eq!(files[1]["content"], "a = 1\nb = 2\nx = 3\ny = 4\nz = 5\n");
+    }
+
+    #[test]
+    fn gaps_left_by_dropped_chunks_are_marked() {
+        let chunks = vec![
+            chunk("a.py", 3, 4, "c = 3\nd = 4\n"),
+            chunk("a.py", 8, 9, "h = 8\ni = 9\n"),
+            chunk("a.py", 5, 6, "e = 5\nf = 6\n"),
+        ];
+
+        let files: Vec<serde_json::Value> =
+            serde_json::from_str(&render_files_json(&chunks)).expect("json array");
+
+        assert_eq!(
+            files[0]["content"],
+            "# ... lines 1-2 not exported ...\nc = 3\nd = 4\ne = 5\nf = 6\n\
+             # ... lines 7-7 not exported ...\nh = 8\ni = 9\n"
+        );
+    }
+}
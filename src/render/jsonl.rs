@@ -4,6 +4,12 @@ use crate::domain::Chunk;
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+/// Version stamped on every `chunks.jsonl` record as `schema_version`.
+///
+/// Bump it when a field is renamed, removed or changes meaning; readers reject records
+/// from newer versions. Records without the field predate versioning and read as version 1.
+pub const JSONL_SCHEMA_VERSION: u64 = 1;
+
 pub fn render_jsonl(chunks: &[Chunk]) -> String {
     let mut lines = Vec::with_capacity(chunks.len());
     for chunk in chunks {
@@ -14,10 +20,18 @@ pub fn render_jsonl(chunks: &[Chunk]) -> String {
         // matching Python's json.dumps(..., sort_keys=True).
         let mut entry: BTreeMap<&str, Value> = BTreeMap::new();
         entry.insert("content", Value::String(chunk.content.clone()));
+        if let Some(doc) = &chunk.doc {
+            entry.insert("doc", Value::String(doc.clone()));
+        }
+        if let Some((start_byte, end_byte)) = chunk.byte_range {
+            entry.insert("start_byte", Value::Number(start_byte.into()));
+            entry.insert("end_byte", Value::Number(end_byte.into()));
+        }
         entry.insert("end_line", Value::Number(chunk.end_line.into()));
         entry.insert("id", Value::String(chunk.id.clone()));
         entry.insert("lang", Value::String(chunk.language.clone()));
         entry.insert("path", Value::String(chunk.path.clone()));
+        entry.insert("schema_version", Value::Number(JSONL_SCHEMA_VERSION.into()));
         entry.insert(
             "priority",
             serde_json::to_value((chunk.priority * 1000.0).round() / 1000.0).unwrap(),
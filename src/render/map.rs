@@ -0,0 +1,147 @@
+//! Per-file repository map rendering (`--mode map`)
+
+use crate::domain::{Chunk, FileInfo};
+use crate::utils::estimate_tokens;
+use std::collections::HashMap;
+
+/// Symbols listed per file before the rest are counted as "+N more".
+const MAX_SYMBOLS: usize = 6;
+
+/// Render a Markdown map with one line per exported file: path, language, its defined
+/// symbols, and the first sentence of the first docstring found in it.
+///
+/// Files keep the ranker's order; files without an exported chunk are left out. No
+/// file content is included. With `max_tokens`, lines stop before the map would exceed
+/// it and a closing line counts the files left out.
+pub fn render_repo_map(files: &[FileInfo], chunks: &[Chunk], max_tokens: Option<usize>) -> String {
+    let mut by_path: HashMap<&str, Vec<&Chunk>> = HashMap::new();
+    for chunk in chunks {
+        by_path.entry(chunk.path.as_str()).or_default().push(chunk);
+    }
+
+    let mut out = String::from("# Repository Map\n\n");
+    let mut used_tokens = estimate_tokens(&out);
+    let mut omitted = 0usize;
+    for file in files {
+        let Some(file_chunks) = by_path.get_mut(file.relative_path.as_str()) else {
+            continue;
+        };
+        if omitted > 0 {
+            omitted += 1;
+            continue;
+        }
+        file_chunks.sort_by_key(|c| (c.start_line, c.end_line));
+
+        let mut line = format!("- `{}` ({})", file.relative_path, file.language);
+        let symbols = file_symbols(file_chunks);
+        if !symbols.is_empty() {
+            let shown = symbols.iter().take(MAX_SYMBOLS).cloned().collect::<Vec<_>>().join(", ");
+            line.push_str(&format!(" — symbols: {shown}"));
+            if symbols.len() > MAX_SYMBOLS {
+                line.push_str(&format!(" +{} more", symbols.len() - MAX_SYMBOLS));
+            }
+        }
+        if let Some(summary) =
+            file_chunks.iter().filter_map(|c| c.doc.as_deref()).find_map(first_sentence)
+        {
+            line.push_str(&format!(" — {summary}"));
+        }
+        line.push('\n');
+        let line_tokens = estimate_tokens(&line);
+        if max_tokens.is_some_and(|max| used_tokens + line_tokens > max) {
+            omitted = 1;
+            continue;
+        }
+        used_tokens += line_tokens;
+        out.push_str(&line);
+    }
+    if omitted > 0 {
+        out.push_str(&format!("\n_{omitted} more file(s) left out by the map's token budget_\n"));
+    }
+    out
+}
+
+/// `type:`/`def:` tag names in line order, without duplicates.
+fn file_symbols(chunks: &[&Chunk]) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for chunk in chunks {
+        // A chunk's type comes before the methods defined in it.
+        for prefix in ["type:", "def:"] {
+            for name in chunk.tags.iter().filter_map(|t| t.strip_prefix(prefix)) {
+                if !symbols.iter().any(|s| s == name) {
+                    symbols.push(name.to_string());
+                }
+            }
+        }
+    }
+    symbols
+}
+
+/// First sentence of a docstring, collapsed onto one line.
+fn first_sentence(doc: &str) -> Option<String> {
+    let paragraph = doc.trim().split("\n\n").next().unwrap_or_default();
+    let text = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sentence = match text.find(". ") {
+        Some(idx) => &text[..=idx],
+        None => text.as_str(),
+    };
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_repo_map;
+    use crate::domain::test_support::file_info;
+    use crate::domain::{test_support, Chunk, FileInfo};
+    use crate::utils::estimate_tokens;
+
+    fn file(path: &str) -> FileInfo {
+        FileInfo { size_bytes: 100, ..file_info(path) }
+    }
+
+    fn chunk(path: &str, start_line: usize, tags: &[&str], doc: Option<&str>) -> Chunk {
+        Chunk {
+            id: format!("{path}:{start_line}"),
+            start_line,
+            end_line: start_line + 2,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            doc: doc.map(str::to_string),
+            ..test_support::chunk(path, "def body():\n    return 1\n")
+        }
+    }
+
+    #[test]
+    fn one_line_per_exported_file_in_ranked_order() {
+        let files = vec![file("app/auth.py"), file("app/unused.py"), file("app/db.py")];
+        let chunks = vec![
+            chunk("app/db.py", 1, &["def:connect"], None),
+            chunk("app/auth.py", 10, &["def:Session.refresh"], None),
+            chunk(
+                "app/auth.py",
+                1,
+                &["type:Session", "def:login"],
+                Some("Handle user sessions. Tokens expire hourly.\n\nMore detail."),
+            ),
+        ];
+
+        let map = render_repo_map(&files, &chunks, None);
+        let lines: Vec<&str> = map.lines().filter(|l| l.starts_with("- ")).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "- `app/auth.py` (python) — symbols: Session, login, Session.refresh — \
+                 Handle user sessions.",
+                "- `app/db.py` (python) — symbols: connect",
+            ]
+        );
+        assert!(!map.contains("return 1"));
+
+        // The budget covers the heading and the first line only.
+        let budget =
+            estimate_tokens("# Repository Map\n\n") + estimate_tokens(&format!("{}\n", lines[0]));
+        let capped = render_repo_map(&files, &chunks, Some(budget));
+        assert!(capped.contains("- `app/auth.py`"));
+        assert!(!capped.contains("- `app/db.py`"));
+        assert!(capped.ends_with("_1 more file(s) left out by the map's token budget_\n"));
+    }
+}
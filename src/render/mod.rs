@@ -1,11 +1,15 @@
 //! Output rendering (Markdown, JSONL, reports)
 
 pub mod context_pack;
+pub mod files_json;
 pub mod guardrails;
 pub mod jsonl;
+pub mod map;
 pub mod pr_context;
 pub mod report;
 
-pub use context_pack::render_context_pack;
-pub use jsonl::render_jsonl;
-pub use report::{write_report, ReportOptions};
+pub use context_pack::{render_context_pack, resolve_section_order, PackRenderOptions};
+pub use files_json::render_files_json;
+pub use jsonl::{render_jsonl, JSONL_SCHEMA_VERSION};
+pub use map::render_repo_map;
+pub use report::{report_value, write_report, ReportOptions};
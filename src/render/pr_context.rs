@@ -62,6 +62,19 @@ pub fn render_pr_context(report: &PrContextReport) -> String {
         }
     }
 
+    if let Some(diff) = report.symbol_diff.as_ref() {
+        out.push_str(&format!("\n### Changed Symbols (since `{}`)\n", diff.base_ref));
+        if diff.changes.is_empty() {
+            out.push_str("- No definitions changed.\n");
+        }
+        for change in &diff.changes {
+            out.push_str(&format!(
+                "- **{}** `{}` — `{}`\n",
+                change.kind, change.path, change.symbol
+            ));
+        }
+    }
+
     if report.graph_available {
         out.push_str("\n> Symbol graph available: yes\n");
     } else {
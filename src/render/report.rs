@@ -11,6 +11,8 @@ pub struct ReportOptions<'a> {
     pub include_timestamp: bool,
     pub provenance: Option<&'a Value>,
     pub coverage: Option<&'a Value>,
+    /// Write single-line JSON instead of the default pretty-printed form
+    pub compact: bool,
 }
 
 pub fn write_report(
@@ -21,6 +23,27 @@ pub fn write_report(
     config: &Value,
     options: ReportOptions<'_>,
 ) -> Result<()> {
+    let report = report_value(stats, files, output_files, config, options)?;
+    if let Some(parent) = report_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = if options.compact {
+        serde_json::to_string(&report)?
+    } else {
+        serde_json::to_string_pretty(&report)?
+    };
+    std::fs::write(report_path, json)?;
+    Ok(())
+}
+
+/// The report [`write_report`] writes, as a value.
+pub fn report_value(
+    stats: &ScanStats,
+    files: &[FileInfo],
+    output_files: &[String],
+    config: &Value,
+    options: ReportOptions<'_>,
+) -> Result<Value> {
     let mut sorted_output_files = output_files.to_vec();
     sorted_output_files.sort();
 
@@ -65,11 +88,7 @@ pub fn write_report(
         report.insert("files".to_string(), serde_json::to_value(file_manifest)?);
     }
 
-    if let Some(parent) = report_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    std::fs::write(report_path, serde_json::to_string_pretty(&Value::Object(report))?)?;
-    Ok(())
+    Ok(Value::Object(report))
 }
 
 fn round_priority(priority: f64) -> f64 {
@@ -115,6 +134,32 @@ mod tests {
         assert_eq!(skipped["size"], json!(5));
     }
 
+    #[test]
+    fn capping_dropped_records_flags_truncation_and_keeps_counts() {
+        let push_drops = |stats: &mut ScanStats| {
+            for i in 0..40 {
+                let mut record = std::collections::HashMap::new();
+                record.insert("path".to_string(), json!(format!("src/f{i}.rs")));
+                record.insert("reason".to_string(), json!("budget"));
+                stats.push_dropped_record(record);
+            }
+        };
+
+        let mut stats = ScanStats { max_dropped_records: Some(40), ..Default::default() };
+        push_drops(&mut stats);
+        assert!(!stats.dropped_files_truncated);
+        assert!(stats.to_report_value().get("dropped_files_truncated").is_none());
+
+        let mut stats = ScanStats { max_dropped_records: Some(5), ..Default::default() };
+        push_drops(&mut stats);
+        assert_eq!(stats.dropped_files.len(), 5);
+        assert_eq!(stats.dropped_files[4]["path"], json!("src/f4.rs"));
+        assert_eq!(stats.dropped_files_total(), 40);
+        assert!(stats.dropped_files_truncated);
+        let v = stats.to_report_value();
+        assert_eq!(v["dropped_files_truncated"], json!(true));
+    }
+
     #[test]
     fn report_omits_timestamp_when_disabled() {
         let tmp = TempDir::new().expect("tmp");
@@ -132,6 +177,8 @@ mod tests {
             is_readme: false,
             is_config: false,
             is_doc: false,
+            modified_at: None,
+            priority_reason: None,
         };
 
         write_report(
@@ -140,7 +187,7 @@ mod tests {
             &[file],
             &["out/chunks.jsonl".to_string()],
             &json!({"mode":"rag"}),
-            ReportOptions { include_timestamp: false, provenance: None, coverage: None },
+            ReportOptions { include_timestamp: false, ..ReportOptions::default() },
         )
         .expect("write report");
 
@@ -149,4 +196,25 @@ mod tests {
         assert!(parsed.get("generated_at").is_none());
         assert_eq!(parsed["files"][0]["priority"], json!(0.812));
     }
+
+    #[test]
+    fn compact_report_is_one_line_with_the_same_value() {
+        let tmp = TempDir::new().expect("tmp");
+        let stats = ScanStats { files_scanned: 3, files_included: 2, ..Default::default() };
+        let config = json!({"mode": "prompt", "max_tokens": 1000});
+        let write = |name: &str, compact: bool| {
+            let path = tmp.path().join(name);
+            let options = ReportOptions { compact, ..ReportOptions::default() };
+            write_report(&path, &stats, &[], &["out/a.md".to_string()], &config, options)
+                .expect("write report");
+            fs::read_to_string(path).expect("read report")
+        };
+
+        let pretty = write("pretty.json", false);
+        let compact = write("compact.json", true);
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(compact.lines().count(), 1);
+        let parse = |s: &str| serde_json::from_str::<serde_json::Value>(s).expect("json");
+        assert_eq!(parse(&compact), parse(&pretty));
+    }
 }
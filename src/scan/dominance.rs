@@ -0,0 +1,100 @@
+//! Dominant-directory guardrail.
+//!
+//! A newly vendored or generated tree that slips past the excludes can crowd out the
+//! rest of the repository. This finds a directory holding most of the scanned files and
+//! bytes so the export can warn about it; nothing is filtered.
+
+use crate::domain::FileInfo;
+use std::collections::BTreeMap;
+
+/// Scans smaller than this are too small for a share to mean anything.
+const MIN_FILES: usize = 10;
+
+/// Top-level directories expected to hold most of a repository; never flagged
+/// themselves, though a directory inside them still can be.
+const SOURCE_ROOTS: &[&str] =
+    &["src", "lib", "app", "pkg", "packages", "crates", "cmd", "internal", "source"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DominantDirectory {
+    /// Repository-relative directory, without a trailing slash
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub file_share: f64,
+    pub byte_share: f64,
+}
+
+/// The deepest directory holding more than `max_fraction` of both the scanned files and
+/// the scanned bytes, if any. A fraction of 1.0 or more disables the check.
+pub fn find_dominant_directory(files: &[FileInfo], max_fraction: f64) -> Option<DominantDirectory> {
+    if max_fraction >= 1.0 || files.len() < MIN_FILES {
+        return None;
+    }
+    let total_bytes: u64 = files.iter().map(|f| f.size_bytes).sum();
+    if total_bytes == 0 {
+        return None;
+    }
+
+    let mut totals: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+    for file in files {
+        let path = file.relative_path.as_str();
+        for (idx, _) in path.match_indices('/') {
+            let entry = totals.entry(&path[..idx]).or_default();
+            entry.0 += 1;
+            entry.1 += file.size_bytes;
+        }
+    }
+
+    totals
+        .into_iter()
+        .filter(|(dir, _)| !SOURCE_ROOTS.contains(dir))
+        .map(|(dir, (count, bytes))| DominantDirectory {
+            path: dir.to_string(),
+            files: count,
+            bytes,
+            file_share: count as f64 / files.len() as f64,
+            byte_share: bytes as f64 / total_bytes as f64,
+        })
+        .filter(|d| d.file_share > max_fraction && d.byte_share > max_fraction)
+        .max_by(|a, b| {
+            a.path
+                .matches('/')
+                .count()
+                .cmp(&b.path.matches('/').count())
+                .then_with(|| b.path.cmp(&a.path))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_dominant_directory;
+    use crate::domain::test_support::file_info;
+    use crate::domain::FileInfo;
+
+    fn file(path: &str, size_bytes: u64) -> FileInfo {
+        FileInfo { size_bytes, ..file_info(path) }
+    }
+
+    #[test]
+    fn deepest_dominant_directory_is_reported() {
+        let mut files: Vec<FileInfo> =
+            (0..4).map(|i| file(&format!("src/app_{i}.js"), 1_000)).collect();
+        files.extend((0..12).map(|i| file(&format!("src/assets/chartlib/mod_{i}.js"), 5_000)));
+
+        let dominant = find_dominant_directory(&files, 0.5).expect("dominant directory");
+        assert_eq!(dominant.path, "src/assets/chartlib");
+        assert_eq!(dominant.files, 12);
+        assert!(dominant.byte_share > 0.9);
+        assert!(find_dominant_directory(&files, 1.0).is_none());
+    }
+
+    #[test]
+    fn source_root_alone_is_not_flagged() {
+        let mut files: Vec<FileInfo> =
+            (0..12).map(|i| file(&format!("src/module_{i}.js"), 2_000)).collect();
+        files.push(file("README.md", 500));
+
+        assert!(find_dominant_directory(&files, 0.5).is_none());
+    }
+}
@@ -0,0 +1,84 @@
+//! Language hints from a root `.editorconfig`.
+//!
+//! EditorConfig has no standard language property, so only sections that set a
+//! `language = <name>` key are honored. Hints apply to files `get_language` can't
+//! classify (`text`).
+
+use globset::{Glob, GlobMatcher};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct LanguageHints {
+    rules: Vec<(GlobMatcher, String)>,
+}
+
+impl LanguageHints {
+    /// Load hints from `<root>/.editorconfig`; a missing or unreadable file yields no hints.
+    pub fn load(root: &Path) -> Self {
+        fs::read_to_string(root.join(".editorconfig"))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut section: Option<GlobMatcher> = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(pattern) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = section_matcher(pattern);
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().to_ascii_lowercase();
+            if key.trim().eq_ignore_ascii_case("language") && !value.is_empty() {
+                if let Some(matcher) = section.as_ref() {
+                    rules.push((matcher.clone(), value));
+                }
+            }
+        }
+        Self { rules }
+    }
+
+    /// Language for a repository-relative path; later sections win, as in EditorConfig.
+    pub fn language_for(&self, relative_path: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.is_match(relative_path))
+            .map(|(_, language)| language.as_str())
+    }
+}
+
+/// Patterns without a `/` match at any depth; others are anchored at the root.
+fn section_matcher(pattern: &str) -> Option<GlobMatcher> {
+    let pattern = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+    Glob::new(&pattern).ok().map(|glob| glob.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LanguageHints;
+
+    #[test]
+    fn parses_language_sections_with_last_match_winning() {
+        let hints = LanguageHints::parse(
+            "root = true\n\n[*]\ncharset = utf-8\n\n[*.{tpl,tmpl}]\nlanguage = HTML\n\n\
+             [templates/mail/*.tpl]\nlanguage = text\n",
+        );
+        assert_eq!(hints.language_for("views/page.tpl"), Some("html"));
+        assert_eq!(hints.language_for("page.tmpl"), Some("html"));
+        assert_eq!(hints.language_for("templates/mail/welcome.tpl"), Some("text"));
+        assert_eq!(hints.language_for("main.rs"), None);
+    }
+}
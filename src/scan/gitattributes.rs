@@ -0,0 +1,123 @@
+//! Language overrides from a root `.gitattributes`.
+//!
+//! GitHub Linguist reclassifies files carrying a `linguist-language=<name>` attribute.
+//! Those overrides win over `get_language`'s extension mapping and over `.editorconfig`
+//! hints; files without one keep the extension-based language.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::fs;
+use std::path::Path;
+
+const LINGUIST_LANGUAGE: &str = "linguist-language";
+
+/// Linguist names that differ from the ids `get_language` uses.
+const LINGUIST_ALIASES: &[(&str, &str)] = &[
+    ("c++", "cpp"),
+    ("c#", "csharp"),
+    ("shell", "bash"),
+    ("protocol buffer", "protobuf"),
+    ("protocol buffers", "protobuf"),
+];
+
+#[derive(Debug, Default)]
+pub struct LinguistOverrides {
+    /// `None` records an unset attribute (`-linguist-language`), which cancels earlier lines.
+    rules: Vec<(GlobMatcher, Option<String>)>,
+}
+
+impl LinguistOverrides {
+    /// Load overrides from `<root>/.gitattributes`; a missing or unreadable file yields none.
+    pub fn load(root: &Path) -> Self {
+        fs::read_to_string(root.join(".gitattributes"))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let language = parts.rev().find_map(|attr| {
+                if let Some(value) = attr.strip_prefix("linguist-language=") {
+                    Some(Some(language_id(value)))
+                } else if matches!(attr.strip_prefix(['-', '!']), Some(LINGUIST_LANGUAGE)) {
+                    Some(None)
+                } else {
+                    None
+                }
+            });
+            let (Some(language), Some(matcher)) = (language, pattern_matcher(pattern)) else {
+                continue;
+            };
+            rules.push((matcher, language));
+        }
+        Self { rules }
+    }
+
+    /// Language for a repository-relative path; the last matching line wins, as in git.
+    pub fn language_for(&self, relative_path: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.is_match(relative_path))
+            .and_then(|(_, language)| language.as_deref())
+    }
+}
+
+/// Lowercased Linguist name, mapped onto the repo's own ids where they differ.
+fn language_id(name: &str) -> String {
+    let name = name.replace('-', " ").to_ascii_lowercase();
+    LINGUIST_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, id)| id.to_string())
+        .unwrap_or_else(|| name.replace(' ', "-"))
+}
+
+/// Patterns without a `/` match the file name at any depth; others are anchored at the
+/// root. Directory patterns (`dir/`) never match files in gitattributes.
+fn pattern_matcher(pattern: &str) -> Option<GlobMatcher> {
+    if pattern.ends_with('/') || pattern.starts_with('!') {
+        return None;
+    }
+    let pattern = if pattern.trim_start_matches('/').contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern.trim_start_matches('/'))
+    };
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .ok()
+        .map(|glob| glob.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinguistOverrides;
+
+    #[test]
+    fn parses_linguist_language_with_last_match_winning() {
+        let overrides = LinguistOverrides::parse(
+            "* text=auto\n# templates\n*.inc linguist-language=PHP\n\
+             *.tpl   linguist-language=HTML linguist-vendored\n\
+             vendor/*.inc -linguist-language\nscripts/*.in linguist-language=Shell\n\
+             lib/*.hpp linguist-language=C++\n",
+        );
+        assert_eq!(overrides.language_for("includes/db.inc"), Some("php"));
+        assert_eq!(overrides.language_for("page.tpl"), Some("html"));
+        assert_eq!(overrides.language_for("vendor/lib.inc"), None);
+        assert_eq!(overrides.language_for("vendor/nested/lib.inc"), Some("php"));
+        assert_eq!(overrides.language_for("scripts/setup.in"), Some("bash"));
+        assert_eq!(overrides.language_for("other/scripts/setup.in"), None);
+        assert_eq!(overrides.language_for("lib/vec.hpp"), Some("cpp"));
+        assert_eq!(overrides.language_for("main.py"), None);
+    }
+}
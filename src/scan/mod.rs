@@ -4,6 +4,9 @@ use crate::domain::{FileInfo, ScanStats};
 use anyhow::Result;
 use std::path::Path;
 
+pub mod dominance;
+pub mod editorconfig;
+pub mod gitattributes;
 pub mod scanner;
 pub mod tree;
 
@@ -1,10 +1,12 @@
 //! File scanner implementation with gitignore support
 
+use super::editorconfig::LanguageHints;
+use super::gitattributes::LinguistOverrides;
 use crate::domain::{FileInfo, ScanStats};
-use crate::utils::{is_binary_file, is_likely_minified, normalize_path};
+use crate::utils::{is_binary_file, is_likely_minified, lfs_pointer_oid, normalize_path};
 use anyhow::Result;
 use globset::{Glob, GlobSet, GlobSetBuilder};
-use ignore::WalkBuilder;
+use ignore::{Walk, WalkBuilder};
 use sha2::{Digest, Sha256};
 use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
@@ -20,7 +22,12 @@ pub struct FileScanner {
     respect_gitignore: bool,
     follow_symlinks: bool,
     skip_minified: bool,
+    lfs_resolve: bool,
     max_line_length: usize,
+    editorconfig_hints: bool,
+    language_hints: LanguageHints,
+    linguist_overrides: LinguistOverrides,
+    force_text: GlobSet,
     stats: ScanStats,
 }
 
@@ -41,7 +48,12 @@ impl FileScanner {
             respect_gitignore: true,
             follow_symlinks: false,
             skip_minified: true,
+            lfs_resolve: false,
             max_line_length: 5000,
+            editorconfig_hints: true,
+            language_hints: LanguageHints::default(),
+            linguist_overrides: LinguistOverrides::default(),
+            force_text: GlobSet::empty(),
             stats: ScanStats::default(),
         }
     }
@@ -82,6 +94,30 @@ impl FileScanner {
         self
     }
 
+    /// Set whether Git LFS pointers are read from `.git/lfs/objects` when the object is present
+    pub fn lfs_resolve(mut self, resolve: bool) -> Self {
+        self.lfs_resolve = resolve;
+        self
+    }
+
+    /// Set whether `language = ...` hints in a root `.editorconfig` apply to `text` files
+    pub fn editorconfig_hints(mut self, enabled: bool) -> Self {
+        self.editorconfig_hints = enabled;
+        self
+    }
+
+    /// Set glob patterns for files admitted as text without binary detection
+    pub fn force_text_globs(mut self, globs: Vec<String>) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &globs {
+            if let Ok(glob) = Glob::new(pattern) {
+                builder.add(glob);
+            }
+        }
+        self.force_text = builder.build().unwrap_or_else(|_| GlobSet::empty());
+        self
+    }
+
     fn build_exclude_globset(&self) -> Result<GlobSet> {
         let mut builder = GlobSetBuilder::new();
         for pattern in &self.exclude_globs {
@@ -121,201 +157,191 @@ impl FileScanner {
     ///
     /// Files are returned in deterministic sorted order by relative path.
     pub fn scan(&mut self) -> Result<Vec<FileInfo>> {
-        self.stats = ScanStats::default();
-
-        let mut files: Vec<(PathBuf, String)> = Vec::new();
-        let exclude_globset = self.build_exclude_globset()?;
-
-        // Directory filter function matching Python's _walk_files behavior
-        let dir_filter = |entry: &ignore::DirEntry| -> bool {
-            if let Some(file_type) = entry.file_type() {
-                if file_type.is_dir() {
-                    if let Some(name) = entry.file_name().to_str() {
-                        // Skip known large directories unconditionally (Python lines 880-887)
-                        if matches!(
-                            name,
-                            "node_modules" | "__pycache__" | ".git" | ".venv" | "venv"
-                        ) {
-                            return false;
-                        }
-                        // Skip hidden directories except .github (Python lines 875-877)
-                        if name.starts_with('.') && name != ".github" {
-                            return false;
-                        }
-                    }
-                }
-            }
-            true
-        };
+        let mut files: Vec<FileInfo> = self.scan_iter()?.collect();
+        files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        Ok(files)
+    }
 
-        // Count raw files (no gitignore) so we can derive files_skipped_gitignore.
-        // We use a separate walk that ignores gitignore rules.
-        // IMPORTANT: Apply the same directory filter to maintain consistent counts.
-        let raw_file_count = if self.respect_gitignore {
-            let mut raw_builder = WalkBuilder::new(&self.root_path);
-            raw_builder
-                .git_ignore(false)
-                .git_global(false)
-                .git_exclude(false)
-                .follow_links(self.follow_symlinks)
-                .hidden(false)
-                .parents(false)
-                .filter_entry(dir_filter);
-            let mut count = 0usize;
-            for entry in raw_builder.build().flatten() {
-                if !entry.path().is_dir() {
-                    count += 1;
-                }
-            }
-            count
+    /// Stream files as the walk discovers them, in walk order rather than sorted order.
+    ///
+    /// Stats accumulate while iterating; the gitignore-skip count and `files_skipped` total
+    /// are finalized once the iterator is exhausted.
+    pub fn scan_iter(&mut self) -> Result<ScanIter<'_>> {
+        self.stats = ScanStats::default();
+        self.language_hints = if self.editorconfig_hints {
+            LanguageHints::load(&self.root_path)
         } else {
-            0
+            LanguageHints::default()
         };
+        self.linguist_overrides = LinguistOverrides::load(&self.root_path);
+        let exclude_globset = self.build_exclude_globset()?;
+        let walker = self.walk_builder(false).build();
+        Ok(ScanIter {
+            scanner: self,
+            walker,
+            exclude_globset,
+            gitignore_filtered_count: 0,
+            finished: false,
+        })
+    }
 
-        // Build walker with gitignore support using the `ignore` crate
+    /// Walker over the root; `raw` ignores gitignore rules entirely (used for skip counts).
+    fn walk_builder(&self, raw: bool) -> WalkBuilder {
+        let respect_gitignore = self.respect_gitignore && !raw;
         let mut builder = WalkBuilder::new(&self.root_path);
         builder
-            .git_ignore(self.respect_gitignore)
-            .git_global(self.respect_gitignore)
-            .git_exclude(self.respect_gitignore)
+            .git_ignore(respect_gitignore)
+            .git_global(respect_gitignore)
+            .git_exclude(respect_gitignore)
             .follow_links(self.follow_symlinks)
             .hidden(false) // Don't automatically skip hidden files
-            .parents(true) // Read .gitignore files from parent directories
+            .parents(!raw) // Read .gitignore files from parent directories
             .filter_entry(dir_filter);
+        builder
+    }
+
+    /// Count raw files (no gitignore) so we can derive files_skipped_gitignore.
+    /// Applies the same directory filter to maintain consistent counts.
+    fn count_raw_files(&self) -> usize {
+        self.walk_builder(true).build().flatten().filter(|entry| !entry.path().is_dir()).count()
+    }
 
-        let walker = builder.build();
+    /// Apply scanner filters to one walked file, updating stats; `None` when skipped.
+    fn admit_file(&mut self, path: &Path, exclude_globset: &GlobSet) -> Option<FileInfo> {
+        self.stats.files_scanned += 1;
 
-        // Count files seen after gitignore filtering (before our own filters).
-        let mut gitignore_filtered_count = 0usize;
+        // Get relative path
+        let rel_path = normalize_path(path.strip_prefix(&self.root_path).ok()?.to_str()?);
 
-        // Collect all files
-        for entry_result in walker {
-            let entry = match entry_result {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
+        // Check explicit exclude globs
+        if exclude_globset.is_match(&rel_path) {
+            self.stats.files_skipped_glob += 1;
+            return None;
+        }
 
-            let path = entry.path();
+        // Check extension
+        if !self.should_include_extension(path) {
+            self.stats.files_skipped_extension += 1;
+            return self.skip_file(rel_path);
+        }
 
-            // Skip directories
-            if path.is_dir() {
-                continue;
-            }
+        // Check file size
+        let metadata = path.metadata().ok()?;
+        let mut size = metadata.len();
+        self.stats.total_bytes_scanned += size;
 
-            // Count this file toward files_scanned (Python only counts files, not dirs).
-            gitignore_filtered_count += 1;
-            self.stats.files_scanned += 1;
-
-            // Get relative path
-            let rel_path = match path.strip_prefix(&self.root_path) {
-                Ok(p) => normalize_path(p.to_str().unwrap_or("")),
-                Err(_) => continue,
-            };
-
-            // Check explicit exclude globs
-            if exclude_globset.is_match(&rel_path) {
-                self.stats.files_skipped_glob += 1;
-                continue;
-            }
+        if size > self.max_file_bytes {
+            self.stats.files_skipped_size += 1;
+            return self.skip_file(rel_path);
+        }
 
-            // Check extension
-            if !self.should_include_extension(path) {
-                self.stats.files_skipped_extension += 1;
-                continue;
+        // Git LFS pointers carry no content; read the local object instead when asked
+        let mut content_path = path.to_path_buf();
+        if let Some(oid) = lfs_pointer_oid(path) {
+            let object = self.lfs_object_path(&oid);
+            let object_size = object.metadata().ok().filter(|m| m.is_file()).map(|m| m.len());
+            match object_size {
+                Some(object_size) if self.lfs_resolve => {
+                    if object_size > self.max_file_bytes {
+                        self.stats.files_skipped_size += 1;
+                        return self.skip_file(rel_path);
+                    }
+                    content_path = object;
+                    size = object_size;
+                }
+                _ => {
+                    self.stats.files_skipped_lfs += 1;
+                    return self.skip_file(rel_path);
+                }
             }
+        }
 
-            // Check file size
-            let metadata = match path.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
+        // Check if binary
+        if !self.force_text.is_match(&rel_path)
+            && is_binary_file(&content_path, DEFAULT_SAMPLE_SIZE)
+        {
+            self.stats.files_skipped_binary += 1;
+            return self.skip_file(rel_path);
+        }
 
-            let size = metadata.len();
-            self.stats.total_bytes_scanned += size;
+        // Check if minified
+        if self.skip_minified && is_likely_minified(&content_path, self.max_line_length) {
+            self.stats.files_skipped_glob += 1;
+            return self.skip_file(rel_path);
+        }
 
-            if size > self.max_file_bytes {
-                self.stats.files_skipped_size += 1;
-                continue;
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        let ext_with_dot =
+            if !ext.is_empty() && !ext.starts_with('.') { format!(".{}", ext) } else { ext };
+
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let mut language = crate::domain::get_language(&ext_with_dot, filename);
+        if let Some(language_override) = self.linguist_overrides.language_for(&rel_path) {
+            language = language_override.to_string();
+        } else if language == "text" {
+            if let Some(hint) = self.language_hints.language_for(&rel_path) {
+                language = hint.to_string();
             }
+        }
 
-            // Check if binary
-            if is_binary_file(path, DEFAULT_SAMPLE_SIZE) {
-                self.stats.files_skipped_binary += 1;
-                continue;
-            }
+        // Generate stable ID: SHA-256 of relative path, first 16 hex chars (matches Python)
+        let id = {
+            let hash = Sha256::digest(rel_path.as_bytes());
+            format!("{:x}", hash)[..16].to_string()
+        };
 
-            // Check if minified
-            if self.skip_minified && is_likely_minified(path, self.max_line_length) {
-                self.stats.files_skipped_glob += 1;
-                continue;
-            }
+        // Update language stats
+        *self.stats.languages_detected.entry(language.clone()).or_insert(0) += 1;
+        self.stats.files_included += 1;
+        self.stats.total_bytes_included += size;
+
+        Some(FileInfo {
+            path: content_path,
+            relative_path: rel_path,
+            size_bytes: size,
+            extension: ext_with_dot,
+            language,
+            id,
+            priority: 0.5,         // Default priority, will be set by ranker
+            token_estimate: 0,     // Will be calculated later
+            tags: BTreeSet::new(), // Will be populated by ranker
+            is_readme: false,      // Will be detected by ranker
+            is_config: false,      // Will be detected by ranker
+            is_doc: false,         // Will be detected by ranker
+            modified_at: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64),
+            priority_reason: None, // Will be set by ranker
+        })
+    }
 
-            files.push((path.to_path_buf(), rel_path));
-        }
+    /// Record `rel_path` as skipped by a filter past the exclude globs.
+    fn skip_file(&mut self, rel_path: String) -> Option<FileInfo> {
+        self.stats.skipped_file_paths.insert(rel_path);
+        None
+    }
 
+    fn finish_stats(&mut self, gitignore_filtered_count: usize) {
         // Derive gitignore-skipped count from the difference between the raw walk
         // and the gitignore-respecting walk.
         if self.respect_gitignore {
             self.stats.files_skipped_gitignore =
-                raw_file_count.saturating_sub(gitignore_filtered_count);
-        }
-
-        // Sort by relative path for deterministic ordering
-        files.sort_by(|a, b| a.1.cmp(&b.1));
-
-        // Convert to FileInfo objects
-        let mut result = Vec::new();
-        for (path, rel_path) in files {
-            let metadata = match path.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-
-            let size = metadata.len();
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
-            let ext_with_dot =
-                if !ext.is_empty() && !ext.starts_with('.') { format!(".{}", ext) } else { ext };
-
-            let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            let language = crate::domain::get_language(&ext_with_dot, filename);
-
-            // Generate stable ID: SHA-256 of relative path, first 16 hex chars (matches Python)
-            let id = {
-                let hash = Sha256::digest(rel_path.as_bytes());
-                format!("{:x}", hash)[..16].to_string()
-            };
-
-            // Update language stats
-            *self.stats.languages_detected.entry(language.clone()).or_insert(0) += 1;
-
-            let file_info = FileInfo {
-                path: path.clone(),
-                relative_path: rel_path.clone(),
-                size_bytes: size,
-                extension: ext_with_dot,
-                language: language.clone(),
-                id,
-                priority: 0.5,         // Default priority, will be set by ranker
-                token_estimate: 0,     // Will be calculated later
-                tags: BTreeSet::new(), // Will be populated by ranker
-                is_readme: false,      // Will be detected by ranker
-                is_config: false,      // Will be detected by ranker
-                is_doc: false,         // Will be detected by ranker
-            };
-
-            self.stats.files_included += 1;
-            self.stats.total_bytes_included += size;
-
-            result.push(file_info);
+                self.count_raw_files().saturating_sub(gitignore_filtered_count);
         }
 
         self.stats.files_skipped = self.stats.files_skipped_size
             + self.stats.files_skipped_binary
             + self.stats.files_skipped_extension
             + self.stats.files_skipped_gitignore
-            + self.stats.files_skipped_glob;
+            + self.stats.files_skipped_glob
+            + self.stats.files_skipped_lfs;
+    }
 
-        Ok(result)
+    /// Location of an LFS object in the local store (`.git/lfs/objects/ab/cd/abcd...`).
+    fn lfs_object_path(&self, oid: &str) -> PathBuf {
+        self.root_path.join(".git/lfs/objects").join(&oid[..2]).join(&oid[2..4]).join(oid)
     }
 
     /// Get scanning statistics
@@ -324,6 +350,56 @@ impl FileScanner {
     }
 }
 
+/// Streaming scan returned by [`FileScanner::scan_iter`].
+pub struct ScanIter<'a> {
+    scanner: &'a mut FileScanner,
+    walker: Walk,
+    exclude_globset: GlobSet,
+    gitignore_filtered_count: usize,
+    finished: bool,
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = FileInfo;
+
+    fn next(&mut self) -> Option<FileInfo> {
+        if self.finished {
+            return None;
+        }
+        for entry in self.walker.by_ref().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                continue;
+            }
+            // Count this file toward files_scanned (Python only counts files, not dirs).
+            self.gitignore_filtered_count += 1;
+            if let Some(file) = self.scanner.admit_file(path, &self.exclude_globset) {
+                return Some(file);
+            }
+        }
+        self.finished = true;
+        self.scanner.finish_stats(self.gitignore_filtered_count);
+        None
+    }
+}
+
+/// Directory filter matching Python's _walk_files behavior.
+fn dir_filter(entry: &ignore::DirEntry) -> bool {
+    if entry.file_type().is_some_and(|file_type| file_type.is_dir()) {
+        if let Some(name) = entry.file_name().to_str() {
+            // Skip known large directories unconditionally (Python lines 880-887)
+            if matches!(name, "node_modules" | "__pycache__" | ".git" | ".venv" | "venv") {
+                return false;
+            }
+            // Skip hidden directories except .github (Python lines 875-877)
+            if name.starts_with('.') && name != ".github" {
+                return false;
+            }
+        }
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -482,4 +558,131 @@ mod tests {
         // files_included = only the .rs ones
         assert_eq!(stats.files_included, 3, "files_included should be 3");
     }
+
+    #[test]
+    fn test_scan_iter_yields_same_files_as_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("scratch")).unwrap();
+        fs::write(root.join(".gitignore"), "scratch/\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn lib() {}").unwrap();
+        fs::write(root.join("src/nested/mod.rs"), "pub fn nested() {}").unwrap();
+        fs::write(root.join("scratch/tmp.rs"), "fn ignored() {}").unwrap();
+        fs::write(root.join("main.py"), "print('hi')").unwrap();
+        fs::write(root.join("data.xyz"), "skipped by extension").unwrap();
+
+        let mut scanner = FileScanner::new(root.to_path_buf());
+        let scanned: Vec<String> =
+            scanner.scan().unwrap().into_iter().map(|f| f.relative_path).collect();
+        let scan_stats = scanner.stats().clone();
+
+        let mut streamed: Vec<String> =
+            scanner.scan_iter().unwrap().map(|f| f.relative_path).collect();
+        streamed.sort();
+        let stream_stats = scanner.stats();
+
+        assert_eq!(streamed, scanned);
+        assert_eq!(scanned, vec!["main.py", "src/lib.rs", "src/nested/mod.rs"]);
+        assert_eq!(stream_stats.files_included, scan_stats.files_included);
+        assert_eq!(stream_stats.files_skipped_gitignore, 1);
+        assert_eq!(stream_stats.files_skipped, scan_stats.files_skipped);
+    }
+
+    #[test]
+    fn test_editorconfig_language_hint_applies_to_unknown_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("views")).unwrap();
+        fs::write(root.join(".editorconfig"), "root = true\n\n[*.tpl]\nlanguage = html\n").unwrap();
+        fs::write(root.join("views/page.tpl"), "<p>{{ title }}</p>").unwrap();
+        fs::write(root.join("notes.txt"), "plain notes").unwrap();
+
+        let extensions = vec![".tpl".to_string(), ".txt".to_string()];
+        let mut scanner =
+            FileScanner::new(root.to_path_buf()).include_extensions(extensions.clone());
+        let files = scanner.scan().unwrap();
+        let language_of = |files: &[FileInfo], path: &str| {
+            files.iter().find(|f| f.relative_path == path).map(|f| f.language.clone())
+        };
+        assert_eq!(language_of(&files, "views/page.tpl").as_deref(), Some("html"));
+        assert_eq!(language_of(&files, "notes.txt").as_deref(), Some("text"));
+
+        let mut scanner = FileScanner::new(root.to_path_buf())
+            .include_extensions(extensions)
+            .editorconfig_hints(false);
+        let files = scanner.scan().unwrap();
+        assert_eq!(language_of(&files, "views/page.tpl").as_deref(), Some("text"));
+    }
+
+    #[test]
+    fn test_gitattributes_linguist_language_overrides_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("views")).unwrap();
+        fs::write(
+            root.join(".gitattributes"),
+            "*.inc linguist-language=PHP\n*.tpl linguist-language=HTML\n*.js linguist-language=TypeScript\n",
+        )
+        .unwrap();
+        fs::write(root.join(".editorconfig"), "[*.tpl]\nlanguage = text\n").unwrap();
+        fs::write(root.join("db.inc"), "<?php function connect() {}").unwrap();
+        fs::write(root.join("views/page.tpl"), "<p>{{ title }}</p>").unwrap();
+        fs::write(root.join("app.js"), "const x = 1;").unwrap();
+        fs::write(root.join("main.py"), "print('hi')").unwrap();
+
+        let extensions =
+            vec![".inc".to_string(), ".tpl".to_string(), ".js".to_string(), ".py".to_string()];
+        let mut scanner = FileScanner::new(root.to_path_buf()).include_extensions(extensions);
+        let files = scanner.scan().unwrap();
+        let language_of = |path: &str| {
+            files.iter().find(|f| f.relative_path == path).map(|f| f.language.as_str())
+        };
+        assert_eq!(language_of("db.inc"), Some("php"));
+        assert_eq!(language_of("views/page.tpl"), Some("html"));
+        assert_eq!(language_of("app.js"), Some("typescript"));
+        assert_eq!(language_of("main.py"), Some("python"));
+        assert_eq!(scanner.stats().languages_detected.get("php"), Some(&1));
+    }
+
+    #[test]
+    fn test_lfs_pointer_skipped_or_resolved() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+
+        fs::write(
+            root.join("data.py"),
+            format!("version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize 21\n"),
+        )
+        .unwrap();
+        fs::write(root.join("main.py"), "print('hello')").unwrap();
+
+        let mut scanner = FileScanner::new(root.to_path_buf());
+        let files = scanner.scan().unwrap();
+        assert_eq!(files.iter().map(|f| f.relative_path.as_str()).collect::<Vec<_>>(), ["main.py"]);
+        assert_eq!(scanner.stats().files_skipped_lfs, 1);
+        assert_eq!(scanner.stats().files_skipped, 1);
+
+        // Resolution needs the object in the local store; without it the pointer is still skipped
+        let mut scanner = FileScanner::new(root.to_path_buf()).lfs_resolve(true);
+        assert_eq!(scanner.scan().unwrap().len(), 1);
+        assert_eq!(scanner.stats().files_skipped_lfs, 1);
+
+        let object_dir = root.join(".git/lfs/objects/4d/7a");
+        fs::create_dir_all(&object_dir).unwrap();
+        fs::write(object_dir.join(oid), "DATA = [1, 2, 3, 4]\n").unwrap();
+
+        let mut scanner = FileScanner::new(root.to_path_buf()).lfs_resolve(true);
+        let files = scanner.scan().unwrap();
+        let data = files.iter().find(|f| f.relative_path == "data.py").expect("resolved pointer");
+        assert_eq!(data.language, "python");
+        assert_eq!(data.size_bytes, 20);
+        assert_eq!(fs::read_to_string(&data.path).unwrap(), "DATA = [1, 2, 3, 4]\n");
+        assert_eq!(scanner.stats().files_skipped_lfs, 0);
+    }
 }
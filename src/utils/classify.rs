@@ -160,6 +160,34 @@ pub fn is_vendored(path: &Path) -> bool {
     false
 }
 
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+
+/// Pointer files are a few lines of text; anything larger is real content.
+const LFS_POINTER_MAX_BYTES: usize = 1024;
+
+/// Return the SHA-256 object id when `path` is a Git LFS pointer file.
+///
+/// # Arguments
+/// * `path` - Path to the file
+///
+/// # Returns
+/// The 64-character hex `oid`, or `None` when the file is not a pointer
+pub fn lfs_pointer_oid(path: &Path) -> Option<String> {
+    let mut buffer = vec![0u8; LFS_POINTER_MAX_BYTES + 1];
+    let mut file = File::open(path).ok()?;
+    let bytes_read = file.read(&mut buffer).ok()?;
+    if bytes_read > LFS_POINTER_MAX_BYTES || !buffer[..bytes_read].starts_with(LFS_POINTER_PREFIX) {
+        return None;
+    }
+
+    let text = std::str::from_utf8(&buffer[..bytes_read]).ok()?;
+    text.lines()
+        .filter_map(|line| line.strip_prefix("oid sha256:"))
+        .map(str::trim)
+        .find(|oid| oid.len() == 64 && oid.bytes().all(|b| b.is_ascii_hexdigit()))
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,4 +237,20 @@ mod tests {
         ));
         assert!(!is_likely_generated(Path::new("src/main.rs"), "fn main() {}"));
     }
+
+    #[test]
+    fn test_lfs_pointer_oid() {
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let mut pointer = NamedTempFile::new().unwrap();
+        write!(
+            pointer,
+            "version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize 12345\n"
+        )
+        .unwrap();
+        assert_eq!(lfs_pointer_oid(pointer.path()).as_deref(), Some(oid));
+
+        let mut regular = NamedTempFile::new().unwrap();
+        writeln!(regular, "version 1.0\noid sha256:{oid}").unwrap();
+        assert_eq!(lfs_pointer_oid(regular.path()), None);
+    }
 }
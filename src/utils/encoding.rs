@@ -7,9 +7,12 @@
 //! - Binary file detection
 //! - Safe error handling with replacement characters
 
+use crate::domain::{Config, FileInfo};
 use anyhow::{Context, Result};
 use chardetng::EncodingDetector;
-use encoding_rs::{Encoding, UTF_8};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use globset::{Glob, GlobMatcher};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -120,6 +123,93 @@ fn is_binary_file_impl(path: &Path, sample_size: usize) -> Result<bool> {
     Ok((printable_count as f64 / sample.len() as f64) < 0.70)
 }
 
+/// Per-file charsets from `encoding_overrides` (and UTF-8 for `force_text_globs`),
+/// matched against relative paths.
+#[derive(Debug, Clone, Default)]
+pub struct EncodingOverrides {
+    rules: Vec<(GlobMatcher, String)>,
+}
+
+impl EncodingOverrides {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut rules = Vec::with_capacity(config.encoding_overrides.len());
+        for (pattern, encoding) in &config.encoding_overrides {
+            let glob = Glob::new(pattern)
+                .with_context(|| format!("Invalid encoding_overrides glob '{pattern}'"))?;
+            if Encoding::for_label(encoding.as_bytes()).is_none() {
+                anyhow::bail!(
+                    "Unknown encoding '{encoding}' in encoding_overrides for '{pattern}'"
+                );
+            }
+            rules.push((glob.compile_matcher(), encoding.clone()));
+        }
+        // Forced-text files skipped binary detection, so auto-detection could misread
+        // them; lossy UTF-8 keeps them readable.
+        for pattern in &config.force_text_globs {
+            let glob = Glob::new(pattern)
+                .with_context(|| format!("Invalid force_text_globs glob '{pattern}'"))?;
+            rules.push((glob.compile_matcher(), "utf-8".to_string()));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Encoding to pass to [`read_file_safe`] for `relative_path`; the first matching glob
+    /// wins, and `None` leaves the file to auto-detection.
+    pub fn encoding_for(&self, relative_path: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(glob, _)| glob.is_match(relative_path))
+            .map(|(_, encoding)| encoding.as_str())
+    }
+
+    /// `bytes` of `relative_path` (e.g. a git blob) decoded with its override charset, or
+    /// as lossy UTF-8 when none matches.
+    pub fn decode(&self, relative_path: &str, bytes: &[u8]) -> String {
+        let encoding = self
+            .encoding_for(relative_path)
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8);
+        encoding.decode(bytes).0.into_owned()
+    }
+
+    /// Whether each `\n` in `bytes` of `relative_path` is the single byte `0x0A`, so its
+    /// lines can be found in the raw bytes. False for UTF-16, by BOM or override.
+    pub fn has_byte_newlines(&self, relative_path: &str, bytes: &[u8]) -> bool {
+        let encoding = Encoding::for_bom(bytes).map(|(encoding, _)| encoding).or_else(|| {
+            self.encoding_for(relative_path).and_then(|label| Encoding::for_label(label.as_bytes()))
+        });
+        !matches!(encoding, Some(e) if e == UTF_16LE || e == UTF_16BE)
+    }
+}
+
+/// Supplies the text of a selected file; `None` skips the file.
+///
+/// Everything an export reads from selected files goes through this, so library callers
+/// can hand over content they already hold and the CLI applies its encoding overrides
+/// in one place.
+pub trait ContentSource {
+    fn read(&self, file: &FileInfo) -> Option<String>;
+}
+
+/// In-memory contents keyed by repository-relative path.
+impl ContentSource for HashMap<String, String> {
+    fn read(&self, file: &FileInfo) -> Option<String> {
+        self.get(&file.relative_path).cloned()
+    }
+}
+
+/// Reads selected files from the checked-out repository, honoring encoding overrides.
+pub struct WorkingTree<'a> {
+    pub encodings: &'a EncodingOverrides,
+}
+
+impl ContentSource for WorkingTree<'_> {
+    fn read(&self, file: &FileInfo) -> Option<String> {
+        let encoding = self.encodings.encoding_for(&file.relative_path);
+        read_file_safe(&file.path, None, encoding).ok().map(|(content, _)| content)
+    }
+}
+
 /// Read a file safely with encoding detection and error handling.
 ///
 /// Strategy (matching Python implementation):
@@ -315,6 +405,42 @@ mod tests {
         assert_eq!(content.chars().count(), 5);
     }
 
+    #[test]
+    fn test_encoding_override_decodes_latin1_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"x = '\xb5\xe6\xf8'\n").unwrap();
+        file.flush().unwrap();
+
+        // Too little text for detection, which guesses a Baltic code page.
+        let (detected, _) = read_file_safe(file.path(), None, None).unwrap();
+        assert_ne!(detected, "x = 'µæø'\n");
+
+        let config = Config {
+            encoding_overrides: vec![("legacy/**".to_string(), "latin1".to_string())],
+            ..Config::default()
+        };
+        let overrides = EncodingOverrides::from_config(&config).unwrap();
+        assert_eq!(overrides.encoding_for("src/main.rs"), None);
+        let encoding = overrides.encoding_for("legacy/prices.py");
+        let (content, _) = read_file_safe(file.path(), None, encoding).unwrap();
+        assert_eq!(content, "x = 'µæø'\n");
+        // Blobs from git history decode the same way.
+        assert_eq!(overrides.decode("legacy/prices.py", b"x = '\xb5\xe6\xf8'\n"), "x = 'µæø'\n");
+        assert_eq!(overrides.decode("src/main.rs", b"x = '\xb5'\n"), "x = '\u{FFFD}'\n");
+    }
+
+    #[test]
+    fn test_utf16_has_no_byte_newlines() {
+        let config = Config {
+            encoding_overrides: vec![("*.txt".to_string(), "utf-16le".to_string())],
+            ..Config::default()
+        };
+        let overrides = EncodingOverrides::from_config(&config).unwrap();
+        assert!(overrides.has_byte_newlines("a.py", b"x = '\xb5'\n"));
+        assert!(!overrides.has_byte_newlines("a.txt", b"x\0\n\0"));
+        assert!(!overrides.has_byte_newlines("a.py", b"\xff\xfex\0\n\0"));
+    }
+
     #[test]
     fn test_stream_file_lines() {
         let mut file = NamedTempFile::new().unwrap();
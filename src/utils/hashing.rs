@@ -1,6 +1,9 @@
 //! Stable hashing for chunk IDs
 
 use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io;
+use std::path::Path;
 
 pub fn stable_hash(content: &str, path: &str, start_line: usize, end_line: usize) -> String {
     // Match Python: hashlib.sha256(f"{path}:{start_line}-{end_line}:{content[:1000]}".encode()).hexdigest()[:16]
@@ -12,3 +15,19 @@ pub fn stable_hash(content: &str, path: &str, start_line: usize, end_line: usize
     let result = hasher.finalize();
     format!("{:x}", result)[..16].to_string()
 }
+
+/// Hex SHA-256 of a JSON value's serialization; object keys serialize in sorted order, so
+/// equal values hash equally.
+pub fn stable_json_hash(value: &serde_json::Value) -> String {
+    let canonical = serde_json::to_vec(value).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(&canonical);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hex SHA-256 of a file's bytes, streamed so large assets are not read into memory.
+pub fn sha256_file_hex(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -3,15 +3,29 @@
 pub mod classify;
 pub mod encoding;
 pub mod hashing;
+pub mod output;
 pub mod paths;
 pub mod tokens;
+pub mod unicode;
 
-pub use classify::{is_likely_generated, is_likely_minified, is_lock_file, is_vendored};
-pub use encoding::{is_binary_file, read_file_safe};
-pub use hashing::stable_hash;
+pub use classify::{
+    is_likely_generated, is_likely_minified, is_lock_file, is_vendored, lfs_pointer_oid,
+};
+pub use encoding::{is_binary_file, read_file_safe, ContentSource, EncodingOverrides, WorkingTree};
+pub use hashing::{sha256_file_hex, stable_hash, stable_json_hash};
 pub use paths::normalize_path;
 pub use tokens::estimate_tokens;
 
+/// Line comment prefix for a detected language (`//` when unknown).
+pub fn line_comment_prefix(language: &str) -> &'static str {
+    match language {
+        "python" | "ruby" | "shell" | "bash" | "yaml" | "toml" | "perl" | "r" | "makefile"
+        | "dockerfile" => "#",
+        "sql" | "lua" | "haskell" => "--",
+        _ => "//",
+    }
+}
+
 /// Format a number with thousands separators (e.g. 1048576 → "1,048,576").
 ///
 /// Matches Python's `{:,}` format specifier used in the context pack header
@@ -0,0 +1,28 @@
+//! Status output on stdout (`--quiet`).
+//!
+//! Modules that only print progress and summaries import this `println!` in place of
+//! the std/anstream one, so `--quiet` silences them without touching each call site.
+//! Errors and warnings go to stderr and are never silenced.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silence status output for the rest of the process.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `anstream::println!`, skipped under `--quiet`.
+macro_rules! println {
+    ($($arg:tt)*) => {
+        if !$crate::utils::output::is_quiet() {
+            anstream::println!($($arg)*);
+        }
+    };
+}
+pub(crate) use println;
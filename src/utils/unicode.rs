@@ -0,0 +1,65 @@
+//! Unicode hygiene: suspicious character detection and normalization
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Zero-width and bidi control characters that can hide or reorder source text
+/// (homoglyph / "Trojan Source" style tricks).
+const SUSPICIOUS_CHARS: &[char] = &[
+    '\u{200B}', // zero width space
+    '\u{200C}', // zero width non-joiner
+    '\u{200D}', // zero width joiner
+    '\u{200E}', // left-to-right mark
+    '\u{200F}', // right-to-left mark
+    '\u{2060}', // word joiner
+    '\u{FEFF}', // zero width no-break space (BOM inside text)
+    '\u{202A}', // left-to-right embedding
+    '\u{202B}', // right-to-left embedding
+    '\u{202C}', // pop directional formatting
+    '\u{202D}', // left-to-right override
+    '\u{202E}', // right-to-left override
+    '\u{2066}', // left-to-right isolate
+    '\u{2067}', // right-to-left isolate
+    '\u{2068}', // first strong isolate
+    '\u{2069}', // pop directional isolate
+];
+
+pub fn is_suspicious_char(ch: char) -> bool {
+    SUSPICIOUS_CHARS.contains(&ch)
+}
+
+pub fn has_suspicious_unicode(text: &str) -> bool {
+    text.chars().any(is_suspicious_char)
+}
+
+/// NFC-normalize `text` and drop invisible characters that carry no meaning in code.
+///
+/// ZWJ/ZWNJ and the LRM/RLM marks are kept because emoji sequences and RTL prose
+/// legitimately use them; they are still reported by [`has_suspicious_unicode`].
+pub fn normalize_unicode(text: &str) -> String {
+    text.nfc()
+        .filter(|ch| {
+            !is_suspicious_char(*ch)
+                || matches!(ch, '\u{200C}' | '\u{200D}' | '\u{200E}' | '\u{200F}')
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_suspicious_unicode, normalize_unicode};
+
+    #[test]
+    fn zero_width_space_is_detected_and_removed() {
+        let text = "let admin\u{200B} = true;";
+        assert!(has_suspicious_unicode(text));
+        let cleaned = normalize_unicode(text);
+        assert_eq!(cleaned, "let admin = true;");
+        assert!(!has_suspicious_unicode(&cleaned));
+    }
+
+    #[test]
+    fn normalization_composes_to_nfc() {
+        assert_eq!(normalize_unicode("cafe\u{0301}"), "caf\u{00E9}");
+        assert!(!has_suspicious_unicode("plain ascii"));
+    }
+}